@@ -1,6 +1,7 @@
 use criterion::{BatchSize, Criterion, black_box};
 
-use toon::Grid;
+use toon::output::Ext as _;
+use toon::{Grid, Style};
 
 fn main() {
     let mut c = Criterion::default().configure_from_args();
@@ -15,4 +16,25 @@ fn main() {
             BatchSize::LargeInput,
         );
     });
+
+    c.bench_function("Grid::clone", |b| {
+        let mut grid = Grid::new((320, 96));
+        for y in 0..grid.height() {
+            grid.write((0, y), "Hello World! こんにちは e\u{301}", Style::default());
+        }
+        b.iter(|| black_box(&grid).clone());
+    });
+
+    c.bench_function("Grid::write", |b| {
+        let mut grid = Grid::new((320, 96));
+        b.iter(|| {
+            for y in 0..grid.height() {
+                grid.write(
+                    (0, y),
+                    black_box("Hello World! こんにちは e\u{301}"),
+                    Style::default(),
+                );
+            }
+        });
+    });
 }
@@ -18,7 +18,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let element = dev.wrap(
                 toon::span("Hello World!")
                     .title("Hello World!")
-                    .on('q', |_| ()),
+                    .on('q', |_| ())
+                    // Show the span in the inspector
+                    .filter(dev::Inspect::new("hello")),
             );
 
             let events = future::race(
@@ -0,0 +1,101 @@
+//! An example using Toon with Tokio, waiting for terminal events, messages from another task and
+//! captured output at the same time with `tokio::select!`.
+
+use std::time::Duration;
+
+use futures_lite::stream::StreamExt;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::{mpsc, watch};
+use tokio_crate as tokio;
+use toon::{Crossterm, Element, ElementExt, Terminal};
+
+/// The state of the application, which is redrawn whenever it changes.
+#[derive(Clone)]
+struct App {
+    /// The number of ticks sent by the other task.
+    ticks: u64,
+    /// The log of captured output.
+    log: toon::Log,
+}
+
+/// Events that can occur.
+enum Event {
+    /// Print a line to the standard output.
+    Print,
+    /// Print a line to the standard error.
+    PrintError,
+    /// An event in the log of captured output.
+    Log(toon::LogEvent),
+    /// Quit.
+    Quit,
+}
+
+fn view(app: &App) -> Box<dyn Element<Event = Event> + '_> {
+    toon::column(
+        toon::stretch(1),
+        (
+            toon::span(format!(
+                "Ticks: {}   [O]: Print   [E]: Print error   [Q]: Quit",
+                app.ticks
+            )),
+            app.log.element().map_event(Event::Log),
+        ),
+    )
+    .on('o', |_| Event::Print)
+    .on('e', |_| Event::PrintError)
+    .on('q', |_| Event::Quit)
+    .boxed()
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut terminal = Terminal::new(Crossterm::default())?;
+
+    // Read the lines printed to the standard output and standard error.
+    let captured = terminal.take_captured().unwrap();
+    let mut stdout = BufReader::new(captured.stdout.into_tokio()?).lines();
+    let mut stderr = BufReader::new(captured.stderr.into_tokio()?).lines();
+
+    // A task that sends messages to the application, like a network connection or a worker would.
+    let (sender, mut receiver) = mpsc::channel(16);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        for tick in 0_u64.. {
+            interval.tick().await;
+            if sender.send(tick).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let (app, state) = watch::channel(App {
+        ticks: 0,
+        log: toon::Log::new().max_lines(100),
+    });
+    let mut events = terminal.watch(state, view);
+
+    loop {
+        tokio::select! {
+            // The user has caused an event to occur.
+            Some(event) = events.next() => match event? {
+                Event::Print => println!("Printed after {} ticks", app.borrow().ticks),
+                Event::PrintError => {
+                    eprintln!("Printed to stderr after {} ticks", app.borrow().ticks);
+                }
+                Event::Log(event) => app.send_modify(|app| app.log.apply(event)),
+                Event::Quit => break,
+            },
+            // The other task has sent a message.
+            Some(tick) = receiver.recv() => app.send_modify(|app| app.ticks = tick),
+            // Something has been printed, which is shown in the log.
+            Ok(Some(line)) = stdout.next_line() => app.send_modify(|app| app.log.push(line)),
+            Ok(Some(line)) = stderr.next_line() => {
+                app.send_modify(|app| app.log.push(format!("error: {}", line)));
+            }
+        }
+    }
+
+    drop(events);
+    terminal.cleanup()?;
+    Ok(())
+}
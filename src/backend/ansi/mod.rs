@@ -0,0 +1,518 @@
+use std::cmp;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::{self, Write};
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::style::{Color, ColorSupport, Intensity, Link, Rgb, Rgba, UnderlineKind};
+use crate::util::Base64;
+use crate::{Bitmap, CursorShape, MouseCapture, Vec2};
+
+use super::{sixel, Backend, ReadEvents, TerminalEvent, Tty};
+
+pub(super) mod parser;
+use parser::Parser;
+
+mod terminfo;
+use terminfo::{Cap, Terminfo};
+
+#[cfg(unix)]
+mod unix;
+#[cfg(unix)]
+use unix as sys;
+
+#[cfg(windows)]
+mod windows;
+#[cfg(windows)]
+use windows as sys;
+
+/// How often, in milliseconds, the input thread checks whether the terminal has been resized.
+const RESIZE_POLL_INTERVAL: i32 = 100;
+
+/// How long to wait for the terminal to reply to a query before assuming it doesn't support it, and
+/// parsing input that looks like the start of a reply as keys.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A backend that writes VT100/xterm escape sequences directly, with no extra dependencies.
+///
+/// The only configuration is the [escape timeout](Self::with_escape_timeout).
+///
+/// This supports all features on terminals that understand xterm's escape sequences, which is
+/// nearly all of them, including the Windows console on Windows 10 and later. Input is read and
+/// parsed on a separate thread, which also checks for resizes periodically.
+///
+/// If the terminal named by the `TERM` environment variable has an entry in the terminfo database,
+/// the sequences for moving the cursor, colors and most styles are taken from it instead, so that
+/// terminals like `screen` and `linux` display correctly. Features the entry says the terminal
+/// doesn't have aren't used.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Ansi {
+    escape_timeout: Duration,
+}
+
+impl Ansi {
+    /// Create a new ANSI backend.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long to wait after the escape key is read for the rest of an escape sequence or a
+    /// key pressed with alt, before deciding that the escape key was pressed on its own.
+    ///
+    /// Escape sequences can be split up over slow connections such as SSH sessions, which can be
+    /// fixed by increasing this, at the cost of the escape key responding more slowly. It defaults
+    /// to 50 milliseconds.
+    #[must_use]
+    pub fn with_escape_timeout(self, escape_timeout: Duration) -> Self {
+        Self { escape_timeout }
+    }
+}
+
+impl Default for Ansi {
+    fn default() -> Self {
+        Self {
+            escape_timeout: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Set up the terminal once it is in raw mode.
+fn set_up(io: &mut Tty, terminfo: Option<&Terminfo>) -> io::Result<()> {
+    // Enter the alternate screen, clear it, disable line wrapping, make the mouse report SGR
+    // coordinates once it is captured, bracket pasted text and ask terminals that support the
+    // kitty keyboard protocol to report key repeats, releases and keys like those on the keypad
+    // unambiguously.
+    let sequence = |cap| terminfo::sequence(terminfo, cap, &[]);
+    io.write_all(&sequence(terminfo::ENTER_CA_MODE))?;
+    io.write_all(&sequence(terminfo::CLEAR_SCREEN))?;
+    io.write_all(b"\x1B[?7l\x1B[?1006h\x1B[?2004h\x1B[>3u")?;
+    io.flush()
+}
+
+impl Backend for Ansi {
+    type Error = io::Error;
+    type Bound = Bound;
+
+    fn bind(self, mut io: Tty) -> Result<Self::Bound, Self::Error> {
+        io.flush()?;
+        let raw_mode = sys::RawMode::enable(&io)?;
+        let terminfo = Terminfo::from_env();
+        set_up(&mut io, terminfo.as_ref())?;
+
+        let shared = Arc::new(Mutex::new(Shared {
+            events: VecDeque::new(),
+            background: None,
+            background_queried: false,
+            waker: None,
+            stopped: false,
+        }));
+
+        let mut input = sys::Input::new(&io)?;
+        let mut size = sys::size(&io)?;
+        let escape_timeout = self.escape_timeout;
+        let thread_shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            let mut parser = Parser::default();
+            let mut buf = [0; 1024];
+            let mut events = Vec::new();
+            // When a lone escape was read.
+            let mut escape_read: Option<Instant> = None;
+            // When the background color was last queried.
+            let mut query_sent: Option<Instant> = None;
+
+            loop {
+                let timeout = escape_read.map_or(RESIZE_POLL_INTERVAL, |read| {
+                    let remaining = escape_timeout.saturating_sub(read.elapsed());
+                    cmp::min(remaining.as_millis(), RESIZE_POLL_INTERVAL as u128) as i32
+                });
+                let ready = input.wait(timeout);
+
+                let mut shared = thread_shared.lock().unwrap();
+                if shared.stopped {
+                    break;
+                }
+
+                if mem::take(&mut shared.background_queried) {
+                    parser.await_reply();
+                    query_sent = Some(Instant::now());
+                }
+
+                // Input is only read once it's known that the backend hasn't been reset, so that
+                // input typed afterwards is left for whatever reads the terminal next.
+                let result =
+                    ready.and_then(|ready| if ready { input.read(&mut buf) } else { Ok(0) });
+
+                match result {
+                    Ok(len) => {
+                        parser.feed(&buf[..len], &mut events);
+                        if let Some(background) = parser.background.take() {
+                            shared.background = Some(background);
+                            events.push(TerminalEvent::BackgroundColor(background));
+                        }
+                        if parser.is_awaiting_reply()
+                            && query_sent.map_or(false, |sent| sent.elapsed() >= QUERY_TIMEOUT)
+                        {
+                            parser.stop_awaiting_reply(&mut events);
+                        }
+
+                        if !parser.has_lone_escape() {
+                            escape_read = None;
+                        } else if len > 0 || escape_read.is_none() {
+                            escape_read = Some(Instant::now());
+                        } else if escape_read.map_or(false, |read| read.elapsed() >= escape_timeout)
+                        {
+                            parser.flush_escape(&mut events);
+                            escape_read = None;
+                        }
+                    }
+                    Err(e) => {
+                        shared.events.push_back(Err(e));
+                        shared.wake();
+                        break;
+                    }
+                }
+
+                if let Ok(new_size) = input.size() {
+                    if new_size != size {
+                        size = new_size;
+                        events.push(TerminalEvent::Resize(new_size));
+                    }
+                }
+
+                if !events.is_empty() {
+                    shared.events.extend(events.drain(..).map(Ok));
+                    shared.wake();
+                }
+            }
+        });
+
+        Ok(Bound {
+            io,
+            raw_mode,
+            shared,
+            terminfo,
+            cursor_shape: CursorShape::Block,
+            cursor_blinking: true,
+        })
+    }
+}
+
+/// State shared between the input thread and the bound backend.
+#[derive(Debug)]
+struct Shared {
+    events: VecDeque<io::Result<TerminalEvent>>,
+    /// The background color the terminal replied with to the last query.
+    background: Option<Rgb>,
+    /// Whether the background color has been queried since the input thread last checked, so that
+    /// it should expect a reply.
+    background_queried: bool,
+    waker: Option<Waker>,
+    /// Whether the backend has been reset and the input thread should stop.
+    stopped: bool,
+}
+
+impl Shared {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Bound {
+    io: Tty,
+    raw_mode: sys::RawMode,
+    shared: Arc<Mutex<Shared>>,
+    terminfo: Option<Terminfo>,
+    cursor_shape: CursorShape,
+    cursor_blinking: bool,
+}
+
+impl Bound {
+    /// Write a capability from the terminal's terminfo entry.
+    fn write_cap(&mut self, cap: Cap, params: &[i32]) -> io::Result<()> {
+        self.io
+            .write_all(&terminfo::sequence(self.terminfo.as_ref(), cap, params))
+    }
+
+    fn write_cursor_style(&mut self) -> io::Result<()> {
+        // DECSCUSR
+        let style = match self.cursor_shape {
+            CursorShape::Block => 1,
+            CursorShape::Underline => 3,
+            CursorShape::Bar => 5,
+        } + if self.cursor_blinking { 0 } else { 1 };
+        self.write_cap(terminfo::SET_CURSOR_STYLE, &[style])
+    }
+
+    /// Write a color, where `base` is 30 for the foreground and 40 for the background.
+    fn write_color(&mut self, color: Color, cap: Cap, base: u8) -> io::Result<()> {
+        let colors = self.terminfo.as_ref().map_or(256, Terminfo::colors);
+
+        // Terminals with only 8 colors don't have the bright variants.
+        let color = if colors < 16 { color.to_dark() } else { color };
+
+        let index = match color {
+            Color::Default => return write!(self.io, "\x1B[{}m", base + 9),
+            Color::Black => 0,
+            Color::DarkRed => 1,
+            Color::DarkGreen => 2,
+            Color::DarkYellow => 3,
+            Color::DarkBlue => 4,
+            Color::DarkMagenta => 5,
+            Color::DarkCyan => 6,
+            Color::LightGray => 7,
+            Color::DarkGray => 8,
+            Color::Red => 9,
+            Color::Green => 10,
+            Color::Yellow => 11,
+            Color::Blue => 12,
+            Color::Magenta => 13,
+            Color::Cyan => 14,
+            Color::White => 15,
+            Color::AnsiValue(v) => v.get(),
+            // Translucent colors are blended by the terminal before they get here.
+            Color::Rgb(Rgb { r, g, b }) | Color::Rgba(Rgba { r, g, b, .. }) => {
+                return write!(self.io, "\x1B[{};2;{};{};{}m", base + 8, r, g, b)
+            }
+        };
+        self.write_cap(cap, &[i32::from(index)])
+    }
+}
+
+impl super::Bound for Bound {
+    type Error = io::Error;
+
+    // General functions
+
+    fn size(&mut self) -> Result<Vec2<u16>, Self::Error> {
+        sys::size(&self.io)
+    }
+    fn cell_size(&mut self) -> Result<Option<Vec2<u16>>, Self::Error> {
+        super::cell_size(&self.io)
+    }
+    fn background_color(&mut self) -> Result<Option<Rgb>, Self::Error> {
+        let background = {
+            let mut shared = self.shared.lock().unwrap();
+            shared.background_queried = true;
+            shared.background
+        };
+
+        // OSC 11 with `?` asks the terminal to reply with its background color. The input thread
+        // reads the reply and reports it as an event, so until then the color from the last reply
+        // or a guess from the environment is used.
+        self.io.write_all(b"\x1B]11;?\x1B\\")?;
+        self.io.flush()?;
+
+        Ok(background.or_else(super::background_from_env))
+    }
+    fn set_title(&mut self, title: &str) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B]0;{}\x07", title)
+    }
+    fn push_title(&mut self) -> Result<(), Self::Error> {
+        // XTWINOPS: save the window and icon titles on the stack.
+        self.io.write_all(b"\x1B[22;0t")
+    }
+    fn pop_title(&mut self) -> Result<(), Self::Error> {
+        // XTWINOPS: restore the window and icon titles from the stack.
+        self.io.write_all(b"\x1B[23;0t")
+    }
+    fn set_clipboard(&mut self, text: &str) -> Result<(), Self::Error> {
+        // OSC 52. Since it is an escape sequence this works over SSH too.
+        write!(self.io, "\x1B]52;c;{}\x07", Base64(text.as_bytes()))
+    }
+    fn bell(&mut self) -> Result<(), Self::Error> {
+        self.io.write_all(b"\x07")
+    }
+    fn notify(&mut self, title: &str, body: &str) -> Result<(), Self::Error> {
+        super::write_notification(&mut self.io, title, body)
+    }
+    fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<(), Self::Error> {
+        super::write_mouse_capture(&mut self.io, capture)
+    }
+
+    // Cursor functions
+
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
+        self.io.write_all(b"\x1B[?25l")
+    }
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
+        self.io.write_all(b"\x1B[?25h")
+    }
+    fn set_cursor_shape(&mut self, shape: CursorShape) -> Result<(), Self::Error> {
+        self.cursor_shape = shape;
+        self.write_cursor_style()
+    }
+    fn set_cursor_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
+        self.cursor_blinking = blinking;
+        self.write_cursor_style()
+    }
+    fn set_cursor_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        super::write_cursor_color(&mut self.io, color)
+    }
+    fn set_cursor_pos(&mut self, pos: Vec2<u16>) -> Result<(), Self::Error> {
+        self.write_cap(
+            terminfo::CURSOR_ADDRESS,
+            &[i32::from(pos.y), i32::from(pos.x)],
+        )
+    }
+
+    // Style functions
+
+    fn color_support(&mut self) -> ColorSupport {
+        // `COLORTERM` can indicate true color support even if the terminfo entry doesn't.
+        let detected = ColorSupport::detect();
+        match &self.terminfo {
+            Some(terminfo) if detected != ColorSupport::TrueColor => terminfo.color_support(),
+            _ => detected,
+        }
+    }
+    fn set_foreground(&mut self, foreground: Color) -> Result<(), Self::Error> {
+        self.write_color(foreground, terminfo::SET_A_FOREGROUND, 30)
+    }
+    fn set_background(&mut self, background: Color) -> Result<(), Self::Error> {
+        self.write_color(background, terminfo::SET_A_BACKGROUND, 40)
+    }
+    fn set_intensity(&mut self, intensity: Intensity) -> Result<(), Self::Error> {
+        match intensity {
+            Intensity::Dim => self.write_cap(terminfo::ENTER_DIM_MODE, &[]),
+            // SGR 22 resets both bold and dim. Terminfo has no capability for this.
+            Intensity::Normal => self.io.write_all(b"\x1B[22m"),
+            Intensity::Bold => self.write_cap(terminfo::ENTER_BOLD_MODE, &[]),
+        }
+    }
+    fn set_italic(&mut self, italic: bool) -> Result<(), Self::Error> {
+        if italic {
+            self.write_cap(terminfo::ENTER_ITALICS_MODE, &[])
+        } else {
+            self.write_cap(terminfo::EXIT_ITALICS_MODE, &[])
+        }
+    }
+    fn set_underlined(&mut self, underlined: bool) -> Result<(), Self::Error> {
+        if underlined {
+            self.write_cap(terminfo::ENTER_UNDERLINE_MODE, &[])
+        } else {
+            self.write_cap(terminfo::EXIT_UNDERLINE_MODE, &[])
+        }
+    }
+    fn set_underline_kind(&mut self, kind: UnderlineKind) -> Result<(), Self::Error> {
+        super::write_underline_kind(&mut self.io, kind)
+    }
+    fn set_underline_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        super::write_underline_color(&mut self.io, color)
+    }
+    fn set_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
+        if blinking {
+            self.write_cap(terminfo::ENTER_BLINK_MODE, &[])
+        } else {
+            self.io.write_all(b"\x1B[25m")
+        }
+    }
+    fn set_crossed_out(&mut self, crossed_out: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if crossed_out { 9 } else { 29 })
+    }
+    fn set_reversed(&mut self, reversed: bool) -> Result<(), Self::Error> {
+        if reversed {
+            self.write_cap(terminfo::ENTER_REVERSE_MODE, &[])
+        } else {
+            self.io.write_all(b"\x1B[27m")
+        }
+    }
+    fn set_hidden(&mut self, hidden: bool) -> Result<(), Self::Error> {
+        if hidden {
+            self.write_cap(terminfo::ENTER_SECURE_MODE, &[])
+        } else {
+            self.io.write_all(b"\x1B[28m")
+        }
+    }
+    fn set_overlined(&mut self, overlined: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if overlined { 53 } else { 55 })
+    }
+    fn set_link(&mut self, link: Option<&Link>) -> Result<(), Self::Error> {
+        // OSC 8. Terminals that don't support hyperlinks ignore it.
+        write!(self.io, "\x1B]8;;{}\x1B\\", link.map_or("", Link::url))
+    }
+
+    // Graphics functions
+
+    fn supports_graphics(&mut self) -> bool {
+        sixel::detect()
+    }
+    fn draw_image(&mut self, size: Vec2<u16>, bitmap: &Bitmap) -> Result<(), Self::Error> {
+        let cell_size = self.cell_size()?;
+        sixel::write(&mut self.io, bitmap, size, cell_size)
+    }
+
+    // Writing
+
+    fn write(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.io.write_all(text.as_bytes())
+    }
+
+    // Finalizing functions
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.io.flush()
+    }
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        self.raw_mode.reenable()?;
+        // Pop the keyboard flags pushed when the backend was bound in case the terminal still has
+        // them, so that they aren't pushed twice.
+        self.io.write_all(b"\x1B[<u")?;
+        set_up(&mut self.io, self.terminfo.as_ref())
+    }
+    fn reset(mut self) -> Result<Tty, Self::Error> {
+        self.shared.lock().unwrap().stopped = true;
+
+        super::write_mouse_capture(&mut self.io, MouseCapture::None)?;
+        self.io
+            .write_all(b"\x1B[<u\x1B[?2004l\x1B[?1006l\x1B[?7h")?;
+        self.write_cap(terminfo::EXIT_ATTRIBUTE_MODE, &[])?;
+        self.io.write_all(b"\x1B[?25h")?;
+        super::write_cursor_color(&mut self.io, Color::Default)?;
+        self.write_cap(terminfo::EXIT_CA_MODE, &[])?;
+        self.io.flush()?;
+        self.raw_mode.disable()?;
+
+        Ok(self.io)
+    }
+}
+
+impl<'a> ReadEvents<'a> for Bound {
+    type EventError = <Self as super::Bound>::Error;
+    type EventFuture = EventFuture<'a>;
+
+    fn read_event(&'a mut self) -> Self::EventFuture {
+        EventFuture {
+            shared: &self.shared,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EventFuture<'a> {
+    shared: &'a Mutex<Shared>,
+}
+
+impl<'a> Future for EventFuture<'a> {
+    type Output = io::Result<TerminalEvent>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.events.pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,649 @@
+//! Parsing of terminal input.
+
+use crate::input::{Key, KeyKind, KeyPress, MediaKey, Modifiers, MouseButton};
+use crate::{Rgb, Vec2};
+
+use super::super::{TerminalEvent, TerminalMouse, TerminalMouseKind};
+
+const ESC: u8 = 0x1B;
+
+/// The sequences that surround pasted text when bracketed paste mode is enabled.
+const PASTE_START: &[u8] = b"\x1B[200~";
+const PASTE_END: &[u8] = b"\x1B[201~";
+
+/// An incremental parser of the bytes that the terminal sends as input.
+#[derive(Debug, Default)]
+pub(in crate::backend) struct Parser {
+    /// Bytes of an incomplete sequence from the previous read.
+    buffer: Vec<u8>,
+    /// Whether the terminal is expected to reply to a query.
+    awaiting_reply: bool,
+    /// The background color the terminal last replied with to an OSC 11 query.
+    pub(super) background: Option<Rgb>,
+}
+
+impl Parser {
+    /// Parse a chunk of input that was read in one go, pushing the events in it.
+    ///
+    /// Incomplete sequences at the end of the chunk are kept to be completed by the next chunk. If
+    /// that leaves a lone escape, [`flush_escape`](Self::flush_escape) should be called if nothing
+    /// more is read for a while.
+    pub(in crate::backend) fn feed(&mut self, bytes: &[u8], events: &mut Vec<TerminalEvent>) {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut start = 0;
+        loop {
+            let bytes = &self.buffer[start..];
+            // Replies to queries are operating system commands starting with a number. Escape
+            // followed by `]` and anything else, or when no reply is expected, is alt+`]`.
+            let osc = self.awaiting_reply
+                && bytes.starts_with(b"\x1B]")
+                && bytes.get(2).map_or(false, u8::is_ascii_digit);
+            let len = if osc {
+                match parse_osc(bytes) {
+                    Some((len, background)) => {
+                        if background.is_some() {
+                            self.background = background;
+                            self.awaiting_reply = false;
+                        }
+                        len
+                    }
+                    None => break,
+                }
+            } else {
+                match parse(bytes) {
+                    Some((len, event)) => {
+                        events.extend(event);
+                        len
+                    }
+                    None => break,
+                }
+            };
+            start += len;
+        }
+        self.buffer.drain(..start);
+    }
+
+    /// Expect the terminal to reply to a background color query.
+    ///
+    /// Until the reply is read or [`stop_awaiting_reply`](Self::stop_awaiting_reply) is called,
+    /// escape followed by `]` and a digit is the start of a reply instead of alt+`]`.
+    pub(in crate::backend) fn await_reply(&mut self) {
+        self.awaiting_reply = true;
+    }
+
+    /// Get whether a reply to a query is still expected.
+    pub(in crate::backend) fn is_awaiting_reply(&self) -> bool {
+        self.awaiting_reply
+    }
+
+    /// Stop expecting a reply to a query, for when the terminal didn't reply in time. The start of
+    /// a reply that is still incomplete is parsed as keys instead.
+    pub(in crate::backend) fn stop_awaiting_reply(&mut self, events: &mut Vec<TerminalEvent>) {
+        self.awaiting_reply = false;
+        self.feed(&[], events);
+    }
+
+    /// Get whether the input so far ends with an escape that could be the escape key or the start
+    /// of a sequence.
+    pub(in crate::backend) fn has_lone_escape(&self) -> bool {
+        self.buffer == [ESC]
+    }
+
+    /// Treat a lone escape at the end of the input as the escape key, instead of waiting for the
+    /// rest of a sequence.
+    pub(in crate::backend) fn flush_escape(&mut self, events: &mut Vec<TerminalEvent>) {
+        if self.has_lone_escape() {
+            self.buffer.clear();
+            events.push(TerminalEvent::Key(key_press(
+                Key::Escape,
+                Modifiers::default(),
+            )));
+        }
+    }
+}
+
+/// Parse a single event from the start of the bytes, returning how many bytes it took up.
+///
+/// Returns `None` if the bytes are empty or an incomplete sequence. Unrecognized sequences are
+/// skipped over without producing an event.
+fn parse(bytes: &[u8]) -> Option<(usize, Option<TerminalEvent>)> {
+    if *bytes.first()? != ESC {
+        let (len, key) = parse_char(bytes)?;
+        return Some((len, key.map(TerminalEvent::Key)));
+    }
+
+    if bytes.starts_with(PASTE_START) {
+        return parse_paste(bytes);
+    }
+
+    Some(match *bytes.get(1)? {
+        b'[' => parse_csi(bytes)?,
+        b'O' => {
+            let key = match *bytes.get(2)? {
+                b'A' => Some(Key::Up),
+                b'B' => Some(Key::Down),
+                b'C' => Some(Key::Right),
+                b'D' => Some(Key::Left),
+                b'H' => Some(Key::Home),
+                b'F' => Some(Key::End),
+                c @ b'P'..=b'S' => Some(Key::F(c - b'P' + 1)),
+                // The keypad in application mode.
+                b'M' => Some(Key::Keypad('\n')),
+                b'X' => Some(Key::Keypad('=')),
+                c @ b'j'..=b'o' => Some(Key::Keypad(char::from(b"*+,-./"[usize::from(c - b'j')]))),
+                c @ b'p'..=b'y' => Some(Key::Keypad(char::from(c - b'p' + b'0'))),
+                _ => None,
+            };
+            (
+                3,
+                key.map(|key| TerminalEvent::Key(key_press(key, Modifiers::default()))),
+            )
+        }
+        ESC => (
+            1,
+            Some(TerminalEvent::Key(key_press(
+                Key::Escape,
+                Modifiers::default(),
+            ))),
+        ),
+        // Escape followed by a key is that key pressed with alt.
+        _ => {
+            let (len, key) = parse_char(&bytes[1..])?;
+            let key = key.map(|key| KeyPress {
+                modifiers: key.modifiers | Modifiers::ALT,
+                ..key
+            });
+            (len + 1, key.map(TerminalEvent::Key))
+        }
+    })
+}
+
+/// Parse a single character or control character.
+fn parse_char(bytes: &[u8]) -> Option<(usize, Option<KeyPress>)> {
+    let control = |c: u8| Some((1, Some(key_press(Key::Char(c as char), Modifiers::CONTROL))));
+
+    match bytes[0] {
+        b'\r' | b'\n' => Some((1, Some(KeyPress::from('\n')))),
+        b'\t' => Some((1, Some(KeyPress::from('\t')))),
+        0x7F | 0x08 => Some((1, Some(key_press(Key::Backspace, Modifiers::default())))),
+        0x00 => control(b' '),
+        c @ 0x01..=0x1A => control(c - 0x01 + b'a'),
+        c @ 0x1C..=0x1F => control(c - 0x1C + b'4'),
+        first => {
+            let len = match first {
+                0x00..=0x7F => 1,
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => return Some((1, None)),
+            };
+            let bytes = bytes.get(..len)?;
+
+            Some(match std::str::from_utf8(bytes) {
+                Ok(s) => (len, s.chars().next().map(KeyPress::from)),
+                Err(_) => (1, None),
+            })
+        }
+    }
+}
+
+/// Parse bracketed pasted text, starting with `ESC [ 200 ~` and ending with `ESC [ 201 ~`.
+fn parse_paste(bytes: &[u8]) -> Option<(usize, Option<TerminalEvent>)> {
+    let text = &bytes[PASTE_START.len()..];
+    let end = text
+        .windows(PASTE_END.len())
+        .position(|window| window == PASTE_END)?;
+
+    // Terminals send line breaks in pasted text as carriage returns.
+    let text = String::from_utf8_lossy(&text[..end])
+        .replace("\r\n", "\n")
+        .replace('\r', "\n");
+
+    Some((
+        PASTE_START.len() + end + PASTE_END.len(),
+        Some(TerminalEvent::Paste(text)),
+    ))
+}
+
+/// Parse a control sequence introducer sequence, starting with `ESC [`.
+fn parse_csi(bytes: &[u8]) -> Option<(usize, Option<TerminalEvent>)> {
+    match *bytes.get(2)? {
+        b'<' => return parse_sgr_mouse(bytes),
+        // An X10 mouse sequence; these are never enabled, so skip over it.
+        b'M' => return bytes.get(5).map(|_| (6, None)),
+        _ => {}
+    }
+
+    let end = bytes[2..]
+        .iter()
+        .position(|byte| (0x40..=0x7E).contains(byte))?
+        + 2;
+    let params = parse_params(&bytes[2..end]);
+    let param = |i: usize| params.as_ref().and_then(|params| params.get(i).copied());
+
+    let modifiers = param(1).map_or_else(Modifiers::default, from_modifier_param);
+    let kind = parse_key_kind(&bytes[2..end]);
+
+    let key = match bytes[end] {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'Z' => {
+            return Some((
+                end + 1,
+                Some(TerminalEvent::Key(key_press(
+                    Key::Char('\t'),
+                    Modifiers::SHIFT,
+                ))),
+            ))
+        }
+        b'~' => match param(0) {
+            Some(1) | Some(7) => Key::Home,
+            Some(2) => Key::Insert,
+            Some(3) => Key::Char('\x7f'),
+            Some(4) | Some(8) => Key::End,
+            Some(5) => Key::PageUp,
+            Some(6) => Key::PageDown,
+            Some(n @ 11..=15) => Key::F((n - 10) as u8),
+            Some(n @ 17..=21) => Key::F((n - 11) as u8),
+            // F13 to F20 continue on from F11 and F12 on terminals emulating the VT220's keyboard.
+            Some(n @ 23..=26) => Key::F((n - 12) as u8),
+            Some(n @ 28..=29) => Key::F((n - 13) as u8),
+            Some(n @ 31..=34) => Key::F((n - 14) as u8),
+            _ => return Some((end + 1, None)),
+        },
+        // The kitty keyboard protocol, where the parameter is the Unicode code point of the key.
+        b'u' => match param(0).map(u32::from).and_then(std::char::from_u32) {
+            // Keys with no character use code points in the private use area.
+            Some(c @ '\u{E000}'..='\u{F8FF}') => match kitty_functional_key(c) {
+                Some(key) => key,
+                None => return Some((end + 1, None)),
+            },
+            Some('\x1B') => Key::Escape,
+            Some('\r') => Key::Char('\n'),
+            Some('\x7F') => Key::Backspace,
+            Some(c) if c == '\t' || !c.is_control() => Key::Char(c.to_ascii_lowercase()),
+            _ => return Some((end + 1, None)),
+        },
+        _ => return Some((end + 1, None)),
+    };
+
+    Some((
+        end + 1,
+        Some(TerminalEvent::Key(KeyPress {
+            key,
+            modifiers,
+            kind,
+        })),
+    ))
+}
+
+/// Get the key of a code point in the private use area that the kitty keyboard protocol uses for
+/// a key with no character.
+///
+/// Keypad keys that don't type anything are treated like their equivalents on the main keyboard.
+fn kitty_functional_key(c: char) -> Option<Key> {
+    Some(match c as u32 {
+        n @ 57376..=57398 => Key::F((n - 57376 + 13) as u8),
+        n @ 57399..=57408 => Key::Keypad(char::from((n - 57399) as u8 + b'0')),
+        n @ 57409..=57416 => Key::Keypad(char::from(b"./*-+\n=,"[(n - 57409) as usize])),
+        57417 => Key::Left,
+        57418 => Key::Right,
+        57419 => Key::Up,
+        57420 => Key::Down,
+        57421 => Key::PageUp,
+        57422 => Key::PageDown,
+        57423 => Key::Home,
+        57424 => Key::End,
+        57425 => Key::Insert,
+        57426 => Key::Char('\x7F'),
+        n @ 57428..=57440 => Key::Media(MediaKey::ALL[(n - 57428) as usize]),
+        _ => return None,
+    })
+}
+
+/// Parse an SGR mouse sequence, starting with `ESC [ <`.
+fn parse_sgr_mouse(bytes: &[u8]) -> Option<(usize, Option<TerminalEvent>)> {
+    let end = bytes[3..]
+        .iter()
+        .position(|&byte| !byte.is_ascii_digit() && byte != b';')?
+        + 3;
+    let len = end + 1;
+
+    let params = match parse_params(&bytes[3..end]) {
+        Some(params) if params.len() == 3 && matches!(bytes[end], b'M' | b'm') => params,
+        _ => return Some((len, None)),
+    };
+    let (code, x, y) = (params[0], params[1], params[2]);
+
+    let kind = if code & 64 != 0 {
+        match code & 0b11 {
+            0 => TerminalMouseKind::ScrollUp,
+            1 => TerminalMouseKind::ScrollDown,
+            2 => TerminalMouseKind::ScrollLeft,
+            _ => TerminalMouseKind::ScrollRight,
+        }
+    } else if bytes[end] == b'm' {
+        TerminalMouseKind::Release
+    } else if code & 32 != 0 {
+        TerminalMouseKind::Move
+    } else {
+        TerminalMouseKind::Press(match code & 0b11 {
+            0 => MouseButton::Left,
+            1 => MouseButton::Middle,
+            2 => MouseButton::Right,
+            _ => return Some((len, None)),
+        })
+    };
+
+    Some((
+        len,
+        Some(TerminalEvent::Mouse(TerminalMouse {
+            kind,
+            // The coordinates are one-indexed.
+            at: Vec2::new(x.saturating_sub(1), y.saturating_sub(1)),
+            modifiers: Modifiers {
+                shift: code & 4 != 0,
+                alt: code & 8 != 0,
+                control: code & 16 != 0,
+            },
+        })),
+    ))
+}
+
+/// Parse an operating system command, starting with `ESC ]` and ending with BEL or `ESC \`.
+///
+/// Only replies to OSC 11 background color queries, like `ESC ] 11 ; rgb:ffff/ffff/ffff BEL`, are
+/// recognized.
+fn parse_osc(bytes: &[u8]) -> Option<(usize, Option<Rgb>)> {
+    let end = bytes[2..]
+        .iter()
+        .position(|&byte| byte == 0x07 || byte == ESC)?
+        + 2;
+    let len = if bytes[end] == ESC {
+        bytes.get(end + 1)?;
+        end + 2
+    } else {
+        end + 1
+    };
+
+    let background = std::str::from_utf8(&bytes[2..end])
+        .ok()
+        .and_then(|command| command.strip_prefix("11;rgb:"))
+        .and_then(|color| {
+            // Each component has one to four hex digits.
+            let mut components = color.split('/').map(|component| {
+                if component.is_empty() || component.len() > 4 {
+                    return None;
+                }
+                let value = u32::from_str_radix(component, 16).ok()?;
+                let max = (1 << (component.len() * 4)) - 1;
+                Some((value * 255 / max) as u8)
+            });
+            let rgb = Rgb::new(
+                components.next()??,
+                components.next()??,
+                components.next()??,
+            );
+            Some(rgb).filter(|_| components.next().is_none())
+        });
+
+    Some((len, background))
+}
+
+/// Parse the kind of a key from the event type in the kitty keyboard protocol, which follows the
+/// modifier parameter after a colon, like the `3` in `ESC [ 97 ; 1 : 3 u`.
+fn parse_key_kind(params: &[u8]) -> KeyKind {
+    let event_type = params
+        .split(|&byte| byte == b';')
+        .nth(1)
+        .and_then(|param| param.split(|&byte| byte == b':').nth(1));
+    match event_type {
+        Some(b"2") => KeyKind::Repeat,
+        Some(b"3") => KeyKind::Release,
+        _ => KeyKind::Press,
+    }
+}
+
+/// Parse semicolon-separated numeric parameters. Empty parameters are treated as 1, and
+/// colon-separated subparameters are ignored.
+fn parse_params(bytes: &[u8]) -> Option<Vec<u16>> {
+    if bytes.is_empty() {
+        return Some(Vec::new());
+    }
+    std::str::from_utf8(bytes)
+        .ok()?
+        .split(';')
+        .map(|param| param.split(':').next().unwrap())
+        .map(|param| {
+            if param.is_empty() {
+                Some(1)
+            } else {
+                param.parse().ok()
+            }
+        })
+        .collect()
+}
+
+/// Convert an xterm modifier parameter to modifiers.
+fn from_modifier_param(param: u16) -> Modifiers {
+    let bits = param.saturating_sub(1);
+    Modifiers {
+        shift: bits & 1 != 0,
+        alt: bits & 2 != 0,
+        control: bits & 4 != 0,
+    }
+}
+
+fn key_press(key: Key, modifiers: Modifiers) -> KeyPress {
+    KeyPress {
+        key,
+        modifiers,
+        kind: KeyKind::Press,
+    }
+}
+
+#[test]
+fn test_parser() {
+    fn key(key: Key, modifiers: Modifiers) -> TerminalEvent {
+        TerminalEvent::Key(key_press(key, modifiers))
+    }
+    fn parse_all(chunks: &[&[u8]]) -> Vec<TerminalEvent> {
+        let mut parser = Parser::default();
+        let mut events = Vec::new();
+        for chunk in chunks {
+            parser.feed(chunk, &mut events);
+        }
+        parser.flush_escape(&mut events);
+        events
+    }
+    let none = Modifiers::default();
+
+    assert_eq!(
+        parse_all(&[b"aB\r\x7f\x01"]),
+        [
+            key(Key::Char('a'), none),
+            key(Key::Char('b'), Modifiers::SHIFT),
+            key(Key::Char('\n'), none),
+            key(Key::Backspace, none),
+            key(Key::Char('a'), Modifiers::CONTROL),
+        ]
+    );
+    assert_eq!(
+        parse_all(&["é".as_bytes(), b"\x1Bx"]),
+        [
+            key(Key::Char('é'), none),
+            key(Key::Char('x'), Modifiers::ALT),
+        ]
+    );
+    assert_eq!(
+        parse_all(&[b"\x1B[A\x1B[1;5C\x1BOP\x1B[3~\x1B[15;2~\x1B[Z"]),
+        [
+            key(Key::Up, none),
+            key(Key::Right, Modifiers::CONTROL),
+            key(Key::F(1), none),
+            key(Key::Char('\x7f'), none),
+            key(Key::F(5), Modifiers::SHIFT),
+            key(Key::Char('\t'), Modifiers::SHIFT),
+        ]
+    );
+
+    let kind = |kind, event| match event {
+        TerminalEvent::Key(press) => TerminalEvent::Key(KeyPress { kind, ..press }),
+        event => event,
+    };
+    assert_eq!(
+        parse_all(&[b"\x1B[97u\x1B[97;2:2u\x1B[97;1:3u\x1B[27u\x1B[13;5u\x1B[1;1:3A"]),
+        [
+            key(Key::Char('a'), none),
+            kind(KeyKind::Repeat, key(Key::Char('a'), Modifiers::SHIFT)),
+            kind(KeyKind::Release, key(Key::Char('a'), none)),
+            key(Key::Escape, none),
+            key(Key::Char('\n'), Modifiers::CONTROL),
+            kind(KeyKind::Release, key(Key::Up, none)),
+        ]
+    );
+
+    let mut parser = Parser::default();
+    let mut events = Vec::new();
+    parser.await_reply();
+    parser.feed(b"\x1B]10;rgb:0/0/0\x07\x1B]11;rgb:ffff/8080/0", &mut events);
+    assert_eq!(parser.background, None);
+    parser.feed(b"\x1B\\a\x1B]1", &mut events);
+    assert_eq!(parser.background, Some(Rgb::new(255, 128, 0)));
+    assert!(!parser.is_awaiting_reply());
+    assert_eq!(
+        events,
+        [
+            key(Key::Char('a'), none),
+            key(Key::Char(']'), Modifiers::ALT),
+            key(Key::Char('1'), none),
+        ]
+    );
+
+    // Something that looks like the start of a reply is keys if the reply doesn't come.
+    let mut events = Vec::new();
+    parser.await_reply();
+    parser.feed(b"\x1B]1", &mut events);
+    assert_eq!(events, []);
+    parser.stop_awaiting_reply(&mut events);
+    assert_eq!(
+        events,
+        [
+            key(Key::Char(']'), Modifiers::ALT),
+            key(Key::Char('1'), none),
+        ]
+    );
+
+    // A lone escape is the escape key once it is flushed, but sequences can be split across reads.
+    assert_eq!(parse_all(&[b"\x1B"]), [key(Key::Escape, none)]);
+    assert_eq!(parse_all(&[b"\x1B", b"[A"]), [key(Key::Up, none)]);
+    assert_eq!(
+        parse_all(&[b"\x1B[1;", b"2B"]),
+        [key(Key::Down, Modifiers::SHIFT)]
+    );
+    assert_eq!(parse_all(&[b"\xC3", b"\xA9"]), [key(Key::Char('é'), none)]);
+}
+
+#[test]
+fn test_parse_paste() {
+    let mut parser = Parser::default();
+    let mut events = Vec::new();
+    parser.feed(b"a\x1B[200~one\r\ntwo\rthree\x1B", &mut events);
+    assert_eq!(events, [TerminalEvent::Key(KeyPress::from('a'))]);
+
+    parser.feed(b"[A\x1B[201~b", &mut events);
+    assert_eq!(
+        events[1..],
+        [
+            TerminalEvent::Paste("one\ntwo\nthree\x1B[A".to_owned()),
+            TerminalEvent::Key(KeyPress::from('b')),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_extended_keys() {
+    let parse_all = |bytes: &[u8]| {
+        let mut events = Vec::new();
+        Parser::default().feed(bytes, &mut events);
+        events
+    };
+    let key = |key| TerminalEvent::Key(key_press(key, Modifiers::default()));
+
+    assert_eq!(
+        parse_all(b"\x1BOp\x1BOy\x1BOk\x1BOM\x1BOX"),
+        [
+            key(Key::Keypad('0')),
+            key(Key::Keypad('9')),
+            key(Key::Keypad('+')),
+            key(Key::Keypad('\n')),
+            key(Key::Keypad('=')),
+        ]
+    );
+    assert_eq!(
+        parse_all(b"\x1B[25~\x1B[29~\x1B[34~"),
+        [key(Key::F(13)), key(Key::F(16)), key(Key::F(20))]
+    );
+    assert_eq!(
+        parse_all(b"\x1B[57376u\x1B[57398u\x1B[57399u\x1B[57410;5u\x1B[57414u\x1B[57417u"),
+        [
+            key(Key::F(13)),
+            key(Key::F(35)),
+            key(Key::Keypad('0')),
+            TerminalEvent::Key(key_press(Key::Keypad('/'), Modifiers::CONTROL)),
+            key(Key::Keypad('\n')),
+            key(Key::Left),
+        ]
+    );
+    assert_eq!(
+        parse_all(b"\x1B[57430u\x1B[57440u\x1B[57427u\x1B[57441u"),
+        [
+            key(Key::Media(MediaKey::PlayPause)),
+            key(Key::Media(MediaKey::Mute)),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_mouse() {
+    let mut events = Vec::new();
+    Parser::default().feed(
+        b"\x1B[<0;3;5M\x1B[<0;3;5m\x1B[<65;1;1M\x1B[<66;1;1M\x1B[<50;2;2M",
+        &mut events,
+    );
+    assert_eq!(
+        events,
+        [
+            TerminalEvent::Mouse(TerminalMouse {
+                kind: TerminalMouseKind::Press(MouseButton::Left),
+                at: Vec2::new(2, 4),
+                modifiers: Modifiers::default(),
+            }),
+            TerminalEvent::Mouse(TerminalMouse {
+                kind: TerminalMouseKind::Release,
+                at: Vec2::new(2, 4),
+                modifiers: Modifiers::default(),
+            }),
+            TerminalEvent::Mouse(TerminalMouse {
+                kind: TerminalMouseKind::ScrollDown,
+                at: Vec2::new(0, 0),
+                modifiers: Modifiers::default(),
+            }),
+            TerminalEvent::Mouse(TerminalMouse {
+                kind: TerminalMouseKind::ScrollLeft,
+                at: Vec2::new(0, 0),
+                modifiers: Modifiers::default(),
+            }),
+            TerminalEvent::Mouse(TerminalMouse {
+                kind: TerminalMouseKind::Move,
+                at: Vec2::new(1, 1),
+                modifiers: Modifiers::CONTROL,
+            }),
+        ]
+    );
+}
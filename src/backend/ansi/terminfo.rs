@@ -0,0 +1,570 @@
+//! Reading the terminfo database, so that the backend can use the right escape sequences on
+//! terminals that aren't xterm-compatible.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::style::ColorSupport;
+
+/// The index of the `colors` number capability.
+const MAX_COLORS: usize = 13;
+
+/// A string capability, along with the sequence xterm uses for it.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Cap {
+    id: CapId,
+    /// The sequence to use when there is no terminfo entry.
+    xterm: &'static [u8],
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CapId {
+    /// A standard capability, stored by its index.
+    Standard(usize),
+    /// An extended capability, stored by its name.
+    Extended(&'static str),
+}
+
+macro_rules! caps {
+    ($($(#[doc = $doc:literal])* $name:ident = $id:expr, $xterm:literal;)*) => {
+        $(
+            $(#[doc = $doc])*
+            pub(super) const $name: Cap = Cap {
+                id: $id,
+                xterm: $xterm,
+            };
+        )*
+    }
+}
+
+caps! {
+    /// `clear`: Clear the screen and move the cursor to the top left.
+    CLEAR_SCREEN = CapId::Standard(5), b"\x1B[H\x1B[2J";
+    /// `cup`: Move the cursor to row `%p1` and column `%p2`.
+    CURSOR_ADDRESS = CapId::Standard(10), b"\x1B[%i%p1%d;%p2%dH";
+    /// `blink`: Start blinking text.
+    ENTER_BLINK_MODE = CapId::Standard(26), b"\x1B[5m";
+    /// `bold`: Start bold text.
+    ENTER_BOLD_MODE = CapId::Standard(27), b"\x1B[1m";
+    /// `smcup`: Enter the alternate screen.
+    ENTER_CA_MODE = CapId::Standard(28), b"\x1B[?1049h";
+    /// `dim`: Start dim text.
+    ENTER_DIM_MODE = CapId::Standard(30), b"\x1B[2m";
+    /// `invis`: Start hidden text.
+    ENTER_SECURE_MODE = CapId::Standard(32), b"\x1B[8m";
+    /// `rev`: Start reversed text.
+    ENTER_REVERSE_MODE = CapId::Standard(34), b"\x1B[7m";
+    /// `smul`: Start underlined text.
+    ENTER_UNDERLINE_MODE = CapId::Standard(36), b"\x1B[4m";
+    /// `sgr0`: Turn off all attributes and colors.
+    EXIT_ATTRIBUTE_MODE = CapId::Standard(39), b"\x1B[0m";
+    /// `rmcup`: Leave the alternate screen.
+    EXIT_CA_MODE = CapId::Standard(40), b"\x1B[?1049l";
+    /// `rmul`: Stop underlined text.
+    EXIT_UNDERLINE_MODE = CapId::Standard(44), b"\x1B[24m";
+    /// `sitm`: Start italic text.
+    ENTER_ITALICS_MODE = CapId::Standard(311), b"\x1B[3m";
+    /// `ritm`: Stop italic text.
+    EXIT_ITALICS_MODE = CapId::Standard(321), b"\x1B[23m";
+    /// `setaf`: Set the foreground to the ANSI color `%p1`.
+    SET_A_FOREGROUND = CapId::Standard(359),
+        b"\x1B[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m";
+    /// `setab`: Set the background to the ANSI color `%p1`.
+    SET_A_BACKGROUND = CapId::Standard(360),
+        b"\x1B[%?%p1%{8}%<%t4%p1%d%e%p1%{16}%<%t10%p1%{8}%-%d%e48;5;%p1%d%;m";
+    /// `Ss`: Set the cursor style to the DECSCUSR value `%p1`.
+    SET_CURSOR_STYLE = CapId::Extended("Ss"), b"\x1B[%p1%d q";
+}
+
+/// A parsed terminfo entry.
+#[derive(Debug, Default)]
+pub(super) struct Terminfo {
+    /// Number capabilities; absent ones are negative.
+    numbers: Vec<i32>,
+    strings: Vec<Option<Vec<u8>>>,
+    /// The names of the extended boolean capabilities that are set.
+    extended_flags: Vec<String>,
+    extended_strings: HashMap<String, Vec<u8>>,
+}
+
+impl Terminfo {
+    /// Load the terminfo entry of the terminal named by the `TERM` environment variable.
+    pub(super) fn from_env() -> Option<Self> {
+        Self::load(&env::var("TERM").ok()?)
+    }
+
+    /// Load the terminfo entry of a terminal from the terminfo database.
+    pub(super) fn load(term: &str) -> Option<Self> {
+        let first = term.chars().next()?;
+        if term.contains(std::path::is_separator) {
+            return None;
+        }
+
+        // Entries are stored under their first letter, or its hexadecimal value on systems with
+        // case-insensitive filesystems.
+        let subdirs = [first.to_string(), format!("{:x}", u32::from(first))];
+
+        search_dirs()
+            .iter()
+            .flat_map(|dir| {
+                subdirs
+                    .iter()
+                    .map(move |subdir| dir.join(subdir).join(term))
+            })
+            .find_map(|path| fs::read(path).ok())
+            .and_then(|bytes| Self::parse(&bytes))
+    }
+
+    /// Parse a compiled terminfo entry, in either the legacy or the 32-bit number format.
+    pub(super) fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let number_size = match reader.i16()? {
+            0o432 => 2,
+            0o1036 => 4,
+            _ => return None,
+        };
+        let names_size = reader.count()?;
+        let bools = reader.count()?;
+        let numbers = reader.count()?;
+        let strings = reader.count()?;
+        let table_size = reader.count()?;
+
+        reader.take(names_size + bools)?;
+        reader.align();
+        let numbers = (0..numbers)
+            .map(|_| reader.number(number_size))
+            .collect::<Option<_>>()?;
+        let offsets = (0..strings)
+            .map(|_| reader.i16())
+            .collect::<Option<Vec<_>>>()?;
+        let table = reader.take(table_size)?;
+
+        let mut terminfo = Self {
+            numbers,
+            strings: offsets
+                .iter()
+                .map(|&offset| string_at(table, offset))
+                .collect(),
+            ..Self::default()
+        };
+
+        // The extended section is optional, and a broken one shouldn't prevent using the standard
+        // capabilities.
+        reader.align();
+        let _ = terminfo.parse_extended(&mut reader, number_size);
+
+        Some(terminfo)
+    }
+
+    fn parse_extended(&mut self, reader: &mut Reader<'_>, number_size: usize) -> Option<()> {
+        let bools = reader.count()?;
+        let numbers = reader.count()?;
+        let strings = reader.count()?;
+        let _items = reader.count()?;
+        let table_size = reader.count()?;
+
+        let flags = reader.take(bools)?;
+        reader.align();
+        reader.take(numbers * number_size)?;
+        let value_offsets = (0..strings)
+            .map(|_| reader.i16())
+            .collect::<Option<Vec<_>>>()?;
+        let name_offsets = (0..bools + numbers + strings)
+            .map(|_| reader.i16())
+            .collect::<Option<Vec<_>>>()?;
+        let table = reader.take(table_size)?;
+
+        // The names are stored in the table after all the values.
+        let names_start = value_offsets
+            .iter()
+            .filter_map(|&offset| {
+                let value = string_at(table, offset)?;
+                Some(usize::try_from(offset).ok()? + value.len() + 1)
+            })
+            .max()
+            .unwrap_or(0);
+        let names = table.get(names_start..)?;
+        let name = |i: usize| String::from_utf8(string_at(names, name_offsets[i])?).ok();
+
+        for (i, &flag) in flags.iter().enumerate() {
+            if flag == 1 {
+                self.extended_flags.extend(name(i));
+            }
+        }
+        for (i, &offset) in value_offsets.iter().enumerate() {
+            if let (Some(name), Some(value)) = (name(bools + numbers + i), string_at(table, offset))
+            {
+                self.extended_strings.insert(name, value);
+            }
+        }
+
+        Some(())
+    }
+
+    /// Get a number capability.
+    fn number(&self, index: usize) -> Option<i32> {
+        self.numbers.get(index).copied().filter(|&n| n >= 0)
+    }
+
+    /// Get a string capability.
+    fn string(&self, cap: Cap) -> Option<&[u8]> {
+        match cap.id {
+            CapId::Standard(index) => self.strings.get(index)?.as_deref(),
+            CapId::Extended(name) => self.extended_strings.get(name).map(Vec::as_slice),
+        }
+    }
+
+    /// Get the number of colors the terminal supports.
+    pub(super) fn colors(&self) -> i32 {
+        self.number(MAX_COLORS).unwrap_or(0)
+    }
+
+    /// Get the colors the terminal supports.
+    ///
+    /// The `Tc` and `RGB` extended capabilities indicate true color support.
+    pub(super) fn color_support(&self) -> ColorSupport {
+        if self
+            .extended_flags
+            .iter()
+            .any(|flag| flag == "Tc" || flag == "RGB")
+        {
+            ColorSupport::TrueColor
+        } else if self.colors() >= 256 {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+}
+
+/// Get the sequence of a capability with the given parameters.
+///
+/// If there is no terminfo entry xterm's sequence is used. If the entry doesn't have the
+/// capability the terminal doesn't support it, so this is empty.
+pub(super) fn sequence(terminfo: Option<&Terminfo>, cap: Cap, params: &[i32]) -> Vec<u8> {
+    let sequence = match terminfo {
+        Some(terminfo) => match terminfo.string(cap) {
+            Some(sequence) => sequence,
+            None => return Vec::new(),
+        },
+        None => cap.xterm,
+    };
+    expand(sequence, params)
+}
+
+/// The directories to search for terminfo entries in, in order.
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+
+    let defaults = ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"];
+    match env::var("TERMINFO_DIRS") {
+        // An empty entry stands for the default directories.
+        Ok(list) => {
+            for dir in list.split(':') {
+                if dir.is_empty() {
+                    dirs.extend(defaults.iter().map(PathBuf::from));
+                } else {
+                    dirs.push(PathBuf::from(dir));
+                }
+            }
+        }
+        Err(_) => dirs.extend(defaults.iter().map(PathBuf::from)),
+    }
+
+    dirs
+}
+
+/// Get the NUL-terminated string at an offset in a string table, if the offset is not negative.
+fn string_at(table: &[u8], offset: i16) -> Option<Vec<u8>> {
+    let rest = table.get(usize::try_from(offset).ok()?..)?;
+    let len = rest.iter().position(|&byte| byte == 0)?;
+    Some(rest[..len].to_vec())
+}
+
+/// A cursor over the bytes of a compiled terminfo entry.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    /// Skip a padding byte if necessary so that the position is even.
+    fn align(&mut self) {
+        self.pos += self.pos % 2;
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        let bytes = self.take(2)?;
+        Some(i16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Read a count in a header, which must not be negative.
+    fn count(&mut self) -> Option<usize> {
+        usize::try_from(self.i16()?).ok()
+    }
+
+    fn number(&mut self, size: usize) -> Option<i32> {
+        if size == 2 {
+            self.i16().map(i32::from)
+        } else {
+            let bytes = self.take(4)?;
+            Some(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        }
+    }
+}
+
+/// Expand the parameters in a terminfo string, like ncurses' `tparm`.
+///
+/// Only numeric parameters are supported, and padding (`$<n>`) is removed.
+#[allow(clippy::too_many_lines)]
+pub(super) fn expand(cap: &[u8], params: &[i32]) -> Vec<u8> {
+    let mut params = {
+        let mut array = [0; 9];
+        for (param, &value) in array.iter_mut().zip(params) {
+            *param = value;
+        }
+        array
+    };
+    let mut stack: Vec<i32> = Vec::new();
+    let mut vars = [0; 52];
+    let mut out = Vec::new();
+
+    let pop = |stack: &mut Vec<i32>| stack.pop().unwrap_or(0);
+
+    let mut i = 0;
+    while i < cap.len() {
+        let byte = cap[i];
+        i += 1;
+
+        if byte == b'$' && cap.get(i) == Some(&b'<') {
+            if let Some(len) = cap[i..].iter().position(|&byte| byte == b'>') {
+                i += len + 1;
+                continue;
+            }
+        }
+        if byte != b'%' {
+            out.push(byte);
+            continue;
+        }
+
+        let op = match cap.get(i) {
+            Some(&op) => op,
+            None => break,
+        };
+        i += 1;
+
+        match op {
+            b'%' => out.push(b'%'),
+            b'c' => out.push(pop(&mut stack) as u8),
+            b'p' => {
+                let n = cap.get(i).map_or(0, |&n| n.wrapping_sub(b'1'));
+                i += 1;
+                stack.push(params.get(usize::from(n)).copied().unwrap_or(0));
+            }
+            b'P' | b'g' => {
+                let var = match cap.get(i) {
+                    Some(&c @ b'a'..=b'z') => usize::from(c - b'a'),
+                    Some(&c @ b'A'..=b'Z') => usize::from(c - b'A') + 26,
+                    _ => continue,
+                };
+                i += 1;
+                if op == b'P' {
+                    vars[var] = pop(&mut stack);
+                } else {
+                    stack.push(vars[var]);
+                }
+            }
+            b'\'' => {
+                stack.push(cap.get(i).map_or(0, |&c| i32::from(c)));
+                i += 2;
+            }
+            b'{' => {
+                let len = cap[i..].iter().position(|&byte| byte == b'}').unwrap_or(0);
+                let number = std::str::from_utf8(&cap[i..i + len])
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0);
+                stack.push(number);
+                i += len + 1;
+            }
+            b'i' => {
+                params[0] += 1;
+                params[1] += 1;
+            }
+            b'+' | b'-' | b'*' | b'/' | b'm' | b'&' | b'|' | b'^' | b'=' | b'>' | b'<' | b'A'
+            | b'O' => {
+                let b = pop(&mut stack);
+                let a = pop(&mut stack);
+                stack.push(match op {
+                    b'+' => a.wrapping_add(b),
+                    b'-' => a.wrapping_sub(b),
+                    b'*' => a.wrapping_mul(b),
+                    b'/' => a.checked_div(b).unwrap_or(0),
+                    b'm' => a.checked_rem(b).unwrap_or(0),
+                    b'&' => a & b,
+                    b'|' => a | b,
+                    b'^' => a ^ b,
+                    b'=' => i32::from(a == b),
+                    b'>' => i32::from(a > b),
+                    b'<' => i32::from(a < b),
+                    b'A' => i32::from(a != 0 && b != 0),
+                    _ => i32::from(a != 0 || b != 0),
+                });
+            }
+            b'!' => {
+                let a = pop(&mut stack);
+                stack.push(i32::from(a == 0));
+            }
+            b'~' => {
+                let a = pop(&mut stack);
+                stack.push(!a);
+            }
+            b'?' | b';' => {}
+            b't' => {
+                if pop(&mut stack) == 0 {
+                    // Skip to just after the matching `%e`, or to the matching `%;`.
+                    i = skip_branch(cap, i, true);
+                }
+            }
+            // Reaching an else means the then-branch was taken, so skip to the end.
+            b'e' => i = skip_branch(cap, i, false),
+            _ => {
+                // A printf-style format: `%[[:]flags][width[.precision]][doxXs]`.
+                let start = i - 1;
+                let end = match cap[start..]
+                    .iter()
+                    .position(|byte| !b":-+# 0123456789.".contains(byte))
+                {
+                    Some(len) if b"doxXs".contains(&cap[start + len]) => start + len,
+                    // Unknown operators are ignored.
+                    _ => continue,
+                };
+                let spec = std::str::from_utf8(&cap[start..end]).unwrap_or("");
+                let spec = spec.strip_prefix(':').unwrap_or(spec);
+                let flags_len = spec
+                    .find(|c| !matches!(c, '-' | '+' | '#' | ' '))
+                    .unwrap_or(spec.len());
+                let (flags, width) = spec.split_at(flags_len);
+                let left = flags.contains('-');
+                let zero = width.starts_with('0');
+                let width = width
+                    .split('.')
+                    .next()
+                    .and_then(|width| width.parse().ok())
+                    .unwrap_or(0);
+
+                let value = pop(&mut stack);
+                let formatted = match cap[end] {
+                    b'o' => format!("{:o}", value),
+                    b'x' => format!("{:x}", value),
+                    b'X' => format!("{:X}", value),
+                    _ => value.to_string(),
+                };
+                let formatted = match (left, zero) {
+                    (true, _) => format!("{:<1$}", formatted, width),
+                    (false, true) => format!("{:0>1$}", formatted, width),
+                    (false, false) => format!("{:>1$}", formatted, width),
+                };
+                out.extend_from_slice(formatted.as_bytes());
+                i = end + 1;
+            }
+        }
+    }
+
+    out
+}
+
+/// Find the end of a conditional branch starting at `i`, returning the position after it.
+///
+/// If `stop_at_else` is set, this stops after an `%e` at the same nesting level as well as a `%;`.
+fn skip_branch(cap: &[u8], mut i: usize, stop_at_else: bool) -> usize {
+    let mut depth = 0_usize;
+    while i + 1 < cap.len() {
+        if cap[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        i += 2;
+        match cap[i - 1] {
+            b'?' => depth += 1,
+            b';' if depth == 0 => return i,
+            b';' => depth -= 1,
+            b'e' if depth == 0 && stop_at_else => return i,
+            _ => {}
+        }
+    }
+    cap.len()
+}
+
+#[test]
+fn test_expand() {
+    let setaf = SET_A_FOREGROUND.xterm;
+    assert_eq!(expand(setaf, &[1]), b"\x1B[31m");
+    assert_eq!(expand(setaf, &[12]), b"\x1B[94m");
+    assert_eq!(expand(setaf, &[208]), b"\x1B[38;5;208m");
+
+    assert_eq!(expand(CURSOR_ADDRESS.xterm, &[4, 9]), b"\x1B[5;10H");
+    assert_eq!(expand(b"%p1%02d,%p2%:-3d|%p1%x%%", &[7, 5]), b"07,5  |7%");
+    assert_eq!(expand(b"a$<5/>b%p1%'0'%+%c", &[3]), b"ab3");
+    assert_eq!(
+        expand(b"%?%p1%t%?%p2%tA%eB%;%eC%;%p1%Pa%ga%ga%*%d", &[2, 0]),
+        b"B4"
+    );
+}
+
+#[test]
+fn test_parse() {
+    fn i16s(values: &[i16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|v| v.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    let mut entry = i16s(&[0o432, 4, 1, 14, 11, 14]);
+    entry.extend_from_slice(b"tst\0");
+    // One boolean, followed by a padding byte.
+    entry.extend_from_slice(&[1, 0]);
+    let mut numbers = vec![-1; 14];
+    numbers[MAX_COLORS] = 8;
+    entry.extend(i16s(&numbers));
+    let mut offsets = vec![-1; 11];
+    offsets[5] = 0;
+    offsets[10] = 5;
+    entry.extend(i16s(&offsets));
+    entry.extend_from_slice(b"\x1B[2J\0\x1B[%p1%dH\0");
+
+    // The extended section, with `Tc` and `Ss`.
+    entry.extend(i16s(&[1, 0, 1, 3, 13]));
+    entry.extend_from_slice(&[1, 0]);
+    entry.extend(i16s(&[0, 0, 3]));
+    entry.extend_from_slice(b"\x1B[%d q\0Tc\0Ss\0");
+
+    let terminfo = Terminfo::parse(&entry).unwrap();
+    assert_eq!(terminfo.colors(), 8);
+    assert_eq!(terminfo.string(CLEAR_SCREEN), Some(&b"\x1B[2J"[..]));
+    assert_eq!(terminfo.string(CURSOR_ADDRESS), Some(&b"\x1B[%p1%dH"[..]));
+    assert_eq!(terminfo.string(ENTER_BOLD_MODE), None);
+    assert_eq!(terminfo.string(SET_CURSOR_STYLE), Some(&b"\x1B[%d q"[..]));
+    assert_eq!(terminfo.color_support(), ColorSupport::TrueColor);
+
+    assert_eq!(sequence(Some(&terminfo), ENTER_BOLD_MODE, &[]), b"");
+    assert_eq!(sequence(None, ENTER_BOLD_MODE, &[]), b"\x1B[1m");
+}
@@ -0,0 +1,121 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::mem::MaybeUninit;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+use crate::Vec2;
+
+use super::super::Tty;
+
+/// Check the return value of a libc function.
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+/// The terminal's mode from before it was put into raw mode.
+#[derive(Clone, Copy)]
+pub(super) struct RawMode {
+    fd: RawFd,
+    original: libc::termios,
+}
+
+impl RawMode {
+    pub(super) fn enable(tty: &Tty) -> io::Result<Self> {
+        let fd = tty.as_raw_fd();
+
+        let original = unsafe {
+            let mut termios = MaybeUninit::uninit();
+            check(libc::tcgetattr(fd, termios.as_mut_ptr()))?;
+            termios.assume_init()
+        };
+
+        let this = Self { fd, original };
+        this.reenable()?;
+        Ok(this)
+    }
+
+    /// Put the terminal back into raw mode, after something else has changed its mode.
+    pub(super) fn reenable(&self) -> io::Result<()> {
+        let mut raw = self.original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        check(unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &raw) })?;
+        Ok(())
+    }
+
+    pub(super) fn disable(self) -> io::Result<()> {
+        check(unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.original) })?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for RawMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawMode").field("fd", &self.fd).finish()
+    }
+}
+
+pub(super) fn size(tty: &Tty) -> io::Result<Vec2<u16>> {
+    fd_size(tty.as_raw_fd())
+}
+
+fn fd_size(fd: RawFd) -> io::Result<Vec2<u16>> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    check(unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) })?;
+    Ok(Vec2::new(size.ws_col, size.ws_row))
+}
+
+/// A handle to the terminal's input.
+#[derive(Debug)]
+pub(super) struct Input {
+    file: File,
+}
+
+impl Input {
+    pub(super) fn new(tty: &Tty) -> io::Result<Self> {
+        let fd = check(unsafe { libc::dup(tty.as_raw_fd()) })?;
+        Ok(Self {
+            file: unsafe { File::from_raw_fd(fd) },
+        })
+    }
+
+    /// Get the size of the terminal.
+    pub(super) fn size(&self) -> io::Result<Vec2<u16>> {
+        fd_size(self.file.as_raw_fd())
+    }
+
+    /// Wait up to the timeout in milliseconds for input, returning whether there is any to read.
+    pub(super) fn wait(&self, timeout: i32) -> io::Result<bool> {
+        let mut fd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        match unsafe { libc::poll(&mut fd, 1, timeout) } {
+            -1 => {
+                let e = io::Error::last_os_error();
+                if e.kind() == io::ErrorKind::Interrupted {
+                    Ok(false)
+                } else {
+                    Err(e)
+                }
+            }
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    /// Read the input that [`wait`](Self::wait) found into the buffer.
+    ///
+    /// Returns `Err` with `UnexpectedEof` at the end of input.
+    pub(super) fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.file.read(buf)? {
+            0 => Err(io::ErrorKind::UnexpectedEof.into()),
+            n => Ok(n),
+        }
+    }
+}
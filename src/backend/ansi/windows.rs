@@ -0,0 +1,179 @@
+use std::io;
+use std::os::windows::io::AsRawHandle;
+
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE};
+use winapi::shared::winerror::WAIT_TIMEOUT;
+use winapi::um::consoleapi::{GetConsoleMode, ReadConsoleInputW, SetConsoleMode};
+use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+use winapi::um::processenv::GetStdHandle;
+use winapi::um::synchapi::WaitForSingleObject;
+use winapi::um::winbase::{STD_INPUT_HANDLE, WAIT_OBJECT_0};
+use winapi::um::wincon::{
+    GetConsoleScreenBufferInfo, CONSOLE_SCREEN_BUFFER_INFO, ENABLE_ECHO_INPUT,
+    ENABLE_EXTENDED_FLAGS, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT, ENABLE_QUICK_EDIT_MODE,
+    ENABLE_VIRTUAL_TERMINAL_INPUT, ENABLE_VIRTUAL_TERMINAL_PROCESSING, ENABLE_WINDOW_INPUT,
+};
+use winapi::um::wincontypes::{INPUT_RECORD, KEY_EVENT};
+use winapi::um::winnt::HANDLE;
+
+use crate::Vec2;
+
+use super::super::Tty;
+
+/// Check the return value of a WinAPI function.
+fn check(ret: BOOL) -> io::Result<()> {
+    if ret == FALSE {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+fn input_handle() -> io::Result<HANDLE> {
+    let handle = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+    if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(handle)
+    }
+}
+
+fn get_mode(handle: HANDLE) -> io::Result<DWORD> {
+    let mut mode = 0;
+    check(unsafe { GetConsoleMode(handle, &mut mode) })?;
+    Ok(mode)
+}
+
+fn set_mode(handle: HANDLE, mode: DWORD) -> io::Result<()> {
+    check(unsafe { SetConsoleMode(handle, mode) })
+}
+
+/// The console's modes from before it was put into raw mode.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RawMode {
+    // Handles are stored as integers so that this type is `Send`.
+    input: usize,
+    output: usize,
+    original_input: DWORD,
+    original_output: DWORD,
+}
+
+impl RawMode {
+    pub(super) fn enable(tty: &Tty) -> io::Result<Self> {
+        let input = input_handle()?;
+        let output = tty.as_raw_handle() as HANDLE;
+
+        let this = Self {
+            input: input as usize,
+            output: output as usize,
+            original_input: get_mode(input)?,
+            original_output: get_mode(output)?,
+        };
+        this.reenable()?;
+        Ok(this)
+    }
+
+    /// Put the console back into raw mode, after something else has changed its modes.
+    pub(super) fn reenable(&self) -> io::Result<()> {
+        set_mode(
+            self.input as HANDLE,
+            (self.original_input
+                & !(ENABLE_ECHO_INPUT
+                    | ENABLE_LINE_INPUT
+                    | ENABLE_PROCESSED_INPUT
+                    | ENABLE_QUICK_EDIT_MODE))
+                | ENABLE_EXTENDED_FLAGS
+                | ENABLE_WINDOW_INPUT
+                | ENABLE_VIRTUAL_TERMINAL_INPUT,
+        )?;
+        set_mode(
+            self.output as HANDLE,
+            self.original_output | ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        )
+    }
+
+    pub(super) fn disable(self) -> io::Result<()> {
+        set_mode(self.input as HANDLE, self.original_input)?;
+        set_mode(self.output as HANDLE, self.original_output)
+    }
+}
+
+pub(super) fn size(tty: &Tty) -> io::Result<Vec2<u16>> {
+    handle_size(tty.as_raw_handle() as HANDLE)
+}
+
+fn handle_size(handle: HANDLE) -> io::Result<Vec2<u16>> {
+    let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { std::mem::zeroed() };
+    check(unsafe { GetConsoleScreenBufferInfo(handle, &mut info) })?;
+    let window = info.srWindow;
+    Ok(Vec2::new(
+        (window.Right - window.Left + 1) as u16,
+        (window.Bottom - window.Top + 1) as u16,
+    ))
+}
+
+/// A handle to the console's input.
+#[derive(Debug)]
+pub(super) struct Input {
+    // Handles are stored as integers so that this type is `Send`.
+    handle: usize,
+    /// The output handle, used to get the size of the console.
+    output: usize,
+}
+
+impl Input {
+    pub(super) fn new(tty: &Tty) -> io::Result<Self> {
+        Ok(Self {
+            handle: input_handle()? as usize,
+            output: tty.as_raw_handle() as usize,
+        })
+    }
+
+    /// Get the size of the console.
+    pub(super) fn size(&self) -> io::Result<Vec2<u16>> {
+        handle_size(self.output as HANDLE)
+    }
+
+    /// Wait up to the timeout in milliseconds for input, returning whether there is any to read.
+    pub(super) fn wait(&self, timeout: i32) -> io::Result<bool> {
+        match unsafe { WaitForSingleObject(self.handle as HANDLE, timeout as DWORD) } {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Read the input that [`wait`](Self::wait) found into the buffer.
+    ///
+    /// With virtual terminal input enabled, the console reports escape sequences as characters of
+    /// key events, so those characters are collected and encoded as UTF-8. Returns `Ok(0)` if there
+    /// were no characters to read.
+    pub(super) fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let handle = self.handle as HANDLE;
+
+        // Each character can take up to three bytes in UTF-8.
+        let mut records: Vec<INPUT_RECORD> = vec![unsafe { std::mem::zeroed() }; buf.len() / 3];
+        let mut read = 0;
+        check(unsafe {
+            ReadConsoleInputW(
+                handle,
+                records.as_mut_ptr(),
+                records.len() as DWORD,
+                &mut read,
+            )
+        })?;
+
+        let units: Vec<u16> = records[..read as usize]
+            .iter()
+            .filter(|record| record.EventType == KEY_EVENT)
+            .map(|record| unsafe { record.Event.KeyEvent() })
+            .filter(|event| event.bKeyDown != FALSE)
+            .map(|event| unsafe { *event.uChar.UnicodeChar() })
+            .filter(|&unit| unit != 0)
+            .collect();
+
+        let text = String::from_utf16_lossy(&units);
+        buf[..text.len()].copy_from_slice(text.as_bytes());
+        Ok(text.len())
+    }
+}
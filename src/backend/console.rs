@@ -0,0 +1,731 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::{self, Write};
+use std::mem;
+use std::os::windows::io::AsRawHandle;
+use std::pin::Pin;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, TRUE, WORD};
+use winapi::shared::winerror::WAIT_TIMEOUT;
+use winapi::um::consoleapi::{GetConsoleMode, ReadConsoleInputW, SetConsoleMode, WriteConsoleW};
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+use winapi::um::processenv::GetStdHandle;
+use winapi::um::synchapi::WaitForSingleObject;
+use winapi::um::winbase::{STD_INPUT_HANDLE, WAIT_OBJECT_0};
+use winapi::um::wincon::{
+    CreateConsoleScreenBuffer, FillConsoleOutputAttribute, FillConsoleOutputCharacterW,
+    GetConsoleCursorInfo, GetConsoleScreenBufferInfo, GetConsoleTitleW,
+    SetConsoleActiveScreenBuffer, SetConsoleCursorInfo, SetConsoleCursorPosition,
+    SetConsoleTextAttribute, SetConsoleTitleW, COMMON_LVB_GRID_HORIZONTAL,
+    COMMON_LVB_REVERSE_VIDEO, COMMON_LVB_UNDERSCORE, CONSOLE_CURSOR_INFO,
+    CONSOLE_SCREEN_BUFFER_INFO, CONSOLE_TEXTMODE_BUFFER, ENABLE_EXTENDED_FLAGS, ENABLE_MOUSE_INPUT,
+    ENABLE_PROCESSED_OUTPUT, ENABLE_WINDOW_INPUT, FOREGROUND_INTENSITY,
+};
+use winapi::um::wincontypes::{
+    COORD, ENHANCED_KEY, FROM_LEFT_1ST_BUTTON_PRESSED, FROM_LEFT_2ND_BUTTON_PRESSED, INPUT_RECORD,
+    KEY_EVENT, KEY_EVENT_RECORD, LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, MOUSE_EVENT,
+    MOUSE_EVENT_RECORD, MOUSE_HWHEELED, MOUSE_MOVED, MOUSE_WHEELED, RIGHTMOST_BUTTON_PRESSED,
+    RIGHT_ALT_PRESSED, RIGHT_CTRL_PRESSED, SHIFT_PRESSED,
+};
+use winapi::um::winnt::{FILE_SHARE_READ, FILE_SHARE_WRITE, GENERIC_READ, GENERIC_WRITE, HANDLE};
+use winapi::um::winuser::{
+    VK_ADD, VK_BACK, VK_DECIMAL, VK_DELETE, VK_DIVIDE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_F24,
+    VK_HOME, VK_INSERT, VK_LEFT, VK_MEDIA_NEXT_TRACK, VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK,
+    VK_MEDIA_STOP, VK_MULTIPLY, VK_NEXT, VK_NUMPAD0, VK_NUMPAD9, VK_PRIOR, VK_RETURN, VK_RIGHT,
+    VK_SEPARATOR, VK_SUBTRACT, VK_TAB, VK_UP, VK_VOLUME_DOWN, VK_VOLUME_MUTE, VK_VOLUME_UP,
+};
+
+use crate::input::{Key, KeyKind, KeyPress, MediaKey, Modifiers, MouseButton, MouseCapture};
+use crate::style::{color_distance, Color, ColorSupport, Intensity};
+use crate::{CursorShape, Vec2};
+
+use super::{Backend, ReadEvents, TerminalEvent, TerminalMouse, TerminalMouseKind, Tty};
+
+/// How often, in milliseconds, the input thread checks whether the console has been resized.
+const RESIZE_POLL_INTERVAL: DWORD = 100;
+
+/// A backend that uses the Windows console API directly. This is only available on Windows.
+///
+/// Currently there is no configuration here.
+///
+/// Unlike the other backends this doesn't use escape sequences, so it works on versions of the
+/// Windows console older than Windows 10. However, only the 16 colors of the console's palette
+/// are supported, and italics, blinking, strikethrough, hyperlinks and images are ignored. Output
+/// is drawn to a separate screen buffer, which acts like the alternate screen of other terminals.
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct Console {}
+
+impl Backend for Console {
+    type Error = io::Error;
+    type Bound = Bound;
+
+    fn bind(self, mut io: Tty) -> Result<Self::Bound, Self::Error> {
+        io.flush()?;
+
+        let input = input_handle()?;
+        let original_input_mode = get_mode(input)?;
+        set_mode(input, ENABLE_EXTENDED_FLAGS | ENABLE_WINDOW_INPUT)?;
+
+        let original_screen = io.as_raw_handle() as HANDLE;
+        let original_info = screen_info(original_screen)?;
+
+        let screen = unsafe {
+            CreateConsoleScreenBuffer(
+                GENERIC_READ | GENERIC_WRITE,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                ptr::null(),
+                CONSOLE_TEXTMODE_BUFFER,
+                ptr::null_mut(),
+            )
+        };
+        if screen == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Disable line wrapping so that writing to the bottom right cell doesn't scroll.
+        set_mode(screen, ENABLE_PROCESSED_OUTPUT)?;
+
+        let info = screen_info(screen)?;
+        let cells = DWORD::from(info.dwSize.X as u16) * DWORD::from(info.dwSize.Y as u16);
+        let origin = COORD { X: 0, Y: 0 };
+        let mut written = 0;
+        check(unsafe {
+            FillConsoleOutputCharacterW(screen, u16::from(b' '), cells, origin, &mut written)
+        })?;
+        check(unsafe {
+            FillConsoleOutputAttribute(
+                screen,
+                original_info.wAttributes,
+                cells,
+                origin,
+                &mut written,
+            )
+        })?;
+        check(unsafe { SetConsoleTextAttribute(screen, original_info.wAttributes) })?;
+        check(unsafe { SetConsoleActiveScreenBuffer(screen) })?;
+
+        let shared = Arc::new(Mutex::new(Shared {
+            events: VecDeque::new(),
+            mouse_capture: MouseCapture::None,
+            waker: None,
+            stopped: false,
+        }));
+        let thread_shared = Arc::clone(&shared);
+        let (thread_input, thread_screen) = (input as usize, screen as usize);
+        thread::spawn(move || read_input(thread_input, thread_screen, &thread_shared));
+
+        Ok(Bound {
+            io,
+            input: input as usize,
+            original_input_mode,
+            screen: screen as usize,
+            original_screen: original_screen as usize,
+            origin: window(&info).0,
+            shared,
+            titles: Vec::new(),
+            default_attributes: original_info.wAttributes,
+            foreground: Color::Default,
+            background: Color::Default,
+            intensity: Intensity::Normal,
+            underlined: false,
+            reversed: false,
+            hidden: false,
+            overlined: false,
+        })
+    }
+}
+
+/// State shared between the input thread and the bound backend.
+#[derive(Debug)]
+struct Shared {
+    events: VecDeque<io::Result<TerminalEvent>>,
+    /// Which mouse movements should be reported.
+    mouse_capture: MouseCapture,
+    waker: Option<Waker>,
+    /// Whether the backend has been reset and the input thread should stop.
+    stopped: bool,
+}
+
+impl Shared {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Bound {
+    io: Tty,
+    // Handles are stored as integers so that this type is `Send`.
+    input: usize,
+    original_input_mode: DWORD,
+    /// The screen buffer being drawn to.
+    screen: usize,
+    /// The screen buffer that was active before the backend was bound.
+    original_screen: usize,
+    /// The top left corner of the visible part of the screen buffer.
+    origin: Vec2<u16>,
+    shared: Arc<Mutex<Shared>>,
+    /// The stack of titles saved by `push_title`.
+    titles: Vec<Vec<u16>>,
+    /// The attributes of the console before the backend was bound, used for the default colors.
+    default_attributes: WORD,
+    foreground: Color,
+    background: Color,
+    intensity: Intensity,
+    underlined: bool,
+    reversed: bool,
+    hidden: bool,
+    overlined: bool,
+}
+
+impl Bound {
+    fn screen(&self) -> HANDLE {
+        self.screen as HANDLE
+    }
+
+    fn write_attributes(&mut self) -> io::Result<()> {
+        let mut foreground =
+            color_bits(self.foreground).unwrap_or(self.default_attributes & 0x000F);
+        let background =
+            color_bits(self.background).map_or(self.default_attributes & 0x00F0, |bits| bits << 4);
+
+        // The console can't display bold text, so make the text brighter instead.
+        if self.intensity == Intensity::Bold {
+            foreground |= FOREGROUND_INTENSITY;
+        }
+        // The console can't hide text, so make it the same color as the background instead.
+        if self.hidden {
+            foreground = background >> 4;
+        }
+        let mut attributes = foreground | background;
+        if self.underlined {
+            attributes |= COMMON_LVB_UNDERSCORE;
+        }
+        if self.reversed {
+            attributes |= COMMON_LVB_REVERSE_VIDEO;
+        }
+        if self.overlined {
+            attributes |= COMMON_LVB_GRID_HORIZONTAL;
+        }
+
+        check(unsafe { SetConsoleTextAttribute(self.screen(), attributes) })
+    }
+
+    fn cursor_info(&self) -> io::Result<CONSOLE_CURSOR_INFO> {
+        let mut info = unsafe { mem::zeroed() };
+        check(unsafe { GetConsoleCursorInfo(self.screen(), &mut info) })?;
+        Ok(info)
+    }
+
+    fn set_cursor_info(&mut self, f: impl FnOnce(&mut CONSOLE_CURSOR_INFO)) -> io::Result<()> {
+        let mut info = self.cursor_info()?;
+        f(&mut info);
+        check(unsafe { SetConsoleCursorInfo(self.screen(), &info) })
+    }
+}
+
+impl super::Bound for Bound {
+    type Error = io::Error;
+
+    // General functions
+
+    fn size(&mut self) -> Result<Vec2<u16>, Self::Error> {
+        let (origin, size) = window(&screen_info(self.screen())?);
+        self.origin = origin;
+        Ok(size)
+    }
+    fn set_title(&mut self, title: &str) -> Result<(), Self::Error> {
+        check(unsafe { SetConsoleTitleW(wide(title).as_ptr()) })
+    }
+    fn title(&mut self) -> Result<Option<String>, Self::Error> {
+        let mut title = vec![0; 1024];
+        let len = unsafe { GetConsoleTitleW(title.as_mut_ptr(), title.len() as DWORD) };
+        Ok(Some(String::from_utf16_lossy(&title[..len as usize])))
+    }
+    fn push_title(&mut self) -> Result<(), Self::Error> {
+        let mut title = vec![0; 1024];
+        let len = unsafe { GetConsoleTitleW(title.as_mut_ptr(), title.len() as DWORD) };
+        title.truncate(len as usize);
+        title.push(0);
+        self.titles.push(title);
+        Ok(())
+    }
+    fn pop_title(&mut self) -> Result<(), Self::Error> {
+        match self.titles.pop() {
+            Some(title) => check(unsafe { SetConsoleTitleW(title.as_ptr()) }),
+            None => Ok(()),
+        }
+    }
+    fn bell(&mut self) -> Result<(), Self::Error> {
+        // Processed output makes the console beep when BEL is written.
+        self.write("\x07")
+    }
+    fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<(), Self::Error> {
+        let mode = ENABLE_EXTENDED_FLAGS | ENABLE_WINDOW_INPUT;
+        let mode = if capture == MouseCapture::None {
+            mode
+        } else {
+            mode | ENABLE_MOUSE_INPUT
+        };
+        set_mode(self.input as HANDLE, mode)?;
+        self.shared.lock().unwrap().mouse_capture = capture;
+        Ok(())
+    }
+
+    // Cursor functions
+
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
+        self.set_cursor_info(|info| info.bVisible = FALSE)
+    }
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
+        self.set_cursor_info(|info| info.bVisible = TRUE)
+    }
+    fn set_cursor_shape(&mut self, shape: CursorShape) -> Result<(), Self::Error> {
+        // The console can only change the percentage of the cell the cursor fills from the bottom,
+        // so bars are shown as underlines.
+        self.set_cursor_info(|info| {
+            info.dwSize = match shape {
+                CursorShape::Block => 100,
+                CursorShape::Underline | CursorShape::Bar => 25,
+            }
+        })
+    }
+    fn set_cursor_blinking(&mut self, _blinking: bool) -> Result<(), Self::Error> {
+        // The console cursor always blinks.
+        Ok(())
+    }
+    fn set_cursor_pos(&mut self, pos: Vec2<u16>) -> Result<(), Self::Error> {
+        let pos = COORD {
+            X: self.origin.x.saturating_add(pos.x) as i16,
+            Y: self.origin.y.saturating_add(pos.y) as i16,
+        };
+        check(unsafe { SetConsoleCursorPosition(self.screen(), pos) })
+    }
+
+    // Style functions
+
+    fn color_support(&mut self) -> ColorSupport {
+        ColorSupport::Ansi16
+    }
+    fn set_foreground(&mut self, foreground: Color) -> Result<(), Self::Error> {
+        self.foreground = foreground;
+        self.write_attributes()
+    }
+    fn set_background(&mut self, background: Color) -> Result<(), Self::Error> {
+        self.background = background;
+        self.write_attributes()
+    }
+    fn set_intensity(&mut self, intensity: Intensity) -> Result<(), Self::Error> {
+        self.intensity = intensity;
+        self.write_attributes()
+    }
+    fn set_italic(&mut self, _italic: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn set_underlined(&mut self, underlined: bool) -> Result<(), Self::Error> {
+        self.underlined = underlined;
+        self.write_attributes()
+    }
+    fn set_blinking(&mut self, _blinking: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn set_crossed_out(&mut self, _crossed_out: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn set_reversed(&mut self, reversed: bool) -> Result<(), Self::Error> {
+        self.reversed = reversed;
+        self.write_attributes()
+    }
+    fn set_hidden(&mut self, hidden: bool) -> Result<(), Self::Error> {
+        self.hidden = hidden;
+        self.write_attributes()
+    }
+    fn set_overlined(&mut self, overlined: bool) -> Result<(), Self::Error> {
+        self.overlined = overlined;
+        self.write_attributes()
+    }
+
+    // Writing
+
+    fn write(&mut self, text: &str) -> Result<(), Self::Error> {
+        let text: Vec<u16> = text.encode_utf16().collect();
+        let mut remaining = &*text;
+        while !remaining.is_empty() {
+            let mut written = 0;
+            check(unsafe {
+                WriteConsoleW(
+                    self.screen(),
+                    remaining.as_ptr().cast(),
+                    remaining.len() as DWORD,
+                    &mut written,
+                    ptr::null_mut(),
+                )
+            })?;
+            remaining = &remaining[written as usize..];
+        }
+        Ok(())
+    }
+
+    // Finalizing functions
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // Everything is written to the console immediately.
+        Ok(())
+    }
+    fn reset(mut self) -> Result<Tty, Self::Error> {
+        self.shared.lock().unwrap().stopped = true;
+
+        check(unsafe { SetConsoleActiveScreenBuffer(self.original_screen as HANDLE) })?;
+        check(unsafe { CloseHandle(self.screen()) })?;
+        set_mode(self.input as HANDLE, self.original_input_mode)?;
+        self.io.flush()?;
+
+        Ok(self.io)
+    }
+}
+
+impl<'a> ReadEvents<'a> for Bound {
+    type EventError = <Self as super::Bound>::Error;
+    type EventFuture = EventFuture<'a>;
+
+    fn read_event(&'a mut self) -> Self::EventFuture {
+        EventFuture {
+            shared: &self.shared,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EventFuture<'a> {
+    shared: &'a Mutex<Shared>,
+}
+
+impl<'a> Future for EventFuture<'a> {
+    type Output = io::Result<TerminalEvent>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.events.pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Read input records from the console until the backend is reset, converting them to events.
+fn read_input(input: usize, screen: usize, shared: &Mutex<Shared>) {
+    let (input, screen) = (input as HANDLE, screen as HANDLE);
+
+    let mut records: Vec<INPUT_RECORD> = vec![unsafe { mem::zeroed() }; 64];
+    let mut events = Vec::new();
+    let mut size = screen_info(screen).map(|info| window(&info).1).ok();
+    let mut buttons = 0;
+    // The key code of the key being held down, if any.
+    let mut held = None;
+
+    loop {
+        let ready = wait_input(input);
+
+        let mut shared = shared.lock().unwrap();
+        if shared.stopped {
+            break;
+        }
+
+        // Input is only read once it's known that the backend hasn't been reset, so that input
+        // typed afterwards is left for whatever reads the console next.
+        let result = match ready {
+            Ok(true) => read_records(input, &mut records),
+            Ok(false) => Ok(&[][..]),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(records) => {
+                for record in records {
+                    match record.EventType {
+                        KEY_EVENT => {
+                            let event = unsafe { record.Event.KeyEvent() };
+                            let down = event.bKeyDown != FALSE;
+                            let code = Some(event.wVirtualKeyCode);
+
+                            // Holding a key down produces more key down events for it.
+                            let kind = if !down {
+                                KeyKind::Release
+                            } else if held == code {
+                                KeyKind::Repeat
+                            } else {
+                                KeyKind::Press
+                            };
+                            if down {
+                                held = code;
+                            } else if held == code {
+                                held = None;
+                            }
+
+                            if let Some(press) = key_press(event) {
+                                let count = if down { event.wRepeatCount.max(1) } else { 1 };
+                                events.extend((0..count).map(|i| {
+                                    TerminalEvent::Key(KeyPress {
+                                        kind: if i == 0 { kind } else { KeyKind::Repeat },
+                                        ..press
+                                    })
+                                }));
+                            }
+                        }
+                        MOUSE_EVENT => {
+                            let event = unsafe { record.Event.MouseEvent() };
+                            events.extend(
+                                mouse(event, &mut buttons, shared.mouse_capture)
+                                    .map(TerminalEvent::Mouse),
+                            );
+                        }
+                        // Resizes are detected below, since the console only reports changes to
+                        // the size of the screen buffer and not the window.
+                        _ => {}
+                    }
+                }
+            }
+            Err(e) => {
+                shared.events.push_back(Err(e));
+                shared.wake();
+                break;
+            }
+        }
+
+        if let Ok(info) = screen_info(screen) {
+            let new_size = window(&info).1;
+            if size != Some(new_size) {
+                size = Some(new_size);
+                events.push(TerminalEvent::Resize(new_size));
+            }
+        }
+
+        if !events.is_empty() {
+            shared.events.extend(events.drain(..).map(Ok));
+            shared.wake();
+        }
+    }
+}
+
+/// Wait a short time for input, returning whether there is any to read.
+fn wait_input(input: HANDLE) -> io::Result<bool> {
+    match unsafe { WaitForSingleObject(input, RESIZE_POLL_INTERVAL) } {
+        WAIT_OBJECT_0 => Ok(true),
+        WAIT_TIMEOUT => Ok(false),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Read the records that are available.
+fn read_records(input: HANDLE, records: &mut [INPUT_RECORD]) -> io::Result<&[INPUT_RECORD]> {
+    let mut read = 0;
+    check(unsafe {
+        ReadConsoleInputW(
+            input,
+            records.as_mut_ptr(),
+            records.len() as DWORD,
+            &mut read,
+        )
+    })?;
+    Ok(&records[..read as usize])
+}
+
+/// Convert a key event to a key press, ignoring whether it was pressed or released.
+///
+/// Returns [`None`] for keys that don't correspond to a [`Key`], such as modifier keys, and for
+/// characters outside the Basic Multilingual Plane, which the console can't display anyway.
+fn key_press(event: &KEY_EVENT_RECORD) -> Option<KeyPress> {
+    let modifiers = modifiers(event.dwControlKeyState);
+
+    let code = i32::from(event.wVirtualKeyCode);
+    let key = match code {
+        VK_BACK => Key::Backspace,
+        VK_LEFT => Key::Left,
+        VK_RIGHT => Key::Right,
+        VK_UP => Key::Up,
+        VK_DOWN => Key::Down,
+        VK_HOME => Key::Home,
+        VK_END => Key::End,
+        VK_PRIOR => Key::PageUp,
+        VK_NEXT => Key::PageDown,
+        VK_INSERT => Key::Insert,
+        VK_DELETE => Key::Char('\x7f'),
+        VK_ESCAPE => Key::Escape,
+        // The keypad's Enter key is reported as an enhanced return key.
+        VK_RETURN if event.dwControlKeyState & ENHANCED_KEY != 0 => Key::Keypad('\n'),
+        VK_RETURN => Key::Char('\n'),
+        VK_TAB => Key::Char('\t'),
+        VK_F1..=VK_F24 => Key::F((code - VK_F1 + 1) as u8),
+        VK_NUMPAD0..=VK_NUMPAD9 => Key::Keypad((b'0' + (code - VK_NUMPAD0) as u8) as char),
+        VK_MULTIPLY => Key::Keypad('*'),
+        VK_ADD => Key::Keypad('+'),
+        VK_SEPARATOR => Key::Keypad(','),
+        VK_SUBTRACT => Key::Keypad('-'),
+        VK_DECIMAL => Key::Keypad('.'),
+        VK_DIVIDE => Key::Keypad('/'),
+        VK_MEDIA_PLAY_PAUSE => Key::Media(MediaKey::PlayPause),
+        VK_MEDIA_STOP => Key::Media(MediaKey::Stop),
+        VK_MEDIA_NEXT_TRACK => Key::Media(MediaKey::NextTrack),
+        VK_MEDIA_PREV_TRACK => Key::Media(MediaKey::PreviousTrack),
+        VK_VOLUME_DOWN => Key::Media(MediaKey::VolumeDown),
+        VK_VOLUME_UP => Key::Media(MediaKey::VolumeUp),
+        VK_VOLUME_MUTE => Key::Media(MediaKey::Mute),
+        // With control held, letters and digits produce control characters, so use the key code
+        // instead. The key codes of letters and digits are their uppercase ASCII values.
+        0x30..=0x39 | 0x41..=0x5A if modifiers.control => {
+            Key::Char((code as u8).to_ascii_lowercase() as char)
+        }
+        _ => {
+            let c = std::char::from_u32(u32::from(unsafe { *event.uChar.UnicodeChar() }))?;
+            if c == '\0' {
+                return None;
+            }
+            KeyPress::from(c).key
+        }
+    };
+
+    Some(KeyPress {
+        key,
+        modifiers,
+        kind: KeyKind::Press,
+    })
+}
+
+/// Convert a mouse event to a terminal mouse event, given the buttons that were previously held
+/// down.
+fn mouse(
+    event: &MOUSE_EVENT_RECORD,
+    buttons: &mut DWORD,
+    capture: MouseCapture,
+) -> Option<TerminalMouse> {
+    const BUTTONS: [(DWORD, MouseButton); 3] = [
+        (FROM_LEFT_1ST_BUTTON_PRESSED, MouseButton::Left),
+        (RIGHTMOST_BUTTON_PRESSED, MouseButton::Right),
+        (FROM_LEFT_2ND_BUTTON_PRESSED, MouseButton::Middle),
+    ];
+
+    let state = event.dwButtonState;
+    let flags = event.dwEventFlags;
+
+    let kind = if flags & MOUSE_WHEELED != 0 {
+        // The high word is the signed distance scrolled, positive for away from the user.
+        if (state >> 16) as i16 > 0 {
+            TerminalMouseKind::ScrollUp
+        } else {
+            TerminalMouseKind::ScrollDown
+        }
+    } else if flags & MOUSE_HWHEELED != 0 {
+        // The high word is the signed distance scrolled, positive for right.
+        if (state >> 16) as i16 > 0 {
+            TerminalMouseKind::ScrollRight
+        } else {
+            TerminalMouseKind::ScrollLeft
+        }
+    } else {
+        let held = state & BUTTONS.iter().fold(0, |mask, &(bit, _)| mask | bit);
+        let pressed = held & !*buttons;
+        let released = *buttons & !held;
+        *buttons = held;
+
+        if let Some(&(_, button)) = BUTTONS.iter().find(|&&(bit, _)| pressed & bit != 0) {
+            TerminalMouseKind::Press(button)
+        } else if released != 0 {
+            TerminalMouseKind::Release
+        } else if flags & MOUSE_MOVED != 0
+            && (capture == MouseCapture::Motion || (capture == MouseCapture::Drag && held != 0))
+        {
+            TerminalMouseKind::Move
+        } else {
+            return None;
+        }
+    };
+
+    let pos = event.dwMousePosition;
+    Some(TerminalMouse {
+        kind,
+        at: Vec2::new(pos.X.max(0) as u16, pos.Y.max(0) as u16),
+        modifiers: modifiers(event.dwControlKeyState),
+    })
+}
+
+/// Get the modifiers held down from the control key state of an input record.
+fn modifiers(state: DWORD) -> Modifiers {
+    Modifiers {
+        shift: state & SHIFT_PRESSED != 0,
+        control: state & (LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED) != 0,
+        alt: state & (LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED) != 0,
+    }
+}
+
+/// Convert a color to the four attribute bits the console uses for foreground colors, or [`None`]
+/// for the default color.
+fn color_bits(color: Color) -> Option<WORD> {
+    let index = WORD::from(
+        ColorSupport::Ansi16
+            .downgrade(color, color_distance)
+            .ansi_value()?,
+    );
+    // ANSI colors have red in the lowest bit, but the console has blue there.
+    Some(((index & 1) << 2) | (index & 2) | ((index & 4) >> 2) | (index & 8))
+}
+
+/// Get the top left corner and size of the visible window of a screen buffer.
+fn window(info: &CONSOLE_SCREEN_BUFFER_INFO) -> (Vec2<u16>, Vec2<u16>) {
+    let window = info.srWindow;
+    (
+        Vec2::new(window.Left as u16, window.Top as u16),
+        Vec2::new(
+            (window.Right - window.Left + 1) as u16,
+            (window.Bottom - window.Top + 1) as u16,
+        ),
+    )
+}
+
+/// Encode a string as a null-terminated UTF-16 string.
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(Some(0)).collect()
+}
+
+/// Check the return value of a WinAPI function.
+fn check(ret: BOOL) -> io::Result<()> {
+    if ret == FALSE {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+fn input_handle() -> io::Result<HANDLE> {
+    let handle = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+    if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(handle)
+    }
+}
+
+fn get_mode(handle: HANDLE) -> io::Result<DWORD> {
+    let mut mode = 0;
+    check(unsafe { GetConsoleMode(handle, &mut mode) })?;
+    Ok(mode)
+}
+
+fn set_mode(handle: HANDLE, mode: DWORD) -> io::Result<()> {
+    check(unsafe { SetConsoleMode(handle, mode) })
+}
+
+fn screen_info(handle: HANDLE) -> io::Result<CONSOLE_SCREEN_BUFFER_INFO> {
+    let mut info = unsafe { mem::zeroed() };
+    check(unsafe { GetConsoleScreenBufferInfo(handle, &mut info) })?;
+    Ok(info)
+}
@@ -12,11 +12,12 @@ use crossterm::{execute, queue};
 use crossterm_crate as crossterm;
 use futures_core::stream::Stream as _;
 
-use crate::input::{Key, KeyPress, Modifiers, MouseButton};
-use crate::style::{Color, Intensity, Rgb};
-use crate::{CursorShape, Vec2};
+use crate::input::{Key, KeyKind, KeyPress, Modifiers, MouseButton, MouseCapture};
+use crate::style::{Color, ColorSupport, Intensity, Link, Rgb, Rgba, UnderlineKind};
+use crate::util::Base64;
+use crate::{Bitmap, CursorShape, Vec2};
 
-use super::{Backend, ReadEvents, TerminalEvent, TerminalMouse, TerminalMouseKind, Tty};
+use super::{sixel, Backend, ReadEvents, TerminalEvent, TerminalMouse, TerminalMouseKind, Tty};
 
 /// Crossterm backend.
 ///
@@ -33,14 +34,7 @@ impl Backend for Crossterm {
     type Bound = Bound;
 
     fn bind(self, mut io: Tty) -> Result<Self::Bound, Self::Error> {
-        terminal::enable_raw_mode()?;
-        execute!(
-            io,
-            terminal::EnterAlternateScreen,
-            terminal::Clear(terminal::ClearType::All),
-            terminal::DisableLineWrap,
-            event::EnableMouseCapture,
-        )?;
+        set_up(&mut io)?;
 
         Ok(Bound {
             io,
@@ -49,6 +43,18 @@ impl Backend for Crossterm {
     }
 }
 
+/// Put the terminal into raw mode, enter the alternate screen and clear it.
+fn set_up(io: &mut Tty) -> Result<(), crossterm::ErrorKind> {
+    terminal::enable_raw_mode()?;
+    execute!(
+        io,
+        terminal::EnterAlternateScreen,
+        terminal::Clear(terminal::ClearType::All),
+        terminal::DisableLineWrap,
+        event::EnableMouseCapture,
+    )
+}
+
 #[derive(Debug)]
 pub struct Bound {
     io: Tty,
@@ -63,9 +69,47 @@ impl super::Bound for Bound {
     fn size(&mut self) -> Result<Vec2<u16>, Self::Error> {
         terminal::size().map(Vec2::from)
     }
+    fn cell_size(&mut self) -> Result<Option<Vec2<u16>>, Self::Error> {
+        Ok(super::cell_size(&self.io)?)
+    }
+    fn background_color(&mut self) -> Result<Option<Rgb>, Self::Error> {
+        // Crossterm's input parser doesn't understand the reply to an OSC 11 query.
+        Ok(super::background_from_env())
+    }
     fn set_title(&mut self, title: &str) -> Result<(), Self::Error> {
         queue!(self.io, terminal::SetTitle(title))
     }
+    fn push_title(&mut self) -> Result<(), Self::Error> {
+        // XTWINOPS: save the window and icon titles on the stack. Terminals that don't support it
+        // ignore it.
+        self.io.write_all(b"\x1B[22;0t")?;
+        Ok(())
+    }
+    fn pop_title(&mut self) -> Result<(), Self::Error> {
+        // XTWINOPS: restore the window and icon titles from the stack.
+        self.io.write_all(b"\x1B[23;0t")?;
+        Ok(())
+    }
+    fn set_clipboard(&mut self, text: &str) -> Result<(), Self::Error> {
+        // OSC 52, which Crossterm has no command for. Since it is an escape sequence this works
+        // over SSH too.
+        write!(self.io, "\x1B]52;c;{}\x07", Base64(text.as_bytes()))?;
+        Ok(())
+    }
+    fn bell(&mut self) -> Result<(), Self::Error> {
+        self.io.write_all(b"\x07")?;
+        Ok(())
+    }
+    fn notify(&mut self, title: &str, body: &str) -> Result<(), Self::Error> {
+        super::write_notification(&mut self.io, title, body)?;
+        Ok(())
+    }
+    fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<(), Self::Error> {
+        // Crossterm's mouse capture command always uses button-event tracking, so the mode is set
+        // directly. On the legacy Windows console this has no effect.
+        super::write_mouse_capture(&mut self.io, capture)?;
+        Ok(())
+    }
 
     // Cursor functions
 
@@ -86,11 +130,19 @@ impl super::Bound for Bound {
             queue!(self.io, cursor::DisableBlinking)
         }
     }
+    fn set_cursor_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        // Crossterm doesn't have a command for this.
+        super::write_cursor_color(&mut self.io, color)?;
+        Ok(())
+    }
     fn set_cursor_pos(&mut self, pos: Vec2<u16>) -> Result<(), Self::Error> {
         queue!(self.io, cursor::MoveTo(pos.x, pos.y))
     }
 
     // Style functions
+    fn color_support(&mut self) -> ColorSupport {
+        ColorSupport::detect()
+    }
     fn set_foreground(&mut self, foreground: Color) -> Result<(), Self::Error> {
         queue!(
             self.io,
@@ -133,6 +185,15 @@ impl super::Bound for Bound {
             })
         )
     }
+    fn set_underline_kind(&mut self, kind: UnderlineKind) -> Result<(), Self::Error> {
+        // Crossterm has no command for extended underlines.
+        super::write_underline_kind(&mut self.io, kind)?;
+        Ok(())
+    }
+    fn set_underline_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        super::write_underline_color(&mut self.io, color)?;
+        Ok(())
+    }
     fn set_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
         queue!(
             self.io,
@@ -153,6 +214,53 @@ impl super::Bound for Bound {
             })
         )
     }
+    fn set_reversed(&mut self, reversed: bool) -> Result<(), Self::Error> {
+        queue!(
+            self.io,
+            style::SetAttribute(if reversed {
+                Attribute::Reverse
+            } else {
+                Attribute::NoReverse
+            })
+        )
+    }
+    fn set_hidden(&mut self, hidden: bool) -> Result<(), Self::Error> {
+        queue!(
+            self.io,
+            style::SetAttribute(if hidden {
+                Attribute::Hidden
+            } else {
+                Attribute::NoHidden
+            })
+        )
+    }
+    fn set_overlined(&mut self, overlined: bool) -> Result<(), Self::Error> {
+        queue!(
+            self.io,
+            style::SetAttribute(if overlined {
+                Attribute::OverLined
+            } else {
+                Attribute::NotOverLined
+            })
+        )
+    }
+    fn set_link(&mut self, link: Option<&Link>) -> Result<(), Self::Error> {
+        // OSC 8, which Crossterm has no command for. Terminals that don't support hyperlinks ignore
+        // it.
+        write!(self.io, "\x1B]8;;{}\x1B\\", link.map_or("", Link::url))?;
+        Ok(())
+    }
+
+    // Graphics functions
+
+    fn supports_graphics(&mut self) -> bool {
+        sixel::detect()
+    }
+    fn draw_image(&mut self, size: Vec2<u16>, bitmap: &Bitmap) -> Result<(), Self::Error> {
+        let cell_size = self.cell_size()?;
+        sixel::write(&mut self.io, bitmap, size, cell_size)?;
+        Ok(())
+    }
 
     // Writing
 
@@ -167,7 +275,12 @@ impl super::Bound for Bound {
         self.io.flush()?;
         Ok(())
     }
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        set_up(&mut self.io)
+    }
     fn reset(mut self) -> Result<Tty, Self::Error> {
+        super::write_mouse_capture(&mut self.io, MouseCapture::None)?;
+        super::write_cursor_color(&mut self.io, Color::Default)?;
         execute!(
             self.io,
             event::DisableMouseCapture,
@@ -228,7 +341,7 @@ fn to_crossterm_color(color: Color) -> CColor {
         Color::Cyan => CColor::Cyan,
         Color::DarkCyan => CColor::DarkCyan,
         Color::AnsiValue(v) => CColor::AnsiValue(v.get()),
-        Color::Rgb(Rgb { r, g, b }) => CColor::Rgb { r, g, b },
+        Color::Rgb(Rgb { r, g, b }) | Color::Rgba(Rgba { r, g, b, .. }) => CColor::Rgb { r, g, b },
     }
 }
 
@@ -261,6 +374,8 @@ fn from_crossterm_event(event: Event) -> TerminalEvent {
                     || matches!(key.code, KeyCode::Char(c) if c.is_uppercase());
                 modifiers
             },
+            // Crossterm doesn't report repeats or releases.
+            kind: KeyKind::Press,
         }),
         Event::Mouse(mouse) => TerminalEvent::Mouse({
             let (kind, x, y, modifiers) = match mouse {
@@ -3,14 +3,14 @@ use std::collections::VecDeque;
 use std::convert::Infallible;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use unicode_width::UnicodeWidthStr;
-
 use crate::buffer::{Buffer, Grid};
+use crate::clock::{Clock, MockClock};
 use crate::output::Ext as _;
-use crate::style::{Color, Intensity, Style};
-use crate::{Cursor, CursorShape, Output, Vec2};
+use crate::style::{Color, ColorSupport, Intensity, Link, Rgb, Style, UnderlineKind};
+use crate::{Bitmap, Cursor, CursorShape, MouseCapture, Output, Vec2};
 
 use super::{Backend, Bound, ReadEvents, TerminalEvent, Tty};
 
@@ -18,11 +18,45 @@ use super::{Backend, Bound, ReadEvents, TerminalEvent, Tty};
 ///
 /// This backend doesn't display any output to the screen, but records all operations it receives
 /// and stores a terminal buffer.
+///
+/// It can be used for end-to-end tests of a draw loop: queue up the events the user would cause
+/// with [`with_events`](Self::with_events), draw until the queue is empty, and then check the
+/// frames that were drawn in [`frames`](Self::frames), for example with
+/// [`assert_frame_contains`](Self::assert_frame_contains) and
+/// [`frames_between`](Self::frames_between). [`test::Harness`](crate::test::Harness) does this
+/// without needing an async runtime.
+///
+/// # Examples
+///
+/// ```
+/// use toon::backend::Dummy;
+/// use toon::{ElementExt, KeyPress};
+///
+/// let backend = Dummy::new((5, 1).into()).with_events(vec![KeyPress::from('a'); 2]);
+/// let mut terminal = toon::Terminal::new(backend)?;
+///
+/// let mut count = 0;
+/// while !terminal.backend().events.is_empty() {
+///     let events = futures_lite::future::block_on(
+///         terminal.draw(toon::span(count.to_string()).on('a', |_| ())),
+///     )?;
+///     count += events.len();
+/// }
+///
+/// let frames: Vec<_> = terminal.backend().frames.iter().map(|f| f.contents()).collect();
+/// assert_eq!(frames, [["0    "], ["1    "]]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct Dummy {
     /// The operations the dummy backend has received.
     pub operations: Vec<Operation>,
+    /// The contents of the buffer each time the backend was flushed.
+    ///
+    /// The terminal flushes the backend once after drawing each frame, so these are the frames
+    /// that were drawn.
+    pub frames: Vec<Grid>,
     /// Events to feed the terminal. They will be popped from the front of the queue.
     ///
     /// If this is empty and the terminal requests an event it will return a never-completing
@@ -30,6 +64,17 @@ pub struct Dummy {
     pub events: VecDeque<TerminalEvent>,
     /// The title of the terminal.
     pub title: String,
+    /// The stack of titles saved by [`push_title`](Bound::push_title).
+    pub title_stack: Vec<String>,
+    /// The contents of the clipboard.
+    pub clipboard: String,
+    /// How much mouse input is captured.
+    pub mouse_capture: MouseCapture,
+    /// Whether the dummy supports the mouse. Default is `true`.
+    ///
+    /// When this is `false` the dummy ignores requests to capture the mouse, and drops mouse
+    /// events from the queue of events instead of feeding them to the terminal.
+    pub supports_mouse: bool,
     /// The buffer the dummy backend writes to.
     pub buffer: Buffer,
     /// The current position of the cursor.
@@ -39,6 +84,18 @@ pub struct Dummy {
     pub cursor_pos: Vec2<u16>,
     /// The current style being written with.
     pub style: Style,
+    /// The current hyperlink being written with.
+    pub link: Option<Link>,
+    /// The colors the dummy reports that it supports. Default is [`ColorSupport::TrueColor`].
+    pub color_support: ColorSupport,
+    /// Whether the dummy reports that it can display images. Default is `false`.
+    pub supports_graphics: bool,
+    /// The size of a cell in pixels that the dummy reports. Default is [`None`].
+    pub cell_size: Option<Vec2<u16>>,
+    /// The background color that the dummy reports. Default is [`None`].
+    pub background_color: Option<Rgb>,
+    /// The clock the dummy gives the terminal, which only moves when it is advanced.
+    pub clock: MockClock,
     /// The TTY this dummy was given.
     ///
     /// Writing to this TTY will panic as the terminal won't give the dummy a real TTY since it
@@ -54,14 +111,111 @@ impl Dummy {
     pub fn new(size: Vec2<u16>) -> Self {
         Self {
             operations: Vec::new(),
+            frames: Vec::new(),
             events: VecDeque::new(),
             title: String::new(),
+            title_stack: Vec::new(),
+            clipboard: String::new(),
+            mouse_capture: MouseCapture::None,
+            supports_mouse: true,
             buffer: Buffer::from(Grid::new(size)),
             cursor_pos: Vec2::new(0, 0),
             style: Style::default(),
+            link: None,
+            color_support: ColorSupport::TrueColor,
+            supports_graphics: false,
+            cell_size: None,
+            background_color: None,
+            clock: MockClock::new(),
             tty: None,
         }
     }
+
+    /// Create a new dummy backend that simulates an old terminal: it is 80x24, only supports the
+    /// 16 named colors and doesn't support the mouse.
+    ///
+    /// This can be used to check that an application still looks and works acceptably in
+    /// terminals with fewer capabilities. Colors the terminal can't display are converted to the
+    /// closest named colors when they are drawn, so the frames show what the user would see.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toon::backend::Dummy;
+    /// use toon::{ColorSupport, MouseCapture, Output as _, Vec2};
+    ///
+    /// let terminal = toon::Terminal::new(Dummy::legacy())?;
+    /// assert_eq!(terminal.color_support(), ColorSupport::Ansi16);
+    /// assert_eq!(terminal.backend().buffer.grid.size(), Vec2::new(80, 24));
+    /// assert_eq!(terminal.backend().mouse_capture, MouseCapture::None);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn legacy() -> Self {
+        Self {
+            supports_mouse: false,
+            color_support: ColorSupport::Ansi16,
+            ..Self::new(Vec2::new(80, 24))
+        }
+    }
+
+    /// Add events to the end of the queue of events to feed the terminal.
+    #[must_use]
+    pub fn with_events<I>(mut self, events: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<TerminalEvent>,
+    {
+        self.push_events(events);
+        self
+    }
+
+    /// Add events to the end of the queue of events to feed the terminal.
+    pub fn push_events<I>(&mut self, events: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<TerminalEvent>,
+    {
+        self.events.extend(events.into_iter().map(Into::into));
+    }
+
+    /// Assert that the last frame drawn contains the given text on one of its lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no frames have been drawn or the last frame doesn't contain the text.
+    pub fn assert_frame_contains(&self, text: &str) {
+        let frame = self.frames.last().expect("no frames have been drawn");
+        assert!(
+            frame_contains(frame, text),
+            "frame does not contain {:?}:\n{}",
+            text,
+            frame.contents().join("\n"),
+        );
+    }
+
+    /// Get the frames drawn from the first frame containing `start` up to and including the
+    /// first frame after it containing `end`, or [`None`] if there are no such frames.
+    ///
+    /// Text is only searched for within single lines of the frames.
+    #[must_use]
+    pub fn frames_between(&self, start: &str, end: &str) -> Option<&[Grid]> {
+        let start = self
+            .frames
+            .iter()
+            .position(|frame| frame_contains(frame, start))?;
+        let len = self.frames[start..]
+            .iter()
+            .position(|frame| frame_contains(frame, end))?;
+        Some(&self.frames[start..=start + len])
+    }
+}
+
+fn frame_contains(frame: &Grid, text: &str) -> bool {
+    frame
+        .lines()
+        .iter()
+        .any(|line| line.contents().contains(text))
 }
 
 /// An operation on a terminal backend, stored by [`Dummy`].
@@ -72,6 +226,18 @@ impl Dummy {
 pub enum Operation {
     /// The title was set.
     SetTitle(String),
+    /// The title was pushed onto the title stack.
+    PushTitle,
+    /// The title was popped from the title stack.
+    PopTitle,
+    /// The contents of the clipboard were set.
+    SetClipboard(String),
+    /// The bell was rung.
+    Bell,
+    /// A desktop notification was shown with a title and body.
+    Notify(String, String),
+    /// The mouse capture was set.
+    SetMouseCapture(MouseCapture),
     /// The cursor was hidden.
     HideCursor,
     /// The cursor was shown.
@@ -80,6 +246,8 @@ pub enum Operation {
     SetCursorShape(CursorShape),
     /// Whether the cursor blinks was set.
     SetCursorBlinking(bool),
+    /// The cursor's color was set.
+    SetCursorColor(Color),
     /// The position of the cursor was set.
     SetCursorPos(Vec2<u16>),
     /// The foreground color was set.
@@ -92,14 +260,30 @@ pub enum Operation {
     SetItalic(bool),
     /// Whether the text is underlined was set.
     SetUnderlined(bool),
+    /// The text was underlined with a kind of underline.
+    SetUnderlineKind(UnderlineKind),
+    /// The underline color was set.
+    SetUnderlineColor(Color),
     /// Whether the text blinks was set.
     SetBlinking(bool),
     /// Whether the text is crossed out was set.
     SetCrossedOut(bool),
+    /// Whether the text is reversed was set.
+    SetReversed(bool),
+    /// Whether the text is hidden was set.
+    SetHidden(bool),
+    /// Whether the text is overlined was set.
+    SetOverlined(bool),
+    /// The hyperlink was set.
+    SetLink(Option<Link>),
+    /// An image was drawn at the cursor, covering an area of the given size.
+    DrawImage(Vec2<u16>, Bitmap),
     /// Text was written to the output.
     Write(String),
     /// The output was flushed.
     Flush,
+    /// The terminal was set up again after the process was resumed.
+    Resume,
 }
 
 impl Backend for Dummy {
@@ -126,12 +310,58 @@ impl Bound for Dummy {
     fn size(&mut self) -> Result<Vec2<u16>, Self::Error> {
         Ok(self.buffer.grid.size())
     }
+    fn cell_size(&mut self) -> Result<Option<Vec2<u16>>, Self::Error> {
+        Ok(self.cell_size)
+    }
+    fn background_color(&mut self) -> Result<Option<Rgb>, Self::Error> {
+        Ok(self.background_color)
+    }
+    fn clock(&mut self) -> Arc<dyn Clock> {
+        Arc::new(self.clock.clone())
+    }
 
     fn set_title(&mut self, title: &str) -> Result<(), Self::Error> {
         self.operations.push(Operation::SetTitle(title.to_owned()));
         self.title = title.to_owned();
         Ok(())
     }
+    fn title(&mut self) -> Result<Option<String>, Self::Error> {
+        Ok(Some(self.title.clone()))
+    }
+    fn push_title(&mut self) -> Result<(), Self::Error> {
+        self.operations.push(Operation::PushTitle);
+        self.title_stack.push(self.title.clone());
+        Ok(())
+    }
+    fn pop_title(&mut self) -> Result<(), Self::Error> {
+        self.operations.push(Operation::PopTitle);
+        if let Some(title) = self.title_stack.pop() {
+            self.title = title;
+        }
+        Ok(())
+    }
+    fn set_clipboard(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.operations
+            .push(Operation::SetClipboard(text.to_owned()));
+        self.clipboard = text.to_owned();
+        Ok(())
+    }
+    fn bell(&mut self) -> Result<(), Self::Error> {
+        self.operations.push(Operation::Bell);
+        Ok(())
+    }
+    fn notify(&mut self, title: &str, body: &str) -> Result<(), Self::Error> {
+        self.operations
+            .push(Operation::Notify(title.to_owned(), body.to_owned()));
+        Ok(())
+    }
+    fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<(), Self::Error> {
+        self.operations.push(Operation::SetMouseCapture(capture));
+        if self.supports_mouse {
+            self.mouse_capture = capture;
+        }
+        Ok(())
+    }
 
     // Cursor functions
 
@@ -145,6 +375,7 @@ impl Bound for Dummy {
         self.buffer.cursor = Some(Cursor {
             shape: CursorShape::Block,
             blinking: false,
+            color: Color::Default,
             pos: self.cursor_pos,
         });
         Ok(())
@@ -159,6 +390,11 @@ impl Bound for Dummy {
         self.buffer.cursor.as_mut().unwrap().blinking = blinking;
         Ok(())
     }
+    fn set_cursor_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        self.operations.push(Operation::SetCursorColor(color));
+        self.buffer.cursor.as_mut().unwrap().color = color;
+        Ok(())
+    }
     fn set_cursor_pos(&mut self, pos: Vec2<u16>) -> Result<(), Self::Error> {
         self.operations.push(Operation::SetCursorPos(pos));
         if let Some(cursor) = &mut self.buffer.cursor {
@@ -170,6 +406,9 @@ impl Bound for Dummy {
 
     // Style functions
 
+    fn color_support(&mut self) -> ColorSupport {
+        self.color_support
+    }
     fn set_foreground(&mut self, foreground: Color) -> Result<(), Self::Error> {
         self.operations.push(Operation::SetForeground(foreground));
         self.style.foreground = foreground;
@@ -193,6 +432,18 @@ impl Bound for Dummy {
     fn set_underlined(&mut self, underlined: bool) -> Result<(), Self::Error> {
         self.operations.push(Operation::SetUnderlined(underlined));
         self.style.attributes.underlined = underlined;
+        self.style.attributes.underline_kind = UnderlineKind::Single;
+        Ok(())
+    }
+    fn set_underline_kind(&mut self, kind: UnderlineKind) -> Result<(), Self::Error> {
+        self.operations.push(Operation::SetUnderlineKind(kind));
+        self.style.attributes.underlined = true;
+        self.style.attributes.underline_kind = kind;
+        Ok(())
+    }
+    fn set_underline_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        self.operations.push(Operation::SetUnderlineColor(color));
+        self.style.underline_color = color;
         Ok(())
     }
     fn set_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
@@ -205,6 +456,38 @@ impl Bound for Dummy {
         self.style.attributes.crossed_out = crossed_out;
         Ok(())
     }
+    fn set_reversed(&mut self, reversed: bool) -> Result<(), Self::Error> {
+        self.operations.push(Operation::SetReversed(reversed));
+        self.style.attributes.reversed = reversed;
+        Ok(())
+    }
+    fn set_hidden(&mut self, hidden: bool) -> Result<(), Self::Error> {
+        self.operations.push(Operation::SetHidden(hidden));
+        self.style.attributes.hidden = hidden;
+        Ok(())
+    }
+    fn set_overlined(&mut self, overlined: bool) -> Result<(), Self::Error> {
+        self.operations.push(Operation::SetOverlined(overlined));
+        self.style.attributes.overlined = overlined;
+        Ok(())
+    }
+    fn set_link(&mut self, link: Option<&Link>) -> Result<(), Self::Error> {
+        self.operations.push(Operation::SetLink(link.cloned()));
+        self.link = link.cloned();
+        Ok(())
+    }
+
+    // Graphics functions
+
+    fn supports_graphics(&mut self) -> bool {
+        self.supports_graphics
+    }
+    fn draw_image(&mut self, size: Vec2<u16>, bitmap: &Bitmap) -> Result<(), Self::Error> {
+        self.operations
+            .push(Operation::DrawImage(size, bitmap.clone()));
+        self.buffer.draw_image(self.cursor_pos, size, bitmap);
+        Ok(())
+    }
 
     // Writing
 
@@ -212,11 +495,20 @@ impl Bound for Dummy {
         self.operations.push(Operation::Write(text.to_owned()));
         self.buffer.write(self.cursor_pos, text, self.style);
 
+        let start = self.cursor_pos;
         self.cursor_pos.x = min(
-            self.cursor_pos.x.saturating_add(text.width() as u16),
+            self.cursor_pos
+                .x
+                .saturating_add(crate::util::str_width(text) as u16),
             self.buffer.grid.width(),
         );
 
+        if let Some(link) = &self.link {
+            for x in start.x..self.cursor_pos.x {
+                self.buffer.set_link(Vec2::new(x, start.y), link);
+            }
+        }
+
         if let Some(cursor) = &mut self.buffer.cursor {
             cursor.pos = self.cursor_pos;
         }
@@ -227,6 +519,11 @@ impl Bound for Dummy {
 
     fn flush(&mut self) -> Result<(), Self::Error> {
         self.operations.push(Operation::Flush);
+        self.frames.push(self.buffer.grid.clone());
+        Ok(())
+    }
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        self.operations.push(Operation::Resume);
         Ok(())
     }
     fn reset(self) -> Result<Tty, Self::Error> {
@@ -239,13 +536,18 @@ impl<'a> ReadEvents<'a> for Dummy {
     type EventFuture = EventFuture;
 
     fn read_event(&'a mut self) -> Self::EventFuture {
-        let event = self.events.pop_front();
+        let mut event = self.events.pop_front();
+        if !self.supports_mouse {
+            while let Some(TerminalEvent::Mouse(_)) = event {
+                event = self.events.pop_front();
+            }
+        }
 
         if let Some(TerminalEvent::Resize(size)) = event {
             self.buffer.grid.resize_width(size.x);
             self.buffer
                 .grid
-                .resize_height_with_anchor(size.x, self.cursor_pos.y);
+                .resize_height_with_anchor(size.y, self.cursor_pos.y);
         }
 
         EventFuture(event)
@@ -265,3 +567,67 @@ impl Future for EventFuture {
         }
     }
 }
+
+#[test]
+fn test_frame_history() {
+    let mut bound = Dummy::new(Vec2::new(5, 1)).bind(Tty::dummy()).unwrap();
+    for text in &["one", "two", "three", "four"] {
+        bound.buffer.grid.clear();
+        bound
+            .buffer
+            .grid
+            .write(Vec2::new(0, 0), text, Style::default());
+        bound.flush().unwrap();
+    }
+
+    bound.assert_frame_contains("four");
+    bound.assert_frame_contains("ou");
+
+    let contents = |frames: &[Grid]| -> Vec<_> { frames.iter().map(Grid::contents).collect() };
+    assert_eq!(
+        contents(bound.frames_between("two", "four").unwrap()),
+        [["two  "], ["three"], ["four "]],
+    );
+    assert_eq!(
+        contents(bound.frames_between("three", "three").unwrap()),
+        [["three"]],
+    );
+    assert_eq!(bound.frames_between("four", "one"), None);
+    assert_eq!(bound.frames_between("five", "one"), None);
+}
+
+#[test]
+#[should_panic(expected = "frame does not contain \"one\"")]
+fn test_assert_frame_contains() {
+    let mut bound = Dummy::new(Vec2::new(5, 1)).bind(Tty::dummy()).unwrap();
+    bound.flush().unwrap();
+    bound.assert_frame_contains("one");
+}
+
+#[test]
+fn test_legacy() {
+    use super::{TerminalMouse, TerminalMouseKind};
+    use crate::{KeyPress, Modifiers};
+
+    let mouse = TerminalMouse {
+        kind: TerminalMouseKind::Release,
+        at: Vec2::new(0, 0),
+        modifiers: Modifiers::default(),
+    };
+    let mut bound = Dummy::legacy()
+        .with_events(vec![
+            TerminalEvent::Mouse(mouse),
+            KeyPress::from('a').into(),
+        ])
+        .bind(Tty::dummy())
+        .unwrap();
+    assert_eq!(bound.buffer.grid.size(), Vec2::new(80, 24));
+    assert_eq!(bound.color_support(), ColorSupport::Ansi16);
+
+    bound.set_mouse_capture(MouseCapture::Drag).unwrap();
+    assert_eq!(bound.mouse_capture, MouseCapture::None);
+
+    let event = futures_lite::future::block_on(bound.read_event()).unwrap();
+    assert_eq!(event, TerminalEvent::from(KeyPress::from('a')));
+    assert!(bound.events.is_empty());
+}
@@ -0,0 +1,389 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::{self, BufWriter, Read, Write};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use crate::style::{Color, ColorSupport, Intensity, Link, Rgb, Rgba, UnderlineKind};
+use crate::util::Base64;
+use crate::{CursorShape, MouseCapture, Vec2};
+
+use super::ansi::parser::Parser;
+use super::{Backend, ReadEvents, TerminalEvent, Tty};
+
+/// A backend that draws to any writer and reads input from any reader, such as a serial port or a
+/// socket.
+///
+/// There is no OS TTY to query, so the terminal is assumed to stay at the size it was created with
+/// and to understand xterm's escape sequences. Input is read and parsed on a separate thread.
+/// Reads from the reader can't be interrupted, so if the thread is waiting on the reader when the
+/// backend is reset, whatever it reads next is discarded.
+///
+/// Because it doesn't touch the process's own terminal, stdio isn't captured and any number of
+/// terminals using this backend can exist at once, for example one per connected client.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::net::TcpListener;
+///
+/// let (socket, _) = TcpListener::bind("127.0.0.1:2323")?.accept()?;
+/// let backend = toon::backend::Generic::new(socket.try_clone()?, socket, (80, 24));
+/// let terminal = toon::Terminal::new(backend)?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Generic<R, W> {
+    reader: R,
+    writer: W,
+    size: Vec2<u16>,
+    color_support: ColorSupport,
+}
+
+impl<R, W> Generic<R, W> {
+    /// Create a new generic backend reading input from `reader` and drawing to `writer`, on a
+    /// terminal of the given size.
+    ///
+    /// By default it assumes the terminal supports the 16 named colors.
+    #[must_use]
+    pub fn new(reader: R, writer: W, size: impl Into<Vec2<u16>>) -> Self {
+        Self {
+            reader,
+            writer,
+            size: size.into(),
+            color_support: ColorSupport::Ansi16,
+        }
+    }
+
+    /// Set the colors the terminal is assumed to support.
+    #[must_use]
+    pub fn with_color_support(self, color_support: ColorSupport) -> Self {
+        Self {
+            color_support,
+            ..self
+        }
+    }
+}
+
+impl<R: Read + Send + 'static, W: Write> Backend for Generic<R, W> {
+    type Error = io::Error;
+    type Bound = Bound<W>;
+
+    fn bind(self, tty: Tty) -> Result<Self::Bound, Self::Error> {
+        let mut io = BufWriter::new(self.writer);
+
+        // Enter the alternate screen, clear it, disable line wrapping, make the mouse report SGR
+        // coordinates once it is captured, bracket pasted text and ask terminals that support the
+        // kitty keyboard protocol to report key repeats, releases and keys like those on the keypad
+        // unambiguously.
+        io.write_all(b"\x1B[?1049h\x1B[2J\x1B[?7l\x1B[?1006h\x1B[?2004h\x1B[>3u")?;
+        io.flush()?;
+
+        let shared = Arc::new(Mutex::new(Shared {
+            events: VecDeque::new(),
+            waker: None,
+            stopped: false,
+        }));
+
+        let mut reader = self.reader;
+        let thread_shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            let mut parser = Parser::default();
+            let mut buf = [0; 1024];
+            let mut events = Vec::new();
+
+            loop {
+                if thread_shared.lock().unwrap().stopped {
+                    break;
+                }
+                let result = reader.read(&mut buf);
+
+                let mut shared = thread_shared.lock().unwrap();
+                if shared.stopped {
+                    break;
+                }
+
+                match result {
+                    Ok(0) => {
+                        shared
+                            .events
+                            .push_back(Err(io::ErrorKind::UnexpectedEof.into()));
+                        shared.wake();
+                        break;
+                    }
+                    // Reads can't time out, so a lone escape can't wait for the rest of a
+                    // sequence.
+                    Ok(len) => {
+                        parser.feed(&buf[..len], &mut events);
+                        parser.flush_escape(&mut events);
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => {
+                        shared.events.push_back(Err(e));
+                        shared.wake();
+                        break;
+                    }
+                }
+
+                if !events.is_empty() {
+                    shared.events.extend(events.drain(..).map(Ok));
+                    shared.wake();
+                }
+            }
+        });
+
+        Ok(Bound {
+            io,
+            tty,
+            shared,
+            size: self.size,
+            color_support: self.color_support,
+            cursor_shape: CursorShape::Block,
+            cursor_blinking: true,
+        })
+    }
+
+    fn is_dummy() -> bool {
+        true
+    }
+}
+
+/// State shared between the input thread and the bound backend.
+#[derive(Debug)]
+struct Shared {
+    events: VecDeque<io::Result<TerminalEvent>>,
+    waker: Option<Waker>,
+    /// Whether the backend has been reset and the input thread should stop.
+    stopped: bool,
+}
+
+impl Shared {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Bound<W: Write> {
+    io: BufWriter<W>,
+    tty: Tty,
+    shared: Arc<Mutex<Shared>>,
+    size: Vec2<u16>,
+    color_support: ColorSupport,
+    cursor_shape: CursorShape,
+    cursor_blinking: bool,
+}
+
+impl<W: Write> Bound<W> {
+    fn write_cursor_style(&mut self) -> io::Result<()> {
+        // DECSCUSR
+        let style = match self.cursor_shape {
+            CursorShape::Block => 1,
+            CursorShape::Underline => 3,
+            CursorShape::Bar => 5,
+        } + if self.cursor_blinking { 0 } else { 1 };
+        write!(self.io, "\x1B[{} q", style)
+    }
+
+    /// Write a color, where `base` is 30 for the foreground and 40 for the background.
+    fn write_color(&mut self, color: Color, base: u8) -> io::Result<()> {
+        match color {
+            Color::Default => write!(self.io, "\x1B[{}m", base + 9),
+            Color::Rgb(Rgb { r, g, b }) | Color::Rgba(Rgba { r, g, b, .. }) => {
+                write!(self.io, "\x1B[{};2;{};{};{}m", base + 8, r, g, b)
+            }
+            _ => match color.ansi_value().unwrap() {
+                index @ 0..=7 => write!(self.io, "\x1B[{}m", base + index),
+                index @ 8..=15 => write!(self.io, "\x1B[{}m", base + 60 + index - 8),
+                index => write!(self.io, "\x1B[{};5;{}m", base + 8, index),
+            },
+        }
+    }
+}
+
+impl<W: Write> super::Bound for Bound<W> {
+    type Error = io::Error;
+
+    // General functions
+
+    fn size(&mut self) -> Result<Vec2<u16>, Self::Error> {
+        Ok(self.size)
+    }
+    fn set_title(&mut self, title: &str) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B]0;{}\x07", title)
+    }
+    fn set_clipboard(&mut self, text: &str) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B]52;c;{}\x07", Base64(text.as_bytes()))
+    }
+    fn bell(&mut self) -> Result<(), Self::Error> {
+        self.io.write_all(b"\x07")
+    }
+    fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<(), Self::Error> {
+        super::write_mouse_capture(&mut self.io, capture)
+    }
+
+    // Cursor functions
+
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
+        self.io.write_all(b"\x1B[?25l")
+    }
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
+        self.io.write_all(b"\x1B[?25h")
+    }
+    fn set_cursor_shape(&mut self, shape: CursorShape) -> Result<(), Self::Error> {
+        self.cursor_shape = shape;
+        self.write_cursor_style()
+    }
+    fn set_cursor_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
+        self.cursor_blinking = blinking;
+        self.write_cursor_style()
+    }
+    fn set_cursor_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        super::write_cursor_color(&mut self.io, color)
+    }
+    fn set_cursor_pos(&mut self, pos: Vec2<u16>) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{};{}H", pos.y + 1, pos.x + 1)
+    }
+
+    // Style functions
+
+    fn color_support(&mut self) -> ColorSupport {
+        self.color_support
+    }
+    fn set_foreground(&mut self, foreground: Color) -> Result<(), Self::Error> {
+        self.write_color(foreground, 30)
+    }
+    fn set_background(&mut self, background: Color) -> Result<(), Self::Error> {
+        self.write_color(background, 40)
+    }
+    fn set_intensity(&mut self, intensity: Intensity) -> Result<(), Self::Error> {
+        self.io.write_all(match intensity {
+            Intensity::Dim => b"\x1B[2m",
+            // SGR 22 resets both bold and dim.
+            Intensity::Normal => b"\x1B[22m",
+            Intensity::Bold => b"\x1B[1m",
+        })
+    }
+    fn set_italic(&mut self, italic: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if italic { 3 } else { 23 })
+    }
+    fn set_underlined(&mut self, underlined: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if underlined { 4 } else { 24 })
+    }
+    fn set_underline_kind(&mut self, kind: UnderlineKind) -> Result<(), Self::Error> {
+        super::write_underline_kind(&mut self.io, kind)
+    }
+    fn set_underline_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        super::write_underline_color(&mut self.io, color)
+    }
+    fn set_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if blinking { 5 } else { 25 })
+    }
+    fn set_crossed_out(&mut self, crossed_out: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if crossed_out { 9 } else { 29 })
+    }
+    fn set_reversed(&mut self, reversed: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if reversed { 7 } else { 27 })
+    }
+    fn set_hidden(&mut self, hidden: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if hidden { 8 } else { 28 })
+    }
+    fn set_overlined(&mut self, overlined: bool) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B[{}m", if overlined { 53 } else { 55 })
+    }
+    fn set_link(&mut self, link: Option<&Link>) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B]8;;{}\x1B\\", link.map_or("", Link::url))
+    }
+
+    // Writing
+
+    fn write(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.io.write_all(text.as_bytes())
+    }
+
+    // Finalizing functions
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.io.flush()
+    }
+    fn reset(mut self) -> Result<Tty, Self::Error> {
+        // The input thread can't be interrupted, so it will stop after its next read returns.
+        self.shared.lock().unwrap().stopped = true;
+
+        super::write_mouse_capture(&mut self.io, MouseCapture::None)?;
+        self.io
+            .write_all(b"\x1B[<u\x1B[?2004l\x1B[?1006l\x1B[?7h\x1B[0m\x1B[?25h")?;
+        super::write_cursor_color(&mut self.io, Color::Default)?;
+        self.io.write_all(b"\x1B[?1049l")?;
+        self.io.flush()?;
+
+        Ok(self.tty)
+    }
+}
+
+impl<'a, W: Write> ReadEvents<'a> for Bound<W> {
+    type EventError = <Self as super::Bound>::Error;
+    type EventFuture = EventFuture<'a>;
+
+    fn read_event(&'a mut self) -> Self::EventFuture {
+        EventFuture {
+            shared: &self.shared,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EventFuture<'a> {
+    shared: &'a Mutex<Shared>,
+}
+
+impl<'a> Future for EventFuture<'a> {
+    type Output = io::Result<TerminalEvent>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.events.pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[test]
+fn test_generic() {
+    use super::Bound as _;
+    use crate::KeyPress;
+
+    let mut output = Vec::new();
+    let backend = Generic::new(io::Cursor::new(b"a\x1B[<0;3;2M"), &mut output, (4, 2));
+
+    let mut bound = backend.bind(Tty::dummy()).unwrap();
+    assert_eq!(bound.size().unwrap(), Vec2::new(4, 2));
+    assert_eq!(bound.color_support(), ColorSupport::Ansi16);
+
+    let event = futures_lite::future::block_on(bound.read_event()).unwrap();
+    assert_eq!(event, TerminalEvent::Key(KeyPress::from('a')));
+    let event = futures_lite::future::block_on(bound.read_event()).unwrap();
+    assert!(matches!(event, TerminalEvent::Mouse(mouse) if mouse.at == Vec2::new(2, 1)));
+    let error = futures_lite::future::block_on(bound.read_event()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+    bound.set_cursor_pos(Vec2::new(1, 0)).unwrap();
+    bound.set_foreground(Color::Red).unwrap();
+    bound.set_background(Color::DarkBlue).unwrap();
+    bound.write("Hi").unwrap();
+    bound.reset().unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with(
+        "\x1B[?1049h\x1B[2J\x1B[?7l\x1B[?1006h\x1B[?2004h\x1B[>3u\x1B[1;2H\x1B[91m\x1B[44mHi"
+    ));
+    assert!(output.ends_with("\x1B[?1049l"));
+}
@@ -7,11 +7,19 @@ use std::io::{self, BufWriter, IoSlice, Write};
 use std::os::unix::io::{AsRawFd, RawFd};
 #[cfg(windows)]
 use std::os::windows::io::{AsRawHandle, RawHandle};
+use std::sync::Arc;
 
 use os_pipe::PipeReader;
 use stdio_override::{StderrOverride, StdoutOverride};
 
-use crate::{Color, CursorShape, Intensity, KeyPress, Modifiers, MouseButton, Vec2};
+use crate::clock::{Clock, SystemClock};
+use crate::{
+    Bitmap, Color, ColorSupport, CursorShape, Intensity, KeyPress, Link, Modifiers, MouseButton,
+    MouseCapture, Rgb, Rgba, Signal, UnderlineKind, Vec2,
+};
+
+mod ansi;
+pub use self::ansi::Ansi;
 
 #[cfg(feature = "crossterm")]
 #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "crossterm")))]
@@ -19,9 +27,29 @@ mod crossterm;
 #[cfg(feature = "crossterm")]
 pub use self::crossterm::Crossterm;
 
+#[cfg(all(unix, feature = "termion"))]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(all(unix, feature = "termion"))))]
+mod termion;
+#[cfg(all(unix, feature = "termion"))]
+pub use self::termion::Termion;
+
+#[cfg(windows)]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+mod console;
+#[cfg(windows)]
+pub use self::console::Console;
+
+mod generic;
+pub use self::generic::Generic;
+
 mod dummy;
 pub use self::dummy::{Dummy, Operation};
 
+mod sixel;
+
+mod record;
+pub use self::record::{Entry, Record, RecordError, Recording};
+
 /// A backend that can be used with Toon.
 pub trait Backend {
     /// Errors produced by this backend.
@@ -59,9 +87,102 @@ pub trait Bound: for<'a> ReadEvents<'a, EventError = <Self as Bound>::Error> + S
     /// Get the size of the terminal.
     fn size(&mut self) -> Result<Vec2<u16>, Self::Error>;
 
+    /// Get the size of a single cell of the terminal in pixels, or [`None`] if it isn't known.
+    ///
+    /// By default this is [`None`].
+    fn cell_size(&mut self) -> Result<Option<Vec2<u16>>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Get the background color of the terminal, or [`None`] if it isn't known.
+    ///
+    /// This shouldn't wait for the terminal to reply to a query. Backends that have to ask the
+    /// terminal can return a guess and report the reply later as a
+    /// [`TerminalEvent::BackgroundColor`].
+    ///
+    /// By default this is [`None`].
+    fn background_color(&mut self) -> Result<Option<Rgb>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Get the clock the terminal reads the time from.
+    ///
+    /// By default this is a [`SystemClock`].
+    fn clock(&mut self) -> Arc<dyn Clock> {
+        Arc::new(SystemClock)
+    }
+
     /// Set the title of the terminal.
     fn set_title(&mut self, title: &str) -> Result<(), Self::Error>;
 
+    /// Get the current title of the terminal, or [`None`] if it can't be read.
+    ///
+    /// This is read when the terminal is created, so that the user's original title can be
+    /// restored with [`set_title`](Self::set_title) when it is cleaned up even if the terminal
+    /// doesn't support a title stack. Most terminals don't allow their title to be read.
+    ///
+    /// By default this is [`None`].
+    fn title(&mut self) -> Result<Option<String>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Save the current title of the terminal onto the terminal's title stack.
+    ///
+    /// This is called when the terminal is created so that the user's original title can be
+    /// restored with [`pop_title`](Self::pop_title) when it is cleaned up. Terminals that don't
+    /// support a title stack should ignore this; the title is then restored from
+    /// [`title`](Self::title) if it could be read.
+    ///
+    /// By default this does nothing.
+    fn push_title(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Restore the title of the terminal from the top of the terminal's title stack.
+    ///
+    /// By default this does nothing.
+    fn pop_title(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Set the contents of the user's clipboard.
+    ///
+    /// Terminals that don't support writing to the clipboard should ignore this.
+    ///
+    /// By default this does nothing.
+    fn set_clipboard(&mut self, text: &str) -> Result<(), Self::Error> {
+        let _ = text;
+        Ok(())
+    }
+
+    /// Ring the terminal's bell.
+    ///
+    /// By default this does nothing.
+    fn bell(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Show a desktop notification with a title and body.
+    ///
+    /// Terminals that don't support notifications should ignore this.
+    ///
+    /// By default this does nothing.
+    fn notify(&mut self, title: &str, body: &str) -> Result<(), Self::Error> {
+        let _ = (title, body);
+        Ok(())
+    }
+
+    /// Set how much mouse input the terminal captures.
+    ///
+    /// This is called when the terminal is created, so backends don't need to capture the mouse
+    /// when they are bound.
+    ///
+    /// By default this does nothing.
+    fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<(), Self::Error> {
+        let _ = capture;
+        Ok(())
+    }
+
     // Cursor functions
 
     /// Hide the cursor.
@@ -76,11 +197,33 @@ pub trait Bound: for<'a> ReadEvents<'a, EventError = <Self as Bound>::Error> + S
     /// Set whether the cursor blinks.
     fn set_cursor_blinking(&mut self, blinking: bool) -> Result<(), Self::Error>;
 
+    /// Set the color of the cursor.
+    ///
+    /// Since the cursor color outlives the program on most terminals, backends that support this
+    /// should reset it to the default when they are [reset](Self::reset).
+    ///
+    /// By default this does nothing.
+    fn set_cursor_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        let _ = color;
+        Ok(())
+    }
+
     /// Set the position of the cursor (zero-indexed).
     fn set_cursor_pos(&mut self, pos: Vec2<u16>) -> Result<(), Self::Error>;
 
     // Style functions
 
+    /// Get the colors that the terminal is able to display.
+    ///
+    /// Colors that aren't supported are converted to the closest supported color before being
+    /// passed to [`set_foreground`](Self::set_foreground) and
+    /// [`set_background`](Self::set_background).
+    ///
+    /// By default this is [`ColorSupport::TrueColor`].
+    fn color_support(&mut self) -> ColorSupport {
+        ColorSupport::TrueColor
+    }
+
     /// Set the foreground color to write with.
     fn set_foreground(&mut self, foreground: Color) -> Result<(), Self::Error>;
 
@@ -93,15 +236,86 @@ pub trait Bound: for<'a> ReadEvents<'a, EventError = <Self as Bound>::Error> + S
     /// Set whether the text is emphasized.
     fn set_italic(&mut self, italic: bool) -> Result<(), Self::Error>;
 
-    /// Set whether the text is underlined.
+    /// Set whether the text is underlined with a single underline.
     fn set_underlined(&mut self, underlined: bool) -> Result<(), Self::Error>;
 
+    /// Underline the text with the given kind of underline.
+    ///
+    /// By default this calls [`set_underlined`](Self::set_underlined), using a single underline.
+    fn set_underline_kind(&mut self, kind: UnderlineKind) -> Result<(), Self::Error> {
+        let _ = kind;
+        self.set_underlined(true)
+    }
+
+    /// Set the color of the underline. If it is [`Color::Default`] the underline should be the same
+    /// color as the text.
+    ///
+    /// By default this does nothing.
+    fn set_underline_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        let _ = color;
+        Ok(())
+    }
+
     /// Set whether the text blinks.
     fn set_blinking(&mut self, blinking: bool) -> Result<(), Self::Error>;
 
     /// Set whether the text is crossed out.
     fn set_crossed_out(&mut self, crossed_out: bool) -> Result<(), Self::Error>;
 
+    /// Set whether the foreground and background colors of the text are swapped.
+    ///
+    /// By default this does nothing.
+    fn set_reversed(&mut self, reversed: bool) -> Result<(), Self::Error> {
+        let _ = reversed;
+        Ok(())
+    }
+
+    /// Set whether the text is hidden.
+    ///
+    /// By default this does nothing.
+    fn set_hidden(&mut self, hidden: bool) -> Result<(), Self::Error> {
+        let _ = hidden;
+        Ok(())
+    }
+
+    /// Set whether the text has a line over it.
+    ///
+    /// By default this does nothing.
+    fn set_overlined(&mut self, overlined: bool) -> Result<(), Self::Error> {
+        let _ = overlined;
+        Ok(())
+    }
+
+    /// Set the hyperlink that written text points to, or stop writing a hyperlink if it is
+    /// [`None`].
+    ///
+    /// By default this does nothing.
+    fn set_link(&mut self, link: Option<&Link>) -> Result<(), Self::Error> {
+        let _ = link;
+        Ok(())
+    }
+
+    // Graphics functions
+
+    /// Get whether the terminal is able to display images with
+    /// [`draw_image`](Self::draw_image).
+    ///
+    /// By default this is `false`.
+    fn supports_graphics(&mut self) -> bool {
+        false
+    }
+
+    /// Draw an image with its top left corner at the cursor, scaled to cover an area of cells.
+    ///
+    /// The position of the cursor and the style being written with must be the same after this as
+    /// before.
+    ///
+    /// By default this does nothing.
+    fn draw_image(&mut self, size: Vec2<u16>, bitmap: &Bitmap) -> Result<(), Self::Error> {
+        let _ = (size, bitmap);
+        Ok(())
+    }
+
     // Writing
 
     /// Write text to the output.
@@ -115,6 +329,15 @@ pub trait Bound: for<'a> ReadEvents<'a, EventError = <Self as Bound>::Error> + S
     /// Flush all buffered actions to the tty.
     fn flush(&mut self) -> Result<(), Self::Error>;
 
+    /// Set the terminal up again after the process was resumed from being suspended, since the
+    /// shell may have reset it in the meantime. This should put the terminal back into raw mode,
+    /// enter the alternate screen and clear it.
+    ///
+    /// By default this does nothing.
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Reset the terminal to its initial state, returning the TTY.
     ///
     /// This will always be called.
@@ -136,14 +359,40 @@ pub trait ReadEvents<'a> {
 }
 
 /// An event on the terminal.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum TerminalEvent {
     /// A key input occurred.
     Key(KeyPress),
     /// A mouse input occurred.
     Mouse(TerminalMouse),
+    /// Text was pasted into the terminal.
+    Paste(String),
+    /// The text being composed by an input method changed. See [`Input::Compose`] for details.
+    ///
+    /// None of the built-in terminal backends report this, as terminals display compositions
+    /// themselves.
+    Compose(String),
     /// The terminal was resized. Contains the new size.
     Resize(Vec2<u16>),
+    /// The terminal replied to a query made by [`Bound::background_color`] with its background
+    /// color.
+    BackgroundColor(Rgb),
+    /// A signal was sent to the process.
+    ///
+    /// None of the built-in terminal backends report this; signals are caught by the terminal
+    /// itself when [`Terminal::catch_signals`](crate::Terminal::catch_signals) is called.
+    Signal(Signal),
+}
+
+impl From<KeyPress> for TerminalEvent {
+    fn from(press: KeyPress) -> Self {
+        Self::Key(press)
+    }
+}
+impl From<TerminalMouse> for TerminalEvent {
+    fn from(mouse: TerminalMouse) -> Self {
+        Self::Mouse(mouse)
+    }
 }
 
 /// A mouse event on the terminal.
@@ -170,6 +419,120 @@ pub enum TerminalMouseKind {
     ScrollDown,
     /// The scroll wheel was scrolled up.
     ScrollUp,
+    /// The scroll wheel was scrolled left.
+    ScrollLeft,
+    /// The scroll wheel was scrolled right.
+    ScrollRight,
+}
+
+/// Write the OSC 12 sequence to set the color of the cursor, or the OSC 112 sequence to reset it.
+///
+/// The sequence takes an RGB value, so named and ANSI colors are converted using xterm's palette.
+fn write_cursor_color(out: &mut impl Write, color: Color) -> io::Result<()> {
+    match color.to_rgb() {
+        Some(Rgb { r, g, b }) => write!(out, "\x1B]12;rgb:{:02x}/{:02x}/{:02x}\x1B\\", r, g, b),
+        None => out.write_all(b"\x1B]112\x1B\\"),
+    }
+}
+
+/// Write the escape sequence to set the mouse tracking mode.
+///
+/// The modes are mutually exclusive, and disabling any of them disables mouse tracking entirely,
+/// so the other modes are disabled before the new one is enabled.
+fn write_mouse_capture(out: &mut impl Write, capture: MouseCapture) -> io::Result<()> {
+    out.write_all(match capture {
+        MouseCapture::None => b"\x1B[?1003l\x1B[?1002l\x1B[?1000l",
+        MouseCapture::Click => b"\x1B[?1003l\x1B[?1002l\x1B[?1000h",
+        MouseCapture::Drag => b"\x1B[?1003l\x1B[?1000h\x1B[?1002h",
+        MouseCapture::Motion => b"\x1B[?1000h\x1B[?1002h\x1B[?1003h",
+    })
+}
+
+/// Write the escape sequence to show a desktop notification.
+///
+/// iTerm2 and Windows Terminal support OSC 9, which only takes a message, and other terminals such
+/// as urxvt, foot and kitty support OSC 777, which takes both a title and a body.
+fn write_notification(out: &mut impl Write, title: &str, body: &str) -> io::Result<()> {
+    let clean = |s: &str, separator: bool| -> String {
+        s.chars()
+            .filter(|&c| !(c.is_control() || separator && c == ';'))
+            .collect()
+    };
+
+    let osc_9 = std::env::var_os("WT_SESSION").is_some()
+        || std::env::var_os("TERM_PROGRAM").map_or(false, |program| program == "iTerm.app");
+
+    if osc_9 {
+        write!(
+            out,
+            "\x1B]9;{}: {}\x1B\\",
+            clean(title, false),
+            clean(body, false)
+        )
+    } else {
+        write!(
+            out,
+            "\x1B]777;notify;{};{}\x1B\\",
+            clean(title, true),
+            clean(body, false)
+        )
+    }
+}
+
+/// Write the SGR sequence to underline text with the given kind of underline.
+fn write_underline_kind(out: &mut impl Write, kind: UnderlineKind) -> io::Result<()> {
+    let kind = match kind {
+        UnderlineKind::Single => 1,
+        UnderlineKind::Double => 2,
+        UnderlineKind::Curly => 3,
+        UnderlineKind::Dotted => 4,
+        UnderlineKind::Dashed => 5,
+    };
+    write!(out, "\x1B[4:{}m", kind)
+}
+
+/// Write the SGR sequence to set the color of underlines.
+fn write_underline_color(out: &mut impl Write, color: Color) -> io::Result<()> {
+    match color {
+        Color::Default => out.write_all(b"\x1B[59m"),
+        Color::Rgb(Rgb { r, g, b }) | Color::Rgba(Rgba { r, g, b, .. }) => {
+            write!(out, "\x1B[58:2::{}:{}:{}m", r, g, b)
+        }
+        color => write!(out, "\x1B[58:5:{}m", color.ansi_value().unwrap()),
+    }
+}
+
+/// Get the size of a cell of the terminal in pixels from the size of the window reported by the
+/// kernel, or [`None`] if the terminal doesn't report it.
+fn cell_size(tty: &Tty) -> io::Result<Option<Vec2<u16>>> {
+    #[cfg(unix)]
+    {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        if size.ws_xpixel == 0 || size.ws_ypixel == 0 || size.ws_col == 0 || size.ws_row == 0 {
+            return Ok(None);
+        }
+        Ok(Some(Vec2::new(
+            size.ws_xpixel / size.ws_col,
+            size.ws_ypixel / size.ws_row,
+        )))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tty;
+        Ok(None)
+    }
+}
+
+/// Guess the background color of the terminal from the `COLORFGBG` environment variable, which
+/// some terminals such as rxvt and Konsole set to the palette indices of the foreground and
+/// background colors, like `15;0`.
+fn background_from_env() -> Option<Rgb> {
+    let var = std::env::var("COLORFGBG").ok()?;
+    let index: u8 = var.rsplit(';').next()?.parse().ok()?;
+    Color::new_ansi(index).to_rgb()
 }
 
 /// A type which backends use to perform I/O.
@@ -186,13 +549,14 @@ impl Tty {
     pub(crate) fn dummy() -> Self {
         Self { inner: None }
     }
-    pub(crate) fn new() -> io::Result<(Self, PipeReader)> {
-        let (inner, writer) = TtyInner::new()?;
+    /// Create a tty, returning the readers of the captured stdout and stderr if stdio is captured.
+    pub(crate) fn new(capture: bool) -> io::Result<(Self, Option<(PipeReader, PipeReader)>)> {
+        let (inner, captured) = TtyInner::new(capture)?;
         Ok((
             Self {
                 inner: Some(BufWriter::new(inner)),
             },
-            writer,
+            captured,
         ))
     }
     pub(crate) fn cleanup(self) -> io::Result<()> {
@@ -230,17 +594,27 @@ impl AsRawHandle for Tty {
 
 #[derive(Debug)]
 struct TtyInner {
-    stdout: StdoutOverride,
-    stderr: StderrOverride,
+    stdout: Stdout,
+    /// Only [`None`] if stdio isn't being captured.
+    stderr: Option<StderrOverride>,
     tty: Option<File>,
 }
 
 impl TtyInner {
-    fn new() -> io::Result<(Self, PipeReader)> {
-        let (rx, tx) = os_pipe::pipe()?;
-
-        let stdout = StdoutOverride::from_io_ref(&tx)?;
-        let stderr = StderrOverride::from_io(tx)?;
+    fn new(capture: bool) -> io::Result<(Self, Option<(PipeReader, PipeReader)>)> {
+        let (stdout, stderr, rx) = if capture {
+            let (stdout_rx, stdout_tx) = os_pipe::pipe()?;
+            let (stderr_rx, stderr_tx) = os_pipe::pipe()?;
+            let stdout = StdoutOverride::from_io(stdout_tx)?;
+            let stderr = StderrOverride::from_io(stderr_tx)?;
+            (
+                Stdout::Overridden(stdout),
+                Some(stderr),
+                Some((stdout_rx, stderr_rx)),
+            )
+        } else {
+            (Stdout::Std(io::stdout()), None, None)
+        };
 
         let tty = if cfg!(unix) {
             let tty_path = if cfg!(target_os = "redox") {
@@ -270,8 +644,12 @@ impl TtyInner {
         ))
     }
     fn cleanup(self) -> io::Result<()> {
-        self.stdout.reset()?;
-        self.stderr.reset()?;
+        if let Stdout::Overridden(stdout) = self.stdout {
+            stdout.reset()?;
+        }
+        if let Some(stderr) = self.stderr {
+            stderr.reset()?;
+        }
         Ok(())
     }
 }
@@ -316,3 +694,52 @@ impl AsRawHandle for TtyInner {
         self.stdout.as_raw_handle()
     }
 }
+
+/// The standard output of the process before Toon captured it.
+#[derive(Debug)]
+enum Stdout {
+    /// Stdio is captured, and the override writes to the original standard output.
+    Overridden(StdoutOverride),
+    /// Stdio isn't captured.
+    Std(io::Stdout),
+}
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Overridden(stdout) => stdout.write(buf),
+            Self::Std(stdout) => stdout.write(buf),
+        }
+    }
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        match self {
+            Self::Overridden(stdout) => stdout.write_vectored(bufs),
+            Self::Std(stdout) => stdout.write_vectored(bufs),
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Overridden(stdout) => stdout.flush(),
+            Self::Std(stdout) => stdout.flush(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for Stdout {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Self::Overridden(stdout) => stdout.as_raw_fd(),
+            Self::Std(stdout) => stdout.as_raw_fd(),
+        }
+    }
+}
+#[cfg(windows)]
+impl AsRawHandle for Stdout {
+    fn as_raw_handle(&self) -> RawHandle {
+        match self {
+            Self::Overridden(stdout) => stdout.as_raw_handle(),
+            Self::Std(stdout) => stdout.as_raw_handle(),
+        }
+    }
+}
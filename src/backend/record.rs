@@ -0,0 +1,1094 @@
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::future::Future;
+use std::io::{self, BufRead, Write};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::clock::Clock;
+use crate::input::{Key, KeyKind, KeyPress, MediaKey, Modifiers, MouseButton, Signal};
+use crate::style::{AnsiColor, Color, ColorSupport, Intensity, Link, Rgb, Rgba, UnderlineKind};
+use crate::{Bitmap, CursorShape, MouseCapture, Vec2};
+
+use super::{
+    Backend, Bound as _, Operation, ReadEvents, TerminalEvent, TerminalMouse, TerminalMouseKind,
+    Tty,
+};
+
+/// A backend wrapper that records a session to a writer, so that it can be replayed later with a
+/// [`Recording`].
+///
+/// Every operation performed on the backend and every event read from it is written out with the
+/// time since the backend was bound, one per line. This is useful for bug reports and demos.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "crossterm")]
+/// # {
+/// use std::fs::File;
+///
+/// use toon::backend::Record;
+///
+/// let file = File::create("session.txt")?;
+/// let terminal = toon::Terminal::new(Record::new(toon::Crossterm::default(), file))?;
+/// # }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Record<B, W> {
+    backend: B,
+    output: W,
+}
+
+impl<B, W> Record<B, W> {
+    /// Record the backend to the writer.
+    ///
+    /// The writer is not buffered by the recorder, so you may want to wrap it in a
+    /// [`BufWriter`](std::io::BufWriter).
+    #[must_use]
+    pub fn new(backend: B, output: W) -> Self {
+        Self { backend, output }
+    }
+}
+
+impl<B: Backend, W: Write> Backend for Record<B, W> {
+    type Error = RecordError<B::Error>;
+    type Bound = Bound<B::Bound, W>;
+
+    fn bind(self, io: Tty) -> Result<Self::Bound, Self::Error> {
+        let mut inner = self.backend.bind(io).map_err(RecordError::Backend)?;
+        let size = inner.size().map_err(RecordError::Backend)?;
+
+        let mut log = Log {
+            output: self.output,
+            start: Instant::now(),
+        };
+        log.entry(&Entry::Size(size))?;
+
+        Ok(Bound {
+            inner,
+            log,
+            events: Vec::new(),
+        })
+    }
+
+    fn is_dummy() -> bool {
+        B::is_dummy()
+    }
+}
+
+/// An error produced by a [`Record`] backend.
+#[derive(Debug)]
+pub enum RecordError<E> {
+    /// An error in the recorded backend.
+    Backend(E),
+    /// An error writing the recording.
+    Io(io::Error),
+}
+
+impl<E> From<io::Error> for RecordError<E> {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl<E: Display> Display for RecordError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Backend(e) => e.fmt(f),
+            Self::Io(e) => write!(f, "failed to write recording: {}", e),
+        }
+    }
+}
+impl<E: StdError + 'static> StdError for RecordError<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Backend(e) => Some(e),
+            Self::Io(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Log<W> {
+    output: W,
+    start: Instant,
+}
+
+impl<W: Write> Log<W> {
+    fn entry(&mut self, entry: &Entry) -> io::Result<()> {
+        self.entry_at(self.start.elapsed(), entry)
+    }
+    fn entry_at(&mut self, time: Duration, entry: &Entry) -> io::Result<()> {
+        writeln!(self.output, "{} {}", time.as_micros(), entry)
+    }
+}
+
+#[derive(Debug)]
+pub struct Bound<B, W> {
+    inner: B,
+    log: Log<W>,
+    /// Events that have been read but not yet written to the log.
+    ///
+    /// Event futures can't hold a reference to the writer, so they store their events here and
+    /// they are written before the next operation.
+    events: Vec<(Duration, TerminalEvent)>,
+}
+
+impl<B: super::Bound, W: Write> Bound<B, W> {
+    fn record(
+        &mut self,
+        operation: Operation,
+        f: impl FnOnce(&mut B) -> Result<(), B::Error>,
+    ) -> Result<(), RecordError<B::Error>> {
+        f(&mut self.inner).map_err(RecordError::Backend)?;
+        self.write_events()?;
+        self.log.entry(&Entry::Operation(operation))?;
+        Ok(())
+    }
+
+    fn write_events(&mut self) -> io::Result<()> {
+        for (time, event) in self.events.drain(..) {
+            self.log.entry_at(time, &Entry::Event(event))?;
+        }
+        Ok(())
+    }
+}
+
+impl<B: super::Bound, W: Write> super::Bound for Bound<B, W> {
+    type Error = RecordError<B::Error>;
+
+    // General functions
+
+    fn size(&mut self) -> Result<Vec2<u16>, Self::Error> {
+        self.inner.size().map_err(RecordError::Backend)
+    }
+    fn cell_size(&mut self) -> Result<Option<Vec2<u16>>, Self::Error> {
+        self.inner.cell_size().map_err(RecordError::Backend)
+    }
+    fn background_color(&mut self) -> Result<Option<Rgb>, Self::Error> {
+        self.inner.background_color().map_err(RecordError::Backend)
+    }
+    fn clock(&mut self) -> Arc<dyn Clock> {
+        self.inner.clock()
+    }
+    fn set_title(&mut self, title: &str) -> Result<(), Self::Error> {
+        self.record(Operation::SetTitle(title.to_owned()), |b| {
+            b.set_title(title)
+        })
+    }
+    fn title(&mut self) -> Result<Option<String>, Self::Error> {
+        self.inner.title().map_err(RecordError::Backend)
+    }
+    fn push_title(&mut self) -> Result<(), Self::Error> {
+        self.record(Operation::PushTitle, B::push_title)
+    }
+    fn pop_title(&mut self) -> Result<(), Self::Error> {
+        self.record(Operation::PopTitle, B::pop_title)
+    }
+    fn set_clipboard(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.record(Operation::SetClipboard(text.to_owned()), |b| {
+            b.set_clipboard(text)
+        })
+    }
+    fn bell(&mut self) -> Result<(), Self::Error> {
+        self.record(Operation::Bell, B::bell)
+    }
+    fn notify(&mut self, title: &str, body: &str) -> Result<(), Self::Error> {
+        self.record(Operation::Notify(title.to_owned(), body.to_owned()), |b| {
+            b.notify(title, body)
+        })
+    }
+    fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<(), Self::Error> {
+        self.record(Operation::SetMouseCapture(capture), |b| {
+            b.set_mouse_capture(capture)
+        })
+    }
+
+    // Cursor functions
+
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
+        self.record(Operation::HideCursor, B::hide_cursor)
+    }
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
+        self.record(Operation::ShowCursor, B::show_cursor)
+    }
+    fn set_cursor_shape(&mut self, shape: CursorShape) -> Result<(), Self::Error> {
+        self.record(Operation::SetCursorShape(shape), |b| {
+            b.set_cursor_shape(shape)
+        })
+    }
+    fn set_cursor_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
+        self.record(Operation::SetCursorBlinking(blinking), |b| {
+            b.set_cursor_blinking(blinking)
+        })
+    }
+    fn set_cursor_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        self.record(Operation::SetCursorColor(color), |b| {
+            b.set_cursor_color(color)
+        })
+    }
+    fn set_cursor_pos(&mut self, pos: Vec2<u16>) -> Result<(), Self::Error> {
+        self.record(Operation::SetCursorPos(pos), |b| b.set_cursor_pos(pos))
+    }
+
+    // Style functions
+
+    fn color_support(&mut self) -> ColorSupport {
+        self.inner.color_support()
+    }
+    fn set_foreground(&mut self, foreground: Color) -> Result<(), Self::Error> {
+        self.record(Operation::SetForeground(foreground), |b| {
+            b.set_foreground(foreground)
+        })
+    }
+    fn set_background(&mut self, background: Color) -> Result<(), Self::Error> {
+        self.record(Operation::SetBackground(background), |b| {
+            b.set_background(background)
+        })
+    }
+    fn set_intensity(&mut self, intensity: Intensity) -> Result<(), Self::Error> {
+        self.record(Operation::SetIntensity(intensity), |b| {
+            b.set_intensity(intensity)
+        })
+    }
+    fn set_italic(&mut self, italic: bool) -> Result<(), Self::Error> {
+        self.record(Operation::SetItalic(italic), |b| b.set_italic(italic))
+    }
+    fn set_underlined(&mut self, underlined: bool) -> Result<(), Self::Error> {
+        self.record(Operation::SetUnderlined(underlined), |b| {
+            b.set_underlined(underlined)
+        })
+    }
+    fn set_underline_kind(&mut self, kind: UnderlineKind) -> Result<(), Self::Error> {
+        self.record(Operation::SetUnderlineKind(kind), |b| {
+            b.set_underline_kind(kind)
+        })
+    }
+    fn set_underline_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        self.record(Operation::SetUnderlineColor(color), |b| {
+            b.set_underline_color(color)
+        })
+    }
+    fn set_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
+        self.record(Operation::SetBlinking(blinking), |b| {
+            b.set_blinking(blinking)
+        })
+    }
+    fn set_crossed_out(&mut self, crossed_out: bool) -> Result<(), Self::Error> {
+        self.record(Operation::SetCrossedOut(crossed_out), |b| {
+            b.set_crossed_out(crossed_out)
+        })
+    }
+    fn set_reversed(&mut self, reversed: bool) -> Result<(), Self::Error> {
+        self.record(Operation::SetReversed(reversed), |b| {
+            b.set_reversed(reversed)
+        })
+    }
+    fn set_hidden(&mut self, hidden: bool) -> Result<(), Self::Error> {
+        self.record(Operation::SetHidden(hidden), |b| b.set_hidden(hidden))
+    }
+    fn set_overlined(&mut self, overlined: bool) -> Result<(), Self::Error> {
+        self.record(Operation::SetOverlined(overlined), |b| {
+            b.set_overlined(overlined)
+        })
+    }
+    fn set_link(&mut self, link: Option<&Link>) -> Result<(), Self::Error> {
+        self.record(Operation::SetLink(link.cloned()), |b| b.set_link(link))
+    }
+
+    // Graphics functions
+
+    fn supports_graphics(&mut self) -> bool {
+        self.inner.supports_graphics()
+    }
+    fn draw_image(&mut self, size: Vec2<u16>, bitmap: &Bitmap) -> Result<(), Self::Error> {
+        self.record(Operation::DrawImage(size, bitmap.clone()), |b| {
+            b.draw_image(size, bitmap)
+        })
+    }
+
+    // Writing
+
+    fn write(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.record(Operation::Write(text.to_owned()), |b| b.write(text))
+    }
+
+    // Finalizing functions
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.record(Operation::Flush, B::flush)?;
+        self.log.output.flush()?;
+        Ok(())
+    }
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        self.record(Operation::Resume, B::resume)
+    }
+    fn reset(mut self) -> Result<Tty, Self::Error> {
+        self.write_events()?;
+        self.log.output.flush()?;
+        self.inner.reset().map_err(RecordError::Backend)
+    }
+}
+
+impl<'a, B: super::Bound, W: Write> ReadEvents<'a> for Bound<B, W> {
+    type EventError = RecordError<B::Error>;
+    type EventFuture = EventFuture<'a, <B as ReadEvents<'a>>::EventFuture>;
+
+    fn read_event(&'a mut self) -> Self::EventFuture {
+        EventFuture {
+            inner: Box::pin(self.inner.read_event()),
+            start: self.log.start,
+            events: &mut self.events,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EventFuture<'a, F> {
+    inner: Pin<Box<F>>,
+    start: Instant,
+    events: &'a mut Vec<(Duration, TerminalEvent)>,
+}
+
+impl<'a, F, E> Future for EventFuture<'a, F>
+where
+    F: Future<Output = Result<TerminalEvent, E>>,
+{
+    type Output = Result<TerminalEvent, RecordError<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let event = match self.inner.as_mut().poll(cx) {
+            Poll::Ready(Ok(event)) => event,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(RecordError::Backend(e))),
+            Poll::Pending => return Poll::Pending,
+        };
+        let time = self.start.elapsed();
+        self.events.push((time, event.clone()));
+        Poll::Ready(Ok(event))
+    }
+}
+
+/// A session recorded by [`Record`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// The entries in the recording and the time since the start of the recording that they
+    /// occurred.
+    pub entries: Vec<(Duration, Entry)>,
+}
+
+impl Recording {
+    /// Read a recording written by [`Record`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if reading fails or the recording is malformed.
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let entries = reader
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Ok(line) if line.is_empty()))
+            .map(|(i, line)| {
+                let line = line?;
+                parse_line(&line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("malformed recording on line {}: {:?}", i + 1, line),
+                    )
+                })
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self { entries })
+    }
+
+    /// Apply every operation in the recording to the backend immediately, ignoring events.
+    ///
+    /// This is useful for inspecting the final state of a recorded session with a
+    /// [`Dummy`](super::Dummy) backend.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the backend fails.
+    pub fn apply<B: super::Bound>(&self, backend: &mut B) -> Result<(), B::Error> {
+        for (_, entry) in &self.entries {
+            if let Entry::Operation(operation) = entry {
+                apply_operation(backend, operation)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replay the operations in the recording to the backend in real time, blocking the current
+    /// thread until it has finished.
+    ///
+    /// To replay a recording to a real terminal, create a [`Terminal`](crate::Terminal) and pass
+    /// in its [`backend_mut`](crate::Terminal::backend_mut).
+    ///
+    /// # Errors
+    ///
+    /// Fails if the backend fails.
+    pub fn play<B: super::Bound>(&self, backend: &mut B) -> Result<(), B::Error> {
+        let start = Instant::now();
+
+        for (time, entry) in &self.entries {
+            if let Entry::Operation(operation) = entry {
+                if let Some(wait) = time.checked_sub(start.elapsed()) {
+                    backend.flush()?;
+                    thread::sleep(wait);
+                }
+                apply_operation(backend, operation)?;
+            }
+        }
+
+        backend.flush()
+    }
+}
+
+fn apply_operation<B: super::Bound>(
+    backend: &mut B,
+    operation: &Operation,
+) -> Result<(), B::Error> {
+    match *operation {
+        Operation::SetTitle(ref title) => backend.set_title(title),
+        Operation::PushTitle => backend.push_title(),
+        Operation::PopTitle => backend.pop_title(),
+        Operation::SetClipboard(ref text) => backend.set_clipboard(text),
+        Operation::Bell => backend.bell(),
+        Operation::Notify(ref title, ref body) => backend.notify(title, body),
+        Operation::SetMouseCapture(capture) => backend.set_mouse_capture(capture),
+        Operation::HideCursor => backend.hide_cursor(),
+        Operation::ShowCursor => backend.show_cursor(),
+        Operation::SetCursorShape(shape) => backend.set_cursor_shape(shape),
+        Operation::SetCursorBlinking(blinking) => backend.set_cursor_blinking(blinking),
+        Operation::SetCursorColor(color) => backend.set_cursor_color(color),
+        Operation::SetCursorPos(pos) => backend.set_cursor_pos(pos),
+        Operation::SetForeground(color) => backend.set_foreground(color),
+        Operation::SetBackground(color) => backend.set_background(color),
+        Operation::SetIntensity(intensity) => backend.set_intensity(intensity),
+        Operation::SetItalic(italic) => backend.set_italic(italic),
+        Operation::SetUnderlined(underlined) => backend.set_underlined(underlined),
+        Operation::SetUnderlineKind(kind) => backend.set_underline_kind(kind),
+        Operation::SetUnderlineColor(color) => backend.set_underline_color(color),
+        Operation::SetBlinking(blinking) => backend.set_blinking(blinking),
+        Operation::SetCrossedOut(crossed_out) => backend.set_crossed_out(crossed_out),
+        Operation::SetReversed(reversed) => backend.set_reversed(reversed),
+        Operation::SetHidden(hidden) => backend.set_hidden(hidden),
+        Operation::SetOverlined(overlined) => backend.set_overlined(overlined),
+        Operation::SetLink(ref link) => backend.set_link(link.as_ref()),
+        Operation::DrawImage(size, ref bitmap) => backend.draw_image(size, bitmap),
+        Operation::Write(ref text) => backend.write(text),
+        Operation::Flush => backend.flush(),
+        Operation::Resume => backend.resume(),
+    }
+}
+
+/// An entry in a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Entry {
+    /// The size of the terminal when the recording started.
+    Size(Vec2<u16>),
+    /// An operation was performed on the backend.
+    Operation(Operation),
+    /// An event was read from the backend.
+    Event(TerminalEvent),
+}
+
+impl Display for Entry {
+    #[allow(clippy::too_many_lines)]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Size(size) => write!(f, "size {} {}", size.x, size.y),
+            Self::Operation(operation) => match operation {
+                Operation::SetTitle(title) => write!(f, "title {}", Escaped(title)),
+                Operation::PushTitle => f.write_str("push-title"),
+                Operation::PopTitle => f.write_str("pop-title"),
+                Operation::SetClipboard(text) => write!(f, "clipboard {}", Escaped(text)),
+                Operation::Bell => f.write_str("bell"),
+                // Tabs are escaped, so they can separate the title and body.
+                Operation::Notify(title, body) => {
+                    write!(f, "notify {}\t{}", Escaped(title), Escaped(body))
+                }
+                Operation::SetMouseCapture(capture) => write!(
+                    f,
+                    "mouse-capture {}",
+                    match capture {
+                        MouseCapture::None => "none",
+                        MouseCapture::Click => "click",
+                        MouseCapture::Drag => "drag",
+                        MouseCapture::Motion => "motion",
+                    }
+                ),
+                Operation::HideCursor => f.write_str("hide-cursor"),
+                Operation::ShowCursor => f.write_str("show-cursor"),
+                Operation::SetCursorShape(shape) => write!(
+                    f,
+                    "cursor-shape {}",
+                    match shape {
+                        CursorShape::Bar => "bar",
+                        CursorShape::Block => "block",
+                        CursorShape::Underline => "underline",
+                    }
+                ),
+                Operation::SetCursorBlinking(blinking) => {
+                    write!(f, "cursor-blinking {}", blinking)
+                }
+                Operation::SetCursorColor(color) => write!(f, "cursor-color {}", ColorText(*color)),
+                Operation::SetCursorPos(pos) => write!(f, "cursor-pos {} {}", pos.x, pos.y),
+                Operation::SetForeground(color) => write!(f, "foreground {}", ColorText(*color)),
+                Operation::SetBackground(color) => write!(f, "background {}", ColorText(*color)),
+                Operation::SetIntensity(intensity) => write!(
+                    f,
+                    "intensity {}",
+                    match intensity {
+                        Intensity::Dim => "dim",
+                        Intensity::Normal => "normal",
+                        Intensity::Bold => "bold",
+                    }
+                ),
+                Operation::SetItalic(italic) => write!(f, "italic {}", italic),
+                Operation::SetUnderlined(underlined) => write!(f, "underlined {}", underlined),
+                Operation::SetUnderlineKind(kind) => write!(
+                    f,
+                    "underline-kind {}",
+                    match kind {
+                        UnderlineKind::Single => "single",
+                        UnderlineKind::Double => "double",
+                        UnderlineKind::Curly => "curly",
+                        UnderlineKind::Dotted => "dotted",
+                        UnderlineKind::Dashed => "dashed",
+                    }
+                ),
+                Operation::SetUnderlineColor(color) => {
+                    write!(f, "underline-color {}", ColorText(*color))
+                }
+                Operation::SetBlinking(blinking) => write!(f, "blinking {}", blinking),
+                Operation::SetCrossedOut(crossed_out) => write!(f, "crossed-out {}", crossed_out),
+                Operation::SetReversed(reversed) => write!(f, "reversed {}", reversed),
+                Operation::SetHidden(hidden) => write!(f, "hidden {}", hidden),
+                Operation::SetOverlined(overlined) => write!(f, "overlined {}", overlined),
+                Operation::SetLink(Some(link)) => write!(f, "link {}", Escaped(link.url())),
+                Operation::SetLink(None) => f.write_str("unlink"),
+                Operation::DrawImage(size, bitmap) => {
+                    write!(
+                        f,
+                        "image {} {} {} {} ",
+                        size.x,
+                        size.y,
+                        bitmap.size().x,
+                        bitmap.size().y
+                    )?;
+                    for pixel in bitmap.pixels() {
+                        write!(f, "{:02x}{:02x}{:02x}", pixel.r, pixel.g, pixel.b)?;
+                    }
+                    Ok(())
+                }
+                Operation::Write(text) => write!(f, "write {}", Escaped(text)),
+                Operation::Flush => f.write_str("flush"),
+                Operation::Resume => f.write_str("resume"),
+            },
+            Self::Event(TerminalEvent::Key(press)) => {
+                f.write_str(match press.kind {
+                    KeyKind::Press => "key",
+                    KeyKind::Repeat => "key-repeat",
+                    KeyKind::Release => "key-release",
+                })?;
+                write!(f, " {} ", ModifiersText(press.modifiers))?;
+                match press.key {
+                    Key::Backspace => f.write_str("backspace"),
+                    Key::Left => f.write_str("left"),
+                    Key::Right => f.write_str("right"),
+                    Key::Up => f.write_str("up"),
+                    Key::Down => f.write_str("down"),
+                    Key::Home => f.write_str("home"),
+                    Key::End => f.write_str("end"),
+                    Key::PageUp => f.write_str("page-up"),
+                    Key::PageDown => f.write_str("page-down"),
+                    Key::Insert => f.write_str("insert"),
+                    Key::Escape => f.write_str("escape"),
+                    Key::F(n) => write!(f, "f{}", n),
+                    Key::Keypad(c) => write!(f, "keypad {}", Escaped(&c.to_string())),
+                    Key::Media(key) => write!(f, "media {}", MediaKeyText(key)),
+                    Key::Char(c) => write!(f, "char {}", Escaped(&c.to_string())),
+                }
+            }
+            Self::Event(TerminalEvent::Mouse(mouse)) => {
+                write!(f, "mouse {} ", ModifiersText(mouse.modifiers))?;
+                f.write_str(match mouse.kind {
+                    TerminalMouseKind::Press(MouseButton::Left) => "press-left",
+                    TerminalMouseKind::Press(MouseButton::Middle) => "press-middle",
+                    TerminalMouseKind::Press(MouseButton::Right) => "press-right",
+                    TerminalMouseKind::Release => "release",
+                    TerminalMouseKind::Move => "move",
+                    TerminalMouseKind::ScrollDown => "scroll-down",
+                    TerminalMouseKind::ScrollUp => "scroll-up",
+                    TerminalMouseKind::ScrollLeft => "scroll-left",
+                    TerminalMouseKind::ScrollRight => "scroll-right",
+                })?;
+                write!(f, " {} {}", mouse.at.x, mouse.at.y)
+            }
+            Self::Event(TerminalEvent::Paste(text)) => write!(f, "paste {}", Escaped(text)),
+            Self::Event(TerminalEvent::Compose(text)) => write!(f, "compose {}", Escaped(text)),
+            Self::Event(TerminalEvent::Resize(size)) => write!(f, "resize {} {}", size.x, size.y),
+            Self::Event(TerminalEvent::BackgroundColor(color)) => {
+                write!(f, "background-reply {}", ColorText(Color::Rgb(*color)))
+            }
+            Self::Event(TerminalEvent::Signal(signal)) => f.write_str(match signal {
+                Signal::Interrupt => "signal interrupt",
+                Signal::Terminate => "signal terminate",
+                Signal::Hangup => "signal hangup",
+                Signal::Continue => "signal continue",
+            }),
+        }
+    }
+}
+
+/// Displays a string with backslashes and control characters escaped, so that it fits on one
+/// line.
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            if c == '\\' {
+                f.write_str("\\\\")?;
+            } else if c.is_control() {
+                write!(f, "\\u{{{:x}}}", c as u32)?;
+            } else {
+                f.write_char(c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn unescape(s: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => unescaped.push('\\'),
+            'u' => {
+                let rest = chars.as_str().strip_prefix('{')?;
+                let end = rest.find('}')?;
+                unescaped.push(std::char::from_u32(
+                    u32::from_str_radix(&rest[..end], 16).ok()?,
+                )?);
+                chars = rest[end + 1..].chars();
+            }
+            _ => return None,
+        }
+    }
+
+    Some(unescaped)
+}
+
+/// Displays a media key in kebab case, like `play-pause`.
+struct MediaKeyText(MediaKey);
+
+impl Display for MediaKeyText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.0.name().chars().enumerate() {
+            if i != 0 && c.is_ascii_uppercase() {
+                f.write_char('-')?;
+            }
+            f.write_char(c.to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+struct ColorText(Color);
+
+impl Display for ColorText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let ansi = match self.0 {
+            Color::Default => return f.write_str("default"),
+            Color::Black => 0,
+            Color::DarkRed => 1,
+            Color::DarkGreen => 2,
+            Color::DarkYellow => 3,
+            Color::DarkBlue => 4,
+            Color::DarkMagenta => 5,
+            Color::DarkCyan => 6,
+            Color::LightGray => 7,
+            Color::DarkGray => 8,
+            Color::Red => 9,
+            Color::Green => 10,
+            Color::Yellow => 11,
+            Color::Blue => 12,
+            Color::Magenta => 13,
+            Color::Cyan => 14,
+            Color::White => 15,
+            Color::AnsiValue(color) => color.get(),
+            Color::Rgb(Rgb { r, g, b }) => return write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            Color::Rgba(Rgba { r, g, b, a }) => {
+                return write!(f, "#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+            }
+        };
+        write!(f, "{}", ansi)
+    }
+}
+
+fn parse_color(s: &str) -> Option<Color> {
+    Some(if s == "default" {
+        Color::Default
+    } else if let Some(hex) = s.strip_prefix('#') {
+        match hex.len() {
+            8 => Color::Rgba(Rgba::from_rgb(
+                parse_rgb(hex)?,
+                u8::from_str_radix(hex.get(6..)?, 16).ok()?,
+            )),
+            _ => Color::Rgb(parse_rgb(hex)?),
+        }
+    } else {
+        match s.parse().ok()? {
+            value @ 0..=15 => Color::new_ansi(value),
+            value => Color::AnsiValue(AnsiColor::new(value)),
+        }
+    })
+}
+
+fn parse_rgb(hex: &str) -> Option<Rgb> {
+    let component = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Rgb {
+        r: component(0)?,
+        g: component(2)?,
+        b: component(4)?,
+    })
+}
+
+struct ModifiersText(Modifiers);
+
+impl Display for ModifiersText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.are_none() {
+            return f.write_char('-');
+        }
+        if self.0.shift {
+            f.write_char('s')?;
+        }
+        if self.0.control {
+            f.write_char('c')?;
+        }
+        if self.0.alt {
+            f.write_char('a')?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_modifiers(s: &str) -> Option<Modifiers> {
+    let mut modifiers = Modifiers::default();
+    if s != "-" {
+        for c in s.chars() {
+            match c {
+                's' => modifiers.shift = true,
+                'c' => modifiers.control = true,
+                'a' => modifiers.alt = true,
+                _ => return None,
+            }
+        }
+    }
+    Some(modifiers)
+}
+
+#[allow(clippy::too_many_lines)]
+fn parse_line(line: &str) -> Option<(Duration, Entry)> {
+    let (time, rest) = split_word(line);
+    let time = Duration::from_micros(time.parse().ok()?);
+    let (kind, rest) = split_word(rest);
+
+    let vec2 = |s: &str| {
+        let (x, y) = split_word(s);
+        Some(Vec2::new(x.parse().ok()?, y.parse().ok()?))
+    };
+    let boolean = |s: &str| s.parse::<bool>().ok();
+
+    let entry = match kind {
+        "size" => Entry::Size(vec2(rest)?),
+        "resize" => Entry::Event(TerminalEvent::Resize(vec2(rest)?)),
+        "background-reply" => Entry::Event(TerminalEvent::BackgroundColor(parse_rgb(
+            rest.strip_prefix('#')?,
+        )?)),
+        "key" | "key-repeat" | "key-release" => {
+            let (modifiers, key) = split_word(rest);
+            let key = match key {
+                "backspace" => Key::Backspace,
+                "left" => Key::Left,
+                "right" => Key::Right,
+                "up" => Key::Up,
+                "down" => Key::Down,
+                "home" => Key::Home,
+                "end" => Key::End,
+                "page-up" => Key::PageUp,
+                "page-down" => Key::PageDown,
+                "insert" => Key::Insert,
+                "escape" => Key::Escape,
+                _ => match split_word(key) {
+                    (kind @ "char", c) | (kind @ "keypad", c) => {
+                        let mut chars = unescape(c)?.chars().collect::<Vec<_>>().into_iter();
+                        match (chars.next(), chars.next()) {
+                            (Some(c), None) if kind == "char" => Key::Char(c),
+                            (Some(c), None) => Key::Keypad(c),
+                            _ => return None,
+                        }
+                    }
+                    ("media", name) => Key::Media(
+                        *MediaKey::ALL
+                            .iter()
+                            .find(|&&key| MediaKeyText(key).to_string() == name)?,
+                    ),
+                    (f, "") => Key::F(f.strip_prefix('f')?.parse().ok()?),
+                    _ => return None,
+                },
+            };
+            Entry::Event(TerminalEvent::Key(KeyPress {
+                key,
+                modifiers: parse_modifiers(modifiers)?,
+                kind: match kind {
+                    "key-repeat" => KeyKind::Repeat,
+                    "key-release" => KeyKind::Release,
+                    _ => KeyKind::Press,
+                },
+            }))
+        }
+        "mouse" => {
+            let (modifiers, rest) = split_word(rest);
+            let (kind, at) = split_word(rest);
+            Entry::Event(TerminalEvent::Mouse(TerminalMouse {
+                kind: match kind {
+                    "press-left" => TerminalMouseKind::Press(MouseButton::Left),
+                    "press-middle" => TerminalMouseKind::Press(MouseButton::Middle),
+                    "press-right" => TerminalMouseKind::Press(MouseButton::Right),
+                    "release" => TerminalMouseKind::Release,
+                    "move" => TerminalMouseKind::Move,
+                    "scroll-down" => TerminalMouseKind::ScrollDown,
+                    "scroll-up" => TerminalMouseKind::ScrollUp,
+                    "scroll-left" => TerminalMouseKind::ScrollLeft,
+                    "scroll-right" => TerminalMouseKind::ScrollRight,
+                    _ => return None,
+                },
+                at: vec2(at)?,
+                modifiers: parse_modifiers(modifiers)?,
+            }))
+        }
+        "paste" => Entry::Event(TerminalEvent::Paste(unescape(rest)?)),
+        "compose" => Entry::Event(TerminalEvent::Compose(unescape(rest)?)),
+        "signal" => Entry::Event(TerminalEvent::Signal(match rest {
+            "interrupt" => Signal::Interrupt,
+            "terminate" => Signal::Terminate,
+            "hangup" => Signal::Hangup,
+            "continue" => Signal::Continue,
+            _ => return None,
+        })),
+        _ => Entry::Operation(match kind {
+            "title" => Operation::SetTitle(unescape(rest)?),
+            "push-title" => Operation::PushTitle,
+            "pop-title" => Operation::PopTitle,
+            "clipboard" => Operation::SetClipboard(unescape(rest)?),
+            "bell" => Operation::Bell,
+            "notify" => {
+                let (title, body) = rest.split_at(rest.find('\t')?);
+                Operation::Notify(unescape(title)?, unescape(&body[1..])?)
+            }
+            "mouse-capture" => Operation::SetMouseCapture(match rest {
+                "none" => MouseCapture::None,
+                "click" => MouseCapture::Click,
+                "drag" => MouseCapture::Drag,
+                "motion" => MouseCapture::Motion,
+                _ => return None,
+            }),
+            "hide-cursor" => Operation::HideCursor,
+            "show-cursor" => Operation::ShowCursor,
+            "cursor-shape" => Operation::SetCursorShape(match rest {
+                "bar" => CursorShape::Bar,
+                "block" => CursorShape::Block,
+                "underline" => CursorShape::Underline,
+                _ => return None,
+            }),
+            "cursor-blinking" => Operation::SetCursorBlinking(boolean(rest)?),
+            "cursor-color" => Operation::SetCursorColor(parse_color(rest)?),
+            "cursor-pos" => Operation::SetCursorPos(vec2(rest)?),
+            "foreground" => Operation::SetForeground(parse_color(rest)?),
+            "background" => Operation::SetBackground(parse_color(rest)?),
+            "intensity" => Operation::SetIntensity(match rest {
+                "dim" => Intensity::Dim,
+                "normal" => Intensity::Normal,
+                "bold" => Intensity::Bold,
+                _ => return None,
+            }),
+            "italic" => Operation::SetItalic(boolean(rest)?),
+            "underlined" => Operation::SetUnderlined(boolean(rest)?),
+            "underline-kind" => Operation::SetUnderlineKind(match rest {
+                "single" => UnderlineKind::Single,
+                "double" => UnderlineKind::Double,
+                "curly" => UnderlineKind::Curly,
+                "dotted" => UnderlineKind::Dotted,
+                "dashed" => UnderlineKind::Dashed,
+                _ => return None,
+            }),
+            "underline-color" => Operation::SetUnderlineColor(parse_color(rest)?),
+            "blinking" => Operation::SetBlinking(boolean(rest)?),
+            "crossed-out" => Operation::SetCrossedOut(boolean(rest)?),
+            "reversed" => Operation::SetReversed(boolean(rest)?),
+            "hidden" => Operation::SetHidden(boolean(rest)?),
+            "overlined" => Operation::SetOverlined(boolean(rest)?),
+            "link" => Operation::SetLink(Some(Link::new(&unescape(rest)?))),
+            "unlink" => Operation::SetLink(None),
+            "image" => parse_image(rest)?,
+            "write" => Operation::Write(unescape(rest)?),
+            "flush" => Operation::Flush,
+            "resume" => Operation::Resume,
+            _ => return None,
+        }),
+    };
+
+    Some((time, entry))
+}
+
+fn parse_image(s: &str) -> Option<Operation> {
+    let (columns, rest) = split_word(s);
+    let (rows, rest) = split_word(rest);
+    let (width, rest) = split_word(rest);
+    let (height, pixels) = split_word(rest);
+
+    let bitmap_size = Vec2::new(width.parse().ok()?, height.parse().ok()?);
+    if pixels.len() != bitmap_size.map(usize::from).product() * 6 {
+        return None;
+    }
+    let pixels = (0..pixels.len())
+        .step_by(6)
+        .map(|i| parse_rgb(pixels.get(i..i + 6)?))
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(Operation::DrawImage(
+        Vec2::new(columns.parse().ok()?, rows.parse().ok()?),
+        Bitmap::new(bitmap_size, pixels),
+    ))
+}
+
+/// Split off the first space-separated word of the string.
+fn split_word(s: &str) -> (&str, &str) {
+    match s.find(' ') {
+        Some(i) => (&s[..i], &s[i + 1..]),
+        None => (s, ""),
+    }
+}
+
+#[test]
+fn test_record() {
+    use super::{Bound as _, Dummy};
+
+    let mut output = Vec::new();
+    let mut dummy = Dummy::new(Vec2::new(6, 1));
+    let release = KeyPress {
+        kind: KeyKind::Release,
+        ..KeyPress::from('\\')
+    };
+    dummy.events.push_back(TerminalEvent::Key(release));
+    dummy.events.push_back(TerminalEvent::Mouse(TerminalMouse {
+        kind: TerminalMouseKind::Press(MouseButton::Left),
+        at: Vec2::new(1, 0),
+        modifiers: Modifiers::CONTROL | Modifiers::SHIFT,
+    }));
+    dummy
+        .events
+        .push_back(TerminalEvent::Paste("one\ntwo".to_owned()));
+    dummy
+        .events
+        .push_back(TerminalEvent::Compose("日本".to_owned()));
+    let keypad = KeyPress::from(Key::Keypad('\n'));
+    dummy.events.push_back(TerminalEvent::Key(keypad));
+    let media = KeyPress::from(Key::Media(MediaKey::PlayPause));
+    dummy.events.push_back(TerminalEvent::Key(media));
+    dummy
+        .events
+        .push_back(TerminalEvent::Signal(Signal::Hangup));
+    dummy
+        .events
+        .push_back(TerminalEvent::BackgroundColor(Rgb::new(0, 0x80, 0xFF)));
+
+    let mut bound = Record::new(dummy, &mut output).bind(Tty::dummy()).unwrap();
+    bound.set_title("a\nb\\").unwrap();
+    bound.set_clipboard("copied").unwrap();
+    bound.notify("Done\t", "a b").unwrap();
+    bound.set_mouse_capture(MouseCapture::Motion).unwrap();
+    bound
+        .set_foreground(Color::Rgb(Rgb { r: 1, g: 2, b: 255 }))
+        .unwrap();
+    bound.set_background(Color::Red).unwrap();
+    bound.set_underline_kind(UnderlineKind::Curly).unwrap();
+    bound.set_underline_color(Color::Blue).unwrap();
+    bound.show_cursor().unwrap();
+    bound.set_cursor_color(Color::Green).unwrap();
+    bound.set_cursor_pos(Vec2::new(1, 0)).unwrap();
+    bound.set_link(Some(&Link::new("https://a.b/ c"))).unwrap();
+    bound.write("Hi there").unwrap();
+    bound.set_link(None).unwrap();
+    let bitmap = Bitmap::new((2, 1), vec![Rgb::new(255, 0, 0), Rgb::new(0, 0x80, 0xFF)]);
+    bound.draw_image(Vec2::new(1, 1), &bitmap).unwrap();
+    bound.resume().unwrap();
+    for _ in 0..8 {
+        futures_lite::future::block_on(bound.read_event()).unwrap();
+    }
+    bound.flush().unwrap();
+    let recorded = bound.inner.operations.clone();
+    bound.reset().unwrap();
+
+    let recording = Recording::read(&*output).unwrap();
+    let entries: Vec<_> = recording.entries.iter().map(|(_, e)| e.clone()).collect();
+    assert_eq!(entries[0], Entry::Size(Vec2::new(6, 1)));
+    assert_eq!(
+        &entries[1..],
+        [
+            Entry::Operation(Operation::SetTitle("a\nb\\".to_owned())),
+            Entry::Operation(Operation::SetClipboard("copied".to_owned())),
+            Entry::Operation(Operation::Notify("Done\t".to_owned(), "a b".to_owned())),
+            Entry::Operation(Operation::SetMouseCapture(MouseCapture::Motion)),
+            Entry::Operation(Operation::SetForeground(Color::Rgb(Rgb {
+                r: 1,
+                g: 2,
+                b: 255
+            }))),
+            Entry::Operation(Operation::SetBackground(Color::Red)),
+            Entry::Operation(Operation::SetUnderlineKind(UnderlineKind::Curly)),
+            Entry::Operation(Operation::SetUnderlineColor(Color::Blue)),
+            Entry::Operation(Operation::ShowCursor),
+            Entry::Operation(Operation::SetCursorColor(Color::Green)),
+            Entry::Operation(Operation::SetCursorPos(Vec2::new(1, 0))),
+            Entry::Operation(Operation::SetLink(Some(Link::new("https://a.b/ c")))),
+            Entry::Operation(Operation::Write("Hi there".to_owned())),
+            Entry::Operation(Operation::SetLink(None)),
+            Entry::Operation(Operation::DrawImage(Vec2::new(1, 1), bitmap)),
+            Entry::Operation(Operation::Resume),
+            Entry::Event(TerminalEvent::Key(release)),
+            Entry::Event(TerminalEvent::Mouse(TerminalMouse {
+                kind: TerminalMouseKind::Press(MouseButton::Left),
+                at: Vec2::new(1, 0),
+                modifiers: Modifiers::CONTROL | Modifiers::SHIFT,
+            })),
+            Entry::Event(TerminalEvent::Paste("one\ntwo".to_owned())),
+            Entry::Event(TerminalEvent::Compose("日本".to_owned())),
+            Entry::Event(TerminalEvent::Key(keypad)),
+            Entry::Event(TerminalEvent::Key(media)),
+            Entry::Event(TerminalEvent::Signal(Signal::Hangup)),
+            Entry::Event(TerminalEvent::BackgroundColor(Rgb::new(0, 0x80, 0xFF))),
+            Entry::Operation(Operation::Flush),
+        ]
+    );
+
+    let mut replayed = Dummy::new(Vec2::new(6, 1));
+    recording.apply(&mut replayed).unwrap();
+    assert_eq!(replayed.operations, recorded);
+    crate::assert_grid_eq!(replayed.buffer.grid, [" Hi th"]);
+}
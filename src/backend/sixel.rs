@@ -0,0 +1,145 @@
+//! Drawing images with sixel graphics.
+
+use std::env;
+use std::io::{self, Write};
+
+use crate::{Bitmap, Rgb, Vec2};
+
+/// The size of a cell in pixels assumed when drawing images if the terminal doesn't report it.
+pub(super) const DEFAULT_CELL_SIZE: Vec2<u16> = Vec2 { x: 10, y: 20 };
+
+/// The number of levels of each color component in the palette.
+const LEVELS: u8 = 6;
+
+/// Guess whether the terminal supports sixel graphics from the environment.
+///
+/// There's no reliable way to tell without querying the terminal, so this only recognizes
+/// terminals known to support sixels by default.
+pub(super) fn detect() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    let program = env::var("TERM_PROGRAM").unwrap_or_default();
+
+    ["foot", "mlterm", "contour", "yaft"]
+        .iter()
+        .any(|prefix| term.starts_with(prefix))
+        || program == "WezTerm"
+        || program == "mintty"
+}
+
+/// Write a bitmap as a sixel image scaled to cover an area of cells, given the size of a cell in
+/// pixels.
+///
+/// The cursor position and style are saved before and restored after drawing the image.
+pub(super) fn write(
+    out: &mut impl Write,
+    bitmap: &Bitmap,
+    cells: Vec2<u16>,
+    cell_size: Option<Vec2<u16>>,
+) -> io::Result<()> {
+    let cell_size = cell_size.unwrap_or(DEFAULT_CELL_SIZE);
+    let size = Vec2::new(
+        cells.x.saturating_mul(cell_size.x),
+        cells.y.saturating_mul(cell_size.y),
+    );
+    if size.x == 0 || size.y == 0 || bitmap.size().x == 0 || bitmap.size().y == 0 {
+        return Ok(());
+    }
+
+    // DECSC, then start the sixel sequence with a 1:1 pixel aspect ratio.
+    write!(out, "\x1B7\x1BP0;1q\"1;1;{};{}", size.x, size.y)?;
+
+    let pixels: Vec<u8> = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| Vec2::new(x, y)))
+        .map(|pos| quantize(bitmap.sample(pos, size)))
+        .collect();
+
+    let mut used = [false; (LEVELS * LEVELS * LEVELS) as usize];
+    for &color in &pixels {
+        used[usize::from(color)] = true;
+    }
+    let colors: Vec<u8> = (0..=u8::MAX)
+        .zip(&used)
+        .filter(|&(_, &used)| used)
+        .map(|(color, _)| color)
+        .collect();
+
+    for &color in &colors {
+        // Color components are given as percentages.
+        let component = |place: u8| u16::from(color / place % LEVELS) * 100 / u16::from(LEVELS - 1);
+        write!(
+            out,
+            "#{};2;{};{};{}",
+            color,
+            component(LEVELS * LEVELS),
+            component(LEVELS),
+            component(1),
+        )?;
+    }
+
+    let width = usize::from(size.x);
+    for band in pixels.chunks(width * 6) {
+        let rows = band.len() / width;
+
+        for (i, &color) in colors
+            .iter()
+            .filter(|color| band.contains(color))
+            .enumerate()
+        {
+            if i != 0 {
+                out.write_all(b"$")?;
+            }
+            write!(out, "#{}", color)?;
+
+            let mut run = (0, 0);
+            for x in 0..width {
+                let sixel = (0..rows)
+                    .filter(|row| band[row * width + x] == color)
+                    .fold(0, |sixel, row| sixel | 1 << row);
+                if run.0 > 0 && run.1 != sixel {
+                    write_run(out, run)?;
+                    run.0 = 0;
+                }
+                run = (run.0 + 1, sixel);
+            }
+            write_run(out, run)?;
+        }
+
+        out.write_all(b"-")?;
+    }
+
+    // End the sixel sequence, then DECRC.
+    out.write_all(b"\x1B\\\x1B8")
+}
+
+/// Write a run of identical sixels.
+fn write_run(out: &mut impl Write, (len, sixel): (usize, u8)) -> io::Result<()> {
+    let c = char::from(b'?' + sixel);
+    if len > 3 {
+        write!(out, "!{}{}", len, c)
+    } else {
+        (0..len).try_for_each(|_| write!(out, "{}", c))
+    }
+}
+
+/// Convert a color to its index in the palette.
+fn quantize(color: Rgb) -> u8 {
+    let level = |component: u8| (u16::from(component) * u16::from(LEVELS - 1) + 127) / 255;
+    (level(color.r) * u16::from(LEVELS) * u16::from(LEVELS)
+        + level(color.g) * u16::from(LEVELS)
+        + level(color.b)) as u8
+}
+
+#[test]
+fn test_sixel() {
+    let bitmap = Bitmap::new((2, 1), vec![Rgb::new(255, 0, 0), Rgb::new(0, 0, 255)]);
+
+    let mut out = Vec::new();
+    write(&mut out, &bitmap, Vec2::new(1, 1), None).unwrap();
+
+    let mut expected = "\x1B7\x1BP0;1q\"1;1;10;20#5;2;0;0;100#180;2;100;0;0".to_owned();
+    for _ in 0..3 {
+        expected.push_str("#5!5?!5~$#180!5~!5?-");
+    }
+    expected.push_str("#5!5?!5B$#180!5B!5?-\x1B\\\x1B8");
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
@@ -0,0 +1,505 @@
+use std::fmt::{self, Debug, Formatter};
+use std::fs::File;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+
+use async_channel::{Receiver, Sender};
+use futures_core::stream::Stream as _;
+use os_pipe::{PipeReader, PipeWriter};
+use termion::color::{self as tcolor, Bg, Fg};
+use termion::event::{Event, Key as TKey, MouseButton as TMouseButton, MouseEvent};
+use termion::input::TermRead as _;
+use termion::raw::{IntoRawMode as _, RawTerminal};
+use termion::{clear, cursor, screen, style};
+use termion_crate as termion;
+
+use crate::input::{Key, KeyKind, KeyPress, Modifiers, MouseButton, MouseCapture};
+use crate::style::{Color, ColorSupport, Intensity, Link, Rgb, Rgba, UnderlineKind};
+use crate::util::Base64;
+use crate::{Bitmap, CursorShape, Vec2};
+
+use super::{sixel, Backend, ReadEvents, TerminalEvent, TerminalMouse, TerminalMouseKind, Tty};
+
+/// Termion backend.
+///
+/// Currently there is no configuration here.
+///
+/// Termion only supports Unix-like platforms. It supports all features except querying the
+/// terminal's background color, which Termion's input parser can't read the reply to. Input is read
+/// on a separate thread, which also checks for resizes periodically since Termion has no way to
+/// report them.
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct Termion {}
+
+impl Backend for Termion {
+    type Error = io::Error;
+    type Bound = Bound;
+
+    fn bind(self, mut io: Tty) -> Result<Self::Bound, Self::Error> {
+        let raw = termion::get_tty()?.into_raw_mode()?;
+        set_up(&mut io)?;
+
+        let (stop, stop_writer) = os_pipe::pipe()?;
+        let (sender, events) = async_channel::unbounded();
+        let input = Input {
+            tty: termion::get_tty()?,
+            stop,
+            size: termion::terminal_size()?.into(),
+            sender: sender.clone(),
+        };
+        thread::spawn(move || {
+            for event in input.events() {
+                let event = match event {
+                    Ok(event) => match from_termion_event(event) {
+                        Some(event) => Ok(event),
+                        None => continue,
+                    },
+                    Err(e) => Err(e),
+                };
+                if sender.try_send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Bound {
+            io,
+            raw,
+            events,
+            stop: stop_writer,
+            cursor_shape: CursorShape::Block,
+            cursor_blinking: true,
+        })
+    }
+}
+
+/// Set up the terminal once it is in raw mode: enter the alternate screen, clear it, disable line
+/// wrapping and make the mouse report SGR coordinates once it is captured.
+fn set_up(io: &mut Tty) -> io::Result<()> {
+    write!(
+        io,
+        "{}{}\x1B[?7l\x1B[?1015h\x1B[?1006h",
+        screen::ToAlternateScreen,
+        clear::All,
+    )?;
+    io.flush()
+}
+
+/// How often, in milliseconds, the input thread checks whether the terminal has been resized.
+const RESIZE_POLL_INTERVAL: libc::c_int = 100;
+
+/// The terminal's input, read by the input thread.
+///
+/// Reading waits for either input or the backend to be reset, so that the thread doesn't keep
+/// reading from the terminal after it has been reset. While it waits it reports resizes.
+struct Input {
+    tty: File,
+    /// The read end of a pipe whose write end is closed when the backend is reset.
+    stop: PipeReader,
+    /// The last known size of the terminal, used to detect resizes.
+    size: Vec2<u16>,
+    sender: Sender<io::Result<TerminalEvent>>,
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut fds = [
+            libc::pollfd {
+                fd: self.tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: self.stop.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+
+        loop {
+            if unsafe { libc::poll(fds.as_mut_ptr(), 2, RESIZE_POLL_INTERVAL) } == -1 {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            }
+
+            // Reading nothing makes Termion's events iterator end.
+            if fds[1].revents != 0 {
+                return Ok(0);
+            }
+
+            let size = Vec2::from(termion::terminal_size()?);
+            if size != self.size {
+                self.size = size;
+                if self
+                    .sender
+                    .try_send(Ok(TerminalEvent::Resize(size)))
+                    .is_err()
+                {
+                    return Ok(0);
+                }
+            }
+
+            if fds[0].revents != 0 {
+                return self.tty.read(buf);
+            }
+        }
+    }
+}
+
+/// Write a Termion color wrapped in `Fg` or `Bg`.
+macro_rules! write_color {
+    ($io:expr, $wrapper:ident, $color:expr) => {
+        match $color {
+            Color::Default => write!($io, "{}", $wrapper(tcolor::Reset)),
+            Color::Black => write!($io, "{}", $wrapper(tcolor::Black)),
+            Color::DarkGray => write!($io, "{}", $wrapper(tcolor::LightBlack)),
+            Color::LightGray => write!($io, "{}", $wrapper(tcolor::White)),
+            Color::White => write!($io, "{}", $wrapper(tcolor::LightWhite)),
+            Color::Red => write!($io, "{}", $wrapper(tcolor::LightRed)),
+            Color::DarkRed => write!($io, "{}", $wrapper(tcolor::Red)),
+            Color::Green => write!($io, "{}", $wrapper(tcolor::LightGreen)),
+            Color::DarkGreen => write!($io, "{}", $wrapper(tcolor::Green)),
+            Color::Yellow => write!($io, "{}", $wrapper(tcolor::LightYellow)),
+            Color::DarkYellow => write!($io, "{}", $wrapper(tcolor::Yellow)),
+            Color::Blue => write!($io, "{}", $wrapper(tcolor::LightBlue)),
+            Color::DarkBlue => write!($io, "{}", $wrapper(tcolor::Blue)),
+            Color::Magenta => write!($io, "{}", $wrapper(tcolor::LightMagenta)),
+            Color::DarkMagenta => write!($io, "{}", $wrapper(tcolor::Magenta)),
+            Color::Cyan => write!($io, "{}", $wrapper(tcolor::LightCyan)),
+            Color::DarkCyan => write!($io, "{}", $wrapper(tcolor::Cyan)),
+            Color::AnsiValue(v) => write!($io, "{}", $wrapper(tcolor::AnsiValue(v.get()))),
+            Color::Rgb(Rgb { r, g, b }) | Color::Rgba(Rgba { r, g, b, .. }) => {
+                write!($io, "{}", $wrapper(tcolor::Rgb(r, g, b)))
+            }
+        }
+    };
+}
+
+pub struct Bound {
+    io: Tty,
+    /// Keeps the terminal in raw mode until it is dropped.
+    raw: RawTerminal<File>,
+    events: Receiver<io::Result<TerminalEvent>>,
+    /// Dropped when the backend is reset to stop the input thread.
+    stop: PipeWriter,
+    cursor_shape: CursorShape,
+    cursor_blinking: bool,
+}
+
+impl Debug for Bound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bound")
+            .field("io", &self.io)
+            .field("events", &self.events)
+            .field("stop", &self.stop)
+            .field("cursor_shape", &self.cursor_shape)
+            .field("cursor_blinking", &self.cursor_blinking)
+            .finish()
+    }
+}
+
+impl Bound {
+    fn write_cursor_style(&mut self) -> io::Result<()> {
+        match (self.cursor_shape, self.cursor_blinking) {
+            (CursorShape::Bar, true) => write!(self.io, "{}", cursor::BlinkingBar),
+            (CursorShape::Bar, false) => write!(self.io, "{}", cursor::SteadyBar),
+            (CursorShape::Block, true) => write!(self.io, "{}", cursor::BlinkingBlock),
+            (CursorShape::Block, false) => write!(self.io, "{}", cursor::SteadyBlock),
+            (CursorShape::Underline, true) => write!(self.io, "{}", cursor::BlinkingUnderline),
+            (CursorShape::Underline, false) => write!(self.io, "{}", cursor::SteadyUnderline),
+        }
+    }
+}
+
+impl super::Bound for Bound {
+    type Error = io::Error;
+
+    // General functions
+
+    fn size(&mut self) -> Result<Vec2<u16>, Self::Error> {
+        termion::terminal_size().map(Vec2::from)
+    }
+    fn cell_size(&mut self) -> Result<Option<Vec2<u16>>, Self::Error> {
+        super::cell_size(&self.io)
+    }
+    fn background_color(&mut self) -> Result<Option<Rgb>, Self::Error> {
+        // Termion's input parser doesn't understand the reply to an OSC 11 query.
+        Ok(super::background_from_env())
+    }
+    fn set_title(&mut self, title: &str) -> Result<(), Self::Error> {
+        write!(self.io, "\x1B]0;{}\x07", title)
+    }
+    fn push_title(&mut self) -> Result<(), Self::Error> {
+        // XTWINOPS: save the window and icon titles on the stack.
+        self.io.write_all(b"\x1B[22;0t")
+    }
+    fn pop_title(&mut self) -> Result<(), Self::Error> {
+        // XTWINOPS: restore the window and icon titles from the stack.
+        self.io.write_all(b"\x1B[23;0t")
+    }
+    fn set_clipboard(&mut self, text: &str) -> Result<(), Self::Error> {
+        // OSC 52. Since it is an escape sequence this works over SSH too.
+        write!(self.io, "\x1B]52;c;{}\x07", Base64(text.as_bytes()))
+    }
+    fn bell(&mut self) -> Result<(), Self::Error> {
+        self.io.write_all(b"\x07")
+    }
+    fn notify(&mut self, title: &str, body: &str) -> Result<(), Self::Error> {
+        super::write_notification(&mut self.io, title, body)
+    }
+    fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<(), Self::Error> {
+        super::write_mouse_capture(&mut self.io, capture)
+    }
+
+    // Cursor functions
+
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
+        write!(self.io, "{}", cursor::Hide)
+    }
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
+        write!(self.io, "{}", cursor::Show)
+    }
+    fn set_cursor_shape(&mut self, shape: CursorShape) -> Result<(), Self::Error> {
+        self.cursor_shape = shape;
+        self.write_cursor_style()
+    }
+    fn set_cursor_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
+        self.cursor_blinking = blinking;
+        self.write_cursor_style()
+    }
+    fn set_cursor_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        super::write_cursor_color(&mut self.io, color)
+    }
+    fn set_cursor_pos(&mut self, pos: Vec2<u16>) -> Result<(), Self::Error> {
+        write!(self.io, "{}", cursor::Goto(pos.x + 1, pos.y + 1))
+    }
+
+    // Style functions
+
+    fn color_support(&mut self) -> ColorSupport {
+        ColorSupport::detect()
+    }
+    fn set_foreground(&mut self, foreground: Color) -> Result<(), Self::Error> {
+        write_color!(self.io, Fg, foreground)
+    }
+    fn set_background(&mut self, background: Color) -> Result<(), Self::Error> {
+        write_color!(self.io, Bg, background)
+    }
+    fn set_intensity(&mut self, intensity: Intensity) -> Result<(), Self::Error> {
+        match intensity {
+            Intensity::Dim => write!(self.io, "{}", style::Faint),
+            // SGR 22 resets both bold and faint.
+            Intensity::Normal => self.io.write_all(b"\x1B[22m"),
+            Intensity::Bold => write!(self.io, "{}", style::Bold),
+        }
+    }
+    fn set_italic(&mut self, italic: bool) -> Result<(), Self::Error> {
+        if italic {
+            write!(self.io, "{}", style::Italic)
+        } else {
+            write!(self.io, "{}", style::NoItalic)
+        }
+    }
+    fn set_underlined(&mut self, underlined: bool) -> Result<(), Self::Error> {
+        if underlined {
+            write!(self.io, "{}", style::Underline)
+        } else {
+            write!(self.io, "{}", style::NoUnderline)
+        }
+    }
+    fn set_underline_kind(&mut self, kind: UnderlineKind) -> Result<(), Self::Error> {
+        super::write_underline_kind(&mut self.io, kind)
+    }
+    fn set_underline_color(&mut self, color: Color) -> Result<(), Self::Error> {
+        super::write_underline_color(&mut self.io, color)
+    }
+    fn set_blinking(&mut self, blinking: bool) -> Result<(), Self::Error> {
+        if blinking {
+            write!(self.io, "{}", style::Blink)
+        } else {
+            write!(self.io, "{}", style::NoBlink)
+        }
+    }
+    fn set_crossed_out(&mut self, crossed_out: bool) -> Result<(), Self::Error> {
+        if crossed_out {
+            write!(self.io, "{}", style::CrossedOut)
+        } else {
+            write!(self.io, "{}", style::NoCrossedOut)
+        }
+    }
+    fn set_reversed(&mut self, reversed: bool) -> Result<(), Self::Error> {
+        if reversed {
+            write!(self.io, "{}", style::Invert)
+        } else {
+            write!(self.io, "{}", style::NoInvert)
+        }
+    }
+    fn set_hidden(&mut self, hidden: bool) -> Result<(), Self::Error> {
+        // Termion has no command for hidden text.
+        write!(self.io, "\x1B[{}m", if hidden { 8 } else { 28 })
+    }
+    fn set_overlined(&mut self, overlined: bool) -> Result<(), Self::Error> {
+        // Termion has no command for overlined text.
+        write!(self.io, "\x1B[{}m", if overlined { 53 } else { 55 })
+    }
+    fn set_link(&mut self, link: Option<&Link>) -> Result<(), Self::Error> {
+        // OSC 8, which Termion has no command for. Terminals that don't support hyperlinks ignore
+        // it.
+        write!(self.io, "\x1B]8;;{}\x1B\\", link.map_or("", Link::url))
+    }
+
+    // Graphics functions
+
+    fn supports_graphics(&mut self) -> bool {
+        sixel::detect()
+    }
+    fn draw_image(&mut self, size: Vec2<u16>, bitmap: &Bitmap) -> Result<(), Self::Error> {
+        let cell_size = self.cell_size()?;
+        sixel::write(&mut self.io, bitmap, size, cell_size)
+    }
+
+    // Writing
+
+    fn write(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.io.write_all(text.as_bytes())
+    }
+
+    // Finalizing functions
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.io.flush()
+    }
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        self.raw.activate_raw_mode()?;
+        set_up(&mut self.io)
+    }
+    fn reset(mut self) -> Result<Tty, Self::Error> {
+        super::write_mouse_capture(&mut self.io, MouseCapture::None)?;
+        super::write_cursor_color(&mut self.io, Color::Default)?;
+        write!(
+            self.io,
+            "\x1B[?1006l\x1B[?1015l\x1B[?7h{}{}{}",
+            screen::ToMainScreen,
+            cursor::Show,
+            style::Reset,
+        )?;
+        self.io.flush()?;
+
+        // Stop the input thread before it reads any more input.
+        self.events.close();
+        drop(self.stop);
+        self.raw.suspend_raw_mode()?;
+
+        Ok(self.io)
+    }
+}
+
+impl<'a> ReadEvents<'a> for Bound {
+    type EventError = <Self as super::Bound>::Error;
+    type EventFuture = EventFuture<'a>;
+
+    fn read_event(&'a mut self) -> Self::EventFuture {
+        EventFuture { bound: self }
+    }
+}
+
+#[derive(Debug)]
+pub struct EventFuture<'a> {
+    bound: &'a mut Bound,
+}
+
+impl<'a> Future for EventFuture<'a> {
+    type Output = io::Result<TerminalEvent>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.bound.events).poll_next(cx) {
+            Poll::Ready(Some(event)) => Poll::Ready(event),
+            Poll::Ready(None) => Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+fn from_termion_event(event: Event) -> Option<TerminalEvent> {
+    Some(match event {
+        Event::Key(key) => TerminalEvent::Key(from_termion_key(key)?),
+        Event::Mouse(mouse) => TerminalEvent::Mouse({
+            let (kind, x, y) = match mouse {
+                MouseEvent::Press(button, x, y) => (from_termion_mouse_button(button), x, y),
+                MouseEvent::Release(x, y) => (TerminalMouseKind::Release, x, y),
+                MouseEvent::Hold(x, y) => (TerminalMouseKind::Move, x, y),
+            };
+            TerminalMouse {
+                kind,
+                // Termion's coordinates are one-indexed.
+                at: Vec2 {
+                    x: x.saturating_sub(1),
+                    y: y.saturating_sub(1),
+                },
+                modifiers: Modifiers::default(),
+            }
+        }),
+        Event::Unsupported(_) => return None,
+    })
+}
+fn from_termion_key(key: TKey) -> Option<KeyPress> {
+    let mut modifiers = Modifiers::default();
+
+    let key = match key {
+        TKey::Backspace => Key::Backspace,
+        TKey::Left => Key::Left,
+        TKey::Right => Key::Right,
+        TKey::Up => Key::Up,
+        TKey::Down => Key::Down,
+        TKey::Home => Key::Home,
+        TKey::End => Key::End,
+        TKey::PageUp => Key::PageUp,
+        TKey::PageDown => Key::PageDown,
+        TKey::BackTab => {
+            modifiers.shift = true;
+            Key::Char('\t')
+        }
+        TKey::Delete => Key::Char('\x7f'),
+        TKey::Insert => Key::Insert,
+        TKey::F(n) => Key::F(n),
+        TKey::Char(c) => {
+            modifiers.shift = c.is_uppercase();
+            Key::Char(c.to_ascii_lowercase())
+        }
+        TKey::Alt(c) => {
+            modifiers.alt = true;
+            modifiers.shift = c.is_uppercase();
+            Key::Char(c.to_ascii_lowercase())
+        }
+        TKey::Ctrl(c) => {
+            modifiers.control = true;
+            Key::Char(c.to_ascii_lowercase())
+        }
+        TKey::Null => Key::Char('\0'),
+        TKey::Esc => Key::Escape,
+        _ => return None,
+    };
+
+    Some(KeyPress {
+        key,
+        modifiers,
+        // Termion doesn't report repeats or releases.
+        kind: KeyKind::Press,
+    })
+}
+fn from_termion_mouse_button(button: TMouseButton) -> TerminalMouseKind {
+    match button {
+        TMouseButton::Left => TerminalMouseKind::Press(MouseButton::Left),
+        TMouseButton::Middle => TerminalMouseKind::Press(MouseButton::Middle),
+        TMouseButton::Right => TerminalMouseKind::Press(MouseButton::Right),
+        TMouseButton::WheelUp => TerminalMouseKind::ScrollUp,
+        TMouseButton::WheelDown => TerminalMouseKind::ScrollDown,
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use crate::{Rgb, Vec2};
+
+/// An image made of pixels, drawn with the [`Image`](crate::Image) element.
+///
+/// Bitmaps are reference counted, so they are cheap to clone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bitmap {
+    size: Vec2<u16>,
+    // invariant: length is size.x * size.y
+    pixels: Arc<[Rgb]>,
+}
+
+impl Bitmap {
+    /// Create a new bitmap from its size in pixels and its pixels in row-major order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of pixels doesn't match the size.
+    #[must_use]
+    pub fn new(size: impl Into<Vec2<u16>>, pixels: impl Into<Arc<[Rgb]>>) -> Self {
+        let size = size.into();
+        let pixels = pixels.into();
+        assert_eq!(
+            pixels.len(),
+            usize::from(size.x) * usize::from(size.y),
+            "number of pixels doesn't match the size of the bitmap",
+        );
+        Self { size, pixels }
+    }
+
+    /// Get the size of the bitmap in pixels.
+    #[must_use]
+    pub fn size(&self) -> Vec2<u16> {
+        self.size
+    }
+
+    /// Get the pixels of the bitmap in row-major order.
+    #[must_use]
+    pub fn pixels(&self) -> &[Rgb] {
+        &self.pixels
+    }
+
+    /// Get the pixel at a position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    #[must_use]
+    pub fn pixel(&self, pos: Vec2<u16>) -> Rgb {
+        assert!(pos.x < self.size.x && pos.y < self.size.y);
+        self.pixels[usize::from(pos.y) * usize::from(self.size.x) + usize::from(pos.x)]
+    }
+
+    /// Sample the bitmap as if it were scaled to the given size, using nearest-neighbor scaling.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside the scaled size.
+    #[must_use]
+    pub fn sample(&self, pos: Vec2<u16>, scaled_size: Vec2<u16>) -> Rgb {
+        assert!(pos.x < scaled_size.x && pos.y < scaled_size.y);
+        let scale = |pos: u16, size: u16, scaled_size: u16| {
+            (u32::from(pos) * u32::from(size) / u32::from(scaled_size)) as u16
+        };
+        self.pixel(Vec2::new(
+            scale(pos.x, self.size.x, scaled_size.x),
+            scale(pos.y, self.size.y, scaled_size.y),
+        ))
+    }
+}
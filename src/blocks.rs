@@ -0,0 +1,78 @@
+/// Blocks filling the left eighths of a cell.
+const LEFT_BLOCKS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+/// Blocks filling the lower eighths of a cell.
+const LOWER_BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Shades filling quarters of a cell.
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// Round a fraction between 0 and 1 to the nearest number of steps.
+fn round(fraction: f64, steps: u8) -> usize {
+    (fraction.max(0.0).min(1.0) * f64::from(steps)).round() as usize
+}
+
+/// Get the block that fills a fraction of a cell from the left, rounded to the nearest eighth.
+///
+/// Fractions below zero or above one are clamped, and NaN is treated as zero.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(toon::left_block(0.0), ' ');
+/// assert_eq!(toon::left_block(0.3), '▎');
+/// assert_eq!(toon::left_block(1.0), '█');
+/// ```
+#[must_use]
+pub fn left_block(fraction: f64) -> char {
+    LEFT_BLOCKS[round(fraction, 8)]
+}
+
+/// Get the block that fills a fraction of a cell from the bottom, rounded to the nearest eighth.
+///
+/// Fractions below zero or above one are clamped, and NaN is treated as zero.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(toon::lower_block(0.5), '▄');
+/// assert_eq!(toon::lower_block(0.9), '▇');
+/// ```
+#[must_use]
+pub fn lower_block(fraction: f64) -> char {
+    LOWER_BLOCKS[round(fraction, 8)]
+}
+
+/// Get the shade that covers a fraction of a cell, rounded to the nearest quarter.
+///
+/// Fractions below zero or above one are clamped, and NaN is treated as zero.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(toon::shade_block(0.25), '░');
+/// assert_eq!(toon::shade_block(0.6), '▒');
+/// ```
+#[must_use]
+pub fn shade_block(fraction: f64) -> char {
+    SHADES[round(fraction, 4)]
+}
+
+#[test]
+fn test_blocks() {
+    assert_eq!(left_block(-1.0), ' ');
+    assert_eq!(left_block(f64::NAN), ' ');
+    assert_eq!(left_block(0.06), ' ');
+    assert_eq!(left_block(0.07), '▏');
+    assert_eq!(left_block(0.5), '▌');
+    assert_eq!(left_block(0.95), '█');
+    assert_eq!(left_block(2.0), '█');
+
+    assert_eq!(lower_block(0.0), ' ');
+    assert_eq!(lower_block(0.125), '▁');
+    assert_eq!(lower_block(0.75), '▆');
+    assert_eq!(lower_block(f64::INFINITY), '█');
+
+    assert_eq!(shade_block(0.1), ' ');
+    assert_eq!(shade_block(0.2), '░');
+    assert_eq!(shade_block(0.75), '▓');
+    assert_eq!(shade_block(1.0), '█');
+}
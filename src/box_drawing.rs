@@ -0,0 +1,197 @@
+/// No line in a direction.
+const N: u8 = 0;
+/// A light line in a direction.
+const L: u8 = 1;
+/// A heavy line in a direction.
+const H: u8 = 2;
+/// A double line in a direction.
+const D: u8 = 3;
+
+/// The box-drawing characters that can be merged, sorted by code point, with the weight of their
+/// line in each direction in the order of up, right, down, left.
+///
+/// Dashed and diagonal lines aren't included, as they can't be merged with other lines.
+const BOX_DRAWING: &[(char, [u8; 4])] = &[
+    ('─', [N, L, N, L]),
+    ('━', [N, H, N, H]),
+    ('│', [L, N, L, N]),
+    ('┃', [H, N, H, N]),
+    ('┌', [N, L, L, N]),
+    ('┍', [N, H, L, N]),
+    ('┎', [N, L, H, N]),
+    ('┏', [N, H, H, N]),
+    ('┐', [N, N, L, L]),
+    ('┑', [N, N, L, H]),
+    ('┒', [N, N, H, L]),
+    ('┓', [N, N, H, H]),
+    ('└', [L, L, N, N]),
+    ('┕', [L, H, N, N]),
+    ('┖', [H, L, N, N]),
+    ('┗', [H, H, N, N]),
+    ('┘', [L, N, N, L]),
+    ('┙', [L, N, N, H]),
+    ('┚', [H, N, N, L]),
+    ('┛', [H, N, N, H]),
+    ('├', [L, L, L, N]),
+    ('┝', [L, H, L, N]),
+    ('┞', [H, L, L, N]),
+    ('┟', [L, L, H, N]),
+    ('┠', [H, L, H, N]),
+    ('┡', [H, H, L, N]),
+    ('┢', [L, H, H, N]),
+    ('┣', [H, H, H, N]),
+    ('┤', [L, N, L, L]),
+    ('┥', [L, N, L, H]),
+    ('┦', [H, N, L, L]),
+    ('┧', [L, N, H, L]),
+    ('┨', [H, N, H, L]),
+    ('┩', [H, N, L, H]),
+    ('┪', [L, N, H, H]),
+    ('┫', [H, N, H, H]),
+    ('┬', [N, L, L, L]),
+    ('┭', [N, L, L, H]),
+    ('┮', [N, H, L, L]),
+    ('┯', [N, H, L, H]),
+    ('┰', [N, L, H, L]),
+    ('┱', [N, L, H, H]),
+    ('┲', [N, H, H, L]),
+    ('┳', [N, H, H, H]),
+    ('┴', [L, L, N, L]),
+    ('┵', [L, L, N, H]),
+    ('┶', [L, H, N, L]),
+    ('┷', [L, H, N, H]),
+    ('┸', [H, L, N, L]),
+    ('┹', [H, L, N, H]),
+    ('┺', [H, H, N, L]),
+    ('┻', [H, H, N, H]),
+    ('┼', [L, L, L, L]),
+    ('┽', [L, L, L, H]),
+    ('┾', [L, H, L, L]),
+    ('┿', [L, H, L, H]),
+    ('╀', [H, L, L, L]),
+    ('╁', [L, L, H, L]),
+    ('╂', [H, L, H, L]),
+    ('╃', [H, L, L, H]),
+    ('╄', [H, H, L, L]),
+    ('╅', [L, L, H, H]),
+    ('╆', [L, H, H, L]),
+    ('╇', [H, H, L, H]),
+    ('╈', [L, H, H, H]),
+    ('╉', [H, L, H, H]),
+    ('╊', [H, H, H, L]),
+    ('╋', [H, H, H, H]),
+    ('═', [N, D, N, D]),
+    ('║', [D, N, D, N]),
+    ('╒', [N, D, L, N]),
+    ('╓', [N, L, D, N]),
+    ('╔', [N, D, D, N]),
+    ('╕', [N, N, L, D]),
+    ('╖', [N, N, D, L]),
+    ('╗', [N, N, D, D]),
+    ('╘', [L, D, N, N]),
+    ('╙', [D, L, N, N]),
+    ('╚', [D, D, N, N]),
+    ('╛', [L, N, N, D]),
+    ('╜', [D, N, N, L]),
+    ('╝', [D, N, N, D]),
+    ('╞', [L, D, L, N]),
+    ('╟', [D, L, D, N]),
+    ('╠', [D, D, D, N]),
+    ('╡', [L, N, L, D]),
+    ('╢', [D, N, D, L]),
+    ('╣', [D, N, D, D]),
+    ('╤', [N, D, L, D]),
+    ('╥', [N, L, D, L]),
+    ('╦', [N, D, D, D]),
+    ('╧', [L, D, N, D]),
+    ('╨', [D, L, N, L]),
+    ('╩', [D, D, N, D]),
+    ('╪', [L, D, L, D]),
+    ('╫', [D, L, D, L]),
+    ('╬', [D, D, D, D]),
+    ('╭', [N, L, L, N]),
+    ('╮', [N, N, L, L]),
+    ('╯', [L, N, N, L]),
+    ('╰', [L, L, N, N]),
+    ('╴', [N, N, N, L]),
+    ('╵', [L, N, N, N]),
+    ('╶', [N, L, N, N]),
+    ('╷', [N, N, L, N]),
+    ('╸', [N, N, N, H]),
+    ('╹', [H, N, N, N]),
+    ('╺', [N, H, N, N]),
+    ('╻', [N, N, H, N]),
+    ('╼', [N, H, N, L]),
+    ('╽', [L, N, H, N]),
+    ('╾', [N, L, N, H]),
+    ('╿', [H, N, L, N]),
+];
+
+/// Get the lines of a box-drawing character.
+fn lines(c: char) -> Option<[u8; 4]> {
+    BOX_DRAWING
+        .binary_search_by_key(&c, |&(c, _)| c)
+        .ok()
+        .map(|i| BOX_DRAWING[i].1)
+}
+
+/// Merge a box-drawing character drawn on top of another one into the junction of the two.
+///
+/// The result has the lines of both characters, and where both characters have a line in the
+/// same direction the line of the character on top is used. Rounded corners merge as if they were
+/// square.
+///
+/// Returns [`None`] if either character isn't a solid box-drawing character or if Unicode doesn't
+/// have a character for the junction, such as one mixing heavy and double lines.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(toon::merge_box_drawing('│', '─'), Some('┼'));
+/// assert_eq!(toon::merge_box_drawing('┌', '┘'), Some('┼'));
+/// assert_eq!(toon::merge_box_drawing('═', '│'), Some('╪'));
+/// assert_eq!(toon::merge_box_drawing('┃', '═'), None);
+/// assert_eq!(toon::merge_box_drawing('a', '─'), None);
+/// ```
+#[must_use]
+pub fn merge_box_drawing(below: char, above: char) -> Option<char> {
+    let below = lines(below)?;
+    let above = lines(above)?;
+
+    let mut merged = [N; 4];
+    for ((merged, below), above) in merged.iter_mut().zip(&below).zip(&above) {
+        *merged = if *above == N { *below } else { *above };
+    }
+
+    BOX_DRAWING
+        .iter()
+        .filter(|&&(c, _)| !('╭'..='╰').contains(&c))
+        .find(|&&(_, lines)| lines == merged)
+        .map(|&(c, _)| c)
+}
+
+/// Get whether a character is a box-drawing character that can be merged with
+/// [`merge_box_drawing`].
+pub(crate) fn is_box_drawing(c: char) -> bool {
+    lines(c).is_some()
+}
+
+#[test]
+fn test_merge_box_drawing() {
+    assert_eq!(merge_box_drawing('─', '│'), Some('┼'));
+    assert_eq!(merge_box_drawing('├', '┤'), Some('┼'));
+    assert_eq!(merge_box_drawing('╭', '─'), Some('┬'));
+    assert_eq!(merge_box_drawing('━', '│'), Some('┿'));
+    assert_eq!(merge_box_drawing('│', '━'), Some('┿'));
+    assert_eq!(merge_box_drawing('┃', '─'), Some('╂'));
+    assert_eq!(merge_box_drawing('╴', '╶'), Some('─'));
+    assert_eq!(merge_box_drawing('╷', '╹'), Some('╿'));
+    assert_eq!(merge_box_drawing('║', '╔'), Some('╠'));
+    assert_eq!(merge_box_drawing('─', '═'), Some('═'));
+    assert_eq!(merge_box_drawing('═', '┃'), None);
+    assert_eq!(merge_box_drawing('┄', '│'), None);
+    assert_eq!(merge_box_drawing(' ', '│'), None);
+
+    assert!(is_box_drawing('╋'));
+    assert!(!is_box_drawing('╱'));
+}
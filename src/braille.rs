@@ -0,0 +1,174 @@
+use crate::{Output, Style, Vec2};
+
+/// The bit of each dot in a braille character, indexed by row and column within the cell.
+const DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// A grid of braille dots for plotting at a higher resolution than cells.
+///
+/// Each cell holds a braille character of two columns and four rows of dots. Dots are addressed by
+/// their zero-indexed position in dots, and dots outside the grid are ignored so that lines can
+/// extend beyond it.
+///
+/// # Examples
+///
+/// ```
+/// let mut braille = toon::BrailleGrid::new((2, 1));
+/// braille.line((0, 3), (3, 0));
+///
+/// let mut grid = toon::Grid::new((2, 1));
+/// braille.draw(&mut grid, toon::Style::default());
+/// toon::assert_grid_eq!(grid, ["⡠⠊"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BrailleGrid {
+    size: Vec2<u16>,
+    // invariant: length is size.x * size.y
+    cells: Vec<u8>,
+}
+
+impl BrailleGrid {
+    /// Create an empty braille grid from its size in cells.
+    #[must_use]
+    pub fn new(size: impl Into<Vec2<u16>>) -> Self {
+        let size = size.into();
+        Self {
+            size,
+            cells: vec![0; usize::from(size.x) * usize::from(size.y)],
+        }
+    }
+
+    /// Get the size of the grid in cells.
+    #[must_use]
+    pub fn size(&self) -> Vec2<u16> {
+        self.size
+    }
+
+    /// Get the size of the grid in dots.
+    #[must_use]
+    pub fn dot_size(&self) -> Vec2<u32> {
+        Vec2::new(u32::from(self.size.x) * 2, u32::from(self.size.y) * 4)
+    }
+
+    /// Get the index of the cell containing a dot and the bit of the dot, if it is in the grid.
+    fn dot(&self, pos: Vec2<i32>) -> Option<(usize, u8)> {
+        let pos = pos.try_into::<u32>().ok()?;
+        let cell = Vec2::new(pos.x / 2, pos.y / 4);
+        if cell.x >= u32::from(self.size.x) || cell.y >= u32::from(self.size.y) {
+            return None;
+        }
+        Some((
+            cell.y as usize * usize::from(self.size.x) + cell.x as usize,
+            DOTS[(pos.y % 4) as usize][(pos.x % 2) as usize],
+        ))
+    }
+
+    /// Get whether a dot is set.
+    #[must_use]
+    pub fn get(&self, pos: impl Into<Vec2<i32>>) -> bool {
+        self.dot(pos.into())
+            .map_or(false, |(cell, bit)| self.cells[cell] & bit != 0)
+    }
+
+    /// Set a dot.
+    pub fn set(&mut self, pos: impl Into<Vec2<i32>>) {
+        if let Some((cell, bit)) = self.dot(pos.into()) {
+            self.cells[cell] |= bit;
+        }
+    }
+
+    /// Clear a dot.
+    pub fn clear(&mut self, pos: impl Into<Vec2<i32>>) {
+        if let Some((cell, bit)) = self.dot(pos.into()) {
+            self.cells[cell] &= !bit;
+        }
+    }
+
+    /// Clear every dot in the grid.
+    pub fn clear_all(&mut self) {
+        for cell in &mut self.cells {
+            *cell = 0;
+        }
+    }
+
+    /// Set the dots of a straight line between two dots, including both ends.
+    pub fn line(&mut self, from: impl Into<Vec2<i32>>, to: impl Into<Vec2<i32>>) {
+        let (from, to) = (from.into().into::<i64>(), to.into().into::<i64>());
+
+        // Bresenham's line algorithm
+        let delta = Vec2::new((to.x - from.x).abs(), -(to.y - from.y).abs());
+        let step = Vec2::new((to.x - from.x).signum(), (to.y - from.y).signum());
+        let mut error = delta.x + delta.y;
+        let mut pos = from;
+        loop {
+            self.set(pos.map(|v| v as i32));
+            if pos == to {
+                break;
+            }
+            let double_error = error * 2;
+            if double_error >= delta.y {
+                error += delta.y;
+                pos.x += step.x;
+            }
+            if double_error <= delta.x {
+                error += delta.x;
+                pos.y += step.y;
+            }
+        }
+    }
+
+    /// Get the braille character of a cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    #[must_use]
+    pub fn cell(&self, pos: Vec2<u16>) -> char {
+        assert!(pos.x < self.size.x && pos.y < self.size.y);
+        let dots = self.cells[usize::from(pos.y) * usize::from(self.size.x) + usize::from(pos.x)];
+        std::char::from_u32(0x2800 + u32::from(dots)).unwrap()
+    }
+
+    /// Draw the grid to the top left corner of an output.
+    ///
+    /// Cells without any dots set aren't drawn, so whatever is behind them stays visible.
+    pub fn draw(&self, output: &mut dyn Output, style: Style) {
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let pos = Vec2::new(x, y);
+                let c = self.cell(pos);
+                if c != '\u{2800}' {
+                    output.write_char(pos, c, style);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_braille_grid() {
+    use crate::output::Ext as _;
+
+    let mut braille = BrailleGrid::new((2, 1));
+    assert_eq!(braille.dot_size(), Vec2::new(4, 4));
+
+    braille.line((0, 0), (3, 3));
+    braille.set((-1, 0));
+    braille.set((4, 0));
+    assert_eq!(braille.cell(Vec2::new(0, 0)), '⠑');
+    assert_eq!(braille.cell(Vec2::new(1, 0)), '⢄');
+
+    braille.clear((0, 0));
+    assert!(!braille.get((0, 0)));
+    assert!(braille.get((1, 1)));
+    assert!(!braille.get((4, 0)));
+
+    let mut grid = crate::Grid::new((3, 2));
+    grid.write((0, 0), "abc", Style::default());
+    braille.draw(&mut grid, Style::default());
+    crate::assert_grid_eq!(grid, ["⠐⢄c", "   "]);
+
+    braille.clear_all();
+    braille.line((3, 3), (-10, 3));
+    assert_eq!(braille.cell(Vec2::new(0, 0)), '⣀');
+    assert_eq!(braille.cell(Vec2::new(1, 0)), '⣀');
+}
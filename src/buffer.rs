@@ -1,17 +1,19 @@
 //! Data structures representing terminals.
 //!
-//! Toon uses [`unicode_width`] to get the number of cells a character takes up in a terminal. This
-//! isn't a perfect solution - for example, 👨‍👨‍👧‍👦, a family emoji, cannot be
-//! displayed in Toon as [`unicode_width`] measures it as 8 columns wide. However, it is the same as
-//! what most terminals do and is simple to understand and implement.
+//! Each cell holds an extended grapheme cluster, as split by [`unicode_segmentation`], so that
+//! flags, emoji with skin tone modifiers and emoji joined with zero-width joiners like
+//! 👨‍👨‍👧‍👦 are kept together. Toon uses [`unicode_width`] to get the number of cells a
+//! cluster takes up in a terminal, which is the total width of its characters but at most 2. This
+//! isn't a perfect solution as terminals don't agree on the width of some clusters. However, it is
+//! the same as what most terminals do and is simple to understand and implement.
 
 use std::cmp::Ordering;
-use std::iter;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
 
 use smartstring::{LazyCompact, SmartString};
-use unicode_width::UnicodeWidthChar;
 
-use crate::{Cursor, Output, Style, Vec2};
+use crate::{Bitmap, Cursor, Link, Output, Style, Vec2};
 
 /// A terminal state.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -20,19 +22,60 @@ pub struct Buffer {
     pub grid: Grid,
     /// The cursor on the terminal.
     pub cursor: Option<Cursor>,
+    /// The images drawn on top of the grid, in the order they were drawn.
+    pub images: Vec<ImageArea>,
+    /// The regions registered by elements, in the order they were registered.
+    pub regions: Vec<Region>,
+    /// The data attached to cells by elements.
+    pub cell_data: HashMap<Vec2<u16>, u64>,
 }
 
 impl Buffer {
-    /// Reset the buffer. This clears the grid and removes the cursor.
+    /// Reset the buffer. This clears the grid and removes the cursor, images, regions and cell
+    /// data.
     pub fn reset(&mut self) {
         self.grid.clear();
         self.cursor = None;
+        self.images.clear();
+        self.regions.clear();
+        self.cell_data.clear();
+    }
+
+    /// Free the memory the buffer has kept to reuse in later frames. See
+    /// [`Grid::shrink_to_fit`].
+    pub fn shrink_to_fit(&mut self) {
+        self.grid.shrink_to_fit();
+        self.images.shrink_to_fit();
+        self.regions.shrink_to_fit();
+        self.cell_data.shrink_to_fit();
+    }
+
+    /// Get the ids of the regions that cover a position, from the last registered to the first.
+    pub fn regions_at(&self, pos: impl Into<Vec2<u16>>) -> impl Iterator<Item = &str> + '_ {
+        let pos = pos.into();
+        self.regions
+            .iter()
+            .rev()
+            .filter(move |region| region.contains(pos))
+            .map(|region| region.id.as_str())
+    }
+
+    /// Get the data attached to the cell at a position, if there is any.
+    #[must_use]
+    pub fn cell_data_at(&self, pos: impl Into<Vec2<u16>>) -> Option<u64> {
+        self.cell_data.get(&pos.into()).copied()
     }
 }
 
 impl From<Grid> for Buffer {
     fn from(grid: Grid) -> Self {
-        Self { grid, cursor: None }
+        Self {
+            grid,
+            cursor: None,
+            images: Vec::new(),
+            regions: Vec::new(),
+            cell_data: HashMap::new(),
+        }
     }
 }
 
@@ -43,19 +86,111 @@ impl Output for Buffer {
     fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
         self.grid.write_char(pos, c, style)
     }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        self.grid.write_grapheme(pos, grapheme, style)
+    }
     fn set_cursor(&mut self, cursor: Option<Cursor>) {
         self.cursor = cursor;
     }
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        let image = ImageArea {
+            pos,
+            size,
+            bitmap: bitmap.clone(),
+        };
+        if image.end().map_or(false, |end| {
+            end.x <= self.grid.width() && end.y <= self.grid.height()
+        }) {
+            self.images.push(image);
+        }
+    }
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        let size = size.min(self.grid.size().zip_with(pos, u16::saturating_sub));
+        if size.x > 0 && size.y > 0 {
+            self.regions.push(Region {
+                id: id.to_owned(),
+                pos,
+                size,
+            });
+        }
+    }
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        if pos.x < self.grid.width() && pos.y < self.grid.height() {
+            self.cell_data.insert(pos, data);
+        }
+    }
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        self.grid.set_link(pos, link);
+    }
+}
+
+/// An image drawn on top of an area of a [`Buffer`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageArea {
+    /// The position of the top left cell of the area.
+    pub pos: Vec2<u16>,
+    /// The size of the area in cells.
+    pub size: Vec2<u16>,
+    /// The image, which is scaled to cover the area.
+    pub bitmap: Bitmap,
+}
+
+impl ImageArea {
+    /// Get whether the area covers the cell at the given position.
+    #[must_use]
+    pub fn contains(&self, pos: Vec2<u16>) -> bool {
+        pos.x >= self.pos.x
+            && pos.y >= self.pos.y
+            && self
+                .end()
+                .map_or(true, |end| pos.x < end.x && pos.y < end.y)
+    }
+
+    fn end(&self) -> Option<Vec2<u16>> {
+        self.pos.checked_add(self.size)
+    }
+}
+
+/// A named area of a [`Buffer`], registered by an element while drawing with
+/// [`Output::register_region`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Region {
+    /// The id of the region.
+    pub id: String,
+    /// The position of the top left cell of the region.
+    pub pos: Vec2<u16>,
+    /// The size of the region in cells.
+    pub size: Vec2<u16>,
+}
+
+impl Region {
+    /// Get whether the region covers the cell at the given position.
+    #[must_use]
+    pub fn contains(&self, pos: Vec2<u16>) -> bool {
+        pos.x >= self.pos.x
+            && pos.y >= self.pos.y
+            && pos.x - self.pos.x < self.size.x
+            && pos.y - self.pos.y < self.size.y
+    }
 }
 
 /// The grid of characters on a terminal.
 ///
 /// A grid is a list of [`Line`]s that are all the same length.
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+///
+/// With the `serde` feature grids can be serialized, so that drawn frames can be stored and
+/// compared in tests.
+///
+/// Rows removed by shrinking the grid's height are kept and reused when it grows again, so that
+/// resizing doesn't repeatedly allocate; [`shrink_to_fit`](Self::shrink_to_fit) frees them.
+#[derive(Default)]
 pub struct Grid {
     width: u16,
     // invariant: length <= u16::MAX, the width of each line is the width above
     lines: Vec<Line>,
+    /// Rows removed from the grid, whose allocations are reused when it grows. Their contents and
+    /// widths are meaningless.
+    spare_lines: Vec<Line>,
 }
 
 impl Grid {
@@ -90,7 +225,8 @@ impl Grid {
     /// Resize the grid's width.
     ///
     /// All new cells will be empty. If resizing a line cuts off a double cell, that double cell
-    /// becomes a space.
+    /// becomes a space. To rewrap the text of the grid to the new width instead, use
+    /// [`reflow`](Self::reflow).
     pub fn resize_width(&mut self, new_width: u16) {
         self.width = new_width;
 
@@ -114,13 +250,12 @@ impl Grid {
                 let anchor_line = usize::from(anchor_line);
 
                 if new_height > anchor_line {
-                    self.lines.truncate(new_height);
+                    self.spare_lines.extend(self.lines.drain(new_height..));
                 } else {
                     let after_anchor = anchor_line + 1;
-                    self.lines.truncate(after_anchor);
-                    self.lines
-                        .drain(0..after_anchor - new_height)
-                        .for_each(drop);
+                    self.spare_lines.extend(self.lines.drain(after_anchor..));
+                    self.spare_lines
+                        .extend(self.lines.drain(0..after_anchor - new_height));
                 }
             }
         }
@@ -130,9 +265,33 @@ impl Grid {
     ///
     /// All new cells will be empty.
     pub fn resize_height(&mut self, new_height: u16) {
+        let new_height = usize::from(new_height);
+        if new_height < self.lines.len() {
+            self.spare_lines.extend(self.lines.drain(new_height..));
+            return;
+        }
+
         let width = self.width;
+        let spare_lines = &mut self.spare_lines;
         self.lines
-            .resize_with(usize::from(new_height), || Line::new(width));
+            .resize_with(new_height, || match spare_lines.pop() {
+                Some(mut line) => {
+                    line.clear();
+                    line.resize(width);
+                    line
+                }
+                None => Line::new(width),
+            });
+    }
+
+    /// Free the memory the grid has kept to reuse when it grows, such as the rows removed when
+    /// its height was reduced.
+    pub fn shrink_to_fit(&mut self) {
+        self.spare_lines = Vec::new();
+        self.lines.shrink_to_fit();
+        for line in &mut self.lines {
+            line.cells.shrink_to_fit();
+        }
     }
 
     /// Get the grid's contents as a vector of strings.
@@ -143,13 +302,164 @@ impl Grid {
         self.lines.iter().map(Line::contents).collect()
     }
 
+    /// Get the grid's contents as text with SGR escape sequences for its styles, one line per row.
+    ///
+    /// The text can be written to a file, viewed with `less -R` or printed after the application
+    /// has exited to leave its final screen behind. See [`export::ansi`](crate::export::ansi) for
+    /// details.
+    #[must_use]
+    pub fn to_ansi_string(&self) -> String {
+        crate::export::ansi(self)
+    }
+
     /// Clear the grid.
     pub fn clear(&mut self) {
         for line in &mut self.lines {
             line.clear();
         }
     }
+
+    /// Set whether a row is soft-wrapped, meaning that its text continues on the next row instead
+    /// of ending at the end of this one. Rows aren't soft-wrapped by default, and clearing the grid
+    /// unmarks them.
+    ///
+    /// Setting this for a row out of bounds does nothing.
+    pub fn set_wrapped(&mut self, y: u16, wrapped: bool) {
+        if let Some(line) = self.lines.get_mut(usize::from(y)) {
+            line.wrapped = wrapped;
+        }
+    }
+
+    /// Change the grid's width, rewrapping the text of the grid to fit it like a terminal does
+    /// instead of cutting it off like [`resize_width`](Self::resize_width).
+    ///
+    /// Each row is joined with the soft-wrapped rows before it (see
+    /// [`set_wrapped`](Self::set_wrapped)) into a single line of text, and unstyled spaces are
+    /// removed from the end of it. The line is then wrapped onto as many rows of the new width as
+    /// it needs, which are marked as soft-wrapped. This changes the height of the grid, so
+    /// scrollback-style grids can grow and shrink with their contents; if the grid would have more
+    /// than 65535 rows, rows are removed from the top.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toon::{output::Ext as _, Grid, Style};
+    ///
+    /// let mut grid = Grid::new((3, 3));
+    /// grid.write((0, 0), "abc", Style::default());
+    /// grid.write((0, 1), "de", Style::default());
+    /// grid.set_wrapped(0, true);
+    /// grid.write((0, 2), "f", Style::default());
+    ///
+    /// grid.reflow(2);
+    /// toon::assert_grid_eq!(grid, ["ab", "cd", "e ", "f "]);
+    ///
+    /// grid.reflow(6);
+    /// toon::assert_grid_eq!(grid, ["abcde ", "f     "]);
+    /// ```
+    pub fn reflow(&mut self, new_width: u16) {
+        let is_blank = |cell: &Cell| match &cell.0 {
+            CellInner::Char {
+                contents,
+                style,
+                link,
+                ..
+            } => &**contents == " " && *style == Style::default() && link.is_none(),
+            CellInner::Continuation => false,
+        };
+
+        let old_lines = std::mem::take(&mut self.lines);
+        let old_height = old_lines.len();
+        let mut text = Vec::new();
+
+        for (y, old_line) in old_lines.into_iter().enumerate() {
+            let wrapped = old_line.wrapped;
+            text.extend(old_line.cells.into_iter().filter(Cell::is_char));
+            if wrapped && y + 1 < old_height {
+                continue;
+            }
+
+            while text.last().map_or(false, is_blank) {
+                text.pop();
+            }
+
+            let mut line = Line::default();
+            for cell in text.drain(..) {
+                let width = if cell.is_double() { 2 } else { 1 };
+                if !line.cells.is_empty() && line.cells.len() + width > usize::from(new_width) {
+                    line.wrapped = true;
+                    line.resize(new_width);
+                    self.lines.push(std::mem::take(&mut line));
+                }
+
+                if width > usize::from(new_width) {
+                    // The character doesn't fit even on its own row.
+                    if new_width > 0 {
+                        line.cells.push(Cell(CellInner::Char {
+                            contents: " ".into(),
+                            double: false,
+                            style: cell.style().unwrap_or_default(),
+                            link: None,
+                        }));
+                    }
+                } else {
+                    line.cells.push(cell);
+                    if width == 2 {
+                        line.cells.push(Cell(CellInner::Continuation));
+                    }
+                }
+            }
+            line.resize(new_width);
+            self.lines.push(line);
+        }
+
+        let max_height = usize::from(u16::MAX);
+        if self.lines.len() > max_height {
+            let excess = self.lines.len() - max_height;
+            self.spare_lines.extend(self.lines.drain(0..excess));
+        }
+        self.width = new_width;
+    }
+
+    /// Get a mutable view of the whole grid, which can be split into disjoint parts.
+    #[must_use]
+    pub fn view_mut(&mut self) -> GridMut<'_> {
+        GridMut {
+            width: self.width,
+            lines: self
+                .lines
+                .iter_mut()
+                .map(|line| line.cells.as_mut_slice())
+                .collect(),
+        }
+    }
+}
+
+impl Debug for Grid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Grid")
+            .field("width", &self.width)
+            .field("lines", &self.lines)
+            .finish()
+    }
+}
+
+impl Clone for Grid {
+    fn clone(&self) -> Self {
+        Self {
+            width: self.width,
+            lines: self.lines.clone(),
+            spare_lines: Vec::new(),
+        }
+    }
+}
+
+impl PartialEq for Grid {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.lines == other.lines
+    }
 }
+impl Eq for Grid {}
 
 impl Output for Grid {
     fn size(&self) -> Vec2<u16> {
@@ -160,7 +470,278 @@ impl Output for Grid {
             line.write_char(Vec2::new(pos.x, 0), c, style);
         }
     }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        if let Some(line) = self.lines.get_mut(usize::from(pos.y)) {
+            line.write_grapheme(Vec2::new(pos.x, 0), grapheme, style);
+        }
+    }
     fn set_cursor(&mut self, _cursor: Option<Cursor>) {}
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        if let Some(line) = self.lines.get_mut(usize::from(pos.y)) {
+            line.set_link(Vec2::new(pos.x, 0), link);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for Grid {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerdeGrid {
+            width: self.width,
+            lines: &self.lines,
+            wrapped: (0..self.height())
+                .filter(|&y| self.lines[usize::from(y)].wrapped)
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Grid {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde_crate::de::Error;
+
+        let mut grid = SerdeGrid::<Vec<Line>>::deserialize(deserializer)?;
+        if grid.lines.len() > usize::from(u16::MAX) {
+            return Err(D::Error::invalid_length(
+                grid.lines.len(),
+                &"at most 65535 lines",
+            ));
+        }
+        if let Some(line) = grid.lines.iter().find(|line| line.len() != grid.width) {
+            return Err(D::Error::invalid_length(
+                line.cells.len(),
+                &"lines as long as the width of the grid",
+            ));
+        }
+        for y in grid.wrapped {
+            grid.lines
+                .get_mut(usize::from(y))
+                .ok_or_else(|| D::Error::custom(format_args!("wrapped row {} doesn't exist", y)))?
+                .wrapped = true;
+        }
+        Ok(Self {
+            width: grid.width,
+            lines: grid.lines,
+            spare_lines: Vec::new(),
+        })
+    }
+}
+
+/// How grids are stored with serde.
+#[cfg(feature = "serde")]
+#[derive(serde_crate::Serialize, serde_crate::Deserialize)]
+#[serde(crate = "serde_crate")]
+struct SerdeGrid<L> {
+    width: u16,
+    lines: L,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    wrapped: Vec<u16>,
+}
+
+/// A mutable view of a rectangular part of a [`Grid`], created by [`Grid::view_mut`].
+///
+/// Like [`split_at_mut`](slice::split_at_mut) on slices, a view can be split into two views that
+/// don't overlap. Drawing to one view never affects the cells of another, and views can be sent to
+/// other threads, so containers with many independent children can draw them in parallel, for
+/// example with `rayon::join`.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Element, Grid};
+///
+/// let mut grid = Grid::new((6, 2));
+/// let (top, _) = grid.view_mut().split_at_row(1);
+/// let (mut left, mut right) = top.split_at_column(3);
+///
+/// toon::span::<_, ()>("abcd").draw(&mut left);
+/// toon::span::<_, ()>("efgh").draw(&mut right);
+///
+/// toon::assert_grid_eq!(grid, ["abcefg", "      "]);
+/// ```
+#[derive(Debug)]
+pub struct GridMut<'a> {
+    width: u16,
+    // invariant: length <= u16::MAX, each line is the width above long and upholds the invariants
+    // of `Line`
+    lines: Vec<&'a mut [Cell]>,
+}
+
+impl<'a> GridMut<'a> {
+    /// Get the number of columns in the view.
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+    /// Get the number of rows in the view.
+    #[must_use]
+    pub fn height(&self) -> u16 {
+        self.lines.len() as u16
+    }
+
+    /// Split the view into the rows above `y` and the rows starting at `y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is greater than the height of the view.
+    #[must_use]
+    pub fn split_at_row(mut self, y: u16) -> (Self, Self) {
+        let bottom = self.lines.split_off(usize::from(y));
+        let width = self.width;
+        (
+            self,
+            Self {
+                width,
+                lines: bottom,
+            },
+        )
+    }
+
+    /// Split the view into the columns left of `x` and the columns starting at `x`.
+    ///
+    /// A double-width character that is split between the two views is replaced with spaces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the width of the view.
+    #[must_use]
+    pub fn split_at_column(self, x: u16) -> (Self, Self) {
+        assert!(x <= self.width, "column {} is out of bounds", x);
+        let x = usize::from(x);
+
+        let mut left = Vec::with_capacity(self.lines.len());
+        let mut right = Vec::with_capacity(self.lines.len());
+
+        for cells in self.lines {
+            if cells.get(x).map_or(false, Cell::is_continuation) {
+                // Continuation cells always follow a double-width character.
+                let space = CellInner::Char {
+                    contents: " ".into(),
+                    double: false,
+                    style: cells[x - 1].style().unwrap(),
+                    link: None,
+                };
+                cells[x - 1].0 = space.clone();
+                cells[x].0 = space;
+            }
+
+            let (l, r) = cells.split_at_mut(x);
+            left.push(l);
+            right.push(r);
+        }
+
+        (
+            Self {
+                width: x as u16,
+                lines: left,
+            },
+            Self {
+                width: self.width - x as u16,
+                lines: right,
+            },
+        )
+    }
+}
+
+impl Output for GridMut<'_> {
+    fn size(&self) -> Vec2<u16> {
+        Vec2::new(self.width, self.height())
+    }
+    fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
+        self.write_grapheme(pos, c.encode_utf8(&mut [0; 4]), style);
+    }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        if let Some(cells) = self.lines.get_mut(usize::from(pos.y)) {
+            write_grapheme(cells, usize::from(pos.x), grapheme, style);
+        }
+    }
+    fn set_cursor(&mut self, _cursor: Option<Cursor>) {}
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        if let Some(cells) = self.lines.get_mut(usize::from(pos.y)) {
+            set_link(cells, usize::from(pos.x), link);
+        }
+    }
+}
+
+/// Find the cells that are different in the new grid from the old grid, from top to bottom and
+/// then left to right.
+///
+/// Writing the changes in order to a terminal showing the old grid makes it show the new grid.
+/// Continuation cells are never included, since writing the double-width cell before them covers
+/// them. If the grids are different sizes only the area they share is compared.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{buffer, output::Ext as _, Grid, Style};
+///
+/// let old = Grid::new((5, 1));
+/// let mut new = old.clone();
+/// new.write((1, 0), "ab", Style::default());
+///
+/// let changes: Vec<_> = buffer::diff(&old, &new).map(|change| change.contents).collect();
+/// assert_eq!(changes, ["a", "b"]);
+/// ```
+pub fn diff<'a>(old: &'a Grid, new: &'a Grid) -> impl Iterator<Item = Change<'a>> + 'a {
+    diff_redrawing(old, new, |_| false)
+}
+
+/// Like [`diff`], but also include the cells at the positions for which the function returns
+/// true even if they haven't changed.
+pub(crate) fn diff_redrawing<'a>(
+    old: &'a Grid,
+    new: &'a Grid,
+    redraw: impl Fn(Vec2<u16>) -> bool + Copy + 'a,
+) -> impl Iterator<Item = Change<'a>> + 'a {
+    old.lines
+        .iter()
+        .zip(&new.lines)
+        .enumerate()
+        .flat_map(move |(y, (old_line, new_line))| {
+            old_line
+                .cells
+                .iter()
+                .zip(&new_line.cells)
+                .enumerate()
+                .filter_map(move |(x, (old_cell, new_cell))| {
+                    let pos = Vec2::new(x as u16, y as u16);
+                    if old_cell == new_cell && !redraw(pos) {
+                        return None;
+                    }
+                    match new_cell.kind() {
+                        CellKind::Char {
+                            contents,
+                            double,
+                            style,
+                        } => Some(Change {
+                            pos,
+                            contents,
+                            double,
+                            style,
+                            link: new_cell.link(),
+                        }),
+                        CellKind::Continuation => None,
+                    }
+                })
+        })
+}
+
+/// A cell that has changed between two grids, produced by [`diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Change<'a> {
+    /// The position of the cell.
+    pub pos: Vec2<u16>,
+    /// The new contents of the cell.
+    pub contents: &'a str,
+    /// Whether the new cell is double-width, so the next cell is also covered by the change.
+    pub double: bool,
+    /// The new style of the cell.
+    pub style: Style,
+    /// The hyperlink the new cell points to, if any.
+    pub link: Option<&'a Link>,
 }
 
 /// A line of cells in a terminal.
@@ -171,6 +752,8 @@ impl Output for Grid {
 pub struct Line {
     // invariant: length <= u16::MAX, double cells must be followed by continuation cells
     cells: Vec<Cell>,
+    /// Whether the line's text continues on the next line of its grid.
+    wrapped: bool,
 }
 
 impl Line {
@@ -200,6 +783,13 @@ impl Line {
         &self.cells
     }
 
+    /// Get whether the line is soft-wrapped, meaning that its text continues on the next line of
+    /// its grid. See [`Grid::set_wrapped`].
+    #[must_use]
+    pub fn is_wrapped(&self) -> bool {
+        self.wrapped
+    }
+
     /// Resize the line.
     ///
     /// All new cells will be empty. If resizing the line cuts off a double cell, that double cell
@@ -211,6 +801,7 @@ impl Line {
                 contents: " ".into(),
                 double: false,
                 style: Style::default(),
+                link: None,
             }),
         );
 
@@ -235,11 +826,13 @@ impl Line {
 
     /// Clear the line.
     pub fn clear(&mut self) {
+        self.wrapped = false;
         for cell in &mut self.cells {
             cell.0 = CellInner::Char {
                 contents: " ".into(),
                 double: false,
                 style: Style::default(),
+                link: None,
             };
         }
     }
@@ -250,107 +843,214 @@ impl Output for Line {
         Vec2::new(self.len(), 1)
     }
     fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
-        if pos.y != 0 || c == '\0' {
-            return;
+        self.write_grapheme(pos, c.encode_utf8(&mut [0; 4]), style);
+    }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        if pos.y == 0 {
+            write_grapheme(&mut self.cells, usize::from(pos.x), grapheme, style);
         }
+    }
 
-        let x = usize::from(pos.x);
+    fn set_cursor(&mut self, _cursor: Option<Cursor>) {}
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        if pos.y == 0 {
+            set_link(&mut self.cells, usize::from(pos.x), link);
+        }
+    }
+}
 
-        match c.width() {
-            Some(0) => {
-                if let Some(Cell(CellInner::Char { contents, .. })) = self.cells.get_mut(x) {
-                    contents.push(c);
-                }
+/// Write a grapheme cluster to a cell of a list of cells that upholds the invariants of [`Line`].
+///
+/// Writes never change cells outside the list, so disjoint parts of a line can be written to
+/// independently as long as no double-width cell is split between them.
+fn write_grapheme(cells: &mut [Cell], x: usize, grapheme: &str, style: Style) {
+    if grapheme.contains('\0') {
+        return;
+    }
+
+    match crate::util::grapheme_width(grapheme) {
+        Some(0) => {
+            // Zero-width characters combine with the character before them, which is two
+            // cells back if it is double-width.
+            let before = match x.checked_sub(1) {
+                Some(before) if before < cells.len() => before,
+                _ => return,
+            };
+            let before = match cells[before].0 {
+                CellInner::Char { .. } => before,
+                CellInner::Continuation => before - 1,
+            };
+            if let CellInner::Char { contents, .. } = &mut cells[before].0 {
+                *contents = cell_contents(&[&**contents, grapheme].concat());
             }
-            Some(1) => {
-                let cell = match self.cells.get_mut(x) {
-                    Some(cell) => cell,
-                    None => return,
-                };
-                let old_cell = std::mem::replace(
-                    &mut cell.0,
-                    CellInner::Char {
-                        contents: iter::once(c).collect(),
+        }
+        Some(1) => {
+            let style = match style_at(cells, x) {
+                Some(below) => style.blend_over(below),
+                None => return,
+            };
+            let cell = match cells.get_mut(x) {
+                Some(cell) => cell,
+                None => return,
+            };
+            let old_cell = std::mem::replace(
+                &mut cell.0,
+                CellInner::Char {
+                    contents: cell_contents(grapheme),
+                    double: false,
+                    style,
+                    link: None,
+                },
+            );
+
+            match old_cell {
+                CellInner::Char {
+                    double: true,
+                    style: old_style,
+                    ..
+                } => {
+                    cells[x + 1].0 = CellInner::Char {
+                        contents: " ".into(),
                         double: false,
-                        style,
-                    },
-                );
-
-                match old_cell {
-                    CellInner::Char {
-                        double: true,
                         style: old_style,
+                        link: None,
+                    };
+                }
+                CellInner::Char { .. } => {}
+                CellInner::Continuation => match &mut cells[x - 1].0 {
+                    CellInner::Char {
+                        contents,
+                        double: double @ true,
                         ..
                     } => {
-                        self.cells[x + 1].0 = CellInner::Char {
-                            contents: " ".into(),
-                            double: false,
-                            style: old_style,
-                        };
+                        *contents = " ".into();
+                        *double = false;
                     }
-                    CellInner::Char { .. } => {}
-                    CellInner::Continuation => match &mut self.cells[x - 1].0 {
-                        CellInner::Char {
-                            contents,
-                            double: double @ true,
-                            ..
-                        } => {
-                            *contents = " ".into();
-                            *double = false;
-                        }
-                        _ => unreachable!(),
-                    },
-                }
+                    _ => unreachable!(),
+                },
             }
-            Some(2) => {
-                let second_cell = match self.cells.get_mut(x + 1) {
-                    Some(cell) => &mut cell.0,
-                    None => return,
-                };
+        }
+        Some(2) => {
+            if x + 1 == cells.len() {
+                // Only the first half of the character fits, so it is replaced with a space.
+                return write_grapheme(cells, x, " ", style);
+            }
+            let style = match style_at(cells, x) {
+                Some(below) => style.blend_over(below),
+                None => return,
+            };
+            let second_cell = match cells.get_mut(x + 1) {
+                Some(cell) => &mut cell.0,
+                None => return,
+            };
 
-                let old_second = std::mem::replace(second_cell, CellInner::Continuation);
-                let old_first = std::mem::replace(
-                    &mut self.cells[x].0,
+            let old_second = std::mem::replace(second_cell, CellInner::Continuation);
+            let old_first = std::mem::replace(
+                &mut cells[x].0,
+                CellInner::Char {
+                    contents: cell_contents(grapheme),
+                    double: true,
+                    style,
+                    link: None,
+                },
+            );
+
+            if let CellInner::Continuation = old_first {
+                match &mut cells[x - 1].0 {
                     CellInner::Char {
-                        contents: iter::once(c).collect(),
-                        double: true,
-                        style,
-                    },
-                );
-
-                if let CellInner::Continuation = old_first {
-                    match &mut self.cells[x - 1].0 {
-                        CellInner::Char {
-                            contents, double, ..
-                        } => {
-                            *contents = " ".into();
-                            *double = false;
-                        }
-                        _ => unreachable!(),
+                        contents, double, ..
+                    } => {
+                        *contents = " ".into();
+                        *double = false;
                     }
+                    _ => unreachable!(),
                 }
-                if let CellInner::Char {
-                    double: true,
+            }
+            if let CellInner::Char {
+                double: true,
+                style: old_style,
+                ..
+            } = old_second
+            {
+                cells[x + 2].0 = CellInner::Char {
+                    contents: " ".into(),
+                    double: false,
                     style: old_style,
-                    ..
-                } = old_second
-                {
-                    self.cells[x + 2].0 = CellInner::Char {
-                        contents: " ".into(),
-                        double: false,
-                        style: old_style,
-                    };
-                }
+                    link: None,
+                };
             }
-            Some(_) => unreachable!(),
-            None => {}
-        };
+        }
+        Some(_) => unreachable!(),
+        None => {}
+    };
+}
+
+/// Get the style of the cell at a position, or of the double cell it is the continuation of.
+fn style_at(cells: &[Cell], x: usize) -> Option<Style> {
+    match &cells.get(x)?.0 {
+        CellInner::Char { style, .. } => Some(*style),
+        CellInner::Continuation => cells[x - 1].style(),
     }
+}
 
-    fn set_cursor(&mut self, _cursor: Option<Cursor>) {}
+/// Make the cell at a position of a list of cells that upholds the invariants of [`Line`] point to
+/// a hyperlink. Setting the link of a continuation cell sets the link of its double cell.
+fn set_link(cells: &mut [Cell], x: usize, link: &Link) {
+    let x = match cells.get(x) {
+        Some(Cell(CellInner::Char { .. })) => x,
+        Some(Cell(CellInner::Continuation)) => x - 1,
+        None => return,
+    };
+    if let CellInner::Char { link: old, .. } = &mut cells[x].0 {
+        *old = Some(link.clone());
+    }
+}
+
+/// Lines are stored as a list of cells.
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for Line {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.cells.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Line {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde_crate::de::Error;
+
+        let cells = Vec::<Cell>::deserialize(deserializer)?;
+        if cells.len() > usize::from(u16::MAX) {
+            return Err(D::Error::invalid_length(
+                cells.len(),
+                &"at most 65535 cells",
+            ));
+        }
+
+        let mut after_double = false;
+        for cell in &cells {
+            if cell.is_continuation() != after_double {
+                return Err(D::Error::custom(
+                    "double-width cells must be followed by a single continuation cell",
+                ));
+            }
+            after_double = matches!(cell.0, CellInner::Char { double: true, .. });
+        }
+        if after_double {
+            return Err(D::Error::custom("line ends with a double-width cell"));
+        }
+
+        Ok(Self {
+            cells,
+            wrapped: false,
+        })
+    }
 }
 
 /// A cell in a terminal.
+///
+/// With the `serde` feature continuation cells are stored as `null`, and other cells as their
+/// contents and style.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Cell(CellInner);
 
@@ -363,8 +1063,9 @@ impl Cell {
                 contents,
                 double,
                 style,
+                ..
             } => CellKind::Char {
-                contents: &contents,
+                contents,
                 double: *double,
                 style: *style,
             },
@@ -407,19 +1108,28 @@ impl Cell {
             _ => None,
         }
     }
+
+    /// Get the hyperlink the cell points to, if it is not a continuation cell and has one.
+    #[must_use]
+    pub fn link(&self) -> Option<&Link> {
+        match &self.0 {
+            CellInner::Char { link, .. } => link.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 /// A kind of cell.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CellKind<'a> {
-    /// The cell contains a 1-2 width character followed by any number of zero-width characters.
+    /// The cell contains a 1-2 width grapheme cluster followed by any number of zero-width
+    /// characters.
     Char {
-        /// The characters in the cell. The first character will be 1-2 columns wide, and the rest
-        /// of the characters will be zero columns wide. This will contain no control characters.
+        /// The characters in the cell. The first character will be 1-2 columns wide. This will
+        /// contain no control characters.
         contents: &'a str,
-        /// Whether the cell is double-width (that is, the width of the first character of
-        /// [`contents`](Self::Char::contents) as well as all of [`contents`](Self::Char::contents)
-        /// is 2).
+        /// Whether the cell is double-width (that is, the width of
+        /// [`contents`](Self::Char::contents) is at least 2).
         ///
         /// If a cell is double width the next cell will be a [`Continuation`](Self::Continuation).
         double: bool,
@@ -430,6 +1140,67 @@ pub enum CellKind<'a> {
     Continuation,
 }
 
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for Cell {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.0 {
+            CellInner::Char {
+                contents,
+                style,
+                link,
+                ..
+            } => serializer.serialize_some(&SerdeCell {
+                contents: &**contents,
+                style: *style,
+                link: link.clone(),
+            }),
+            CellInner::Continuation => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Cell {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde_crate::de::{Error, Unexpected};
+
+        let cell = match Option::<SerdeCell<String>>::deserialize(deserializer)? {
+            Some(cell) => cell,
+            None => return Ok(Self(CellInner::Continuation)),
+        };
+        let double = match crate::util::grapheme_width(&cell.contents) {
+            _ if cell.contents.contains('\0') => None,
+            Some(1) => Some(false),
+            Some(2) => Some(true),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            D::Error::invalid_value(
+                Unexpected::Str(&cell.contents),
+                &"a character 1 or 2 columns wide",
+            )
+        })?;
+
+        Ok(Self(CellInner::Char {
+            contents: cell_contents(&cell.contents),
+            double,
+            style: cell.style,
+            link: cell.link,
+        }))
+    }
+}
+
+/// How character cells are stored with serde.
+#[cfg(feature = "serde")]
+#[derive(serde_crate::Serialize, serde_crate::Deserialize)]
+#[serde(crate = "serde_crate")]
+struct SerdeCell<S> {
+    contents: S,
+    style: Style,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    link: Option<Link>,
+}
+
 /// A cell in a terminal. See `CellKind` above for more info on each variant.
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum CellInner {
@@ -439,13 +1210,31 @@ enum CellInner {
         contents: SmartString<LazyCompact>,
         double: bool,
         style: Style,
+        link: Option<Link>,
     },
     Continuation,
 }
 
+/// Create the contents of a cell from a grapheme cluster.
+///
+/// With the `normalize` feature, the grapheme cluster is normalized to NFC first.
+fn cell_contents(grapheme: &str) -> SmartString<LazyCompact> {
+    #[cfg(feature = "normalize")]
+    {
+        use unicode_normalization::UnicodeNormalization as _;
+
+        if !unicode_normalization::is_nfc(grapheme) {
+            return grapheme.nfc().collect::<String>().into();
+        }
+    }
+
+    grapheme.into()
+}
+
 #[cfg(test)]
 #[test]
 fn test_line() {
+    use crate::Styled as _;
     use std::convert::TryFrom;
     use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
@@ -472,8 +1261,8 @@ fn test_line() {
                         assert!(!contents.is_empty());
 
                         let width = if double { 2 } else { 1 };
-                        assert_eq!(contents.width(), width);
-                        assert_eq!(contents.chars().next().unwrap().width().unwrap(), width);
+                        assert_eq!(contents.width().min(2), width);
+                        assert_ne!(contents.chars().next().unwrap().width().unwrap(), 0);
 
                         continuation = double;
                     }
@@ -515,10 +1304,15 @@ fn test_line() {
     assert_invariants(&line);
     assert_eq!(line.contents(), "😊   ");
 
-    // Drawing a double width character at the edge doesn't do anything
-    line.write_char(Vec2::new(4, 0), '😊', Style::default());
+    // Drawing a double width character at the edge draws a space
+    line.write_char(Vec2::new(3, 0), 'b', Style::default());
+    line.write_char(Vec2::new(4, 0), 'b', Style::default());
+    line.write_char(Vec2::new(4, 0), '😊', Style::default().bold());
     assert_invariants(&line);
-    assert_eq!(line.contents(), "😊   ");
+    assert_eq!(line.contents(), "😊 b ");
+    assert_eq!(line.cells()[4].style(), Some(Style::default().bold()));
+    line.write_char(Vec2::new(3, 0), ' ', Style::default());
+    line.write_char(Vec2::new(4, 0), ' ', Style::default());
 
     // Drawing a single width character over a double width character
     line.write_char(Vec2::new(1, 0), 'a', Style::default());
@@ -534,6 +1328,209 @@ fn test_line() {
     line.resize(3);
     assert_invariants(&line);
     assert_eq!(line.contents(), " a ");
+
+    // Drawing grapheme clusters made of several characters
+    line.write_grapheme(Vec2::new(0, 0), "x\u{301}", Style::default());
+    assert_invariants(&line);
+    line.write_grapheme(Vec2::new(1, 0), "🇬🇧", Style::default());
+    assert_invariants(&line);
+    assert_eq!(line.contents(), "x\u{301}🇬🇧");
+    assert_eq!(line.cells()[1].contents(), Some("🇬🇧"));
+    assert!(line.cells()[2].is_continuation());
+
+    line.write_grapheme(Vec2::new(0, 0), "👍🏽", Style::default());
+    assert_invariants(&line);
+    assert_eq!(line.contents(), "👍🏽 ");
+
+    // Drawing zero-width characters after the characters they combine with
+    let mut line = Line::new(4);
+    line.write_char(Vec2::new(0, 0), 'x', Style::default());
+    line.write_char(Vec2::new(1, 0), '\u{301}', Style::default());
+    line.write_char(Vec2::new(1, 0), '😊', Style::default());
+    line.write_char(Vec2::new(3, 0), '\u{20E3}', Style::default());
+    line.write_char(Vec2::new(0, 0), '\u{302}', Style::default());
+    line.write_char(Vec2::new(5, 0), '\u{302}', Style::default());
+    assert_invariants(&line);
+    assert_eq!(line.contents(), "x\u{301}😊\u{20E3} ");
+}
+
+#[cfg(test)]
+#[test]
+fn test_grid_mut() {
+    use crate::output::Ext as _;
+    use crate::Styled as _;
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    let mut grid = Grid::new((6, 3));
+    grid.write((0, 1), "a日本", Style::default().bold());
+
+    let view = grid.view_mut();
+    assert_send(&view);
+    assert_eq!(view.size(), Vec2::new(6, 3));
+
+    let (top, mut bottom) = view.split_at_row(1);
+    assert_eq!(top.size(), Vec2::new(6, 1));
+    assert_eq!(bottom.size(), Vec2::new(6, 2));
+    bottom.write((5, 1), "x", Style::default());
+
+    let (mut left, mut right) = bottom.split_at_column(2);
+    assert_eq!(left.size(), Vec2::new(2, 2));
+    assert_eq!(right.size(), Vec2::new(4, 2));
+
+    // Writes can't reach the other side of the split.
+    left.write((1, 1), "日", Style::default());
+    left.write((0, 1), "ab", Style::default());
+    right.write((0, 0), "\u{301}", Style::default());
+    right.write((3, 0), "本", Style::default());
+    right.write((2, 1), "y", Style::default());
+
+    crate::assert_grid_eq!(grid, ["      ", "a  本 ", "ab  yx"]);
+    assert_eq!(
+        grid.lines()[1].cells()[2].style(),
+        Some(Style::default().bold())
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_reflow() {
+    use crate::output::Ext as _;
+    use crate::Styled;
+
+    let mut grid = Grid::new((4, 4));
+    grid.write((0, 0), "ab日", Style::default());
+    grid.set_wrapped(0, true);
+    grid.write((0, 1), "c", Style::default());
+    grid.write((0, 2), " ", Style::default().on_red());
+    grid.set_wrapped(3, true);
+
+    grid.reflow(3);
+    crate::assert_grid_eq!(grid, ["ab ", "日c", "   ", "   "]);
+    let wrapped: Vec<_> = grid.lines().iter().map(Line::is_wrapped).collect();
+    assert_eq!(wrapped, [true, false, false, false]);
+    assert_eq!(
+        grid.lines()[2].cells()[0].style(),
+        Some(Style::default().on_red())
+    );
+
+    // The double-width character can't fit, so it is replaced with a space.
+    grid.reflow(1);
+    crate::assert_grid_eq!(grid, ["a", "b", " ", " ", "c", " ", " "]);
+    assert!(grid.lines()[3].is_wrapped());
+    assert!(!grid.lines()[4].is_wrapped());
+
+    grid.reflow(5);
+    crate::assert_grid_eq!(grid, ["ab  c", "     ", "     "]);
+
+    grid.clear();
+    assert!(grid.lines().iter().all(|line| !line.is_wrapped()));
+}
+
+#[cfg(test)]
+#[test]
+fn test_resize_reuses_lines() {
+    use crate::output::Ext as _;
+
+    let mut grid = Grid::new((3, 4));
+    grid.write((0, 2), "ab", Style::default());
+    grid.write((0, 3), "日", Style::default());
+    grid.set_wrapped(3, true);
+
+    grid.resize_height_with_anchor(2, 0);
+    crate::assert_grid_eq!(grid, ["   ", "   "]);
+    assert_eq!(grid.spare_lines.len(), 2);
+    assert_eq!(grid, Grid::new((3, 2)));
+
+    // Reused lines are cleared and take the current width.
+    grid.resize_width(2);
+    grid.resize_height(5);
+    crate::assert_grid_eq!(grid, ["  ", "  ", "  ", "  ", "  "]);
+    assert!(grid.lines().iter().all(|line| !line.is_wrapped()));
+    assert!(grid.spare_lines.is_empty());
+
+    grid.resize_height(1);
+    assert_eq!(grid.clone().spare_lines.len(), 0);
+    grid.shrink_to_fit();
+    assert!(grid.spare_lines.is_empty());
+    assert_eq!(grid, Grid::new((2, 1)));
+}
+
+#[cfg(test)]
+#[test]
+fn test_cell_size() {
+    // Grids of large terminals have tens of thousands of cells and are cloned every frame.
+    #[cfg(target_pointer_width = "64")]
+    assert!(std::mem::size_of::<Cell>() <= 64);
+}
+
+#[cfg(test)]
+#[test]
+fn test_diff() {
+    use crate::output::Ext as _;
+    use crate::Styled;
+
+    let mut old = Grid::new((4, 2));
+    old.write((0, 0), "abcd", Style::default());
+    old.write((0, 1), "😊ef", Style::default());
+
+    let mut new = old.clone();
+    new.write((1, 0), "x", Style::default());
+    new.write((1, 1), "😊", Style::default());
+    new.write((3, 1), "f", Style::default().on_red());
+
+    let changes: Vec<_> = diff(&old, &new)
+        .map(|change| (change.pos, change.contents, change.double))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (Vec2::new(1, 0), "x", false),
+            (Vec2::new(0, 1), " ", false),
+            (Vec2::new(1, 1), "😊", true),
+            (Vec2::new(3, 1), "f", false),
+        ]
+    );
+
+    assert_eq!(diff(&new, &new).count(), 0);
+    assert_eq!(
+        diff_redrawing(&new, &new, |pos| pos.y == 1)
+            .map(|change| change.contents)
+            .collect::<Vec<_>>(),
+        [" ", "😊", "f"]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_blend() {
+    use crate::{Color, Rgb, Rgba, Styled as _};
+
+    let mut line = Line::new(3);
+    line.write_char(Vec2::new(0, 0), '😊', Style::default().on_white());
+    line.write_char(Vec2::new(2, 0), 'a', Style::default().on_dark_red());
+
+    let shadow = Style::default().background(Rgba::new(0, 0, 0, 0x80));
+    for x in 0..3 {
+        line.write_char(Vec2::new(x, 0), ' ', shadow);
+    }
+    let backgrounds: Vec<_> = line
+        .cells()
+        .iter()
+        .map(|cell| cell.style().unwrap().background)
+        .collect();
+    assert_eq!(
+        backgrounds,
+        [
+            Color::Rgb(Rgb::new(127, 127, 127)),
+            Color::Rgb(Rgb::new(127, 127, 127)),
+            Color::Rgb(Rgb::new(102, 0, 0)),
+        ]
+    );
+
+    let mut line = Line::new(1);
+    line.write_char(Vec2::new(0, 0), ' ', shadow);
+    assert_eq!(line.cells()[0].style(), Some(shadow));
 }
 
 #[cfg(test)]
@@ -581,3 +1578,72 @@ fn test_resize_anchor() {
     assert_eq!(grid.lines()[1].cells()[0].contents(), Some("2"));
     assert_eq!(grid.lines()[2].cells()[0].contents(), Some("3"));
 }
+
+#[cfg(feature = "normalize")]
+#[test]
+fn test_normalize() {
+    use crate::output::Ext as _;
+
+    let mut composed = Grid::new((3, 1));
+    composed.write((0, 0), "é한", Style::default());
+
+    let mut decomposed = Grid::new((3, 1));
+    decomposed.write((0, 0), "e\u{301}\u{1112}\u{1161}\u{11ab}", Style::default());
+    crate::assert_grid_eq!(decomposed, composed);
+
+    decomposed.write_char(Vec2::new(0, 0), 'e', Style::default());
+    decomposed.write_char(Vec2::new(1, 0), '\u{301}', Style::default());
+    assert_eq!(decomposed.lines()[0].cells()[0].contents(), Some("é"));
+    assert_eq!(decomposed, composed);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use crate::output::Ext as _;
+    use crate::Styled;
+
+    let mut grid = Grid::new((4, 2));
+    grid.write((0, 0), "a😊", Style::default().bold());
+    grid.write((1, 1), "e\u{301}", Style::default().on_blue());
+
+    grid.set_wrapped(0, true);
+
+    let json = serde_json::to_string(&grid).unwrap();
+    assert_eq!(serde_json::from_str::<Grid>(&json).unwrap(), grid);
+
+    let cell = |contents: &str| {
+        let contents = serde_json::to_string(contents).unwrap();
+        serde_json::from_str::<Cell>(&format!(r#"{{ "contents": {}, "style": {{}} }}"#, contents))
+    };
+    assert!(cell("😊").unwrap().is_double());
+    assert!(cell("").is_err());
+    assert!(cell("\u{301}").is_err());
+    assert!(cell("\t").is_err());
+    assert!(serde_json::from_str::<Cell>("null")
+        .unwrap()
+        .is_continuation());
+
+    let line = |json: &str| serde_json::from_str::<Line>(json);
+    let a = r#"{ "contents": "a", "style": {} }"#;
+    let emoji = r#"{ "contents": "😊", "style": {} }"#;
+    assert_eq!(
+        line(&format!("[{}, {}, null]", a, emoji))
+            .unwrap()
+            .contents(),
+        "a😊"
+    );
+    assert!(line(&format!("[{}, null]", a)).is_err());
+    assert!(line(&format!("[{}, {}]", emoji, a)).is_err());
+    assert!(line(&format!("[{}, null, null]", emoji)).is_err());
+    assert!(line(&format!("[{}, {}]", a, emoji)).is_err());
+
+    assert!(
+        serde_json::from_str::<Grid>(&format!(r#"{{ "width": 2, "lines": [[{}]] }}"#, a)).is_err()
+    );
+    assert!(serde_json::from_str::<Grid>(&format!(
+        r#"{{ "width": 1, "lines": [[{}]], "wrapped": [1] }}"#,
+        a
+    ))
+    .is_err());
+}
@@ -0,0 +1,119 @@
+//! Sources of the current time, which can be controlled in tests.
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of the current time.
+///
+/// Everything in Toon that depends on time, like the timeouts of
+/// [`SequenceState`](crate::input::SequenceState)s and the timings in
+/// [`FrameStats`](crate::FrameStats), reads it from a clock instead of the system, so that it can
+/// be tested deterministically with a [`MockClock`]. A terminal gets its clock from its backend,
+/// available through [`Terminal::clock`](crate::Terminal::clock); the [`Dummy`](crate::Dummy)
+/// backend uses a mock clock.
+///
+/// Applications with animations can take the time from the terminal's clock too, so that the
+/// animations can be stepped through in tests.
+pub trait Clock: Debug + Send + Sync {
+    /// Get the current time.
+    fn now(&self) -> Instant;
+
+    /// Get how much time has passed since an earlier instant, or zero if it is in the future.
+    fn elapsed(&self, since: Instant) -> Duration {
+        self.now().saturating_duration_since(since)
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+/// A clock that reads the time from the system. This is what real backends use.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock whose time only changes when it is told to.
+///
+/// Clones of a mock clock share the same time, so a clone can be given to the code under test
+/// while the test keeps another to advance it.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use toon::clock::{Clock, MockClock};
+///
+/// let clock = MockClock::new();
+/// let start = clock.now();
+///
+/// clock.advance(Duration::from_millis(500));
+/// assert_eq!(clock.elapsed(start), Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl MockClock {
+    /// Create a new mock clock, starting at the current time of the system.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Move the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+
+    /// Set the time of the clock.
+    pub fn set(&self, now: Instant) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[test]
+fn test_mock_clock() {
+    let clock = MockClock::new();
+    let start = clock.now();
+    assert_eq!(clock.now(), start);
+
+    let shared = clock.clone();
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(shared.now(), start + Duration::from_secs(2));
+    assert_eq!(shared.elapsed(start), Duration::from_secs(2));
+
+    shared.set(start);
+    assert_eq!(
+        Arc::new(clock).elapsed(start + Duration::from_secs(1)),
+        Duration::default()
+    );
+}
@@ -0,0 +1,136 @@
+//! Asynchronous commands that produce events.
+//!
+//! Event handlers often need to perform I/O, such as making an HTTP request, and then update the
+//! state with the result. Instead of plumbing channels into the draw loop manually, you can push a
+//! [`Command`] to a [`Commands`] queue and draw with
+//! [`Terminal::draw_with_commands`](crate::Terminal::draw_with_commands), which runs the commands
+//! alongside the terminal and returns the events they produce.
+//!
+//! Commands are driven by whichever executor is running the terminal, so they don't require any
+//! particular runtime.
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A future that produces an event when it completes.
+pub struct Command<'a, Event>(Pin<Box<dyn Future<Output = Event> + 'a>>);
+
+impl<'a, Event: 'a> Command<'a, Event> {
+    /// Create a command that runs the future, and produces its output as an event.
+    #[must_use]
+    pub fn future(future: impl Future<Output = Event> + 'a) -> Self {
+        Self(Box::pin(future))
+    }
+
+    /// Create a command that produces the event immediately.
+    #[must_use]
+    pub fn event(event: Event) -> Self {
+        Self::future(async move { event })
+    }
+
+    /// Map the event that the command produces.
+    #[must_use]
+    pub fn map<Event2: 'a>(self, f: impl FnOnce(Event) -> Event2 + 'a) -> Command<'a, Event2> {
+        Command::future(async move { f(self.0.await) })
+    }
+}
+
+impl<'a, Event> Future for Command<'a, Event> {
+    type Output = Event;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.as_mut().poll(cx)
+    }
+}
+
+impl<'a, Event> Debug for Command<'a, Event> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Command").finish()
+    }
+}
+
+/// A queue of running [`Command`]s.
+pub struct Commands<'a, Event> {
+    commands: Vec<Command<'a, Event>>,
+}
+
+impl<'a, Event> Commands<'a, Event> {
+    /// Create an empty queue of commands.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    /// Add a command to the queue.
+    pub fn push(&mut self, command: Command<'a, Event>) {
+        self.commands.push(command);
+    }
+
+    /// Add a future to the queue, which produces its output as an event.
+    pub fn spawn(&mut self, future: impl Future<Output = Event> + 'a)
+    where
+        Event: 'a,
+    {
+        self.push(Command::future(future));
+    }
+
+    /// Get the number of commands that are still running.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Get whether there are no commands running.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Poll all the commands in the queue, returning the events of all the commands that
+    /// completed in the order they were added.
+    ///
+    /// This returns [`Poll::Pending`] if no commands completed, including if the queue is empty.
+    pub fn poll_events(&mut self, cx: &mut Context<'_>) -> Poll<Vec<Event>> {
+        let mut events = Vec::new();
+
+        let mut i = 0;
+        while i < self.commands.len() {
+            if let Poll::Ready(event) = Pin::new(&mut self.commands[i]).poll(cx) {
+                events.push(event);
+                self.commands.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        if events.is_empty() {
+            Poll::Pending
+        } else {
+            Poll::Ready(events)
+        }
+    }
+}
+
+impl<'a, Event> Default for Commands<'a, Event> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, Event> Debug for Commands<'a, Event> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Commands")
+            .field("len", &self.commands.len())
+            .finish()
+    }
+}
+
+impl<'a, Event> Extend<Command<'a, Event>> for Commands<'a, Event> {
+    fn extend<T: IntoIterator<Item = Command<'a, Event>>>(&mut self, iter: T) {
+        self.commands.extend(iter);
+    }
+}
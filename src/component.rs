@@ -0,0 +1,169 @@
+//! Stateful components.
+//!
+//! Elements are immutable, so normally all the state of a UI lives in your application. This can
+//! get tedious for state that the rest of your application doesn't care about, like the scroll
+//! position of a list or the cursor of a text box. A [`Component`] instead owns some internal
+//! state, which is kept in a [`States`] store by the terminal and persists across frames.
+//!
+//! Components are drawn with [`Terminal::draw_component`](crate::Terminal::draw_component).
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use crate::Element;
+
+/// A part of the UI that owns internal state.
+///
+/// The component's element produces [`ComponentEvent`]s; messages are used to update the internal
+/// state of the component with [`update`](Self::update), while events are passed on to your
+/// application.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Component, ComponentEvent, Element, ElementExt};
+///
+/// /// A counter that only tells the application when it reaches 10.
+/// struct Counter;
+///
+/// impl Component for Counter {
+///     type State = u32;
+///     type Message = ();
+///     type Event = ();
+///
+///     fn view<'a>(
+///         &'a self,
+///         state: &'a u32,
+///     ) -> Box<dyn Element<Event = ComponentEvent<(), ()>> + 'a> {
+///         toon::span(format!("Count: {}", state))
+///             .on(' ', |_| ComponentEvent::Message(()))
+///             .boxed()
+///     }
+///
+///     fn update(&self, state: &mut u32, (): ()) -> Option<()> {
+///         *state += 1;
+///         if *state == 10 { Some(()) } else { None }
+///     }
+/// }
+/// ```
+pub trait Component {
+    /// The internal state of the component. The initial state is the [`Default`].
+    type State: Default + Clone + Send + 'static;
+
+    /// Messages that update the internal state of the component.
+    type Message;
+
+    /// Events that are passed on to the application.
+    type Event;
+
+    /// A key to distinguish several components of the same type, whose states are stored
+    /// separately.
+    ///
+    /// Components are identified by their type and this key. By default this is 0.
+    fn key(&self) -> u64 {
+        0
+    }
+
+    /// Create the element of this component from its state.
+    fn view<'a>(
+        &'a self,
+        state: &'a Self::State,
+    ) -> Box<dyn Element<Event = ComponentEvent<Self::Message, Self::Event>> + 'a>;
+
+    /// Update the state of the component in response to a message, optionally producing an event.
+    fn update(&self, state: &mut Self::State, message: Self::Message) -> Option<Self::Event>;
+}
+
+/// An event produced by the element of a [`Component`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentEvent<M, E> {
+    /// A message that updates the component's internal state.
+    Message(M),
+    /// An event that is passed on to the application.
+    Event(E),
+}
+
+/// A store of the states of [`Component`]s, keyed by the component's type and
+/// [`key`](Component::key).
+#[derive(Debug, Default)]
+pub struct States {
+    states: HashMap<(TypeId, u64), Box<dyn Any + Send>>,
+}
+
+impl States {
+    /// Create a new empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the state of a component, inserting the default state if it doesn't exist.
+    pub fn get<C: Component + 'static>(&mut self, component: &C) -> &mut C::State {
+        self.states
+            .entry((TypeId::of::<C>(), component.key()))
+            .or_insert_with(|| Box::new(C::State::default()))
+            .downcast_mut()
+            .unwrap()
+    }
+
+    /// Remove the state of a component, returning it if it existed. The next time the component
+    /// is drawn it will have its default state.
+    pub fn remove<C: Component + 'static>(&mut self, component: &C) -> Option<C::State> {
+        self.states
+            .remove(&(TypeId::of::<C>(), component.key()))
+            .map(|state| *state.downcast().unwrap())
+    }
+
+    /// Remove the states of all components.
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+
+    /// Apply a component event to the store, updating the component's state if it is a message.
+    pub fn apply<C: Component + 'static>(
+        &mut self,
+        component: &C,
+        event: ComponentEvent<C::Message, C::Event>,
+    ) -> Option<C::Event> {
+        match event {
+            ComponentEvent::Message(message) => component.update(self.get(component), message),
+            ComponentEvent::Event(event) => Some(event),
+        }
+    }
+}
+
+#[test]
+fn test_states() {
+    struct Counter(u64);
+
+    impl Component for Counter {
+        type State = u32;
+        type Message = ();
+        type Event = u32;
+
+        fn key(&self) -> u64 {
+            self.0
+        }
+        fn view<'a>(
+            &'a self,
+            state: &'a u32,
+        ) -> Box<dyn Element<Event = ComponentEvent<(), u32>> + 'a> {
+            Box::new(crate::span(state))
+        }
+        fn update(&self, state: &mut u32, (): ()) -> Option<u32> {
+            *state += 1;
+            Some(*state)
+        }
+    }
+
+    let mut states = States::new();
+
+    assert_eq!(states.apply(&Counter(0), ComponentEvent::Message(())), Some(1));
+    assert_eq!(states.apply(&Counter(0), ComponentEvent::Message(())), Some(2));
+    assert_eq!(states.apply(&Counter(1), ComponentEvent::Message(())), Some(1));
+    assert_eq!(states.apply(&Counter(1), ComponentEvent::Event(7)), Some(7));
+
+    assert_eq!(*states.get(&Counter(0)), 2);
+    assert_eq!(states.remove(&Counter(0)), Some(2));
+    assert_eq!(*states.get(&Counter(0)), 0);
+}
@@ -0,0 +1,386 @@
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    output::{Ext as _, Output},
+    Color, Element, Events, Input, Intensity, Link, Rgb, RichText, Style, UnderlineKind, Vec2,
+};
+
+/// Text containing ANSI escape sequences, created by the [`ansi_text`] function.
+///
+/// Each line of the text is displayed on its own row, in the colors and attributes set by the SGR
+/// (Select Graphic Rendition) sequences in it and with the hyperlinks set by the OSC 8 sequences in
+/// it. See [`parse_ansi`] for what is supported.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct AnsiText<T, Event> {
+    /// The text being displayed.
+    pub text: T,
+    event: PhantomData<Event>,
+}
+
+impl<T: AsRef<str>, Event> Element for AnsiText<T, Event> {
+    type Event = Event;
+
+    fn draw(&self, output: &mut dyn Output) {
+        let size = output.size();
+
+        for (y, line) in parse(self.text.as_ref()).iter().enumerate() {
+            if y >= usize::from(size.y) {
+                break;
+            }
+            let y = y as u16;
+            let mut x: u16 = 0;
+            for (text, style, link) in line {
+                if x >= size.x {
+                    break;
+                }
+                output.write((x, y), text, *style);
+                let end = x.saturating_add(crate::util::str_width(text) as u16);
+                if let Some(link) = link {
+                    for x in x..end.min(size.x) {
+                        output.set_link(Vec2::new(x, y), link);
+                    }
+                }
+                x = end;
+            }
+        }
+    }
+    fn ideal_width(&self, _height: u16, _max_width: Option<u16>) -> u16 {
+        parse_ansi(self.text.as_ref())
+            .iter()
+            .map(RichText::width)
+            .max()
+            .unwrap_or(0)
+    }
+    fn ideal_height(&self, _width: u16, _max_height: Option<u16>) -> u16 {
+        parse_ansi(self.text.as_ref()).len() as u16
+    }
+    fn ideal_size(&self, _maximum: Vec2<Option<u16>>) -> Vec2<u16> {
+        let lines = parse_ansi(self.text.as_ref());
+        Vec2::new(
+            lines.iter().map(RichText::width).max().unwrap_or(0),
+            lines.len() as u16,
+        )
+    }
+    fn handle(&self, _input: Input, _events: &mut dyn Events<Event>) {}
+}
+
+/// Create an element that displays text containing ANSI escape sequences, such as the output of
+/// `git diff --color` or captured standard output.
+///
+/// If the text is displayed many times, it is faster to [`parse_ansi`] it once and display each
+/// line with [`rich_span`](crate::rich_span).
+///
+/// # Examples
+///
+/// ```
+/// let element: toon::AnsiText<_, ()> = toon::ansi_text("\x1B[1;31merror\x1B[0m: not found");
+/// ```
+#[must_use]
+pub fn ansi_text<T: AsRef<str>, Event>(text: T) -> AnsiText<T, Event> {
+    AnsiText {
+        text,
+        event: PhantomData,
+    }
+}
+
+/// Parse text containing ANSI escape sequences into lines of [`RichText`].
+///
+/// SGR sequences set the style of the text after them, which carries over between lines. All the
+/// attributes and colors that [`Style`] supports are understood, including 256-color and RGB
+/// colors. Other escape sequences and control characters are removed and tabs are expanded to
+/// spaces. This includes OSC 8 hyperlinks, which [`RichText`] can't store, but they are displayed
+/// by [`ansi_text`].
+///
+/// A newline at the end of the text doesn't start a new line.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Style, Styled};
+///
+/// let lines = toon::parse_ansi("plain \x1B[32mgreen\n\x1B[1mbold green\x1B[m\n");
+/// assert_eq!(lines.len(), 2);
+/// assert_eq!(lines[0].segments[1], ("green".to_owned(), Style::default().dark_green()));
+/// assert_eq!(lines[1].segments[0].1, Style::default().dark_green().bold());
+/// ```
+#[must_use]
+pub fn parse_ansi(text: &str) -> Vec<RichText> {
+    parse(text)
+        .into_iter()
+        .map(|line| {
+            let mut rich_text = RichText::new();
+            for (text, style, _) in line {
+                rich_text.push(text, style);
+            }
+            rich_text
+        })
+        .collect()
+}
+
+/// A line of text parsed by [`parse`], made of segments of text with their style and the link
+/// they point to.
+type Line = Vec<(String, Style, Option<Link>)>;
+
+/// Parse text containing ANSI escape sequences into lines, like [`parse_ansi`] but keeping links.
+fn parse(text: &str) -> Vec<Line> {
+    /// Add text to the end of a line, joining it to the last segment if it looks the same.
+    fn push(segments: &mut Line, text: &str, style: Style, hyperlink: Option<&Link>) {
+        match segments.last_mut() {
+            Some((last, last_style, last_link))
+                if *last_style == style && last_link.as_ref() == hyperlink =>
+            {
+                last.push_str(text);
+            }
+            _ => segments.push((text.to_owned(), style, hyperlink.cloned())),
+        }
+    }
+
+    let mut lines = vec![Line::new()];
+    let mut style = Style::default();
+    let mut hyperlink = None;
+    let mut column = 0;
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        let line = lines.last_mut().unwrap();
+
+        match c {
+            '\n' => {
+                lines.push(Line::new());
+                column = 0;
+            }
+            '\t' => {
+                let spaces = 8 - column % 8;
+                push(line, &"        "[..spaces], style, hyperlink.as_ref());
+                column += spaces;
+            }
+            '\x1B' => rest = parse_escape(rest, &mut style, &mut hyperlink),
+            c if c.is_control() => {}
+            c => {
+                push(line, c.encode_utf8(&mut [0; 4]), style, hyperlink.as_ref());
+                column += c.width().unwrap_or(0);
+            }
+        }
+    }
+
+    if text.ends_with('\n') {
+        lines.pop();
+    }
+    lines
+}
+
+/// Parse an escape sequence after the escape character, applying it to the style if it is an SGR
+/// sequence or to the link if it is a hyperlink sequence. Returns the text after the sequence.
+fn parse_escape<'a>(rest: &'a str, style: &mut Style, link: &mut Option<Link>) -> &'a str {
+    if let Some(csi) = rest.strip_prefix('[') {
+        // Control sequences end with a byte in the range `@` to `~`.
+        match csi.find(|c| ('@'..='~').contains(&c)) {
+            Some(end) => {
+                if csi[end..].starts_with('m') {
+                    apply_sgr(style, &csi[..end]);
+                }
+                &csi[end + 1..]
+            }
+            None => "",
+        }
+    } else if let Some(osc) = rest.strip_prefix(']') {
+        // Operating system commands end with either BEL or ST (escape followed by `\`).
+        let (command, after) = match osc.find(|c| c == '\x07' || c == '\x1B') {
+            Some(end) => {
+                let after = &osc[end..];
+                let after = after.strip_prefix("\x1B\\").unwrap_or_else(|| &after[1..]);
+                (&osc[..end], after)
+            }
+            None => (osc, ""),
+        };
+        if let Some(params) = command.strip_prefix("8;") {
+            let url = params.splitn(2, ';').nth(1).unwrap_or("");
+            *link = if url.is_empty() {
+                None
+            } else {
+                Some(Link::new(url))
+            };
+        }
+        after
+    } else {
+        // Other escape sequences are any number of intermediate bytes followed by a final byte.
+        let mut chars = rest
+            .trim_start_matches(|c| (' '..='/').contains(&c))
+            .chars();
+        chars.next();
+        chars.as_str()
+    }
+}
+
+/// Apply the parameters of an SGR sequence, like `1;38;5;208`, to a style.
+fn apply_sgr(style: &mut Style, params: &str) {
+    let number = |s: &str| s.parse::<u16>().unwrap_or(0);
+    let params: Vec<&str> = params.split(';').collect();
+
+    let mut i = 0;
+    while i < params.len() {
+        let subparams: Vec<u16> = params[i].split(':').map(number).collect();
+        i += 1;
+
+        let mut next_color = || {
+            if subparams.len() > 1 {
+                // Colon-separated colors may contain a color space ID: `38:2::r:g:b`.
+                let args = match &subparams[1..] {
+                    [2, _, r, g, b] => vec![2, *r, *g, *b],
+                    args => args.to_vec(),
+                };
+                extended_color(&args).0
+            } else {
+                let args: Vec<u16> = params[i..].iter().take(4).map(|&p| number(p)).collect();
+                let (color, used) = extended_color(&args);
+                i += used;
+                color
+            }
+        };
+
+        let attributes = &mut style.attributes;
+        match subparams[0] {
+            0 => *style = Style::default(),
+            1 => attributes.intensity = Intensity::Bold,
+            2 => attributes.intensity = Intensity::Dim,
+            3 => attributes.italic = true,
+            4 => {
+                attributes.underlined = subparams.get(1) != Some(&0);
+                attributes.underline_kind = match subparams.get(1) {
+                    Some(2) => UnderlineKind::Double,
+                    Some(3) => UnderlineKind::Curly,
+                    Some(4) => UnderlineKind::Dotted,
+                    Some(5) => UnderlineKind::Dashed,
+                    _ => UnderlineKind::Single,
+                };
+            }
+            5 | 6 => attributes.blinking = true,
+            7 => attributes.reversed = true,
+            8 => attributes.hidden = true,
+            9 => attributes.crossed_out = true,
+            21 => {
+                attributes.underlined = true;
+                attributes.underline_kind = UnderlineKind::Double;
+            }
+            22 => attributes.intensity = Intensity::Normal,
+            23 => attributes.italic = false,
+            24 => attributes.underlined = false,
+            25 => attributes.blinking = false,
+            27 => attributes.reversed = false,
+            28 => attributes.hidden = false,
+            29 => attributes.crossed_out = false,
+            code @ 30..=37 => style.foreground = Color::new_ansi((code - 30) as u8),
+            38 => style.foreground = next_color().unwrap_or(style.foreground),
+            39 => style.foreground = Color::Default,
+            code @ 40..=47 => style.background = Color::new_ansi((code - 40) as u8),
+            48 => style.background = next_color().unwrap_or(style.background),
+            49 => style.background = Color::Default,
+            53 => attributes.overlined = true,
+            55 => attributes.overlined = false,
+            58 => style.underline_color = next_color().unwrap_or(style.underline_color),
+            59 => style.underline_color = Color::Default,
+            code @ 90..=97 => style.foreground = Color::new_ansi((code - 90 + 8) as u8),
+            code @ 100..=107 => style.background = Color::new_ansi((code - 100 + 8) as u8),
+            _ => {}
+        }
+    }
+}
+
+/// Parse the arguments of an extended color, like `5;208` or `2;255;128;0`. Returns the color
+/// and the number of arguments used.
+fn extended_color(args: &[u16]) -> (Option<Color>, usize) {
+    let component = |c: u16| u8::try_from(c).unwrap_or(u8::MAX);
+    match *args {
+        [5, n, ..] => (u8::try_from(n).ok().map(Color::new_ansi), 2),
+        [2, r, g, b, ..] => (
+            Some(Color::Rgb(Rgb::new(
+                component(r),
+                component(g),
+                component(b),
+            ))),
+            4,
+        ),
+        [] => (None, 0),
+        _ => (None, 1),
+    }
+}
+
+#[test]
+fn test_parse_ansi() {
+    use crate::{AnsiColor, Styled};
+
+    let plain = Style::default();
+    let segments = |text| {
+        parse_ansi(text)
+            .into_iter()
+            .map(|line| line.segments)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(segments(""), [Vec::new()]);
+    assert_eq!(
+        segments("a\n\nb\n"),
+        [
+            vec![("a".to_owned(), plain)],
+            Vec::new(),
+            vec![("b".to_owned(), plain)]
+        ]
+    );
+    assert_eq!(
+        segments("a\tb\r\x07"),
+        [vec![("a       b".to_owned(), plain)]]
+    );
+
+    assert_eq!(
+        segments("\x1B[1;31mx\x1B[22;4:3my\x1B[0m\x1B[Kz"),
+        [vec![
+            ("x".to_owned(), plain.dark_red().bold()),
+            (
+                "y".to_owned(),
+                plain.dark_red().underline_kind(UnderlineKind::Curly)
+            ),
+            ("z".to_owned(), plain),
+        ]]
+    );
+    assert_eq!(
+        segments("\x1B[38;5;208;48;2;1;2;3ma\x1B[39;49;38:2::4:5:6;58:5:9;97mb"),
+        [vec![
+            (
+                "a".to_owned(),
+                plain
+                    .foreground(AnsiColor::new(208))
+                    .background(Rgb::new(1, 2, 3))
+            ),
+            ("b".to_owned(), plain.white().underline_color(Color::Red)),
+        ]]
+    );
+    assert_eq!(
+        segments("\x1B]8;;https://example.com\x1B\\link\x1B]8;;\x07 \x1B(Bdone"),
+        [vec![("link done".to_owned(), plain)]]
+    );
+    assert_eq!(
+        parse("\x1B]8;;https://example.com\x1B\\link\x1B]8;;\x07 \x1B(Bdone"),
+        [vec![
+            (
+                "link".to_owned(),
+                plain,
+                Some(Link::new("https://example.com"))
+            ),
+            (" done".to_owned(), plain, None),
+        ]]
+    );
+
+    let mut grid = crate::Grid::new((4, 3));
+    let text = ansi_text::<_, ()>("\x1B[32mab\ncdefg\x1B[m\n");
+    assert_eq!(text.ideal_size(Vec2::new(None, None)), Vec2::new(5, 2));
+    text.draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["ab  ", "cdef", "    "]);
+    assert_eq!(
+        grid.lines()[1].cells()[0].style().unwrap(),
+        plain.dark_green()
+    );
+}
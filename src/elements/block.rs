@@ -1,6 +1,7 @@
 use std::marker::PhantomData;
 
-use crate::{Attributes, Color, Element, Events, Input, Output, Style, Vec2};
+use crate::output::{Ext as _, Output};
+use crate::{Attributes, Color, Element, Events, Input, Style, Vec2};
 
 /// A block of a single color.
 #[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
@@ -27,14 +28,8 @@ impl<Event> Element for Block<Event> {
 
     fn draw(&self, output: &mut dyn Output) {
         if let Some(color) = self.color {
-            let size = output.size();
             let style = Style::new(Color::default(), color, Attributes::default());
-
-            for x in 0..size.x {
-                for y in 0..size.y {
-                    output.write_char(Vec2 { x, y }, ' ', style);
-                }
-            }
+            output.fill_rect((0, 0), output.size(), ' ', style);
         }
     }
     fn ideal_width(&self, _height: u16, _max_width: Option<u16>) -> u16 {
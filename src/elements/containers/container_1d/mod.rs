@@ -2,6 +2,7 @@ use std::cmp;
 use std::fmt::{self, Debug, Formatter};
 
 use crate::output::{Ext as _, Output};
+use crate::tree::Tree;
 use crate::{Element, Events, Input, Vec2};
 
 use super::Collection;
@@ -30,6 +31,13 @@ pub trait Layout1D<'a, C: Collection<'a>> {
         cross_axis_size: u16,
         axis: Axis,
     ) -> Self::Layout;
+
+    /// Describe the layout and its parameters, for [`debug_tree`](crate::debug_tree).
+    ///
+    /// By default this returns the name of the layout's type.
+    fn describe(&self) -> String {
+        crate::tree::type_name::<Self>()
+    }
 }
 
 /// An element arranged by a [`Layout1D`].
@@ -183,12 +191,19 @@ where
     }
     fn handle(&self, input: Input, events: &mut dyn Events<Event>) {
         match input {
-            Input::Key(_) if self.broadcast_keys => {
+            Input::Shortcut(_) | Input::Signal(_) => {
                 for element in self.elements.iter() {
-                    element.handle(input, events);
+                    element.handle(input.clone(), events);
                 }
             }
-            Input::Key(_) => {
+            Input::Key(_) | Input::Paste(_) | Input::Compose(_) | Input::Action(_)
+                if self.broadcast_keys =>
+            {
+                for element in self.elements.iter() {
+                    element.handle(input.clone(), events);
+                }
+            }
+            Input::Key(_) | Input::Paste(_) | Input::Compose(_) | Input::Action(_) => {
                 if let Some(element) = self.focused.and_then(|i| self.elements.iter().nth(i)) {
                     element.handle(input, events);
                 }
@@ -216,6 +231,30 @@ where
             }
         }
     }
+    fn describe(&self, tree: &mut Tree) {
+        let label = format!(
+            "{}({}{}{})",
+            match self.axis {
+                Axis::X => "Row",
+                Axis::Y => "Column",
+            },
+            <L as Layout1D<'_, E>>::describe(&self.layout),
+            match self.focused {
+                Some(focused) => format!(", focused: {}", focused),
+                None => String::new(),
+            },
+            if self.broadcast_keys {
+                ", broadcast_keys"
+            } else {
+                ""
+            },
+        );
+        tree.node(label, |tree| {
+            for element in self.elements.iter() {
+                element.describe(tree);
+            }
+        });
+    }
 }
 
 /// Create a row of elements with the specified layout.
@@ -55,6 +55,12 @@ impl<'a, C: Collection<'a>> Layout1D<'a, C> for Share {
             bias: self.bias,
         }
     }
+    fn describe(&self) -> String {
+        match self.bias {
+            Some(bias) => format!("Share(bias: {:?})", bias),
+            None => "Share".to_owned(),
+        }
+    }
 }
 
 /// Calculate the layout of a [`Share`].
@@ -182,8 +188,8 @@ fn test_too_large() {
     )
     .draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         ["Foo  ", "Bar  ", "Baz  ", "Foo2 ", "Bar2 ", "Baz2 ",]
     );
 }
@@ -206,24 +212,24 @@ fn test_biases() {
     );
 
     three_lines.draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         ["Top L", "Middl", "Botto", "     ", "     ", "     ",]
     );
 
     three_lines.layout.bias = Some(End::Start);
     grid.clear();
     three_lines.draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         ["Top L", "Top L", "     ", "Middl", "Middl", "Botto",]
     );
 
     three_lines.layout.bias = Some(End::End);
     grid.clear();
     three_lines.draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         ["Top L", "Middl", "Middl", "     ", "Botto", "Botto",]
     );
 
@@ -245,8 +251,8 @@ fn test_biases() {
     )
     .draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         ["12345", "Middl", "Middl", "     ", "Botto", "Botto",]
     );
 }
@@ -81,5 +81,5 @@ fn test_static() {
     )
     .draw(&mut grid);
 
-    assert_eq!(grid.contents(), ["Some te", "Foo    ", "Bar    ",]);
+    crate::assert_grid_eq!(grid, ["Some te", "Foo    ", "Bar    ",]);
 }
@@ -41,6 +41,9 @@ impl<'a, C: Collection<'a>> Layout1D<'a, C> for Stretch {
             cross_axis_size,
         }
     }
+    fn describe(&self) -> String {
+        format!("Stretch({})", self.stretched)
+    }
 }
 
 /// The layout of a Stretch.
@@ -154,10 +157,7 @@ fn test_out_of_range() {
     )
     .draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
-        ["1     ", "2     ", "3     ", "      ", "      ",]
-    );
+    crate::assert_grid_eq!(grid, ["1     ", "2     ", "3     ", "      ", "      ",]);
 }
 
 #[test]
@@ -176,7 +176,7 @@ fn test_grow() {
     )
     .draw(&mut grid);
 
-    assert_eq!(grid.contents(), ["111", "222", "222", "222", "333",]);
+    crate::assert_grid_eq!(grid, ["111", "222", "222", "222", "333",]);
 }
 
 #[test]
@@ -195,5 +195,5 @@ fn test_shrink() {
     )
     .draw(&mut grid);
 
-    assert_eq!(grid.contents(), ["111", "333"]);
+    crate::assert_grid_eq!(grid, ["111", "333"]);
 }
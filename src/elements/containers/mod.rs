@@ -20,6 +20,9 @@ use crate::Element;
 mod container_1d;
 pub use container_1d::*;
 
+mod screens;
+pub use screens::*;
+
 mod stack;
 pub use stack::*;
 
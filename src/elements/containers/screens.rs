@@ -0,0 +1,166 @@
+use std::fmt;
+
+use crate::tree::Tree;
+use crate::{Element, Events, Input, Output, Vec2};
+
+/// A stack of full-screen views, of which only the top one is drawn and receives inputs.
+///
+/// This is useful for applications with several views, such as a main menu that opens a settings
+/// screen. Since elements are short-lived, you will usually keep the stack of screens in your
+/// state as some screen type and map it to elements with [`map`](Self::map) each time you draw.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{ElementExt, Screens};
+///
+/// #[derive(Clone, Copy)]
+/// enum Screen {
+///     Menu,
+///     Settings,
+/// }
+///
+/// let mut screens = Screens::new(Screen::Menu);
+/// screens.push(Screen::Settings);
+///
+/// let element = screens.map(|&screen| {
+///     toon::span(match screen {
+///         Screen::Menu => "Menu",
+///         Screen::Settings => "Settings",
+///     })
+///     .on('q', |_| ())
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Screens<T> {
+    /// The screens, from bottom to top.
+    ///
+    /// invariant: never empty
+    screens: Vec<T>,
+}
+
+impl<T> Screens<T> {
+    /// Create a new screen stack with a single root screen.
+    #[must_use]
+    pub fn new(root: T) -> Self {
+        Self {
+            screens: vec![root],
+        }
+    }
+
+    /// Push a screen to the top of the stack.
+    pub fn push(&mut self, screen: T) {
+        self.screens.push(screen);
+    }
+
+    /// Pop the top screen off the stack.
+    ///
+    /// The root screen cannot be popped, so this returns [`None`] if there is only one screen.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.screens.len() > 1 {
+            self.screens.pop()
+        } else {
+            None
+        }
+    }
+
+    /// Replace the top screen with a new one, returning the old screen.
+    pub fn replace(&mut self, screen: T) -> T {
+        std::mem::replace(self.top_mut(), screen)
+    }
+
+    /// Pop every screen except the root screen.
+    pub fn pop_to_root(&mut self) {
+        self.screens.truncate(1);
+    }
+
+    /// Get the top screen.
+    #[must_use]
+    pub fn top(&self) -> &T {
+        self.screens.last().unwrap()
+    }
+
+    /// Get a mutable reference to the top screen.
+    #[must_use]
+    pub fn top_mut(&mut self) -> &mut T {
+        self.screens.last_mut().unwrap()
+    }
+
+    /// Get the root screen at the bottom of the stack.
+    #[must_use]
+    pub fn root(&self) -> &T {
+        &self.screens[0]
+    }
+
+    /// Get the screens in the stack, from bottom to top.
+    #[must_use]
+    pub fn screens(&self) -> &[T] {
+        &self.screens
+    }
+
+    /// Get the number of screens in the stack. This is always at least 1.
+    #[must_use]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.screens.len()
+    }
+
+    /// Create an element from the top screen of the stack.
+    #[must_use]
+    pub fn map<E: Element, F: FnOnce(&T) -> E>(&self, f: F) -> E {
+        f(self.top())
+    }
+}
+
+impl<T: Element> Element for Screens<T> {
+    type Event = T::Event;
+
+    fn draw(&self, output: &mut dyn Output) {
+        self.top().draw(output);
+    }
+    fn ideal_width(&self, height: u16, max_width: Option<u16>) -> u16 {
+        self.top().ideal_width(height, max_width)
+    }
+    fn ideal_height(&self, width: u16, max_height: Option<u16>) -> u16 {
+        self.top().ideal_height(width, max_height)
+    }
+    fn ideal_size(&self, maximum: Vec2<Option<u16>>) -> Vec2<u16> {
+        self.top().ideal_size(maximum)
+    }
+    fn handle(&self, input: Input, events: &mut dyn Events<Self::Event>) {
+        self.top().handle(input, events);
+    }
+    fn title(&self, title: &mut dyn fmt::Write) -> fmt::Result {
+        self.top().title(title)
+    }
+    fn describe(&self, tree: &mut Tree) {
+        tree.node(format!("Screens(len: {})", self.len()), |tree| {
+            self.top().describe(tree);
+        });
+    }
+}
+
+#[test]
+fn test_screens() {
+    use crate::ElementExt;
+
+    let mut screens = Screens::new(crate::span::<_, ()>("root").boxed());
+    assert_eq!(screens.pop().map(|_| ()), None);
+
+    screens.push(crate::span("top").boxed());
+    assert_eq!(screens.len(), 2);
+
+    let mut grid = crate::Grid::new((5, 1));
+    screens.draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["top  "]);
+
+    screens.replace(crate::span("new").boxed());
+    grid.clear();
+    screens.draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["new  "]);
+
+    screens.pop_to_root();
+    grid.clear();
+    screens.draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["root "]);
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 
+use crate::tree::Tree;
 use crate::{Element, Events, Input, Output, Vec2};
 
 use super::Collection;
@@ -60,9 +61,9 @@ where
             .fold(Vec2::default(), Vec2::max)
     }
     fn handle(&self, input: Input, events: &mut dyn Events<Event>) {
-        if self.broadcast_inputs {
+        if self.broadcast_inputs || matches!(input, Input::Shortcut(_) | Input::Signal(_)) {
             for element in self.elements.iter() {
-                element.handle(input, events);
+                element.handle(input.clone(), events);
             }
         } else if let Some(last) = self.elements.iter().next_back() {
             last.handle(input, events);
@@ -74,6 +75,18 @@ where
         }
         Ok(())
     }
+    fn describe(&self, tree: &mut Tree) {
+        let label = if self.broadcast_inputs {
+            "Stack(broadcast_inputs)"
+        } else {
+            "Stack"
+        };
+        tree.node(label, |tree| {
+            for element in self.elements.iter() {
+                element.describe(tree);
+            }
+        });
+    }
 }
 
 /// Create a [`Stack`] of elements.
@@ -120,8 +133,8 @@ fn test_stack() {
     ))
     .draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         [
             "xxxxxxxxxxxx",
             "xxxxxxxxxxxx",
@@ -2,19 +2,52 @@
 //!
 //! See the [example](https://github.com/KaiJewson/toon/blob/master/examples/dev.rs) for how to use
 //! this.
+//!
+//! Pressing <kbd>Alt</kbd>+<kbd>i</kbd> or clicking the inspect button toggles the inspector,
+//! which labels the elements marked with the [`Inspect`] filter with their names, sizes and
+//! positions. While inspecting, clicking an element lists the elements under the mouse in the dev
+//! panel along with the filters applied to them. Pressing <kbd>Alt</kbd>+<kbd>b</kbd> or clicking
+//! the bounds button toggles faint outlines around the same elements, annotated with their sizes,
+//! to help diagnose why an element is the size it is.
+//!
+//! Pressing <kbd>Alt</kbd>+<kbd>e</kbd> or clicking the inputs button toggles a log of the most
+//! recent inputs in the dev panel, along with what handled them: the dev tools, the element or
+//! nothing at all. Mouse inputs handled by the element also show the innermost inspected element
+//! under the mouse.
+//!
+//! Pressing <kbd>Alt</kbd>+<kbd>o</kbd> or clicking the old terminal button simulates an old
+//! terminal: the element is limited to 80x24 cells, its colors are converted to the closest of the
+//! 16 named colors and it doesn't receive any mouse inputs. This shows how the program degrades in
+//! terminals with fewer capabilities; [`Dummy::legacy`](crate::Dummy::legacy) simulates the same
+//! terminal in tests.
+//!
+//! [`Fps`] separately overlays statistics about how fast frames are being drawn.
+//!
+//! [`watch_theme`] reloads a [`Theme`] from a file whenever it changes, so that colors can be
+//! tweaked while the program is running.
 
 #[cfg(not(feature = "either"))]
 compile_error!("Dev mode currently requires `either` feature to be active.");
 
+use std::any::type_name;
+use std::cell::{Cell, RefCell};
 use std::cmp::max;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs;
 use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use either_crate::Either;
 use futures_lite::stream::{Stream, StreamExt as _};
 
+use crate::output::{Ext as _, Output};
+use crate::util::short_type_name;
 use crate::{
-    input, Alignment, Border, Captured, Color, Element, ElementExt, Mouse, MouseButton, MouseKind,
-    Styled,
+    input, Alignment, Bitmap, Border, Captured, Color, ColorSupport, Cursor, Element, ElementExt,
+    Events, Filter, FrameStats, Input, Link, Mouse, MouseButton, MouseKind, Style, Styled, Theme,
+    Vec2,
 };
 
 /// The state of the developer tools.
@@ -36,8 +69,31 @@ pub struct Dev {
     /// Whether the abort confirmation dialogue box is being shown.
     abort_confirm: bool,
 
+    /// Whether the inspector is being shown.
+    inspecting: bool,
+    /// The position in the element that was last clicked while inspecting.
+    selected: Option<Vec2<u16>>,
+    /// Whether the bounds of the inspected elements are being outlined.
+    bounds: bool,
+    /// The elements recorded by the inspector when the element was last drawn.
+    inspected: RefCell<Vec<Inspected>>,
+    /// The name of the innermost inspected element under the mouse during the last mouse input
+    /// the element received.
+    input_target: RefCell<Option<String>>,
+
+    /// Whether the log of inputs is being shown.
+    logging_inputs: bool,
+    /// The descriptions of the most recent inputs, from the oldest to the newest.
+    input_log: VecDeque<String>,
+
+    /// Whether an old terminal is being simulated.
+    old_terminal: bool,
+
     /// Data that has been read from the captured stdio.
     captured: String,
+
+    /// The theme most recently loaded by [`watch_theme`].
+    theme: Option<Theme>,
 }
 
 impl Dev {
@@ -51,7 +107,16 @@ impl Dev {
             bottom_panel_height: 16,
             bottom_panel_resizing: false,
             abort_confirm: false,
+            inspecting: false,
+            selected: None,
+            bounds: false,
+            inspected: RefCell::new(Vec::new()),
+            input_target: RefCell::new(None),
+            logging_inputs: false,
+            input_log: VecDeque::new(),
+            old_terminal: false,
             captured: String::new(),
+            theme: None,
         }
     }
 
@@ -127,6 +192,12 @@ impl Dev {
         })
         .on(input!(Alt + Shift + Key(l)), move |_| {
             EventKind::Resize(Some(self.right_panel_width - 2), None).into()
+        })
+        .on(input!(Alt + Key(i)), |_| EventKind::ToggleInspecting.into())
+        .on(input!(Alt + Key(b)), |_| EventKind::ToggleBounds.into())
+        .on(input!(Alt + Key(e)), |_| EventKind::ToggleInputLog.into())
+        .on(input!(Alt + Key(o)), |_| {
+            EventKind::ToggleOldTerminal.into()
         });
 
         let resizing = self.right_panel_resizing || self.bottom_panel_resizing;
@@ -152,7 +223,7 @@ impl Dev {
                 EventKind::StopResizing.into()
             });
 
-        if self.abort_confirm {
+        let element = if self.abort_confirm {
             Either::Left(
                 crate::stack((
                     element
@@ -166,7 +237,9 @@ impl Dev {
             )
         } else {
             Either::Right(element)
-        }
+        };
+
+        element.filter(InputLog { dev: self })
     }
 
     /// Create the right panel of the developer tools.
@@ -188,6 +261,67 @@ impl Dev {
                         EventKind::ToggleAbortConfirm
                     })
                     .float_x(Alignment::Start),
+                crate::span(if self.inspecting {
+                    "Stop inspecting"
+                } else {
+                    "Inspect"
+                })
+                .bold()
+                .filter(Border::THICK)
+                .on(input!(Mouse(Release Left)), |_| EventKind::ToggleInspecting)
+                .float_x(Alignment::Start),
+                crate::span(if self.bounds {
+                    "Hide bounds"
+                } else {
+                    "Show bounds"
+                })
+                .bold()
+                .filter(Border::THICK)
+                .on(input!(Mouse(Release Left)), |_| EventKind::ToggleBounds)
+                .float_x(Alignment::Start),
+                crate::span(if self.logging_inputs {
+                    "Hide inputs"
+                } else {
+                    "Show inputs"
+                })
+                .bold()
+                .filter(Border::THICK)
+                .on(input!(Mouse(Release Left)), |_| EventKind::ToggleInputLog)
+                .float_x(Alignment::Start),
+                crate::span(if self.old_terminal {
+                    "Stop simulating old terminal"
+                } else {
+                    "Simulate old terminal"
+                })
+                .bold()
+                .filter(Border::THICK)
+                .on(input!(Mouse(Release Left)), |_| {
+                    EventKind::ToggleOldTerminal
+                })
+                .float_x(Alignment::Start),
+                crate::column(
+                    crate::Static,
+                    self.inspector_lines()
+                        .into_iter()
+                        .map(crate::span)
+                        .collect::<Vec<_>>(),
+                ),
+                if self.logging_inputs {
+                    Either::Left(
+                        crate::column(
+                            crate::Static,
+                            self.input_log
+                                .iter()
+                                .rev()
+                                .map(crate::span)
+                                .collect::<Vec<_>>(),
+                        )
+                        .title("Inputs")
+                        .filter(Border::THIN.top_title(Alignment::Start)),
+                    )
+                } else {
+                    Either::Right(crate::empty())
+                },
             ),
         )
         .title("Dev panel")
@@ -231,10 +365,61 @@ impl Dev {
             })
     }
 
+    /// Get the lines of text describing the inspected elements in the right panel.
+    ///
+    /// If an element has been clicked this lists the elements under the mouse from the innermost
+    /// to the outermost along with their filters, otherwise it lists every inspected element.
+    fn inspector_lines(&self) -> Vec<String> {
+        if !self.inspecting {
+            return Vec::new();
+        }
+        let inspected = self.inspected.borrow();
+
+        let describe = |element: &Inspected| {
+            format!(
+                "{} {}x{} at ({}, {})",
+                element.name, element.size.x, element.size.y, element.pos.x, element.pos.y,
+            )
+        };
+
+        let mut lines = Vec::new();
+        if let Some(selected) = self.selected {
+            for element in inspected.iter().rev().filter(|e| e.contains(selected)) {
+                lines.push(describe(element));
+                lines.push(format!("  element: {}", element.element));
+                for filter in &element.filters {
+                    lines.push(format!("  filter: {}", filter));
+                }
+            }
+        } else {
+            lines.push("Click an element to see its filters".to_owned());
+            for element in &*inspected {
+                lines.push(format!(
+                    "{}{}",
+                    "  ".repeat(element.depth),
+                    describe(element)
+                ));
+            }
+        }
+        lines
+    }
+
     /// Create the element panel.
-    fn inner<E: Element>(&self, inner: E) -> impl Element<Event = AppEvent<E::Event>> {
+    fn inner<'a, E: Element + 'a>(
+        &'a self,
+        inner: E,
+    ) -> impl Element<Event = AppEvent<E::Event>> + 'a {
+        let inspecting = self.inspecting;
+
         inner
+            .filter(OldTerminal {
+                enabled: self.old_terminal,
+            })
+            .filter(Inspector { dev: self })
             .map_event(AppEvent::Element)
+            .on(input!(Mouse(Press Left) where (|_| inspecting)), |input| {
+                EventKind::Inspect(input.mouse().unwrap().at).into()
+            })
             .filter(
                 Border::THIN_CURVED
                     .foreground(if self.focus == Focus::Element && !self.abort_confirm {
@@ -316,11 +501,48 @@ impl Dev {
                 self.right_panel_resizing = false;
                 self.bottom_panel_resizing = false;
             }
+            EventKind::ToggleInspecting => {
+                self.inspecting = !self.inspecting;
+                self.selected = None;
+            }
+            EventKind::Inspect(pos) => {
+                self.selected = Some(pos);
+            }
+            EventKind::ToggleBounds => {
+                self.bounds = !self.bounds;
+            }
+            EventKind::ToggleInputLog => {
+                self.logging_inputs = !self.logging_inputs;
+                self.input_log.clear();
+            }
+            EventKind::LogInput(description) => {
+                if self.input_log.len() == INPUT_LOG_LEN {
+                    self.input_log.pop_front();
+                }
+                self.input_log.push_back(description);
+            }
+            EventKind::ToggleOldTerminal => {
+                self.old_terminal = !self.old_terminal;
+            }
             EventKind::CapturedData(s) => {
                 self.captured.push_str(&String::from_utf8_lossy(&s));
             }
+            EventKind::ToggleFps => {}
+            EventKind::Theme(Ok(theme)) => {
+                self.theme = Some(theme);
+            }
+            EventKind::Theme(Err(error)) => {
+                self.captured.push_str(&error);
+                self.captured.push('\n');
+            }
         }
     }
+
+    /// Get the theme most recently loaded by [`watch_theme`], if it has loaded one.
+    #[must_use]
+    pub fn theme(&self) -> Option<&Theme> {
+        self.theme.as_ref()
+    }
 }
 
 impl Default for Dev {
@@ -385,7 +607,15 @@ enum EventKind {
     SetRightPanelResizing,
     SetBottomPanelResizing,
     StopResizing,
+    ToggleInspecting,
+    Inspect(Vec2<u16>),
+    ToggleBounds,
+    ToggleInputLog,
+    LogInput(String),
+    ToggleOldTerminal,
     CapturedData(Vec<u8>),
+    ToggleFps,
+    Theme(Result<Theme, String>),
 }
 
 impl<T> From<EventKind> for AppEvent<T> {
@@ -394,6 +624,512 @@ impl<T> From<EventKind> for AppEvent<T> {
     }
 }
 
+/// The state of an overlay showing how fast frames are being drawn.
+///
+/// The overlay is drawn in the top right corner of the element, and shows the frames per second,
+/// the time taken to draw the last frame, the number of cells it changed and the latency between
+/// receiving the input that caused it and finishing drawing it. These are taken from the
+/// [`FrameStats`] of the previous frame, as given by [`Terminal::stats`](crate::Terminal::stats).
+/// Pressing <kbd>Alt</kbd>+<kbd>Shift</kbd>+<kbd>f</kbd> toggles the overlay.
+///
+/// # Examples
+///
+/// ```
+/// # async {
+/// use toon::dev::{AppEvent, Fps};
+/// use toon::ElementExt;
+///
+/// let mut terminal = toon::Terminal::new(toon::Dummy::new(toon::Vec2::new(80, 24)))?;
+/// let mut fps = Fps::new();
+///
+/// let element = fps.wrap(toon::span("Hello World!").on('q', |_| ()), *terminal.stats());
+/// for event in terminal.draw(element).await? {
+///     match event {
+///         AppEvent::Dev(event) => fps.apply(event),
+///         AppEvent::Element(()) => {}
+///     }
+/// }
+///
+/// terminal.cleanup()
+/// # };
+/// ```
+#[derive(Debug)]
+pub struct Fps {
+    /// Whether the overlay is being shown.
+    shown: bool,
+}
+
+impl Fps {
+    /// Create a new overlay state, which is initially shown.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { shown: true }
+    }
+
+    /// Wrap the inner element in the overlay, showing the statistics of the previous frame.
+    #[must_use]
+    pub fn wrap<E: Element>(
+        &self,
+        inner: E,
+        stats: FrameStats,
+    ) -> impl Element<Event = AppEvent<E::Event>> {
+        inner
+            .map_event(AppEvent::Element)
+            .filter(FpsOverlay {
+                stats,
+                shown: self.shown,
+            })
+            .on(input!(Alt + Shift + Key(f)), |_| {
+                EventKind::ToggleFps.into()
+            })
+    }
+
+    /// Apply the event to the overlay state. Events from the other developer tools are ignored.
+    pub fn apply(&mut self, event: Event) {
+        if let EventKind::ToggleFps = event.0 {
+            self.shown = !self.shown;
+        }
+    }
+}
+
+impl Default for Fps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A filter that draws frame statistics on top of the element.
+struct FpsOverlay {
+    stats: FrameStats,
+    shown: bool,
+}
+
+impl FpsOverlay {
+    /// Get the lines of text shown in the overlay.
+    fn lines(&self) -> [String; 4] {
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let stats = &self.stats;
+
+        [
+            if stats.frame_interval.as_nanos() == 0 {
+                "- fps".to_owned()
+            } else {
+                format!("{:.1} fps", 1.0 / stats.frame_interval.as_secs_f64())
+            },
+            format!(
+                "frame {:.1}ms",
+                millis(stats.draw_time + stats.diff_time + stats.flush_time)
+            ),
+            format!("{} cells changed", stats.cells_changed),
+            match stats.input_latency {
+                Some(latency) => format!("latency {:.1}ms", millis(latency)),
+                None => "latency -".to_owned(),
+            },
+        ]
+    }
+}
+
+impl<Event> Filter<Event> for FpsOverlay {
+    fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
+        element.draw(output);
+        if !self.shown {
+            return;
+        }
+
+        let lines = self.lines();
+        let width = lines.iter().map(String::len).max().unwrap_or(0);
+        let x = output.size().x.saturating_sub(width as u16 + 2);
+        let style = Style::default().black().on_yellow();
+        for (y, line) in lines.iter().enumerate() {
+            output.write(
+                (x, y as u16),
+                format_args!(" {:<width$} ", line, width = width),
+                style,
+            );
+        }
+    }
+}
+
+/// The prefix of the ids of the regions registered by [`Inspect`] for the inspector to record.
+///
+/// An element is recorded as a region with the prefix followed by its name, a nul and its type
+/// name, registered before it is drawn, and a region of just the prefix registered after it is
+/// drawn.
+const INSPECT_REGION: &str = "toon::dev::inspect\0";
+
+/// A filter that shows an element in the inspector of the developer tools.
+///
+/// While inspecting, the element is labelled with its name, size and position, and clicking it
+/// lists the filters applied to it before this one. It is otherwise drawn as normal, so it is best
+/// applied after the element's other filters.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{dev, Border, ElementExt};
+///
+/// let sidebar = toon::span::<_, ()>("Sidebar")
+///     .filter(Border::THIN)
+///     .filter(dev::Inspect::new("sidebar"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Inspect<T> {
+    /// The name the element is shown with.
+    pub name: T,
+}
+
+impl<T> Inspect<T> {
+    /// Create a new filter that shows the element in the inspector with a name.
+    #[must_use]
+    pub fn new(name: T) -> Self {
+        Self { name }
+    }
+}
+
+impl<T: Display, Event> Filter<Event> for Inspect<T> {
+    fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
+        draw_inspected(&self.name, element, output);
+    }
+    fn describe(&self) -> String {
+        format!("Inspect({:?})", self.name.to_string())
+    }
+}
+
+/// Draw an element surrounded by the regions that let the inspector record it.
+fn draw_inspected<E: Element>(name: &dyn Display, element: E, output: &mut dyn Output) {
+    let size = output.size();
+    let id = format!("{}{}\0{}", INSPECT_REGION, name, type_name::<E>());
+    output.register_region(&id, Vec2::new(0, 0), size);
+    element.draw(output);
+    output.register_region(INSPECT_REGION, Vec2::new(0, 0), size);
+}
+
+/// An element recorded by the inspector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Inspected {
+    /// The name given to the element by [`Inspect`].
+    name: String,
+    /// The shortened type name of the element inside its filters.
+    element: String,
+    /// The shortened type names of the filters applied to the element, from the outermost to the
+    /// innermost.
+    filters: Vec<String>,
+    /// The position of the visible part of the element in the inspected output.
+    pos: Vec2<u16>,
+    /// The size of the visible part of the element.
+    size: Vec2<u16>,
+    /// The number of inspected elements the element is drawn inside.
+    depth: usize,
+}
+
+impl Inspected {
+    fn contains(&self, pos: Vec2<u16>) -> bool {
+        pos.x >= self.pos.x
+            && pos.y >= self.pos.y
+            && pos.x - self.pos.x < self.size.x
+            && pos.y - self.pos.y < self.size.y
+    }
+}
+
+/// Split the filters off a shortened type name of an element, returning the type of the element
+/// inside the filters and the filters from the outermost to the innermost.
+fn split_filters(mut name: &str) -> (&str, Vec<&str>) {
+    let mut filters = Vec::new();
+    loop {
+        name = name.trim_start_matches('&');
+        let args = match name
+            .strip_prefix("Filtered<")
+            .and_then(|args| args.strip_suffix('>'))
+        {
+            Some(args) => args,
+            None => break,
+        };
+
+        let mut depth = 0_i32;
+        let comma = args.char_indices().find(|&(_, c)| {
+            match c {
+                '<' | '(' | '[' => depth += 1,
+                '>' | ')' | ']' => depth -= 1,
+                ',' => return depth == 0,
+                _ => {}
+            }
+            false
+        });
+        let (comma, _) = match comma {
+            Some(comma) => comma,
+            None => break,
+        };
+
+        filters.push(args[comma + 1..].trim());
+        name = &args[..comma];
+    }
+    (name, filters)
+}
+
+/// An output that records the elements drawn to it by [`Inspect`].
+struct Recorder<'a> {
+    inner: &'a mut dyn Output,
+    inspected: Vec<Inspected>,
+    depth: usize,
+}
+
+impl Output for Recorder<'_> {
+    fn size(&self) -> Vec2<u16> {
+        self.inner.size()
+    }
+    fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
+        self.inner.write_char(pos, c, style);
+    }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        self.inner.write_grapheme(pos, grapheme, style);
+    }
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.inner.set_cursor(cursor);
+    }
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        self.inner.draw_image(pos, size, bitmap);
+    }
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        let rest = match id.strip_prefix(INSPECT_REGION) {
+            Some(rest) => rest,
+            None => return self.inner.register_region(id, pos, size),
+        };
+        let mut parts = rest.splitn(2, '\0');
+        if let (Some(name), Some(type_name)) = (parts.next(), parts.next()) {
+            let type_name = short_type_name(type_name);
+            let (element, filters) = split_filters(&type_name);
+            self.inspected.push(Inspected {
+                name: name.to_owned(),
+                element: element.to_owned(),
+                filters: filters.into_iter().map(str::to_owned).collect(),
+                pos,
+                size,
+                depth: self.depth,
+            });
+            self.depth += 1;
+        } else {
+            self.depth = self.depth.saturating_sub(1);
+        }
+    }
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        self.inner.set_cell_data(pos, data);
+    }
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        self.inner.set_link(pos, link);
+    }
+    fn origin(&self) -> Vec2<i32> {
+        self.inner.origin()
+    }
+    fn base_style(&self) -> Style {
+        self.inner.base_style()
+    }
+}
+
+/// Draw the outline of a rectangle of an output with its top left corner at a zero-indexed
+/// position.
+fn draw_outline(output: &mut dyn Output, pos: Vec2<u16>, size: Vec2<u16>, style: Style) {
+    if size.x == 0 || size.y == 0 {
+        return;
+    }
+    let mut area = output.area(pos.map(i32::from), size);
+    let end = size - Vec2::new(1, 1);
+    area.hline((0, 0), size.x, '─', style);
+    area.hline((0, end.y), size.x, '─', style);
+    area.vline((0, 0), size.y, '│', style);
+    area.vline((end.x, 0), size.y, '│', style);
+    area.write_char(Vec2::new(0, 0), '┌', style);
+    area.write_char(Vec2::new(end.x, 0), '┐', style);
+    area.write_char(Vec2::new(0, end.y), '└', style);
+    area.write_char(end, '┘', style);
+}
+
+/// A filter that records the inspected elements inside it, and overlays them while inspecting or
+/// showing their bounds.
+struct Inspector<'a> {
+    dev: &'a Dev,
+}
+
+impl<Event> Filter<Event> for Inspector<'_> {
+    fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
+        let mut recorder = Recorder {
+            inner: output,
+            inspected: Vec::new(),
+            depth: 0,
+        };
+        draw_inspected(&"element", element, &mut recorder);
+        let inspected = recorder.inspected;
+
+        if self.dev.bounds {
+            let style = Style::default().dark_gray().dim();
+            for element in &inspected {
+                draw_outline(output, element.pos, element.size, style);
+
+                let label = format!("{}x{}", element.size.x, element.size.y);
+                if let Some(x) = element.size.x.checked_sub(label.len() as u16 + 1) {
+                    if x > 0 {
+                        let pos = element.pos + Vec2::new(x, element.size.y - 1);
+                        output.write(pos, label, style);
+                    }
+                }
+            }
+        }
+
+        if self.dev.inspecting {
+            let label_style = Style::default().black().on_yellow();
+            for element in &inspected {
+                let label = format!("{} {}x{}", element.name, element.size.x, element.size.y);
+                output
+                    .area(element.pos.map(i32::from), element.size)
+                    .write((0, 0), label, label_style);
+            }
+
+            if let Some(selected) = self
+                .dev
+                .selected
+                .and_then(|pos| inspected.iter().rev().find(|e| e.contains(pos)))
+            {
+                let style = Style::default().yellow();
+                draw_outline(output, selected.pos, selected.size, style);
+            }
+        }
+
+        *self.dev.inspected.borrow_mut() = inspected;
+    }
+    fn handle<E: Element<Event = Event>>(
+        &self,
+        element: E,
+        input: Input,
+        events: &mut dyn Events<Event>,
+    ) {
+        if let Some(mouse) = input.clone().mouse() {
+            let inspected = self.dev.inspected.borrow();
+            *self.dev.input_target.borrow_mut() = inspected
+                .iter()
+                .rev()
+                .find(|e| e.contains(mouse.at))
+                .map(|e| e.name.clone());
+            if self.dev.inspecting {
+                return;
+            }
+        }
+        element.handle(input, events);
+    }
+}
+
+/// The number of inputs kept in the input log.
+const INPUT_LOG_LEN: usize = 64;
+
+/// A filter that logs the inputs given to the developer tools while the input log is shown.
+struct InputLog<'a> {
+    dev: &'a Dev,
+}
+
+impl<T> Filter<AppEvent<T>> for InputLog<'_> {
+    fn handle<E: Element<Event = AppEvent<T>>>(
+        &self,
+        element: E,
+        input: Input,
+        events: &mut dyn Events<AppEvent<T>>,
+    ) {
+        if !self.dev.logging_inputs {
+            element.handle(input, events);
+            return;
+        }
+
+        *self.dev.input_target.borrow_mut() = None;
+        let (dev_events, element_events) = (Cell::new(false), Cell::new(false));
+        element.handle(
+            input.clone(),
+            &mut (&mut *events).map(|event: AppEvent<T>| {
+                match &event {
+                    AppEvent::Dev(_) => dev_events.set(true),
+                    AppEvent::Element(_) => element_events.set(true),
+                }
+                event
+            }),
+        );
+
+        let handler = if element_events.get() {
+            match self.dev.input_target.borrow_mut().take() {
+                Some(target) => format!("element ({})", target),
+                None => "element".to_owned(),
+            }
+        } else if dev_events.get() {
+            "dev tools".to_owned()
+        } else if events.propagation_stopped() {
+            "stopped".to_owned()
+        } else {
+            "ignored".to_owned()
+        };
+        events.add(EventKind::LogInput(format!("{} → {}", describe_input(&input), handler)).into());
+    }
+}
+
+/// Describe an input for the input log.
+fn describe_input(input: &Input) -> String {
+    match input {
+        Input::Key(key) => format!("key {}", key),
+        Input::Mouse(mouse) => format!(
+            "mouse {:?} at ({}, {})",
+            mouse.kind, mouse.terminal_at.x, mouse.terminal_at.y
+        ),
+        Input::Paste(text) => format!("paste {:?}", text),
+        Input::Compose(text) => format!("compose {:?}", text),
+        Input::Action(action) => format!("action {}", action),
+        Input::Shortcut(key) => format!("shortcut {}", key),
+        Input::Signal(signal) => format!("signal {:?}", signal),
+    }
+}
+
+/// The size of the terminal simulated by [`OldTerminal`].
+const OLD_TERMINAL_SIZE: Vec2<u16> = Vec2::new(80, 24);
+
+/// A filter that makes an element look and behave like it is in an old terminal while it is
+/// enabled.
+///
+/// The element is drawn in at most [`OLD_TERMINAL_SIZE`] cells at the top left of the output with
+/// only the 16 named colors, and mouse inputs are dropped.
+struct OldTerminal {
+    enabled: bool,
+}
+
+impl<Event> Filter<Event> for OldTerminal {
+    fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
+        if self.enabled {
+            let size = output.size().min(OLD_TERMINAL_SIZE);
+            element
+                .filter(Ansi16Colors)
+                .draw(&mut output.area(Vec2::new(0, 0), size));
+        } else {
+            element.draw(output);
+        }
+    }
+    fn handle<E: Element<Event = Event>>(
+        &self,
+        element: E,
+        input: Input,
+        events: &mut dyn Events<Event>,
+    ) {
+        if !(self.enabled && matches!(input, Input::Mouse(_))) {
+            element.handle(input, events);
+        }
+    }
+}
+
+/// A filter that converts the colors of an element to the closest of the 16 named colors.
+struct Ansi16Colors;
+
+impl<Event> Filter<Event> for Ansi16Colors {
+    fn filter_style(&self, mut style: Style) -> Style {
+        let downgrade = |color| ColorSupport::Ansi16.downgrade(color, crate::color_distance);
+        style.foreground = downgrade(style.foreground);
+        style.background = downgrade(style.background);
+        style.underline_color = downgrade(style.underline_color);
+        style
+    }
+}
+
 /// Create a stream of developer tools events from a program's captured stdio. This stream will
 /// terminate only when the terminal where the [`Captured`] came from is destroyed.
 ///
@@ -403,21 +1139,328 @@ impl<T> From<EventKind> for AppEvent<T> {
 /// **Do not use this function when you are printing from inside the drawing function**, as that
 /// will cause the app to redraw instantly, getting it stuck in an infinite loop of printing and
 /// redrawing.
-pub fn display_captured(mut captured: Captured) -> impl Stream<Item = Event> + Unpin {
+pub fn display_captured(captured: Captured) -> impl Stream<Item = Event> + Unpin {
     let (sender, receiver) = async_channel::bounded(4);
 
-    std::thread::spawn(move || {
-        futures_lite::future::block_on(async move {
-            let mut buf = [0; 1024];
-            loop {
-                if let Ok(i) = captured.read(&mut buf) {
-                    if i == 0 || sender.send(buf[..i].to_vec()).await.is_err() {
-                        break;
+    for mut stream in vec![captured.stdout, captured.stderr] {
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            futures_lite::future::block_on(async move {
+                let mut buf = [0; 1024];
+                loop {
+                    if let Ok(i) = stream.read(&mut buf) {
+                        if i == 0 || sender.send(buf[..i].to_vec()).await.is_err() {
+                            break;
+                        }
                     }
                 }
+            })
+        });
+    }
+
+    receiver.map(|v| Event(EventKind::CapturedData(v)))
+}
+
+/// How often [`watch_theme`] checks whether the theme file has changed.
+const THEME_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Create a stream of developer tools events that load a [`Theme`] from a file whenever it
+/// changes. This stream will only terminate when it is dropped.
+///
+/// The file is loaded when the stream is created and then whenever its modification time changes.
+/// Passing these events to a developer tools will make the theme available from [`Dev::theme`],
+/// which you can use in place of your application's theme to see changes to it without
+/// restarting. Errors reading or parsing the file are displayed on the bottom panel instead.
+pub fn watch_theme(path: impl Into<PathBuf>) -> impl Stream<Item = Event> + Unpin {
+    let path = path.into();
+    let (sender, receiver) = async_channel::bounded(1);
+
+    std::thread::spawn(move || {
+        let mut last_modified = None;
+        while !sender.is_closed() {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if last_modified != Some(modified) {
+                last_modified = Some(modified);
+
+                let theme = fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|theme| theme.parse::<Theme>().map_err(|e| e.to_string()))
+                    .map_err(|e| format!("Failed to load theme {}: {}", path.display(), e));
+                if futures_lite::future::block_on(sender.send(theme)).is_err() {
+                    break;
+                }
             }
+            std::thread::sleep(THEME_POLL_INTERVAL);
+        }
+    });
+
+    receiver.map(|theme| Event(EventKind::Theme(theme)))
+}
+
+#[test]
+fn test_inspect() {
+    let element = crate::column(
+        crate::Static,
+        (
+            crate::span::<_, ()>("a").filter(Inspect::new("a")),
+            crate::span("bc")
+                .filter(Border::THIN.no_padding())
+                .title("b")
+                .filter(Inspect::new("b")),
+        ),
+    );
+
+    let mut grid = crate::Grid::new((4, 4));
+    let mut recorder = Recorder {
+        inner: &mut grid,
+        inspected: Vec::new(),
+        depth: 0,
+    };
+    draw_inspected(&"root", &element, &mut recorder);
+    let inspected = recorder.inspected;
+    crate::assert_grid_eq!(grid, ["a   ", "┌──┐", "│bc│", "└──┘"]);
+
+    let summary: Vec<_> = inspected
+        .iter()
+        .map(|e| (e.name.as_str(), e.pos, e.size, e.depth))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("root", Vec2::new(0, 0), Vec2::new(4, 4), 0),
+            ("a", Vec2::new(0, 0), Vec2::new(4, 1), 1),
+            ("b", Vec2::new(0, 1), Vec2::new(4, 3), 1),
+        ]
+    );
+    assert_eq!(inspected[1].element, "Span<&str, ()>");
+    assert!(inspected[1].filters.is_empty());
+    assert_eq!(inspected[2].element, "Span<&str, ()>");
+    assert_eq!(inspected[2].filters, ["Title<&str>", "Border"]);
+    assert!(inspected[2].contains(Vec2::new(3, 3)));
+    assert!(!inspected[2].contains(Vec2::new(3, 0)));
+}
+
+#[test]
+fn test_bounds() {
+    let mut dev = Dev::new();
+    dev.apply(Event(EventKind::ToggleBounds));
+
+    let element = crate::column(
+        crate::Static,
+        (crate::span::<_, ()>("x").filter(Inspect::new("x")),),
+    );
+    let mut grid = crate::Grid::new((6, 3));
+    element.filter(Inspector { dev: &dev }).draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["└─6x1┘", "│    │", "└─6x3┘"]);
+    assert_eq!(
+        grid.lines()[2].cells()[2].style(),
+        Some(Style::default().dark_gray().dim())
+    );
+    assert_eq!(dev.inspected.borrow().len(), 2);
+}
+
+#[test]
+fn test_input_log() {
+    use crate::{KeyPress, Modifiers};
+
+    let mut dev = Dev::new();
+    let mouse = |x, y| {
+        Input::Mouse(Mouse {
+            kind: MouseKind::Press(MouseButton::Left),
+            at: Vec2::new(x, y),
+            size: Vec2::new(4, 2),
+            terminal_at: Vec2::new(x, y),
+            modifiers: Modifiers::default(),
         })
+    };
+    let inputs = [
+        Input::Key(KeyPress::from('q')),
+        Input::Key(KeyPress::from('x')),
+        mouse(1, 1),
+        mouse(1, 0),
+    ];
+    let handle = |dev: &Dev| {
+        let element = crate::column(
+            crate::Static,
+            (
+                crate::span::<_, u8>("a").on('q', |_| 0),
+                crate::span("b")
+                    .on(|_| true, |_| 1)
+                    .filter(Inspect::new("b")),
+            ),
+        )
+        .focus(0)
+        .filter(Inspector { dev });
+        element.draw(&mut crate::Grid::new((4, 2)));
+
+        let element = element
+            .map_event(AppEvent::Element)
+            .on(input!(Key(x)), |_| EventKind::Focus(Focus::RightDev).into())
+            .filter(InputLog { dev });
+        let mut events = crate::events::Vector::new();
+        for input in inputs.iter().cloned() {
+            events.set_propagation_stopped(false);
+            element.handle(input, &mut events);
+        }
+        events
+            .0
+            .into_iter()
+            .filter_map(|event| match event {
+                AppEvent::Dev(Event(EventKind::LogInput(line))) => Some(line),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert!(handle(&dev).is_empty());
+
+    dev.apply(Event(EventKind::ToggleInputLog));
+    assert_eq!(
+        handle(&dev),
+        [
+            "key q → element",
+            "key x → dev tools",
+            "mouse Press(Left) at (1, 1) → element (b)",
+            "mouse Press(Left) at (1, 0) → ignored",
+        ]
+    );
+}
+
+#[test]
+fn test_old_terminal() {
+    use crate::{KeyPress, Modifiers, Rgb};
+
+    let rgb = Color::Rgb(Rgb::new(0xFF, 0x00, 0x00));
+    let element = crate::span::<_, u8>("x")
+        .foreground(rgb)
+        .float((Alignment::End, Alignment::End))
+        .on(|_: Input| true, |_| 0);
+    let mouse = Input::Mouse(Mouse {
+        kind: MouseKind::Press(MouseButton::Left),
+        at: Vec2::new(0, 0),
+        size: Vec2::new(82, 25),
+        terminal_at: Vec2::new(0, 0),
+        modifiers: Modifiers::default(),
     });
+    let run = |enabled| {
+        let element = (&element).filter(OldTerminal { enabled });
+        let mut grid = crate::Grid::new((82, 25));
+        element.draw(&mut grid);
+        let mut events = crate::events::Vector::new();
+        element.handle(mouse.clone(), &mut events);
+        element.handle(Input::Key(KeyPress::from('a')), &mut events);
+        (grid, events.0)
+    };
 
-    receiver.map(|v| Event(EventKind::CapturedData(v)))
+    let (grid, events) = run(false);
+    let cell = &grid.lines()[24].cells()[81];
+    assert_eq!(cell.contents(), Some("x"));
+    assert_eq!(cell.style(), Some(Style::default().foreground(rgb)));
+    assert_eq!(events, [0, 0]);
+
+    let (grid, events) = run(true);
+    let cell = &grid.lines()[23].cells()[79];
+    assert_eq!(cell.contents(), Some("x"));
+    assert_eq!(cell.style(), Some(Style::default().red()));
+    assert_eq!(grid.lines()[24].contents().trim(), "");
+    assert_eq!(events, [0]);
+
+    let mut dev = Dev::new();
+    dev.apply(Event(EventKind::ToggleOldTerminal));
+    assert!(dev.old_terminal);
+}
+
+#[test]
+fn test_watch_theme() {
+    let path = std::env::temp_dir().join(format!("toon-test-theme-{}", std::process::id()));
+    fs::write(&path, "title = bold").unwrap();
+
+    let mut dev = Dev::new();
+    let mut events = watch_theme(&path);
+    dev.apply(futures_lite::future::block_on(events.next()).unwrap());
+    assert_eq!(
+        dev.theme(),
+        Some(&Theme::new().set("title", Style::default().bold()))
+    );
+    drop(events);
+
+    fs::write(&path, "title").unwrap();
+    let mut events = watch_theme(&path);
+    dev.apply(futures_lite::future::block_on(events.next()).unwrap());
+    assert!(dev.captured.starts_with("Failed to load theme "));
+    assert!(dev.theme().is_some());
+
+    fs::remove_file(&path).unwrap();
+    dev.captured.clear();
+}
+
+#[test]
+fn test_fps() {
+    use std::time::Duration;
+
+    let mut stats = FrameStats {
+        frame_interval: Duration::from_millis(20),
+        cells_changed: 12,
+        draw_time: Duration::from_millis(1),
+        diff_time: Duration::from_micros(500),
+        flush_time: Duration::from_micros(500),
+        input_latency: Some(Duration::from_millis(3)),
+        ..FrameStats::default()
+    };
+    let element = crate::span::<_, ()>("Hello");
+
+    let mut grid = crate::Grid::new((20, 5));
+    Fps::new().wrap(element, stats).draw(&mut grid);
+    crate::assert_grid_eq!(
+        grid,
+        [
+            "He 50.0 fps         ",
+            "   frame 2.0ms      ",
+            "   12 cells changed ",
+            "   latency 3.0ms    ",
+            "                    ",
+        ],
+        [
+            "  yyyyyyyyyyyyyyyyyy",
+            "  yyyyyyyyyyyyyyyyyy",
+            "  yyyyyyyyyyyyyyyyyy",
+            "  yyyyyyyyyyyyyyyyyy",
+        ],
+        { 'y' => Style::default().black().on_yellow() },
+    );
+
+    stats.frame_interval = Duration::default();
+    stats.input_latency = None;
+    let mut grid = crate::Grid::new((12, 4));
+    Fps::new().wrap(element, stats).draw(&mut grid);
+    crate::assert_grid_eq!(
+        grid,
+        [
+            " - fps      ",
+            " frame 2.0ms",
+            " 12 cells ch",
+            " latency -  ",
+        ]
+    );
+
+    let mut fps = Fps::new();
+    fps.apply(Event(EventKind::ToggleFps));
+    let mut grid = crate::Grid::new((20, 1));
+    fps.wrap(element, stats).draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["Hello               "]);
+}
+
+#[test]
+fn test_type_names() {
+    assert_eq!(
+        short_type_name("&toon::Filtered<alloc::vec::Vec<u8>, (a::B, [c::D; 2])>"),
+        "&Filtered<Vec<u8>, (B, [D; 2])>"
+    );
+    assert_eq!(
+        split_filters("&Filtered<Filtered<Span<&str, ()>, On<X<u8, ()>, Y>>, Border>"),
+        ("Span<&str, ()>", vec!["Border", "On<X<u8, ()>, Y>"])
+    );
+    assert_eq!(split_filters("Filtered<"), ("Filtered<", Vec::new()));
 }
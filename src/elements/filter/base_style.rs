@@ -0,0 +1,53 @@
+use crate::output::{Ext as _, Output};
+use crate::{Element, Style};
+
+use super::Filter;
+
+/// A filter that sets the base style of an element, typically used through the
+/// [`base_style`](crate::ElementExt::base_style) method.
+///
+/// Characters drawn by the element with [`Color::Default`](crate::Color::Default) colors take the
+/// colors of the base style instead. See [`Output::base_style`] for more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseStyle {
+    /// The base style.
+    pub style: Style,
+}
+
+impl<Event> Filter<Event> for BaseStyle {
+    fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
+        element.draw(&mut output.with_base_style(self.style));
+    }
+}
+
+#[test]
+fn test_base_style() {
+    use crate::{ElementExt, Styled};
+
+    let mut grid = crate::Grid::new((3, 1));
+
+    crate::row::<_, _, ()>(
+        crate::Static,
+        (
+            crate::span("a"),
+            crate::span("b").red(),
+            crate::span("c").on_blue(),
+        ),
+    )
+    .base_style(Style::default().dark_gray().on_black())
+    .draw(&mut grid);
+
+    let styles: Vec<_> = grid.lines()[0]
+        .cells()
+        .iter()
+        .map(|cell| cell.style().unwrap())
+        .collect();
+    assert_eq!(
+        styles,
+        [
+            Style::default().dark_gray().on_black(),
+            Style::default().red().on_black(),
+            Style::default().dark_gray().on_blue(),
+        ]
+    );
+}
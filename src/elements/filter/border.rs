@@ -217,12 +217,11 @@ impl<Event> Filter<Event> for Border {
         });
 
         // Fill the padding.
+        let inner_height = output_size.y.saturating_sub(2);
         if self.padding {
-            for y in 1..output_size.y.saturating_sub(1) {
-                output.write_char(Vec2::new(1, y), ' ', self.style);
-                if let Some(right_border) = right_border {
-                    output.write_char(Vec2::new(right_border - 1, y), ' ', self.style);
-                }
+            output.vline((1, 1), inner_height, ' ', self.style);
+            if let Some(right_border) = right_border {
+                output.vline((right_border - 1, 1), inner_height, ' ', self.style);
             }
         }
 
@@ -246,11 +245,9 @@ impl<Event> Filter<Event> for Border {
         let (top, left, right, bottom) = self.sides;
 
         // Write both sides
-        for y in 1..output_size.y.saturating_sub(1) {
-            output.write_char(Vec2::new(0, y), left, self.style);
-            if let Some(right_border) = right_border {
-                output.write_char(Vec2::new(right_border, y), right, self.style);
-            }
+        output.vline((0, 1), inner_height, left, self.style);
+        if let Some(right_border) = right_border {
+            output.vline((right_border, 1), inner_height, right, self.style);
         }
 
         // Get the title width, is lazy because only when one of the top title and bottom title is
@@ -292,6 +289,12 @@ impl<Event> Filter<Event> for Border {
                 if let Some(offset) = &mut offset_top {
                     let after = offset.checked_add(width).ok_or(fmt::Error)?;
                     if Some(after) > right_border {
+                        // Only the first half of a double-width character fits, so it is
+                        // replaced with a space.
+                        if Some(*offset) < right_border {
+                            output.write_char(Vec2::new(*offset, 0), ' ', self.title_style);
+                            *offset += 1;
+                        }
                         return Err(fmt::Error);
                     }
                     output.write_char(Vec2::new(*offset, 0), c, self.title_style);
@@ -301,6 +304,12 @@ impl<Event> Filter<Event> for Border {
                 if let (Some(offset), Some(y)) = (&mut offset_bottom, bottom_border) {
                     let after = offset.checked_add(width).ok_or(fmt::Error)?;
                     if Some(after) > right_border {
+                        // Only the first half of a double-width character fits, so it is
+                        // replaced with a space.
+                        if Some(*offset) < right_border {
+                            output.write_char(Vec2::new(*offset, y), ' ', self.title_style);
+                            *offset += 1;
+                        }
                         return Err(fmt::Error);
                     }
                     output.write_char(Vec2::new(*offset, y), c, self.title_style);
@@ -312,16 +321,21 @@ impl<Event> Filter<Event> for Border {
         }
 
         // Write top and bottom borders, not overwriting the title
-        for x in 1..output_size.x.saturating_sub(1) {
-            if title_start_top.map_or(true, |start| x < start || x >= offset_top.unwrap()) {
-                output.write_char(Vec2::new(x, 0), top, self.style);
-            }
-            if let Some(y) = bottom_border {
-                if title_start_bottom.map_or(true, |start| x < start || x >= offset_bottom.unwrap())
-                {
-                    output.write_char(Vec2::new(x, y), bottom, self.style);
-                }
-            }
+        let end = output_size.x.saturating_sub(1);
+        for &(y, c, title_start, title_end) in &[
+            (Some(0), top, title_start_top, offset_top),
+            (bottom_border, bottom, title_start_bottom, offset_bottom),
+        ] {
+            let y = match y {
+                Some(y) => y,
+                None => continue,
+            };
+            let (start, after) = match (title_start, title_end) {
+                (Some(start), Some(after)) => (start, after),
+                _ => (end, end),
+            };
+            output.hline((1, y), start.saturating_sub(1), c, self.style);
+            output.hline((after, y), end.saturating_sub(after), c, self.style);
         }
     }
     fn ideal_width<E: Element>(&self, element: E, height: u16, max_width: Option<u16>) -> u16 {
@@ -356,6 +370,11 @@ impl<Event> Filter<Event> for Border {
     ) {
         let input = match input {
             Input::Key(key) => Some(Input::Key(key)),
+            Input::Paste(text) => Some(Input::Paste(text)),
+            Input::Compose(text) => Some(Input::Compose(text)),
+            Input::Action(action) => Some(Input::Action(action)),
+            Input::Shortcut(key) => Some(Input::Shortcut(key)),
+            Input::Signal(signal) => Some(Input::Signal(signal)),
             Input::Mouse(mouse) => (|| {
                 let xborder = if self.padding { 2 } else { 1 };
 
@@ -390,7 +409,7 @@ fn test_border() {
         .filter(Border::new(('a', 'b', 'c', 'd'), ('e', 'f', 'g', 'h')))
         .draw(&mut grid);
 
-    assert_eq!(grid.contents(), ["eaaaf", "b - c", "b   c", "gdddh"]);
+    crate::assert_grid_eq!(grid, ["eaaaf", "b - c", "b   c", "gdddh"]);
 }
 
 #[test]
@@ -403,8 +422,8 @@ fn test_padding() {
         .filter(Border::THIN)
         .draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         ["┌──────────────┐", "│ Hello World! │", "└──────────────┘",]
     );
 
@@ -413,8 +432,8 @@ fn test_padding() {
         .filter(Border::THIN.no_padding())
         .draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         ["┌────────────┐", "│Hello World!│", "└────────────┘",]
     );
 }
@@ -434,18 +453,12 @@ fn test_title() {
                 .bottom_title(Alignment::End),
         )
         .draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["+Hello😊World❗--+", "+--Hello😊World❗+",]
-    );
+    crate::assert_grid_eq!(grid, ["+Hello😊World❗--+", "+--Hello😊World❗+",]);
 
     element
         .filter(Border::ASCII_PLUS.bottom_title(Alignment::Middle))
         .draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["+----------------+", "+-Hello😊World❗-+",]
-    );
+    crate::assert_grid_eq!(grid, ["+----------------+", "+-Hello😊World❗-+",]);
 
     grid.resize_width(8);
     grid.resize_height(1);
@@ -453,5 +466,51 @@ fn test_title() {
     element
         .filter(Border::ASCII_PLUS.top_title(Alignment::Middle))
         .draw(&mut grid);
-    assert_eq!(grid.contents(), ["+Hello-+",]);
+    crate::assert_grid_eq!(grid, ["+Hello +",]);
+
+    grid.resize_width(9);
+    element
+        .filter(Border::ASCII_PLUS.top_title(Alignment::Start))
+        .draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["+Hello😊+",]);
+}
+
+#[cfg(feature = "quickcheck")]
+#[test]
+fn test_mouse_round_trip() {
+    use crate::ElementExt;
+
+    fn round_trips(border: Border, mouse: Mouse) -> bool {
+        let mut events = crate::events::Vector::new();
+        crate::empty()
+            .on(|_: Input| true, |input| input)
+            .filter(border)
+            .handle(Input::Mouse(mouse), &mut events);
+
+        let padding = Vec2::new(if border.padding { 2 } else { 1 }, 1);
+        match &*events.0 {
+            [] => {
+                mouse.at.x < padding.x
+                    || mouse.at.y < padding.y
+                    || mouse.at.x.saturating_add(padding.x) >= mouse.size.x
+                    || mouse.at.y.saturating_add(padding.y) >= mouse.size.y
+            }
+            [Input::Mouse(inner)] => {
+                inner.at + padding == mouse.at
+                    && inner.size + padding + padding == mouse.size
+                    && inner.origin() - padding.into() == mouse.origin()
+                    && Mouse {
+                        at: mouse.at,
+                        size: mouse.size,
+                        ..*inner
+                    } == mouse
+            }
+            _ => false,
+        }
+    }
+
+    quickcheck_crate::quickcheck((|mouse| round_trips(Border::THIN, mouse)) as fn(Mouse) -> bool);
+    quickcheck_crate::quickcheck(
+        (|mouse| round_trips(Border::THIN.no_padding(), mouse)) as fn(Mouse) -> bool,
+    );
 }
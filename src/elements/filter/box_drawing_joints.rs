@@ -0,0 +1,34 @@
+use crate::output::{Ext as _, Output};
+use crate::Element;
+
+use super::Filter;
+
+/// A filter that joins the box-drawing characters an element draws on top of each other, typically
+/// used through the [`join_box_drawing`](crate::ElementExt::join_box_drawing) method.
+///
+/// This lets borders and lines drawn by different elements in a [`stack`](crate::stack) meet
+/// seamlessly. See [`Ext::join_box_drawing`](crate::output::Ext::join_box_drawing) for more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoxDrawingJoints;
+
+impl<Event> Filter<Event> for BoxDrawingJoints {
+    fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
+        element.draw(&mut output.join_box_drawing());
+    }
+}
+
+#[test]
+fn test_box_drawing_joints() {
+    use crate::{Border, ElementExt};
+
+    let mut grid = crate::Grid::new((3, 3));
+
+    crate::stack::<_, ()>((
+        crate::empty().filter(Border::THIN),
+        crate::span('│').tile((0, 0)),
+    ))
+    .join_box_drawing()
+    .draw(&mut grid);
+
+    crate::assert_grid_eq!(grid, ["├┼┤", "│││", "├┼┤"]);
+}
@@ -1,4 +1,5 @@
-use crate::{Color, Element, Output, Style, Styled, Vec2};
+use crate::output::{Ext as _, Output};
+use crate::{Color, Element, Style, Styled};
 
 use super::Filter;
 
@@ -12,18 +13,13 @@ pub struct FillBackground {
 
 impl<Event> Filter<Event> for FillBackground {
     fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
-        let size = output.size();
-        for x in 0..size.x {
-            for y in 0..size.y {
-                output.write_char(
-                    Vec2::new(x, y),
-                    ' ',
-                    Style::default().background(self.color),
-                );
-            }
-        }
+        let style = Style::default().background(self.color);
+        output.fill_rect((0, 0), output.size(), ' ', style);
         element.draw(output);
     }
+    fn describe(&self) -> String {
+        format!("FillBackground({:?})", self.color)
+    }
 }
 
 #[test]
@@ -38,5 +34,5 @@ fn test_fill_background() {
     ))
     .draw(&mut grid);
 
-    assert_eq!(grid.contents(), ["Hi   ", "     "]);
+    crate::assert_grid_eq!(grid, ["Hi   ", "     "]);
 }
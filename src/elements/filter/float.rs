@@ -78,6 +78,11 @@ impl<Event> Filter<Event> for Float {
     ) {
         let input = match input {
             Input::Key(key) => Some(Input::Key(key)),
+            Input::Paste(text) => Some(Input::Paste(text)),
+            Input::Compose(text) => Some(Input::Compose(text)),
+            Input::Action(action) => Some(Input::Action(action)),
+            Input::Shortcut(key) => Some(Input::Shortcut(key)),
+            Input::Signal(signal) => Some(Input::Signal(signal)),
             Input::Mouse(mouse) => {
                 let (offset, size) = self.calculate_layout(&element, mouse.size);
 
@@ -92,6 +97,9 @@ impl<Event> Filter<Event> for Float {
             element.handle(input, events);
         }
     }
+    fn describe(&self) -> String {
+        crate::tree::describe_axes("Float", self.align)
+    }
 }
 
 #[test]
@@ -105,7 +113,7 @@ fn test_float_x() {
         .float_x(Alignment::Middle)
         .draw(&mut grid);
 
-    assert_eq!(grid.contents(), ["    Foo   "; 3]);
+    crate::assert_grid_eq!(grid, ["    Foo   ", "    Foo   ", "    Foo   "]);
 
     grid.resize_width(2);
     grid.clear();
@@ -114,7 +122,7 @@ fn test_float_x() {
         .float_x(Alignment::Middle)
         .draw(&mut grid);
 
-    assert_eq!(grid.contents(), ["Fo"; 3]);
+    crate::assert_grid_eq!(grid, ["Fo", "Fo", "Fo"]);
 }
 
 #[test]
@@ -129,10 +137,7 @@ fn test_float_y() {
         .float_y(Alignment::Middle)
         .draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
-        ["  ", "  ", "  ", "XX", "XX", "  ", "  ", "  ",]
-    );
+    crate::assert_grid_eq!(grid, ["  ", "  ", "  ", "XX", "XX", "  ", "  ", "  ",]);
 
     grid.clear();
     crate::span::<_, ()>("X")
@@ -141,8 +146,28 @@ fn test_float_y() {
         .float_y(Alignment::End)
         .draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
-        ["  ", "  ", "  ", "  ", "  ", "  ", "XX", "XX",]
+    crate::assert_grid_eq!(grid, ["  ", "  ", "  ", "  ", "  ", "  ", "XX", "XX",]);
+}
+
+#[test]
+fn test_float_mouse_origin() {
+    use crate::{input, ElementExt, Modifiers, MouseKind};
+
+    let element = crate::empty()
+        .on(input!(Mouse), |input| input.mouse().unwrap().origin())
+        .size((2, 1))
+        .float((Alignment::Middle, Alignment::Middle));
+
+    let mut events = crate::events::Vector::new();
+    element.handle(
+        Input::Mouse(Mouse {
+            kind: MouseKind::Move,
+            at: Vec2::new(5, 1),
+            size: Vec2::new(10, 3),
+            terminal_at: Vec2::new(5, 1),
+            modifiers: Modifiers::default(),
+        }),
+        &mut events,
     );
+    assert_eq!(events.0, [Vec2::new(4, 1)]);
 }
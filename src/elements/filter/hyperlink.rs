@@ -0,0 +1,69 @@
+use crate::output::Output;
+use crate::util::grapheme_width;
+use crate::{Link, Style, Vec2};
+
+use super::Filter;
+
+/// A filter that makes everything an element draws a hyperlink, typically used through the
+/// [`link`](crate::ElementExt::link) method.
+///
+/// Zero-width characters combine with the character before them, so they don't change which
+/// cells the link covers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hyperlink {
+    /// The link.
+    pub link: Link,
+}
+
+impl<Event> Filter<Event> for Hyperlink {
+    fn write_char(&self, base: &mut dyn Output, pos: Vec2<u16>, c: char, style: Style) {
+        Filter::<Event>::write_grapheme(self, base, pos, c.encode_utf8(&mut [0; 4]), style);
+    }
+    fn write_grapheme(&self, base: &mut dyn Output, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        base.write_grapheme(pos, grapheme, style);
+        if grapheme_width(grapheme).map_or(false, |width| width > 0) {
+            base.set_link(pos, &self.link);
+        }
+    }
+    fn describe(&self) -> String {
+        format!("Hyperlink({:?})", self.link.url())
+    }
+}
+
+#[test]
+fn test_hyperlink() {
+    use crate::{Element, ElementExt};
+
+    let mut grid = crate::Grid::new((6, 1));
+
+    crate::row::<_, _, ()>(
+        crate::Static,
+        (
+            crate::span("a"),
+            crate::span("b\u{301}😃").link("https://example.com"),
+            crate::span("c").link("https://example.org"),
+        ),
+    )
+    .link("https://example.net")
+    .draw(&mut grid);
+
+    let links: Vec<_> = grid.lines()[0]
+        .cells()
+        .iter()
+        .map(|cell| cell.link().map(Link::url))
+        .collect();
+    assert_eq!(
+        links,
+        [
+            Some("https://example.net"),
+            Some("https://example.com"),
+            Some("https://example.com"),
+            None,
+            Some("https://example.org"),
+            None,
+        ]
+    );
+
+    crate::span::<_, ()>("x").draw(&mut grid);
+    assert_eq!(grid.lines()[0].cells()[0].link(), None);
+}
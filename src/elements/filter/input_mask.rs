@@ -16,8 +16,13 @@ impl<P: input::Pattern, Event> Filter<Event> for InputMask<P> {
         input: Input,
         events: &mut dyn Events<Event>,
     ) {
-        if self.pattern.matches(input) {
+        if matches!(input, Input::Shortcut(_) | Input::Signal(_))
+            || self.pattern.matches(input.clone())
+        {
             element.handle(input, events);
         }
     }
+    fn describe(&self) -> String {
+        format!("InputMask({})", self.pattern.describe())
+    }
 }
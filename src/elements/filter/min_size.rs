@@ -0,0 +1,113 @@
+use crate::output::{Ext as _, Output};
+use crate::{Element, Events, Input, Style, Vec2};
+
+use super::{Filter, Filtered};
+
+/// A filter that replaces an element with a message when the output is too small to display it,
+/// created by the [`min_terminal_size`] function.
+///
+/// Key inputs are always passed to the element, so that shortcuts such as quitting keep working,
+/// but mouse inputs are ignored while the message is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct MinSize {
+    /// The minimum size of the output needed to draw the element.
+    pub size: Vec2<u16>,
+    /// The style of the message shown when the output is too small.
+    pub style: Style,
+}
+
+impl MinSize {
+    /// Create a new filter requiring the output to be at least the given size.
+    #[must_use]
+    pub const fn new(size: Vec2<u16>) -> Self {
+        Self {
+            size,
+            style: Style::default(),
+        }
+    }
+
+    fn fits(self, size: Vec2<u16>) -> bool {
+        size.x >= self.size.x && size.y >= self.size.y
+    }
+}
+
+impl AsRef<Style> for MinSize {
+    fn as_ref(&self) -> &Style {
+        &self.style
+    }
+}
+impl AsMut<Style> for MinSize {
+    fn as_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+}
+
+impl<Event> Filter<Event> for MinSize {
+    fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
+        let size = output.size();
+
+        if self.fits(size) {
+            element.draw(output);
+            return;
+        }
+
+        let message = format!(
+            "Terminal too small (need {}x{}, have {}x{})",
+            self.size.x, self.size.y, size.x, size.y
+        );
+        let x = size.x.saturating_sub(message.len() as u16) / 2;
+        output.write((x, size.y / 2), message, self.style);
+    }
+    fn handle<E: Element<Event = Event>>(
+        &self,
+        element: E,
+        input: Input,
+        events: &mut dyn Events<Event>,
+    ) {
+        match input {
+            Input::Mouse(mouse) if !self.fits(mouse.size) => {}
+            _ => element.handle(input, events),
+        }
+    }
+    fn describe(&self) -> String {
+        crate::tree::describe_axes("MinSize", self.size.map(Some))
+    }
+}
+
+/// Show a message instead of the element when the output is smaller than the required size.
+///
+/// This avoids drawing broken, truncated layouts on small terminals.
+///
+/// # Examples
+///
+/// ```
+/// # let element = toon::empty::<()>();
+/// let element = toon::min_terminal_size((80, 24), element);
+/// ```
+#[must_use]
+pub fn min_terminal_size<E: Element>(
+    required: impl Into<Vec2<u16>>,
+    element: E,
+) -> Filtered<E, MinSize> {
+    Filtered::new(element, MinSize::new(required.into()))
+}
+
+#[test]
+fn test_min_size() {
+    let mut grid = crate::Grid::new((44, 3));
+
+    min_terminal_size((80, 24), crate::span::<_, ()>("Hello")).draw(&mut grid);
+    crate::assert_grid_eq!(
+        grid,
+        [
+            "                                            ",
+            " Terminal too small (need 80x24, have 44x3) ",
+            "                                            ",
+        ]
+    );
+
+    grid.clear();
+    min_terminal_size((5, 1), crate::span::<_, ()>("Hello")).draw(&mut grid);
+    assert_eq!(grid.contents()[0], format!("{:44}", "Hello"));
+}
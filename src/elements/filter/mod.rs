@@ -8,20 +8,33 @@ use std::fmt;
 use std::marker::PhantomData;
 
 use crate::output::Output;
-use crate::{Cursor, Element, Events, Input, KeyPress, Mouse, Style, Vec2};
+use crate::tree::Tree;
+use crate::{Bitmap, Cursor, Element, Events, Input, KeyPress, Link, Mouse, Style, Vec2};
+
+mod base_style;
+pub use base_style::*;
 
 mod border;
 pub use border::*;
 
+mod box_drawing_joints;
+pub use box_drawing_joints::*;
+
 mod fill_background;
 pub use fill_background::*;
 
 mod float;
 pub use float::*;
 
+mod hyperlink;
+pub use hyperlink::*;
+
 mod input_mask;
 pub use input_mask::*;
 
+mod min_size;
+pub use min_size::*;
+
 mod on;
 pub use on::*;
 
@@ -44,8 +57,8 @@ pub use title::*;
 pub trait Filter<Event> {
     /// Draw the filtered element to the output.
     ///
-    /// By default this method forwards to [`write_char`](Self::write_char) and
-    /// [`set_cursor`](Self::set_cursor).
+    /// By default this method forwards to [`write_char`](Self::write_char),
+    /// [`write_grapheme`](Self::write_grapheme) and [`set_cursor`](Self::set_cursor).
     fn draw<E: Element>(&self, element: E, output: &mut dyn Output) {
         struct DrawFilterOutput<'a, F: ?Sized, Event> {
             inner: &'a mut dyn Output,
@@ -59,9 +72,30 @@ pub trait Filter<Event> {
             fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
                 self.filter.write_char(self.inner, pos, c, style);
             }
+            fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+                self.filter.write_grapheme(self.inner, pos, grapheme, style);
+            }
             fn set_cursor(&mut self, cursor: Option<Cursor>) {
                 self.filter.set_cursor(self.inner, cursor);
             }
+            fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+                self.inner.draw_image(pos, size, bitmap);
+            }
+            fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+                self.inner.register_region(id, pos, size);
+            }
+            fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+                self.inner.set_cell_data(pos, data);
+            }
+            fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+                self.inner.set_link(pos, link);
+            }
+            fn origin(&self) -> Vec2<i32> {
+                self.inner.origin()
+            }
+            fn base_style(&self) -> Style {
+                self.inner.base_style()
+            }
         }
 
         element.draw(&mut DrawFilterOutput {
@@ -79,6 +113,19 @@ pub trait Filter<Event> {
         base.write_char(pos, self.filter_char(c), self.filter_style(style));
     }
 
+    /// Write a single filtered grapheme cluster to the output.
+    ///
+    /// By default clusters of a single character are written with
+    /// [`write_char`](Self::write_char), and other clusters are written to the output with their
+    /// style filtered by [`filter_style`](Self::filter_style).
+    fn write_grapheme(&self, base: &mut dyn Output, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        let mut chars = grapheme.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => self.write_char(base, pos, c, style),
+            _ => base.write_grapheme(pos, grapheme, self.filter_style(style)),
+        }
+    }
+
     /// Filter the value of a character being written to the output.
     ///
     /// By default this returns the character.
@@ -120,6 +167,13 @@ pub trait Filter<Event> {
         element.title(title)
     }
 
+    /// Describe the filter and its parameters, for [`debug_tree`](crate::debug_tree).
+    ///
+    /// By default this returns the name of the filter's type.
+    fn describe(&self) -> String {
+        crate::tree::type_name::<Self>()
+    }
+
     /// Get the ideal width the element takes up given a fixed height and optional maximum width.
     ///
     /// By default this calls the element's [`ideal_width`](Element::ideal_width) method.
@@ -155,12 +209,18 @@ pub trait Filter<Event> {
 
     /// Filter inputs given to the wrapped element.
     ///
-    /// By default this forwards to [`filter_key_press`](Self::filter_key_press) and
-    /// [`filter_mouse`](Self::filter_mouse).
+    /// By default this forwards to [`filter_key_press`](Self::filter_key_press),
+    /// [`filter_mouse`](Self::filter_mouse) and [`filter_paste`](Self::filter_paste), and passes
+    /// compositions, actions and signals through unchanged.
     fn filter_input(&self, input: Input) -> Input {
         match input {
             Input::Key(key) => Input::Key(self.filter_key_press(key)),
+            Input::Shortcut(key) => Input::Shortcut(self.filter_key_press(key)),
             Input::Mouse(mouse) => Input::Mouse(self.filter_mouse(mouse)),
+            Input::Paste(text) => Input::Paste(self.filter_paste(text)),
+            Input::Compose(text) => Input::Compose(text),
+            Input::Action(action) => Input::Action(action),
+            Input::Signal(signal) => Input::Signal(signal),
         }
     }
 
@@ -177,6 +237,13 @@ pub trait Filter<Event> {
     fn filter_mouse(&self, input: Mouse) -> Mouse {
         input
     }
+
+    /// Filter the text pasted into the element.
+    ///
+    /// By default this returns the text unchanged.
+    fn filter_paste(&self, text: String) -> String {
+        text
+    }
 }
 
 /// An element with a filter applied.
@@ -217,6 +284,9 @@ impl<T: Element, F: Filter<T::Event>> Element for Filtered<T, F> {
     fn handle(&self, input: Input, events: &mut dyn Events<Self::Event>) {
         self.filter.handle(&self.element, input, events);
     }
+    fn describe(&self, tree: &mut Tree) {
+        tree.node(self.filter.describe(), |tree| self.element.describe(tree));
+    }
 }
 
 /// Alignment to the start, middle or end.
@@ -3,7 +3,8 @@ use crate::{input, Element, Events, Input};
 use super::Filter;
 
 /// A filter that triggers an event when an input occurs, typically used through the
-/// [`on`](crate::ElementExt::on) and [`on_passive`](crate::ElementExt::on_passive) methods.
+/// [`on`](crate::ElementExt::on), [`on_passive`](crate::ElementExt::on_passive) and
+/// [`on_bubble`](crate::ElementExt::on_bubble) methods.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct On<I, F> {
@@ -11,22 +12,25 @@ pub struct On<I, F> {
     pub input_pattern: I,
     /// The event function called when the input occurs.
     pub event: F,
-    /// Whether it listens to inputs passively. If `true`, this type will not intercept inputs if
-    /// it triggers its event.
+    /// Whether it listens to inputs passively. If `true`, this type will not stop propagation of
+    /// inputs if it triggers its event.
     pub passive: bool,
+    /// The phase in which this filter listens to inputs.
+    pub phase: Phase,
 }
 
 impl<I, F> On<I, F> {
     /// Create a new filter that triggers the event when the input occurs.
     ///
-    /// The created filter will listen to inputs _actively_; the input if it occurs will not be
-    /// passed to the inner element.
+    /// The created filter will listen to inputs _actively_ in the capture phase; the input if it
+    /// occurs will not be passed to the inner element.
     #[must_use]
     pub const fn new(input_pattern: I, event: F) -> Self {
         Self {
             input_pattern,
             event,
             passive: false,
+            phase: Phase::Capture,
         }
     }
 
@@ -38,6 +42,30 @@ impl<I, F> On<I, F> {
             ..self
         }
     }
+
+    /// Make the filter listen to events in the bubble phase.
+    #[must_use]
+    pub fn bubble(self) -> Self {
+        Self {
+            phase: Phase::Bubble,
+            ..self
+        }
+    }
+}
+
+/// The phase in which an [`On`] filter listens to inputs.
+///
+/// Inputs travel down the element tree from the root to the innermost element (the capture
+/// phase), and then back up again (the bubble phase). Any listener can stop propagation of the
+/// input, which prevents it from travelling any further.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// The filter listens to inputs before its inner element does. If it is not passive, it
+    /// stops propagation and doesn't pass the input to the inner element at all.
+    Capture,
+    /// The filter listens to inputs after its inner element does, and only if no element inside
+    /// stopped propagation. If it is not passive, it stops propagation itself.
+    Bubble,
 }
 
 impl<I: input::Pattern, F: Fn(Input) -> Event, Event> Filter<Event> for On<I, F> {
@@ -47,12 +75,70 @@ impl<I: input::Pattern, F: Fn(Input) -> Event, Event> Filter<Event> for On<I, F>
         input: Input,
         events: &mut dyn Events<Event>,
     ) {
-        let matches = self.input_pattern.matches(input);
-        if matches {
-            events.add((self.event)(input));
-        }
-        if self.passive || !matches {
-            element.handle(input, events);
+        let matches = self.input_pattern.matches(input.clone());
+
+        match self.phase {
+            Phase::Capture => {
+                if matches {
+                    events.add((self.event)(input.clone()));
+                    if !self.passive {
+                        events.stop_propagation();
+                        return;
+                    }
+                }
+                element.handle(input, events);
+            }
+            Phase::Bubble => {
+                let already_stopped = events.propagation_stopped();
+                events.set_propagation_stopped(false);
+
+                element.handle(input.clone(), events);
+
+                if matches && !events.propagation_stopped() {
+                    events.add((self.event)(input));
+                    if !self.passive {
+                        events.stop_propagation();
+                    }
+                }
+
+                if already_stopped {
+                    events.stop_propagation();
+                }
+            }
         }
     }
+    fn describe(&self) -> String {
+        format!(
+            "On({}{}{})",
+            self.input_pattern.describe(),
+            if self.passive { ", passive" } else { "" },
+            if self.phase == Phase::Bubble {
+                ", bubble"
+            } else {
+                ""
+            },
+        )
+    }
+}
+
+#[test]
+fn test_phases() {
+    use crate::{ElementExt, KeyPress};
+
+    let handle = |element: &dyn Element<Event = u8>| {
+        let mut events = crate::events::Vector::new();
+        element.handle(Input::Key(KeyPress::from('a')), &mut events);
+        events.0
+    };
+
+    let inner = crate::empty::<u8>().on_passive('a', |_| 0);
+    assert_eq!(handle(&inner.on_bubble('a', |_| 1)), [0, 1]);
+    assert_eq!(handle(&inner.on_passive('a', |_| 1)), [1, 0]);
+
+    let inner = crate::empty::<u8>().on('a', |_| 0);
+    assert_eq!(handle(&inner.on_bubble('a', |_| 1)), [0]);
+    assert_eq!(handle(&inner.on('a', |_| 1)), [1]);
+
+    let inner = crate::empty::<u8>().on_bubble('a', |_| 0);
+    assert_eq!(handle(&inner.on_bubble('a', |_| 1).on_bubble('a', |_| 2)), [0]);
 }
@@ -45,6 +45,9 @@ impl<Event> Filter<Event> for Ratio {
     fn ideal_height<E: Element>(&self, _element: E, width: u16, _max_height: Option<u16>) -> u16 {
         (f64::from(width) / self.ratio).round() as u16
     }
+    fn describe(&self) -> String {
+        format!("Ratio({})", self.ratio)
+    }
 }
 
 #[test]
@@ -56,9 +59,9 @@ fn test_ratio() {
     let a = crate::span::<_, ()>("a").tile((0, 0));
 
     a.ratio(2.).float((Middle, Middle)).draw(&mut grid);
-    assert_eq!(grid.contents(), ["     ", "aaaaa", "aaaaa", "aaaaa"]);
+    crate::assert_grid_eq!(grid, ["     ", "aaaaa", "aaaaa", "aaaaa"]);
 
     grid.clear();
     a.ratio(0.5).float((Middle, Middle)).draw(&mut grid);
-    assert_eq!(grid.contents(), [" aa  ", " aa  ", " aa  ", " aa  "]);
+    crate::assert_grid_eq!(grid, [" aa  ", " aa  ", " aa  ", " aa  "]);
 }
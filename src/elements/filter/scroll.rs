@@ -24,7 +24,12 @@ pub struct Scroll {
 impl Scroll {
     /// Get the element size and absolute scroll offset of the element.
     fn layout(self, element: impl Element, output_size: Vec2<u16>) -> (Vec2<u16>, Vec2<u16>) {
-        let size = element.ideal_size(Vec2::new(None, None));
+        // Elements are limited to the size of the output in the dimensions they don't scroll in,
+        // so that text can wrap to it.
+        let size = element.ideal_size(
+            self.by
+                .zip_with(output_size, |by, size| by.map_or(Some(size), |_| None)),
+        );
 
         let (element_width, offset_x) = self.by.x.map_or((output_size.x, 0), |offset| {
             let element_width = cmp::max(size.x, output_size.x);
@@ -74,11 +79,19 @@ impl<Event> Filter<Event> for Scroll {
                         ..mouse
                     })
                 }
-                Input::Key(_) => input,
+                Input::Key(_)
+                | Input::Paste(_)
+                | Input::Compose(_)
+                | Input::Action(_)
+                | Input::Shortcut(_)
+                | Input::Signal(_) => input,
             },
             events,
         );
     }
+    fn describe(&self) -> String {
+        crate::tree::describe_axes("Scroll", self.by)
+    }
 }
 
 /// How much to scroll.
@@ -102,22 +115,13 @@ fn test_scroll_no_fill() {
     no_fill
         .scroll((ScrollOffset::Start(5), ScrollOffset::End(3)))
         .draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["abc  ", "abc  ", "abc  ", "     ", "     "]
-    );
+    crate::assert_grid_eq!(grid, ["abc  ", "abc  ", "abc  ", "     ", "     "]);
 
     no_fill.scroll_x(ScrollOffset::End(0)).draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["abc  ", "abc  ", "abc  ", "     ", "     "]
-    );
+    crate::assert_grid_eq!(grid, ["abc  ", "abc  ", "abc  ", "     ", "     "]);
 
     no_fill.scroll_y(ScrollOffset::Start(0)).draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["abc  ", "abc  ", "abc  ", "     ", "     "]
-    );
+    crate::assert_grid_eq!(grid, ["abc  ", "abc  ", "abc  ", "     ", "     "]);
 }
 
 #[test]
@@ -153,3 +157,16 @@ fn test_scroll_end() {
         .draw(&mut line);
     assert_eq!(line.contents(), "ab");
 }
+
+#[test]
+fn test_scroll_double_width() {
+    use crate::ElementExt;
+
+    let mut line = crate::Line::new(4);
+
+    crate::span::<_, ()>("日本語")
+        .scroll_x(ScrollOffset::Start(1))
+        .draw(&mut line);
+    assert_eq!(line.contents(), " 本 ");
+    assert!(line.cells()[2].is_continuation());
+}
@@ -36,4 +36,7 @@ impl<Event> Filter<Event> for Size {
             )
         }
     }
+    fn describe(&self) -> String {
+        crate::tree::describe_axes("Size", self.size)
+    }
 }
@@ -77,6 +77,11 @@ impl<Event> Filter<Event> for Tile {
         element.handle(
             match input {
                 Input::Key(key) => Input::Key(key),
+                Input::Paste(text) => Input::Paste(text),
+                Input::Compose(text) => Input::Compose(text),
+                Input::Action(action) => Input::Action(action),
+                Input::Shortcut(key) => Input::Shortcut(key),
+                Input::Signal(signal) => Input::Signal(signal),
                 Input::Mouse(mouse) => {
                     let (offset, size) = self.layout(&element, mouse.size);
 
@@ -94,6 +99,9 @@ impl<Event> Filter<Event> for Tile {
             events,
         );
     }
+    fn describe(&self) -> String {
+        crate::tree::describe_axes("Tile", self.offset)
+    }
 }
 
 #[cfg(test)]
@@ -108,16 +116,10 @@ fn test_tile_x() {
     let mut grid = crate::Grid::new((12, 3));
 
     test_el().tile_x(2).draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["bcabcabcabca", "efdefdefdefd", "            ",]
-    );
+    crate::assert_grid_eq!(grid, ["bcabcabcabca", "efdefdefdefd", "            ",]);
 
     test_el().tile_x(3).draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["abcabcabcabc", "defdefdefdef", "            ",]
-    );
+    crate::assert_grid_eq!(grid, ["abcabcabcabc", "defdefdefdef", "            ",]);
 }
 
 #[test]
@@ -127,16 +129,10 @@ fn test_tile_y() {
     let mut grid = crate::Grid::new((4, 6));
 
     test_el().tile_y(1).draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["def ", "abc ", "def ", "abc ", "def ", "abc ",]
-    );
+    crate::assert_grid_eq!(grid, ["def ", "abc ", "def ", "abc ", "def ", "abc ",]);
 
     test_el().tile_y(2).draw(&mut grid);
-    assert_eq!(
-        grid.contents(),
-        ["abc ", "def ", "abc ", "def ", "abc ", "def ",]
-    );
+    crate::assert_grid_eq!(grid, ["abc ", "def ", "abc ", "def ", "abc ", "def ",]);
 }
 
 #[test]
@@ -147,8 +143,8 @@ fn test_tile_both() {
 
     test_el().tile((2, 1)).draw(&mut grid);
 
-    assert_eq!(
-        grid.contents(),
+    crate::assert_grid_eq!(
+        grid,
         [
             "efdefdefdefde",
             "bcabcabcabcab",
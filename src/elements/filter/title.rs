@@ -25,4 +25,7 @@ impl<T: Display, Event> Filter<Event> for Title<T> {
     fn title<E: Element>(&self, _element: E, title: &mut dyn fmt::Write) -> fmt::Result {
         write!(title, "{}", self.title)
     }
+    fn describe(&self) -> String {
+        format!("Title({:?})", self.title.to_string())
+    }
 }
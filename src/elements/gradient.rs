@@ -0,0 +1,179 @@
+use std::fmt::{Display, Write};
+use std::marker::PhantomData;
+
+use crate::util::{grapheme_width, WriteGraphemesFn};
+use crate::{Color, Element, Events, Input, Output, Style, Vec2};
+
+/// A span of text colored along a gradient, created by the [`gradient_span`] function.
+///
+/// The foreground color of each character is mixed between the two colors of the gradient
+/// depending on how far along the text it is, so the foreground color of the style is ignored.
+/// The other parts of the style, such as the background color and attributes, are used as normal.
+///
+/// # Examples
+///
+/// A bold banner that fades from red to blue:
+///
+/// ```
+/// use toon::{Rgb, Styled};
+///
+/// let element: toon::Gradient<_, ()> =
+///     toon::gradient_span("Welcome!", Rgb::new(255, 0, 0), Rgb::new(0, 0, 255)).bold();
+/// ```
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Gradient<T, Event> {
+    /// The text being displayed.
+    pub text: T,
+    /// The style to display the text in.
+    pub style: Style,
+    /// The color of the first character.
+    pub from: Color,
+    /// The color of the last character.
+    pub to: Color,
+    event: PhantomData<Event>,
+}
+
+impl<T: Display, Event> Gradient<T, Event> {
+    /// Get the width of the gradient.
+    pub fn width(&self) -> u16 {
+        let mut width = 0;
+
+        let mut writer = WriteGraphemesFn::new(|grapheme| {
+            width += grapheme_width(grapheme).unwrap_or(0) as u16;
+            Ok(())
+        });
+        write!(writer, "{}", self.text)
+            .and_then(|()| writer.finish())
+            .expect("formatting failed");
+
+        width
+    }
+
+    /// Get the color of the character at a column of the text.
+    #[must_use]
+    pub fn color_at(&self, x: u16) -> Color {
+        self.color_with_width(x, self.width())
+    }
+
+    fn color_with_width(&self, x: u16, width: u16) -> Color {
+        if width <= 1 {
+            return self.from;
+        }
+        self.from.mix(self.to, f64::from(x) / f64::from(width - 1))
+    }
+}
+
+impl<T, Event> AsRef<Style> for Gradient<T, Event> {
+    fn as_ref(&self) -> &Style {
+        &self.style
+    }
+}
+impl<T, Event> AsMut<Style> for Gradient<T, Event> {
+    fn as_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+}
+
+impl<T: Display, Event> Element for Gradient<T, Event> {
+    type Event = Event;
+
+    fn draw(&self, output: &mut dyn Output) {
+        let width = self.width();
+        let total_width = output.size().x;
+        let mut x = 0;
+
+        let mut writer = WriteGraphemesFn::new(|grapheme| {
+            let cluster_width = match grapheme_width(grapheme) {
+                Some(cluster_width) => cluster_width as u16,
+                None => return Ok(()),
+            };
+
+            let style = Style {
+                foreground: self.color_with_width(x, width),
+                ..self.style
+            };
+            output.write_grapheme(Vec2::new(x, 0), grapheme, style);
+
+            x += cluster_width;
+
+            if x >= total_width {
+                Err(std::fmt::Error)
+            } else {
+                Ok(())
+            }
+        });
+        let _ = write!(writer, "{}", self.text).and_then(|()| writer.finish());
+    }
+    fn ideal_width(&self, _height: u16, _max_width: Option<u16>) -> u16 {
+        self.width()
+    }
+    fn ideal_height(&self, _width: u16, _max_height: Option<u16>) -> u16 {
+        1
+    }
+    fn ideal_size(&self, _maximum: Vec2<Option<u16>>) -> Vec2<u16> {
+        Vec2::new(self.width(), 1)
+    }
+    fn handle(&self, _input: Input, _events: &mut dyn Events<Event>) {}
+}
+
+/// Create a span of text whose foreground color goes along a gradient from one color to another.
+///
+/// This is useful for headers and ASCII-art banners. If either color is [`Color::Default`] the
+/// colors can't be mixed, so the text switches from one color to the other halfway through.
+#[must_use]
+pub fn gradient_span<T: Display, Event>(
+    text: T,
+    from: impl Into<Color>,
+    to: impl Into<Color>,
+) -> Gradient<T, Event> {
+    Gradient {
+        text,
+        style: Style::default(),
+        from: from.into(),
+        to: to.into(),
+        event: PhantomData,
+    }
+}
+
+#[test]
+fn test_gradient() {
+    use crate::{Rgb, Styled};
+
+    let mut grid = crate::Grid::new((6, 1));
+
+    let gradient =
+        gradient_span::<_, ()>("abcde", Rgb::new(0, 0, 0), Rgb::new(200, 100, 0)).on_white();
+    assert_eq!(gradient.width(), 5);
+    assert_eq!(gradient.color_at(2), Color::Rgb(Rgb::new(100, 50, 0)));
+    gradient.draw(&mut grid);
+
+    crate::assert_grid_eq!(grid, ["abcde "]);
+
+    let styles: Vec<_> = grid.lines()[0]
+        .cells()
+        .iter()
+        .map(|cell| cell.style().unwrap())
+        .collect();
+    assert_eq!(
+        styles[0],
+        Style::default().foreground(Rgb::new(0, 0, 0)).on_white()
+    );
+    assert_eq!(
+        styles[1],
+        Style::default().foreground(Rgb::new(50, 25, 0)).on_white()
+    );
+    assert_eq!(
+        styles[4],
+        Style::default()
+            .foreground(Rgb::new(200, 100, 0))
+            .on_white()
+    );
+    assert_eq!(styles[5], Style::default());
+
+    let mut grid = crate::Grid::new((1, 1));
+    gradient_span::<_, ()>("x", Color::Red, Color::Blue).draw(&mut grid);
+    assert_eq!(
+        grid.lines()[0].cells()[0].style().unwrap().foreground,
+        Color::Red
+    );
+}
@@ -0,0 +1,118 @@
+use std::marker::PhantomData;
+
+use crate::output::{Ext as _, Output};
+use crate::{Bitmap, Element, Events, Input, Vec2};
+
+/// An image, created by the [`image`] function.
+///
+/// On terminals that can display images, the bitmap is scaled to cover the whole element. On other
+/// terminals it is approximated using half block characters, where each cell shows two pixels
+/// stacked on top of each other. Its ideal size is the size of the approximation, keeping the
+/// aspect ratio of the bitmap.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Image<'a, Event> {
+    /// The bitmap being displayed.
+    pub bitmap: &'a Bitmap,
+    event: PhantomData<Event>,
+}
+
+impl<'a, Event> Element for Image<'a, Event> {
+    type Event = Event;
+
+    fn draw(&self, output: &mut dyn Output) {
+        let size = output.size();
+        if size.x == 0 || size.y == 0 || self.bitmap.size().x == 0 || self.bitmap.size().y == 0 {
+            return;
+        }
+
+        output.draw_half_blocks(Vec2::new(0, 0), size, self.bitmap);
+        output.draw_image(Vec2::new(0, 0), size, self.bitmap);
+    }
+    fn ideal_width(&self, height: u16, _max_width: Option<u16>) -> u16 {
+        let bitmap = self.bitmap.size().map(u32::from);
+        if bitmap.y == 0 {
+            return 0;
+        }
+        (bitmap.x * u32::from(height) * 2 / bitmap.y) as u16
+    }
+    fn ideal_height(&self, width: u16, _max_height: Option<u16>) -> u16 {
+        let bitmap = self.bitmap.size().map(u32::from);
+        if bitmap.x == 0 {
+            return 0;
+        }
+        ((bitmap.y * u32::from(width) + bitmap.x * 2 - 1) / (bitmap.x * 2)) as u16
+    }
+    fn ideal_size(&self, maximum: Vec2<Option<u16>>) -> Vec2<u16> {
+        let mut size = Vec2::new(
+            self.bitmap.size().x,
+            self.ideal_height(self.bitmap.size().x, None),
+        );
+        if let Some(max_width) = maximum.x.filter(|&max| size.x > max) {
+            size = Vec2::new(max_width, self.ideal_height(max_width, None));
+        }
+        if let Some(max_height) = maximum.y.filter(|&max| size.y > max) {
+            size = Vec2::new(self.ideal_width(max_height, None), max_height);
+        }
+        size
+    }
+    fn handle(&self, _input: Input, _events: &mut dyn Events<Event>) {}
+}
+
+/// Create an image from a bitmap.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Bitmap, Rgb};
+///
+/// // A red pixel above a blue pixel.
+/// let bitmap = Bitmap::new((1, 2), vec![Rgb::new(255, 0, 0), Rgb::new(0, 0, 255)]);
+/// let element: toon::Image<'_, ()> = toon::image(&bitmap);
+/// ```
+#[must_use]
+pub fn image<Event>(bitmap: &Bitmap) -> Image<'_, Event> {
+    Image {
+        bitmap,
+        event: PhantomData,
+    }
+}
+
+#[test]
+fn test_image() {
+    use crate::{Buffer, Color, Grid, ImageArea, Rgb};
+
+    let red = Rgb::new(255, 0, 0);
+    let blue = Rgb::new(0, 0, 255);
+    let bitmap = Bitmap::new((2, 2), vec![red, red, blue, red]);
+    let element = image::<()>(&bitmap);
+
+    assert_eq!(element.ideal_size(Vec2::new(None, None)), Vec2::new(2, 1));
+    assert_eq!(
+        element.ideal_size(Vec2::new(Some(1), None)),
+        Vec2::new(1, 1)
+    );
+    assert_eq!(
+        element.ideal_size(Vec2::new(None, Some(2))),
+        Vec2::new(2, 1)
+    );
+
+    let mut buffer = Buffer::from(Grid::new((3, 1)));
+    element.draw(&mut crate::output::Ext::area(&mut buffer, (1, 0), (2, 1)));
+
+    crate::assert_grid_eq!(buffer.grid, [" ▀▀"]);
+    let styles: Vec<_> = buffer.grid.lines()[0]
+        .cells()
+        .iter()
+        .map(|cell| cell.style().unwrap())
+        .collect();
+    assert_eq!(styles[1].background, Color::Rgb(blue));
+    assert_eq!(styles[2].background, Color::Rgb(red));
+    assert_eq!(
+        buffer.images,
+        [ImageArea {
+            pos: Vec2::new(1, 0),
+            size: Vec2::new(2, 1),
+            bitmap: bitmap.clone(),
+        }],
+    );
+}
@@ -0,0 +1,210 @@
+use std::collections::VecDeque;
+
+use crate::{input, Element, ElementExt, ScrollOffset};
+
+/// A scrollable pane of lines of text, such as captured standard output and standard error.
+///
+/// Like [`Dev`](crate::Dev), this is state that you keep around between draws. Feed it lines with
+/// [`push`](Self::push) (for example from a
+/// [`CapturedLines`](https://docs.rs/toon/0.1/toon/struct.CapturedLines.html) stream when the
+/// `stream` feature is enabled), create an element from it with [`element`](Self::element) and
+/// apply the events that element produces with [`apply`](Self::apply).
+///
+/// The pane sticks to the bottom as new lines come in, unless the user has scrolled up.
+///
+/// # Examples
+///
+/// ```
+/// use toon::ElementExt;
+///
+/// let mut log = toon::Log::new();
+/// log.push("Hello World!");
+///
+/// let element = toon::row(toon::stretch(0), (
+///     toon::span("My app").map_event(|()| None),
+///     log.element().map_event(Some),
+/// ));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Log {
+    /// The lines in the log, from oldest to newest.
+    lines: VecDeque<String>,
+    /// The maximum number of lines to keep.
+    max_lines: Option<usize>,
+    /// How many lines the pane is scrolled up from the bottom.
+    scroll: u16,
+    /// Whether long lines are wrapped.
+    wrap: bool,
+}
+
+impl Log {
+    /// Create a new empty log that keeps every line.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of lines kept in the log. Older lines are discarded first.
+    #[must_use]
+    pub fn max_lines(self, max_lines: usize) -> Self {
+        let mut this = Self {
+            max_lines: Some(max_lines),
+            ..self
+        };
+        this.truncate();
+        this
+    }
+
+    /// Wrap lines that are too long for the pane onto multiple rows instead of cutting them off.
+    ///
+    /// Lines are wrapped again whenever the pane is drawn, so they reflow when it is resized.
+    #[must_use]
+    pub fn wrap(self) -> Self {
+        Self { wrap: true, ..self }
+    }
+
+    /// Add a line to the bottom of the log.
+    ///
+    /// If the line contains newlines, it will be split into multiple lines.
+    pub fn push(&mut self, line: impl Into<String>) {
+        let line = line.into();
+
+        let added = if line.contains('\n') {
+            let lines: Vec<_> = line.lines().map(str::to_owned).collect();
+            let added = lines.len();
+            self.lines.extend(lines);
+            added
+        } else {
+            self.lines.push_back(line);
+            1
+        };
+
+        // Keep the same lines in view if the user has scrolled up.
+        if self.scroll > 0 {
+            self.scroll = self.scroll.saturating_add(added as u16);
+        }
+
+        self.truncate();
+    }
+
+    /// Remove all the lines from the log.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll = 0;
+    }
+
+    /// Get the lines in the log, from oldest to newest.
+    pub fn lines(&self) -> impl Iterator<Item = &str> + DoubleEndedIterator + '_ {
+        self.lines.iter().map(|line| &**line)
+    }
+
+    /// Create an element displaying the log.
+    ///
+    /// Scrolling the mouse wheel over it scrolls the log.
+    #[must_use]
+    pub fn element(&self) -> impl Element<Event = LogEvent> + '_ {
+        crate::column(
+            crate::Static,
+            self.lines
+                .iter()
+                .map(|line| {
+                    let span = crate::span(line);
+                    if self.wrap {
+                        span.wrapped()
+                    } else {
+                        span
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+        .scroll_y(ScrollOffset::End(self.scroll))
+        .on(input!(Mouse(ScrollUp)), |_| LogEvent(LogEventKind::ScrollUp))
+        .on(input!(Mouse(ScrollDown)), |_| {
+            LogEvent(LogEventKind::ScrollDown)
+        })
+    }
+
+    /// Apply the event to the log.
+    pub fn apply(&mut self, event: LogEvent) {
+        match event.0 {
+            LogEventKind::ScrollUp => {
+                let max_scroll = self.lines.len().saturating_sub(1) as u16;
+                if self.scroll < max_scroll {
+                    self.scroll += 1;
+                }
+            }
+            LogEventKind::ScrollDown => {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+        }
+    }
+
+    fn truncate(&mut self) {
+        if let Some(max_lines) = self.max_lines {
+            while self.lines.len() > max_lines {
+                self.lines.pop_front();
+            }
+            self.scroll = std::cmp::min(self.scroll, max_lines.saturating_sub(1) as u16);
+        }
+    }
+}
+
+impl<S: Into<String>> Extend<S> for Log {
+    fn extend<T: IntoIterator<Item = S>>(&mut self, iter: T) {
+        for line in iter {
+            self.push(line);
+        }
+    }
+}
+
+/// An event in a [`Log`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogEvent(LogEventKind);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogEventKind {
+    ScrollUp,
+    ScrollDown,
+}
+
+#[test]
+fn test_log() {
+    let mut log = Log::new().max_lines(4);
+    log.extend(vec!["a", "b", "c\nd", "e"]);
+    assert_eq!(log.lines().collect::<Vec<_>>(), ["b", "c", "d", "e"]);
+
+    let mut grid = crate::Grid::new((1, 2));
+    log.element().draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["d", "e"]);
+
+    log.apply(LogEvent(LogEventKind::ScrollUp));
+    grid.clear();
+    log.element().draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["c", "d"]);
+
+    // New lines don't move the view while scrolled up.
+    log.push("f");
+    grid.clear();
+    log.element().draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["c", "d"]);
+
+    log.apply(LogEvent(LogEventKind::ScrollDown));
+    log.apply(LogEvent(LogEventKind::ScrollDown));
+    grid.clear();
+    log.element().draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["e", "f"]);
+}
+
+#[test]
+fn test_log_wrap() {
+    let mut log = Log::new().wrap();
+    log.extend(vec!["abc", "defgh"]);
+
+    let mut grid = crate::Grid::new((3, 3));
+    log.element().draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["abc", "def", "gh "]);
+
+    let mut grid = crate::Grid::new((2, 3));
+    log.element().draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["de", "fg", "h "]);
+}
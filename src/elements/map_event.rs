@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::events::Events;
+use crate::tree::Tree;
 use crate::{Element, Input, Output, Vec2};
 
 /// An element that maps the event type of an element, created by the
@@ -46,4 +47,7 @@ impl<T: Element, F: Fn(T::Event) -> Event2, Event2> Element for MapEvent<T, F> {
     fn title(&self, title: &mut dyn fmt::Write) -> fmt::Result {
         self.inner.title(title)
     }
+    fn describe(&self, tree: &mut Tree) {
+        tree.node("MapEvent", |tree| self.inner.describe(tree));
+    }
 }
@@ -5,7 +5,7 @@
 
 use std::fmt::Display;
 
-use crate::{input, Color, Element, Input, Vec2};
+use crate::{input, keymap, Color, Element, Input, Link, Style, Vec2};
 
 pub mod containers;
 pub use containers::*;
@@ -19,12 +19,33 @@ pub use dev::Dev;
 pub mod filter;
 pub use filter::*;
 
+mod ansi_text;
+pub use ansi_text::*;
+
 mod block;
 pub use block::*;
 
+mod gradient;
+pub use gradient::*;
+
+mod image;
+pub use image::*;
+
+mod log;
+pub use log::*;
+
 mod map_event;
 pub use map_event::*;
 
+#[cfg(feature = "ratatui")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "ratatui")))]
+mod ratatui;
+#[cfg(feature = "ratatui")]
+pub use self::ratatui::*;
+
+mod rich;
+pub use rich::*;
+
 mod span;
 pub use span::*;
 
@@ -73,6 +94,59 @@ pub trait ElementExt: Element + Sized {
         self.filter(On::new(input_pattern, event).passive())
     }
 
+    /// Trigger an event when an input occurs in the bubble phase; that is, after the inner element
+    /// has handled the input, and only if it didn't stop propagation of the input.
+    ///
+    /// This stops propagation of the input if the event is triggered, so outer bubble listeners
+    /// will not fire. See [`Phase`] for more.
+    #[must_use]
+    fn on_bubble<I: input::Pattern, F: Fn(Input) -> Self::Event>(
+        self,
+        input_pattern: I,
+        event: F,
+    ) -> Filtered<Self, On<I, F>> {
+        self.filter(On::new(input_pattern, event).bubble())
+    }
+
+    /// Trigger an event when an action is triggered by a key bound to it in the terminal's
+    /// [`Keymap`](crate::Keymap).
+    ///
+    /// Like [`on`](Self::on), the created element will listen to inputs _actively_, so the key
+    /// that triggered the action will not be given to any elements.
+    #[must_use]
+    fn on_action<F: Fn(Input) -> Self::Event>(
+        self,
+        action: &str,
+        event: F,
+    ) -> Filtered<Self, On<keymap::Action<'_>, F>> {
+        self.on(keymap::Action { name: action }, event)
+    }
+
+    /// Trigger an event when a key is pressed anywhere in the application, even if an element
+    /// outside this one masks inputs, focuses another element or only gives inputs to the top of a
+    /// stack.
+    ///
+    /// This is useful for shortcuts like Ctrl+C or F1 for help that should work even while a modal
+    /// is open. If the event is triggered the key will not be given to any elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use toon::ElementExt;
+    /// # let element = toon::empty();
+    /// # #[derive(Clone)]
+    /// # enum Event { Help }
+    /// let element = element.on_global(toon::input!(Key(F1)), |_| Event::Help);
+    /// ```
+    #[must_use]
+    fn on_global<P: input::Pattern, F: Fn(Input) -> Self::Event>(
+        self,
+        pattern: P,
+        event: F,
+    ) -> Filtered<Self, On<input::Shortcut<P>, F>> {
+        self.on(input::Shortcut(pattern), event)
+    }
+
     /// Make the element float in both axes with the given alignment.
     ///
     /// # Example
@@ -211,6 +285,30 @@ pub trait ElementExt: Element + Sized {
         })
     }
 
+    /// Set the base style of the element, whose colors are used for everything the element draws
+    /// with [`Color::Default`] colors.
+    ///
+    /// This lets a container set the default text color of all the elements inside it.
+    #[must_use]
+    fn base_style(self, style: Style) -> Filtered<Self, BaseStyle> {
+        self.filter(BaseStyle { style })
+    }
+
+    /// Make everything the element draws a hyperlink to the URL, in terminals that support it.
+    #[must_use]
+    fn link(self, url: &str) -> Filtered<Self, Hyperlink> {
+        self.filter(Hyperlink {
+            link: Link::new(url),
+        })
+    }
+
+    /// Join the box-drawing characters the element draws on top of each other, so that for
+    /// example a line drawn over a border meets it with a junction like `┼`.
+    #[must_use]
+    fn join_box_drawing(self) -> Filtered<Self, BoxDrawingJoints> {
+        self.filter(BoxDrawingJoints)
+    }
+
     /// Set the ratio of the element.
     #[must_use]
     fn ratio(self, ratio: f64) -> Filtered<Self, Ratio> {
@@ -0,0 +1,172 @@
+use std::marker::PhantomData;
+
+use ratatui_crate::buffer::{Buffer, Cell};
+use ratatui_crate::layout::Rect;
+use ratatui_crate::style::{Color as RColor, Modifier};
+use ratatui_crate::widgets::Widget;
+
+use crate::{Attributes, Color, Element, Events, Input, Intensity, Output, Rgb, Style, Vec2};
+
+/// An element that draws a [Ratatui](https://crates.io/crates/ratatui) widget, created by the
+/// [`ratatui_widget`] function.
+///
+/// Ratatui widgets are consumed when they are rendered, so the widget is cloned each time the
+/// element is drawn. The widget is rendered into a Ratatui buffer the size of the output, which is
+/// then copied to the output. Cells that the widget leaves blank and unstyled aren't copied, so
+/// whatever is behind them stays visible.
+///
+/// Widgets fill whatever area they are given and don't handle input, so this element has an ideal
+/// size of zero and ignores all inputs. Give it a size with a layout or the
+/// [`size`](crate::ElementExt::size) filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RatatuiWidget<W, Event> {
+    /// The widget that is drawn.
+    pub widget: W,
+    event: PhantomData<Event>,
+}
+
+impl<W: Widget + Clone, Event> Element for RatatuiWidget<W, Event> {
+    type Event = Event;
+
+    fn draw(&self, output: &mut dyn Output) {
+        let size = output.size();
+        let area = Rect::new(0, 0, size.x, size.y);
+        let mut buffer = Buffer::empty(area);
+        self.widget.clone().render(area, &mut buffer);
+
+        let blank = Cell::default();
+        for y in 0..size.y {
+            // The cells covered by the second column of double-width graphemes are filled with
+            // spaces by Ratatui, and must be skipped so they don't overwrite the grapheme.
+            let mut covered = false;
+            for x in 0..size.x {
+                if covered {
+                    covered = false;
+                    continue;
+                }
+                let cell = buffer.get(x, y);
+                covered = crate::util::grapheme_width(cell.symbol()) == Some(2);
+
+                if !cell.skip && *cell != blank {
+                    output.write_grapheme(Vec2::new(x, y), cell.symbol(), cell_style(cell));
+                }
+            }
+        }
+    }
+    fn ideal_width(&self, _height: u16, _max_width: Option<u16>) -> u16 {
+        0
+    }
+    fn ideal_height(&self, _width: u16, _max_height: Option<u16>) -> u16 {
+        0
+    }
+    fn ideal_size(&self, _maximum: Vec2<Option<u16>>) -> Vec2<u16> {
+        Vec2::new(0, 0)
+    }
+    fn handle(&self, _input: Input, _events: &mut dyn Events<Event>) {}
+}
+
+/// Create an element that draws a [Ratatui](https://crates.io/crates/ratatui) widget.
+///
+/// This allows widgets from the Ratatui ecosystem to be reused in Toon, for example while
+/// migrating an application. See [`RatatuiWidget`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use toon::ratatui::widgets::{Block, Borders, Paragraph};
+/// use toon::Element;
+///
+/// let paragraph = Paragraph::new("Hello").block(Block::default().borders(Borders::ALL));
+/// let element = toon::ratatui_widget::<_, ()>(paragraph);
+///
+/// let mut grid = toon::Grid::new((7, 3));
+/// element.draw(&mut grid);
+/// toon::assert_grid_eq!(grid, ["┌─────┐", "│Hello│", "└─────┘"]);
+/// ```
+#[must_use]
+pub fn ratatui_widget<W: Widget + Clone, Event>(widget: W) -> RatatuiWidget<W, Event> {
+    RatatuiWidget {
+        widget,
+        event: PhantomData,
+    }
+}
+
+/// Get the style of a cell of a Ratatui buffer.
+fn cell_style(cell: &Cell) -> Style {
+    let modifier = cell.modifier;
+    let mut style = Style::new(
+        cell.fg.into(),
+        cell.bg.into(),
+        Attributes {
+            intensity: if modifier.contains(Modifier::BOLD) {
+                Intensity::Bold
+            } else if modifier.contains(Modifier::DIM) {
+                Intensity::Dim
+            } else {
+                Intensity::Normal
+            },
+            italic: modifier.contains(Modifier::ITALIC),
+            underlined: modifier.contains(Modifier::UNDERLINED),
+            blinking: modifier.intersects(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+            crossed_out: modifier.contains(Modifier::CROSSED_OUT),
+            reversed: modifier.contains(Modifier::REVERSED),
+            hidden: modifier.contains(Modifier::HIDDEN),
+            ..Attributes::default()
+        },
+    );
+    style.underline_color = cell.underline_color.into();
+    style
+}
+
+impl From<RColor> for Color {
+    fn from(color: RColor) -> Self {
+        match color {
+            RColor::Reset => Self::Default,
+            RColor::Black => Self::Black,
+            RColor::Red => Self::DarkRed,
+            RColor::Green => Self::DarkGreen,
+            RColor::Yellow => Self::DarkYellow,
+            RColor::Blue => Self::DarkBlue,
+            RColor::Magenta => Self::DarkMagenta,
+            RColor::Cyan => Self::DarkCyan,
+            RColor::Gray => Self::LightGray,
+            RColor::DarkGray => Self::DarkGray,
+            RColor::LightRed => Self::Red,
+            RColor::LightGreen => Self::Green,
+            RColor::LightYellow => Self::Yellow,
+            RColor::LightBlue => Self::Blue,
+            RColor::LightMagenta => Self::Magenta,
+            RColor::LightCyan => Self::Cyan,
+            RColor::White => Self::White,
+            RColor::Rgb(r, g, b) => Self::Rgb(Rgb::new(r, g, b)),
+            RColor::Indexed(value) => Self::new_ansi(value),
+        }
+    }
+}
+
+#[test]
+fn test_ratatui_widget() {
+    use ratatui_crate::style::Style as RStyle;
+    use ratatui_crate::widgets::Paragraph;
+
+    use crate::output::Ext as _;
+    use crate::Styled as _;
+
+    let mut grid = crate::Grid::new((4, 2));
+    grid.write((0, 0), "abcd", Style::default());
+    grid.write((0, 1), "efgh", Style::default());
+
+    let paragraph = Paragraph::new("日x\ny").style(RStyle::default().fg(RColor::LightRed));
+    ratatui_widget::<_, ()>(paragraph).draw(&mut (&mut grid).area((1, 0), (3, 2)));
+    crate::assert_grid_eq!(grid, ["a日x", "ey  "]);
+    assert_eq!(
+        grid.lines()[0].cells()[1].style(),
+        Some(Style::default().red())
+    );
+
+    // Blank cells are transparent.
+    let mut grid = crate::Grid::new((3, 1));
+    grid.write((0, 0), "abc", Style::default());
+    ratatui_widget::<_, ()>(Paragraph::new(" x")).draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["axc"]);
+}
@@ -0,0 +1,402 @@
+use std::fmt::Display;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use crate::{
+    output::{Ext as _, Output},
+    Element, Events, Input, ParseMarkupError, Span, Style, Vec2,
+};
+
+/// A single line of text made up of segments with different styles.
+///
+/// Rich text is displayed with the [`rich_span`] function. It can be built up segment by segment
+/// or parsed from [`markup`](crate::markup).
+///
+/// # Examples
+///
+/// ```
+/// use toon::{RichText, Style, Styled};
+///
+/// let text = RichText::new()
+///     .with("Error: ", Style::default().red().bold())
+///     .with("file not found", Style::default());
+/// assert_eq!(text.to_string(), "Error: file not found");
+/// ```
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct RichText {
+    /// The segments of the text and their styles, in order.
+    pub segments: Vec<(String, Style)>,
+}
+
+impl RichText {
+    /// Create empty rich text.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse rich text from markup like `<bold red>text</>`.
+    ///
+    /// See [`markup`](crate::markup) for the syntax.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the markup is invalid.
+    pub fn markup(text: &str) -> Result<Self, ParseMarkupError> {
+        Ok(crate::markup::<()>(text)?.into_iter().collect())
+    }
+
+    /// Add a segment to the end of the text.
+    ///
+    /// If the segment has the same style as the last segment the two are joined together.
+    pub fn push(&mut self, text: impl AsRef<str>, style: Style) {
+        let text = text.as_ref();
+        match self.segments.last_mut() {
+            _ if text.is_empty() => {}
+            Some((last, last_style)) if *last_style == style => last.push_str(text),
+            _ => self.segments.push((text.to_owned(), style)),
+        }
+    }
+
+    /// Add a segment to the end of the text, builder-style.
+    #[must_use]
+    pub fn with(mut self, text: impl AsRef<str>, style: Style) -> Self {
+        self.push(text, style);
+        self
+    }
+
+    /// Get the width of the text.
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        self.segments
+            .iter()
+            .map(|(text, _)| crate::util::str_width(text) as u16)
+            .sum()
+    }
+}
+
+impl AsRef<RichText> for RichText {
+    fn as_ref(&self) -> &RichText {
+        self
+    }
+}
+
+/// Displays the text without any styling.
+impl Display for RichText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (text, _) in &self.segments {
+            f.write_str(text)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for RichText {
+    fn from(text: &str) -> Self {
+        Self::new().with(text, Style::default())
+    }
+}
+impl From<String> for RichText {
+    fn from(text: String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+impl<T: AsRef<str>> FromIterator<(T, Style)> for RichText {
+    fn from_iter<I: IntoIterator<Item = (T, Style)>>(iter: I) -> Self {
+        let mut text = Self::new();
+        text.extend(iter);
+        text
+    }
+}
+impl<T: AsRef<str>> Extend<(T, Style)> for RichText {
+    fn extend<I: IntoIterator<Item = (T, Style)>>(&mut self, iter: I) {
+        for (segment, style) in iter {
+            self.push(segment, style);
+        }
+    }
+}
+
+impl<T: AsRef<str>, Event> FromIterator<Span<T, Event>> for RichText {
+    fn from_iter<I: IntoIterator<Item = Span<T, Event>>>(iter: I) -> Self {
+        iter.into_iter()
+            .map(|span| (span.text, span.style))
+            .collect()
+    }
+}
+
+/// An element displaying [`RichText`], created by the [`rich_span`] function.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct RichSpan<T, Event> {
+    /// The text being displayed.
+    pub text: T,
+    event: PhantomData<Event>,
+}
+
+impl<T: AsRef<RichText>, Event> Element for RichSpan<T, Event> {
+    type Event = Event;
+
+    fn draw(&self, output: &mut dyn Output) {
+        let mut x: u16 = 0;
+        for (text, style) in &self.text.as_ref().segments {
+            if x >= output.size().x {
+                break;
+            }
+            output.write((x, 0), text, *style);
+            x = x.saturating_add(crate::util::str_width(text) as u16);
+        }
+    }
+    fn ideal_width(&self, _height: u16, _max_width: Option<u16>) -> u16 {
+        self.text.as_ref().width()
+    }
+    fn ideal_height(&self, _width: u16, _max_height: Option<u16>) -> u16 {
+        1
+    }
+    fn ideal_size(&self, _maximum: Vec2<Option<u16>>) -> Vec2<u16> {
+        Vec2::new(self.text.as_ref().width(), 1)
+    }
+    fn handle(&self, _input: Input, _events: &mut dyn Events<Event>) {}
+}
+
+/// Create an element that displays a line of [`RichText`].
+///
+/// It takes either the rich text itself or a reference to it.
+///
+/// # Examples
+///
+/// ```
+/// use toon::RichText;
+///
+/// let text = RichText::markup("Press <bold>q</> to quit")?;
+/// let element: toon::RichSpan<_, ()> = toon::rich_span(&text);
+/// # Ok::<_, toon::ParseMarkupError>(())
+/// ```
+#[must_use]
+pub fn rich_span<T: AsRef<RichText>, Event>(text: T) -> RichSpan<T, Event> {
+    RichSpan {
+        text,
+        event: PhantomData,
+    }
+}
+
+/// Create [`RichText`] from a format string containing style switches, like
+/// `styled_format!("{bold}{}{/bold} items", count)`.
+///
+/// Arguments are written like in [`format!`]: `{}` is the next positional argument, `{0}` is the
+/// first positional argument and `{name}` is the argument given as `name = value`. They are
+/// formatted with [`Display`](std::fmt::Display); format specifiers like `{:>3}` aren't supported,
+/// so use [`format_args!`] for the argument instead. Literal braces are written as `{{` and `}}`.
+///
+/// Anything else in braces is a style switch written like a [`Style`] parsed from a string, which
+/// is applied on top of the style of the surrounding text until it is closed. A closing switch can
+/// either be empty, like `{/}`, or repeat the first word of the opening switch, like
+/// `{fg=red}text{/fg}`. This works like [`markup`](crate::markup).
+///
+/// # Errors
+///
+/// Fails with the offending part of the format string if it contains an unmatched brace, an
+/// invalid style switch, a switch that is closed by the wrong name or an argument that doesn't
+/// exist.
+///
+/// # Examples
+///
+/// ```
+/// let count = 3;
+/// let status = toon::styled_format!("{bold}{}{/bold} items in {path}", count, path = "~/src")?;
+/// assert_eq!(status.to_string(), "3 items in ~/src");
+///
+/// let element: toon::RichSpan<_, ()> = toon::rich_span(status);
+/// # Ok::<_, toon::ParseMarkupError>(())
+/// ```
+#[macro_export]
+macro_rules! styled_format {
+    ($format:expr $(, $($args:tt)*)?) => {
+        $crate::__internal_styled_format!($format, [] $($($args)*)?)
+    };
+}
+
+/// Collects the arguments of `styled_format!` in the square brackets.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __internal_styled_format {
+    ($format:expr, [$($collected:tt)*]) => {
+        $crate::__styled_format($format, &[$($collected)*])
+    };
+    ($format:expr, [$($collected:tt)*] $name:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__internal_styled_format!(
+            $format,
+            [
+                $($collected)*
+                (
+                    $crate::std::option::Option::Some($crate::std::stringify!($name)),
+                    &$value as &dyn $crate::std::fmt::Display,
+                ),
+            ]
+            $($($rest)*)?
+        )
+    };
+    ($format:expr, [$($collected:tt)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__internal_styled_format!(
+            $format,
+            [
+                $($collected)*
+                (
+                    $crate::std::option::Option::None,
+                    &$value as &dyn $crate::std::fmt::Display,
+                ),
+            ]
+            $($($rest)*)?
+        )
+    };
+}
+
+#[doc(hidden)]
+pub fn __styled_format(
+    format: &str,
+    args: &[(Option<&str>, &dyn Display)],
+) -> Result<RichText, ParseMarkupError> {
+    let positional: Vec<&dyn Display> = args
+        .iter()
+        .filter(|(name, _)| name.is_none())
+        .map(|&(_, arg)| arg)
+        .collect();
+    let mut next_positional = positional.iter();
+
+    let mut text = RichText::new();
+    let mut style = Style::default();
+    // The open style switches, with the style before each one and its first word.
+    let mut open: Vec<(Style, &str)> = Vec::new();
+    let mut rest = format;
+
+    while let Some(start) = rest.find(|c| c == '{' || c == '}') {
+        text.push(&rest[..start], style);
+        let brace = &rest[start..=start];
+        rest = &rest[start + 1..];
+
+        if let Some(after) = rest.strip_prefix(brace) {
+            text.push(brace, style);
+            rest = after;
+            continue;
+        }
+        if brace == "}" {
+            return Err(ParseMarkupError("}".to_owned()));
+        }
+
+        let end = rest
+            .find('}')
+            .ok_or_else(|| ParseMarkupError(format!("{{{}", rest)))?;
+        let inner = &rest[..end];
+        rest = &rest[end + 1..];
+        let error = || ParseMarkupError(format!("{{{}}}", inner));
+
+        let arg = if inner.is_empty() {
+            next_positional.next()
+        } else if let Ok(index) = inner.parse::<usize>() {
+            positional.get(index)
+        } else {
+            args.iter()
+                .find(|(name, _)| *name == Some(inner))
+                .map(|(_, arg)| arg)
+        };
+
+        if let Some(arg) = arg {
+            text.push(arg.to_string(), style);
+        } else if inner.is_empty() || inner.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(error());
+        } else if let Some(name) = inner.strip_prefix('/') {
+            let name = name.trim();
+            match open.pop() {
+                Some((previous, open_name))
+                    if name.is_empty() || name.eq_ignore_ascii_case(open_name) =>
+                {
+                    style = previous;
+                }
+                _ => return Err(error()),
+            }
+        } else {
+            let previous = style;
+            style.apply_words(inner).map_err(|_| error())?;
+            open.push((previous, inner.split_whitespace().next().unwrap_or("")));
+        }
+    }
+    text.push(rest, style);
+
+    Ok(text)
+}
+
+#[test]
+fn test_rich_text() {
+    use crate::Styled;
+
+    let bold = Style::default().bold();
+    let red = Style::default().red();
+
+    let text = RichText::new()
+        .with("ab", bold)
+        .with("", red)
+        .with("c", bold)
+        .with("de", red);
+    assert_eq!(
+        text.segments,
+        [("abc".to_owned(), bold), ("de".to_owned(), red)]
+    );
+    assert_eq!(text.width(), 5);
+    assert_eq!(text.to_string(), "abcde");
+    assert_eq!(RichText::markup("<b>abc</><red>de</>").unwrap(), text);
+
+    let mut grid = crate::Grid::new((4, 1));
+    rich_span::<_, ()>(&text).draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["abcd"]);
+
+    let styles: Vec<_> = grid.lines()[0]
+        .cells()
+        .iter()
+        .map(|cell| cell.style().unwrap())
+        .collect();
+    assert_eq!(styles, [bold, bold, bold, red]);
+}
+
+#[test]
+fn test_styled_format() {
+    use crate::Styled;
+
+    let plain = Style::default();
+    let count = 3;
+
+    assert_eq!(
+        crate::styled_format!("plain").unwrap(),
+        RichText::from("plain")
+    );
+    assert_eq!(
+        crate::styled_format!(
+            "{{{bold}{}{/bold} {1} {name}}} {red on black}{0}{/}",
+            count,
+            "x",
+            name = format_args!("{:>3}", "n"),
+        )
+        .unwrap(),
+        RichText::new()
+            .with("{", plain)
+            .with("3", plain.bold())
+            .with(" x   n", plain)
+            .with("}", plain)
+            .with(" ", plain)
+            .with("3", plain.red().on_black())
+    );
+}
+
+#[test]
+fn test_styled_format_errors() {
+    let error = |tag: &str| Err(ParseMarkupError(tag.to_owned()));
+
+    assert_eq!(
+        crate::styled_format!("{bold}x{/italic}"),
+        error("{/italic}")
+    );
+    assert_eq!(crate::styled_format!("{/}"), error("{/}"));
+    assert_eq!(crate::styled_format!("{nonsense}"), error("{nonsense}"));
+    assert_eq!(crate::styled_format!("{} {}", 1), error("{}"));
+    assert_eq!(crate::styled_format!("{1}", 1), error("{1}"));
+    assert_eq!(crate::styled_format!("a}b"), error("}"));
+    assert_eq!(crate::styled_format!("a{bold"), error("{bold"));
+}
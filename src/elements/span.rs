@@ -1,10 +1,11 @@
-use std::fmt::{Display, Write};
+use std::cmp;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter, Write};
 use std::marker::PhantomData;
 
-use unicode_width::UnicodeWidthChar;
-
 use crate::{
     output::{Ext as _, Output},
+    tree::Tree,
     Element, Events, Input, Style, Vec2,
 };
 
@@ -25,26 +26,69 @@ pub struct Span<T, Event> {
     pub text: T,
     /// The style to display the text in.
     pub style: Style,
+    wrap: bool,
     event: PhantomData<Event>,
 }
 
 impl<T: Display, Event> Span<T, Event> {
+    /// Wrap the text onto as many rows as it needs instead of cutting it off at the edge of the
+    /// output.
+    ///
+    /// The text is wrapped between grapheme clusters like in a terminal, and it is wrapped again
+    /// whenever it is drawn, so it reflows when the width of the output changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toon::Element;
+    ///
+    /// let mut grid = toon::Grid::new((4, 2));
+    /// toon::span::<_, ()>("Hello!").wrapped().draw(&mut grid);
+    /// toon::assert_grid_eq!(grid, ["Hell", "o!  "]);
+    /// ```
+    #[must_use]
+    pub fn wrapped(self) -> Self {
+        Self { wrap: true, ..self }
+    }
+
     /// Get the width of the span.
     pub fn width(&self) -> u16 {
         let mut width = 0;
 
-        write!(
-            crate::util::WriteCharsFn(|c| {
-                width += c.width().unwrap_or(0) as u16;
-                Ok(())
-            }),
-            "{}",
-            self.text
-        )
-        .expect("formatting failed");
+        let mut writer = crate::util::WriteGraphemesFn::new(|grapheme| {
+            width += crate::util::grapheme_width(grapheme).unwrap_or(0) as u16;
+            Ok(())
+        });
+        write!(writer, "{}", self.text)
+            .and_then(|()| writer.finish())
+            .expect("formatting failed");
 
         width
     }
+
+    /// Lay out the grapheme clusters of the span wrapped to a width, returning the number of rows
+    /// taken up.
+    fn wrap_at(&self, width: u16, mut f: impl FnMut(Vec2<u16>, &str)) -> u16 {
+        let mut pos = Vec2::<u16>::new(0, 0);
+
+        let mut writer = crate::util::WriteGraphemesFn::new(|grapheme| {
+            let grapheme_width = match crate::util::grapheme_width(grapheme) {
+                Some(width) => width as u16,
+                None => return Ok(()),
+            };
+            if pos.x > 0 && pos.x.saturating_add(grapheme_width) > width {
+                pos = Vec2::new(0, pos.y.saturating_add(1));
+            }
+            f(pos, grapheme);
+            pos.x = pos.x.saturating_add(grapheme_width);
+            Ok(())
+        });
+        write!(writer, "{}", self.text)
+            .and_then(|()| writer.finish())
+            .expect("formatting failed");
+
+        pos.y.saturating_add(1)
+    }
 }
 
 impl<T, Event> AsRef<Style> for Span<T, Event> {
@@ -62,18 +106,40 @@ impl<T: Display, Event> Element for Span<T, Event> {
     type Event = Event;
 
     fn draw(&self, output: &mut dyn Output) {
-        output.write((0, 0), &self.text, self.style);
+        if self.wrap {
+            self.wrap_at(output.size().x, |pos, grapheme| {
+                output.write_grapheme(pos, grapheme, self.style);
+            });
+        } else {
+            output.write((0, 0), &self.text, self.style);
+        }
     }
-    fn ideal_width(&self, _height: u16, _max_width: Option<u16>) -> u16 {
-        self.width()
+    fn ideal_width(&self, _height: u16, max_width: Option<u16>) -> u16 {
+        match max_width {
+            Some(max_width) if self.wrap => cmp::min(self.width(), max_width),
+            _ => self.width(),
+        }
     }
-    fn ideal_height(&self, _width: u16, _max_height: Option<u16>) -> u16 {
-        1
+    fn ideal_height(&self, width: u16, _max_height: Option<u16>) -> u16 {
+        if self.wrap {
+            self.wrap_at(width, |_, _| {})
+        } else {
+            1
+        }
     }
-    fn ideal_size(&self, _maximum: Vec2<Option<u16>>) -> Vec2<u16> {
-        Vec2::new(self.width(), 1)
+    fn ideal_size(&self, maximum: Vec2<Option<u16>>) -> Vec2<u16> {
+        let width = self.ideal_width(1, maximum.x);
+        Vec2::new(width, self.ideal_height(width, maximum.y))
     }
     fn handle(&self, _input: Input, _events: &mut dyn Events<Event>) {}
+    fn describe(&self, tree: &mut Tree) {
+        let text = self.text.to_string();
+        if self.wrap {
+            tree.leaf(format!("Span({:?}, wrapped)", text));
+        } else {
+            tree.leaf(format!("Span({:?})", text));
+        }
+    }
 }
 
 /// Create a span of text.
@@ -94,10 +160,102 @@ pub fn span<T: Display, Event>(text: T) -> Span<T, Event> {
     Span {
         text,
         style: Style::default(),
+        wrap: false,
         event: PhantomData,
     }
 }
 
+/// Create spans of text from markup, where tags like `<bold red>text</>` style the text.
+///
+/// The contents of a tag are written like a [`Style`] parsed from a string, and are applied on top
+/// of the style of the surrounding text until the tag is closed. A closing tag can either be empty,
+/// like `</>`, or repeat the first word of the opening tag, like `<b fg=red>text</b>`. A literal
+/// `<` is written as `<<`.
+///
+/// Adjacent text with the same style is put in the same span. The spans are typically laid out in
+/// a [`row`](crate::row).
+///
+/// # Errors
+///
+/// Fails if a tag contains an invalid style, is never closed or is closed by the wrong tag.
+///
+/// # Examples
+///
+/// ```
+/// let spans = toon::markup::<()>("Press <bold yellow>q</> to quit, <b fg=red>Esc</b> to exit")?;
+/// let status = toon::row(toon::Static, spans);
+/// # Ok::<_, toon::ParseMarkupError>(())
+/// ```
+pub fn markup<Event>(text: &str) -> Result<Vec<Span<String, Event>>, ParseMarkupError> {
+    fn push_text<Event>(spans: &mut Vec<Span<String, Event>>, text: &str, style: Style) {
+        match spans.last_mut() {
+            _ if text.is_empty() => {}
+            Some(span) if span.style == style => span.text.push_str(text),
+            _ => spans.push(Span {
+                text: text.to_owned(),
+                style,
+                wrap: false,
+                event: PhantomData,
+            }),
+        }
+    }
+
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    // The open tags, with the style before each one and its first word.
+    let mut open: Vec<(&str, Style, &str)> = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        push_text(&mut spans, &rest[..start], style);
+        rest = &rest[start + 1..];
+
+        if let Some(after) = rest.strip_prefix('<') {
+            push_text(&mut spans, "<", style);
+            rest = after;
+            continue;
+        }
+
+        let end = rest
+            .find('>')
+            .ok_or_else(|| ParseMarkupError(format!("<{}", rest)))?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        let error = || ParseMarkupError(format!("<{}>", tag));
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let (_, previous, open_name) = open.pop().ok_or_else(error)?;
+            let name = name.trim();
+            if !name.is_empty() && !name.eq_ignore_ascii_case(open_name) {
+                return Err(error());
+            }
+            style = previous;
+        } else {
+            let previous = style;
+            style.apply_words(tag).map_err(|_| error())?;
+            open.push((tag, previous, tag.split_whitespace().next().unwrap_or("")));
+        }
+    }
+    push_text(&mut spans, rest, style);
+
+    match open.pop() {
+        Some((tag, _, _)) => Err(ParseMarkupError(format!("<{}>", tag))),
+        None => Ok(spans),
+    }
+}
+
+/// An error parsing markup in [`markup`] or a format string in
+/// [`styled_format!`](crate::styled_format), containing the tag that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMarkupError(pub String);
+
+impl Display for ParseMarkupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid markup tag {:?}", self.0)
+    }
+}
+impl StdError for ParseMarkupError {}
+
 #[test]
 fn test_span() {
     use crate::Styled;
@@ -106,10 +264,66 @@ fn test_span() {
 
     span::<_, ()>("asdf").black().on_white().draw(&mut grid);
 
-    assert_eq!(grid.contents(), ["asd", "   ",]);
+    crate::assert_grid_eq!(grid, ["asd", "   ",]);
 
     for (top, bottom) in grid.lines()[0].cells().iter().zip(grid.lines()[1].cells()) {
         assert_eq!(top.style().unwrap(), Style::default().black().on_white());
         assert_eq!(bottom.style().unwrap(), Style::default());
     }
 }
+
+#[test]
+fn test_wrapped() {
+    let span = span::<_, ()>("ab日cd").wrapped();
+    assert_eq!(span.ideal_size(Vec2::new(None, None)), Vec2::new(6, 1));
+    assert_eq!(span.ideal_size(Vec2::new(Some(3), None)), Vec2::new(3, 3));
+    assert_eq!(span.ideal_height(5, None), 2);
+
+    let mut grid = crate::Grid::new((3, 3));
+    span.draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["ab ", "日c", "d  "]);
+
+    let mut grid = crate::Grid::new((5, 2));
+    span.draw(&mut grid);
+    crate::assert_grid_eq!(grid, ["ab日c", "d    "]);
+}
+
+#[test]
+fn test_markup() {
+    use crate::Styled;
+
+    let spans = |text| {
+        markup::<()>(text).map(|spans| {
+            spans
+                .into_iter()
+                .map(|span| (span.text, span.style))
+                .collect::<Vec<_>>()
+        })
+    };
+    let plain = Style::default();
+
+    assert_eq!(spans(""), Ok(Vec::new()));
+    assert_eq!(
+        spans("a <<b> <bold red on black>c <i>d</> e</bold><u></u>"),
+        Ok(vec![
+            ("a <b> ".to_owned(), plain),
+            ("c ".to_owned(), plain.bold().red().on_black()),
+            ("d".to_owned(), plain.bold().red().on_black().italic()),
+            (" e".to_owned(), plain.bold().red().on_black()),
+        ])
+    );
+    assert_eq!(
+        spans("<b>x</b><B>y</>z"),
+        Ok(vec![
+            ("xy".to_owned(), plain.bold()),
+            ("z".to_owned(), plain)
+        ])
+    );
+
+    let error = |tag: &str| Err(ParseMarkupError(tag.to_owned()));
+    assert_eq!(spans("<bold>x</italic>"), error("</italic>"));
+    assert_eq!(spans("<bold>x"), error("<bold>"));
+    assert_eq!(spans("x</>"), error("</>"));
+    assert_eq!(spans("<orange>x</>"), error("<orange>"));
+    assert_eq!(spans("x <bold"), error("<bold"));
+}
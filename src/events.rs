@@ -10,6 +10,24 @@ pub trait Events<Event>: sealed::Sealed {
     /// Add an event to the collection of events.
     fn add(&mut self, event: Event);
 
+    /// Get whether propagation of the current input has been stopped.
+    ///
+    /// Listeners in the bubble phase (see [`Phase`](crate::Phase)) will not fire if an element
+    /// inside them has stopped propagation.
+    fn propagation_stopped(&self) -> bool;
+
+    /// Set whether propagation of the current input has been stopped.
+    ///
+    /// Elements that implement bubbling themselves should clear this before passing the input to
+    /// their inner element, and restore it afterwards.
+    fn set_propagation_stopped(&mut self, stopped: bool);
+
+    /// Stop propagation of the current input, so that listeners in the bubble phase outside this
+    /// element will not fire.
+    fn stop_propagation(&mut self) {
+        self.set_propagation_stopped(true);
+    }
+
     /// Map the type of event being collected.
     fn map<F: Fn(Event2) -> Event, Event2>(self, f: F) -> Map<Self, F>
     where
@@ -23,16 +41,34 @@ impl<'a, T: Events<Event> + ?Sized, Event> Events<Event> for &'a mut T {
     fn add(&mut self, event: Event) {
         (*self).add(event);
     }
+    fn propagation_stopped(&self) -> bool {
+        (**self).propagation_stopped()
+    }
+    fn set_propagation_stopped(&mut self, stopped: bool) {
+        (*self).set_propagation_stopped(stopped);
+    }
 }
 impl<'a, T: ?Sized> sealed::Sealed for &'a mut T {}
 
 /// An event collector that collects events into a vector.
-pub(crate) struct Vector<E>(pub(crate) Vec<E>);
+pub(crate) struct Vector<E>(pub(crate) Vec<E>, pub(crate) bool);
+
+impl<E> Vector<E> {
+    pub(crate) fn new() -> Self {
+        Self(Vec::new(), false)
+    }
+}
 
 impl<E> Events<E> for Vector<E> {
     fn add(&mut self, event: E) {
         self.0.push(event);
     }
+    fn propagation_stopped(&self) -> bool {
+        self.1
+    }
+    fn set_propagation_stopped(&mut self, stopped: bool) {
+        self.1 = stopped;
+    }
 }
 impl<E> sealed::Sealed for Vector<E> {}
 
@@ -49,6 +85,12 @@ impl<E: Events<Event>, Event2, Event, F: Fn(Event2) -> Event> Events<Event2> for
     fn add(&mut self, event: Event2) {
         self.inner.add((self.f)(event));
     }
+    fn propagation_stopped(&self) -> bool {
+        self.inner.propagation_stopped()
+    }
+    fn set_propagation_stopped(&mut self, stopped: bool) {
+        self.inner.set_propagation_stopped(stopped);
+    }
 }
 impl<E, F> sealed::Sealed for Map<E, F> {}
 
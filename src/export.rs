@@ -0,0 +1,533 @@
+//! Exporting rendered grids to other formats, for documentation and bug reports.
+//!
+//! Grids can be obtained by drawing an element to a [`Grid`] directly, or from
+//! [`Terminal::current_frame`](crate::Terminal::current_frame).
+
+use std::fmt::Write as _;
+
+use crate::buffer::{CellKind, Grid, Line};
+use crate::style::{Attributes, Color, Intensity, Link, Rgb, Rgba, Style, UnderlineKind};
+
+/// The foreground color used for [`Color::Default`](crate::Color::Default).
+const DEFAULT_FOREGROUND: Rgb = Rgb::new(0xE5, 0xE5, 0xE5);
+/// The background color used for [`Color::Default`](crate::Color::Default).
+const DEFAULT_BACKGROUND: Rgb = Rgb::new(0x00, 0x00, 0x00);
+
+/// Render a grid as a self-contained HTML `<pre>` element with inline styles.
+///
+/// Colors, attributes and links are preserved, except blinking which is ignored. The default
+/// foreground and background colors are light gray on black.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Element, Styled};
+///
+/// let mut grid = toon::Grid::new((5, 1));
+/// toon::span::<_, ()>("Hi").bold().draw(&mut grid);
+///
+/// let html = toon::export::html(&grid);
+/// assert!(html.contains("<span style=\"font-weight:bold\">Hi</span>"));
+/// ```
+#[must_use]
+pub fn html(grid: &Grid) -> String {
+    let mut html = format!(
+        "<pre style=\"font-family:monospace;line-height:1.2;padding:0.5em;color:{};\
+        background-color:{}\">",
+        hex(DEFAULT_FOREGROUND),
+        hex(DEFAULT_BACKGROUND),
+    );
+
+    for (i, line) in grid.lines().iter().enumerate() {
+        if i != 0 {
+            html.push('\n');
+        }
+
+        for run in runs(line) {
+            if let Some(link) = &run.link {
+                html.push_str("<a href=\"");
+                escape_into(&mut html, link.url());
+                html.push_str("\">");
+            }
+
+            let css = css(run.style);
+            if css.is_empty() {
+                escape_into(&mut html, &run.text);
+            } else {
+                html.push_str("<span style=\"");
+                html.push_str(&css);
+                html.push_str("\">");
+                escape_into(&mut html, &run.text);
+                html.push_str("</span>");
+            }
+
+            if run.link.is_some() {
+                html.push_str("</a>");
+            }
+        }
+    }
+
+    html.push_str("</pre>");
+    html
+}
+
+/// Render a grid as a standalone SVG image.
+///
+/// Each run of text that shares a style is drawn as a `<text>` element stretched to exactly fit
+/// its cells, on top of a `<rect>` for its background color, so the image lines up like a terminal
+/// regardless of the font that is used. Colors and attributes are preserved, except blinking which
+/// is ignored. The default foreground and background colors are light gray on black.
+///
+/// # Examples
+///
+/// ```
+/// use toon::Element;
+///
+/// let mut grid = toon::Grid::new((5, 1));
+/// toon::span::<_, ()>("Hi").draw(&mut grid);
+///
+/// let svg = toon::export::svg(&grid);
+/// assert!(svg.starts_with("<svg"));
+/// ```
+#[must_use]
+pub fn svg(grid: &Grid) -> String {
+    let width = u32::from(grid.width()) * SVG_CELL_WIDTH;
+    let height = u32::from(grid.height()) * SVG_CELL_HEIGHT;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+        viewBox=\"0 0 {w} {h}\" font-family=\"monospace\" font-size=\"{size}\" \
+        xml:space=\"preserve\">\
+        <rect width=\"100%\" height=\"100%\" fill=\"{bg}\"/>",
+        w = width,
+        h = height,
+        size = SVG_FONT_SIZE,
+        bg = hex(DEFAULT_BACKGROUND),
+    );
+
+    let mut text = String::new();
+
+    for (y, line) in grid.lines().iter().enumerate() {
+        let y = y as u32 * SVG_CELL_HEIGHT;
+
+        for run in runs(line) {
+            let x = u32::from(run.start) * SVG_CELL_WIDTH;
+            let width = u32::from(run.width) * SVG_CELL_WIDTH;
+
+            if let Some(background) = run.style.background.to_rgb() {
+                write!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    x,
+                    y,
+                    width,
+                    SVG_CELL_HEIGHT,
+                    hex(background),
+                )
+                .unwrap();
+            }
+
+            if run.text.trim().is_empty() || run.style.attributes.hidden {
+                continue;
+            }
+
+            let attributes = run.style.attributes;
+            write!(
+                text,
+                "<text x=\"{}\" y=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\" \
+                fill=\"{}\"",
+                x,
+                y + SVG_BASELINE,
+                width,
+                hex(run.style.foreground.to_rgb().unwrap_or(DEFAULT_FOREGROUND)),
+            )
+            .unwrap();
+            match attributes.intensity {
+                Intensity::Dim => text.push_str(" fill-opacity=\"0.6\""),
+                Intensity::Normal => {}
+                Intensity::Bold => text.push_str(" font-weight=\"bold\""),
+            }
+            if attributes.italic {
+                text.push_str(" font-style=\"italic\"");
+            }
+            let decoration = text_decoration(attributes);
+            if !decoration.is_empty() {
+                write!(text, " text-decoration=\"{}\"", decoration).unwrap();
+            }
+            text.push('>');
+            escape_into(&mut text, &run.text);
+            text.push_str("</text>");
+        }
+    }
+
+    // Text is drawn after all the backgrounds so that it is never covered by them.
+    svg.push_str(&text);
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Render a grid as text containing SGR escape sequences, the way a terminal would show it.
+///
+/// Every line is followed by a newline and ends with the style reset, so the text can be written to
+/// a file, viewed with `less -R` or printed after the application has exited. Colors, attributes
+/// and links are preserved. Translucent colors are resolved as if the terminal's background color
+/// were unknown.
+///
+/// This is also available as [`Grid::to_ansi_string`].
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Element, Styled};
+///
+/// let mut grid = toon::Grid::new((3, 1));
+/// toon::span::<_, ()>("Hi").bold().draw(&mut grid);
+///
+/// assert_eq!(toon::export::ansi(&grid), "\x1B[0;1mHi\x1B[0m \n");
+/// ```
+#[must_use]
+pub fn ansi(grid: &Grid) -> String {
+    let mut ansi = String::new();
+
+    for line in grid.lines() {
+        let mut current = Style::default();
+        let mut current_link = None;
+
+        for cell in line.cells() {
+            if let CellKind::Char {
+                contents, style, ..
+            } = cell.kind()
+            {
+                let style = style.to_opaque(None);
+                if cell.link() != current_link {
+                    write!(ansi, "\x1B]8;;{}\x1B\\", cell.link().map_or("", Link::url)).unwrap();
+                    current_link = cell.link();
+                }
+                if style != current {
+                    sgr_into(&mut ansi, style);
+                }
+                current = style;
+                ansi.push_str(contents);
+            }
+        }
+
+        if current_link.is_some() {
+            ansi.push_str("\x1B]8;;\x1B\\");
+        }
+        if current != Style::default() {
+            ansi.push_str("\x1B[0m");
+        }
+        ansi.push('\n');
+    }
+
+    ansi
+}
+
+/// Write the SGR sequence that resets the style and then sets it to the given style.
+fn sgr_into(out: &mut String, style: Style) {
+    let attributes = style.attributes;
+
+    out.push_str("\x1B[0");
+    match attributes.intensity {
+        Intensity::Dim => out.push_str(";2"),
+        Intensity::Normal => {}
+        Intensity::Bold => out.push_str(";1"),
+    }
+    if attributes.italic {
+        out.push_str(";3");
+    }
+    if attributes.underlined {
+        out.push_str(match attributes.underline_kind {
+            UnderlineKind::Single => ";4",
+            UnderlineKind::Double => ";4:2",
+            UnderlineKind::Curly => ";4:3",
+            UnderlineKind::Dotted => ";4:4",
+            UnderlineKind::Dashed => ";4:5",
+        });
+    }
+    if attributes.blinking {
+        out.push_str(";5");
+    }
+    if attributes.reversed {
+        out.push_str(";7");
+    }
+    if attributes.hidden {
+        out.push_str(";8");
+    }
+    if attributes.crossed_out {
+        out.push_str(";9");
+    }
+    if attributes.overlined {
+        out.push_str(";53");
+    }
+    sgr_color_into(out, style.foreground, 30);
+    sgr_color_into(out, style.background, 40);
+    match style.underline_color {
+        Color::Default => {}
+        Color::Rgb(Rgb { r, g, b }) | Color::Rgba(Rgba { r, g, b, .. }) => {
+            write!(out, ";58:2::{}:{}:{}", r, g, b).unwrap();
+        }
+        color => write!(out, ";58:5:{}", color.ansi_value().unwrap()).unwrap(),
+    }
+    out.push('m');
+}
+
+/// Write the SGR parameters of a color, where `base` is 30 for the foreground and 40 for the
+/// background.
+fn sgr_color_into(out: &mut String, color: Color, base: u8) {
+    match color {
+        Color::Default => {}
+        Color::Rgb(Rgb { r, g, b }) | Color::Rgba(Rgba { r, g, b, .. }) => {
+            write!(out, ";{};2;{};{};{}", base + 8, r, g, b).unwrap();
+        }
+        _ => match color.ansi_value().unwrap() {
+            index @ 0..=7 => write!(out, ";{}", base + index).unwrap(),
+            index @ 8..=15 => write!(out, ";{}", base + 60 + index - 8).unwrap(),
+            index => write!(out, ";{};5;{}", base + 8, index).unwrap(),
+        },
+    }
+}
+
+// Sizes in SVGs, in pixels. They are whole numbers so that cells line up with the pixel grid.
+
+/// The size of text.
+const SVG_FONT_SIZE: u32 = 15;
+/// The width of a cell. Most monospace fonts are 0.6em wide.
+const SVG_CELL_WIDTH: u32 = 9;
+/// The height of a cell.
+const SVG_CELL_HEIGHT: u32 = 18;
+/// The distance from the top of a cell to the baseline of its text.
+const SVG_BASELINE: u32 = 14;
+
+/// A run of text in a line that shares a style.
+struct Run {
+    style: Style,
+    link: Option<Link>,
+    text: String,
+    /// The column the run starts at.
+    start: u16,
+    /// The number of columns the run takes up.
+    width: u16,
+}
+
+/// Group the cells of a line into runs of text that share a style and link.
+fn runs(line: &Line) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+
+    for (x, cell) in line.cells().iter().enumerate() {
+        if let CellKind::Char {
+            contents,
+            double,
+            style,
+        } = cell.kind()
+        {
+            let width = if double { 2 } else { 1 };
+            let mut style = style.to_opaque(Some(DEFAULT_BACKGROUND));
+            if style.attributes.reversed {
+                let foreground = style.foreground.to_rgb().unwrap_or(DEFAULT_FOREGROUND);
+                let background = style.background.to_rgb().unwrap_or(DEFAULT_BACKGROUND);
+                style.foreground = Color::Rgb(background);
+                style.background = Color::Rgb(foreground);
+                style.attributes.reversed = false;
+            }
+            match runs.last_mut() {
+                Some(run) if run.style == style && run.link.as_ref() == cell.link() => {
+                    run.text.push_str(contents);
+                    run.width += width;
+                }
+                _ => runs.push(Run {
+                    style,
+                    link: cell.link().cloned(),
+                    text: contents.to_owned(),
+                    start: x as u16,
+                    width,
+                }),
+            }
+        }
+    }
+
+    runs
+}
+
+/// Get the lines drawn on text with the attributes, as a space-separated list of CSS
+/// `text-decoration` lines.
+fn text_decoration(attributes: Attributes) -> String {
+    let lines = [
+        (attributes.underlined, "underline"),
+        (attributes.overlined, "overline"),
+        (attributes.crossed_out, "line-through"),
+    ];
+    let lines: Vec<_> = lines
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, line)| *line)
+        .collect();
+    lines.join(" ")
+}
+
+/// Get the inline CSS for a style, or an empty string if it is the default style.
+fn css(style: Style) -> String {
+    let mut css = String::new();
+
+    if let Some(rgb) = style.foreground.to_rgb() {
+        write!(css, "color:{};", hex(rgb)).unwrap();
+    }
+    if let Some(rgb) = style.background.to_rgb() {
+        write!(css, "background-color:{};", hex(rgb)).unwrap();
+    }
+    match style.attributes.intensity {
+        Intensity::Dim => css.push_str("opacity:0.6;"),
+        Intensity::Normal => {}
+        Intensity::Bold => css.push_str("font-weight:bold;"),
+    }
+    if style.attributes.italic {
+        css.push_str("font-style:italic;");
+    }
+    if style.attributes.hidden {
+        css.push_str("visibility:hidden;");
+    }
+    let decoration = text_decoration(style.attributes);
+    if !decoration.is_empty() {
+        write!(css, "text-decoration:{};", decoration).unwrap();
+    }
+    if style.attributes.underlined {
+        match style.attributes.underline_kind {
+            UnderlineKind::Single => {}
+            UnderlineKind::Double => css.push_str("text-decoration-style:double;"),
+            UnderlineKind::Curly => css.push_str("text-decoration-style:wavy;"),
+            UnderlineKind::Dotted => css.push_str("text-decoration-style:dotted;"),
+            UnderlineKind::Dashed => css.push_str("text-decoration-style:dashed;"),
+        }
+        if let Some(rgb) = style.underline_color.to_rgb() {
+            write!(css, "text-decoration-color:{};", hex(rgb)).unwrap();
+        }
+    }
+
+    // Remove the trailing semicolon.
+    css.pop();
+    css
+}
+
+fn hex(rgb: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb.r, rgb.g, rgb.b)
+}
+
+fn escape_into(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[test]
+fn test_html() {
+    use crate::{Element, ElementExt, Styled};
+
+    let mut grid = Grid::new((4, 2));
+    crate::column::<_, _, ()>(
+        crate::Static,
+        (
+            crate::span("<a>").foreground(Color::Red),
+            crate::span("&")
+                .italic()
+                .underline_kind(UnderlineKind::Curly)
+                .link("?a&b"),
+        ),
+    )
+    .draw(&mut grid);
+
+    assert_eq!(
+        html(&grid),
+        "<pre style=\"font-family:monospace;line-height:1.2;padding:0.5em;color:#e5e5e5;\
+        background-color:#000000\">\
+        <span style=\"color:#ff0000\">&lt;a&gt;</span> \n\
+        <a href=\"?a&amp;b\"><span style=\"font-style:italic;text-decoration:underline;\
+        text-decoration-style:wavy\">&amp;</span></a>   \
+        </pre>"
+    );
+}
+
+#[test]
+fn test_svg() {
+    use crate::{Element, Styled};
+
+    let mut grid = Grid::new((4, 1));
+    crate::span::<_, ()>("a&").on_blue().bold().draw(&mut grid);
+
+    assert_eq!(
+        svg(&grid),
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"36\" height=\"18\" \
+        viewBox=\"0 0 36 18\" font-family=\"monospace\" font-size=\"15\" \
+        xml:space=\"preserve\">\
+        <rect width=\"100%\" height=\"100%\" fill=\"#000000\"/>\
+        <rect x=\"0\" y=\"0\" width=\"18\" height=\"18\" fill=\"#5c5cff\"/>\
+        <text x=\"0\" y=\"14\" textLength=\"18\" lengthAdjust=\"spacingAndGlyphs\" \
+        fill=\"#e5e5e5\" font-weight=\"bold\">a&amp;</text>\
+        </svg>"
+    );
+}
+
+#[test]
+fn test_ansi() {
+    use crate::{Element, ElementExt, Styled};
+
+    let mut grid = Grid::new((4, 3));
+    crate::column::<_, _, ()>(
+        crate::Static,
+        (
+            crate::span("ab")
+                .red()
+                .on_dark_blue()
+                .underline_kind(UnderlineKind::Curly),
+            crate::span("c")
+                .foreground(Color::Rgb(Rgb::new(1, 2, 3)))
+                .link("x"),
+        ),
+    )
+    .draw(&mut grid);
+
+    assert_eq!(
+        ansi(&grid),
+        "\x1B[0;4:3;91;44mab\x1B[0m  \n\
+        \x1B]8;;x\x1B\\\x1B[0;38;2;1;2;3mc\x1B]8;;\x1B\\\x1B[0m   \n\
+        \x20   \n"
+    );
+}
+
+#[test]
+fn test_reversed() {
+    use crate::{Element, Styled};
+
+    let mut grid = Grid::new((2, 1));
+    crate::span::<_, ()>("ab").red().reversed().draw(&mut grid);
+
+    assert!(
+        html(&grid).contains("<span style=\"color:#000000;background-color:#ff0000\">ab</span>")
+    );
+}
+
+#[test]
+fn test_hidden_and_overlined() {
+    use crate::output::Ext as _;
+    use crate::{Element, Styled};
+
+    let mut grid = Grid::new((2, 1));
+    crate::span::<_, ()>("a").hidden().draw(&mut grid);
+    crate::span::<_, ()>("b")
+        .overlined()
+        .underlined()
+        .draw(&mut (&mut grid).area((1, 0), (1, 1)));
+
+    let html = html(&grid);
+    assert!(html.contains("<span style=\"visibility:hidden\">a</span>"));
+    assert!(html.contains("<span style=\"text-decoration:underline overline\">b</span>"));
+
+    let svg = svg(&grid);
+    assert!(!svg.contains(">a</text>"));
+    assert!(svg.contains("text-decoration=\"underline overline\">b</text>"));
+}
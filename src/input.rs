@@ -1,16 +1,56 @@
 //! Terminal inputs, such as keypresses, clicks and resizes.
 
+use std::cell::{Cell, RefCell};
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
 use std::ops::{BitOr, BitOrAssign};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "quickcheck")]
+use quickcheck_crate::{Arbitrary, Gen};
+
+use crate::clock::{Clock, SystemClock};
 use crate::Vec2;
 
 /// A user input on the terminal.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum Input {
-    /// A key was pressed.
+    /// A key was pressed, repeated or released.
     Key(KeyPress),
     /// A mouse button was pressed, released or dragged, or the mouse wheel was scrolled.
     Mouse(Mouse),
+    /// Text was pasted into the terminal.
+    ///
+    /// Only some terminals report pastes like this; others report the pasted text as key presses.
+    Paste(String),
+    /// The user's input method is composing text, such as when typing Chinese, Japanese or Korean.
+    ///
+    /// This contains all the text composed so far, which elements that accept text should show at
+    /// their cursor with [`write_composition`](crate::output::Ext::write_composition). It is empty
+    /// when the composition ends, after which the composed text is sent as key presses or a paste
+    /// if it was committed.
+    Compose(String),
+    /// An action was triggered by a key bound to it in the terminal's [`Keymap`](crate::Keymap).
+    ///
+    /// This is given to elements before the key itself, which is only given to them if
+    /// propagation of the action wasn't stopped.
+    Action(String),
+    /// A key press is being offered to application-wide shortcuts, registered with
+    /// [`on_global`](crate::ElementExt::on_global).
+    ///
+    /// This is given to elements before the key itself and any actions it triggers, which are only
+    /// given to them if propagation of the shortcut wasn't stopped. Unlike other inputs, elements
+    /// that contain other elements should give shortcuts to all of them, regardless of focus or
+    /// input masks.
+    Shortcut(KeyPress),
+    /// A signal was sent to the process. This only occurs once the terminal has been told to
+    /// [catch signals](crate::Terminal::catch_signals).
+    ///
+    /// Like shortcuts, elements that contain other elements should give signals to all of them,
+    /// regardless of focus or input masks.
+    Signal(Signal),
 }
 
 impl Input {
@@ -19,24 +59,60 @@ impl Input {
     pub fn key(self) -> Option<KeyPress> {
         match self {
             Self::Key(press) => Some(press),
-            Self::Mouse(_) => None,
+            _ => None,
         }
     }
     /// Get the mouse input of the input.
     #[must_use]
     pub fn mouse(self) -> Option<Mouse> {
         match self {
-            Self::Key(_) => None,
             Self::Mouse(mouse) => Some(mouse),
+            _ => None,
+        }
+    }
+    /// Get the pasted text of the input.
+    #[must_use]
+    pub fn paste(self) -> Option<String> {
+        match self {
+            Self::Paste(text) => Some(text),
+            _ => None,
+        }
+    }
+    /// Get the text being composed by the input.
+    #[must_use]
+    pub fn composition(self) -> Option<String> {
+        match self {
+            Self::Compose(text) => Some(text),
+            _ => None,
+        }
+    }
+    /// Get the name of the action triggered by the input.
+    #[must_use]
+    pub fn action(self) -> Option<String> {
+        match self {
+            Self::Action(action) => Some(action),
+            _ => None,
+        }
+    }
+    /// Get the signal of the input.
+    #[must_use]
+    pub fn signal(&self) -> Option<Signal> {
+        match *self {
+            Self::Signal(signal) => Some(signal),
+            _ => None,
         }
     }
 
-    /// Get the modifiers of the input.
+    /// Get the modifiers of the input. Pastes, compositions, actions and signals never have any
+    /// modifiers.
     #[must_use]
     pub fn modifiers(&self) -> Modifiers {
         match self {
-            Self::Key(press) => press.modifiers,
+            Self::Key(press) | Self::Shortcut(press) => press.modifiers,
             Self::Mouse(mouse) => mouse.modifiers,
+            Self::Paste(_) | Self::Compose(_) | Self::Action(_) | Self::Signal(_) => {
+                Modifiers::default()
+            }
         }
     }
 }
@@ -85,13 +161,15 @@ impl PartialEq<Input> for Mouse {
     }
 }
 
-/// A key was pressed.
+/// A key was pressed, repeated or released.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct KeyPress {
     /// Which key was pressed.
     pub key: Key,
     /// The modifiers active while the key was pressed.
     pub modifiers: Modifiers,
+    /// Whether the key was pressed, repeated or released.
+    pub kind: KeyKind,
 }
 
 impl From<char> for KeyPress {
@@ -102,6 +180,7 @@ impl From<char> for KeyPress {
                 shift: key.is_ascii_uppercase(),
                 ..Modifiers::default()
             },
+            kind: KeyKind::Press,
         }
     }
 }
@@ -117,6 +196,183 @@ impl PartialEq<KeyPress> for char {
     }
 }
 
+/// Displays the key press like `Ctrl+x`, `Shift+Tab` or `A`.
+impl Display for KeyPress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let uppercase = match self.key {
+            Key::Char(c) if self.modifiers.shift && c.is_ascii_lowercase() => {
+                Some(c.to_ascii_uppercase())
+            }
+            _ => None,
+        };
+
+        if self.modifiers.control {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.alt {
+            f.write_str("Alt+")?;
+        }
+        if self.modifiers.shift && uppercase.is_none() {
+            f.write_str("Shift+")?;
+        }
+
+        match self.key {
+            Key::Backspace => f.write_str("Backspace"),
+            Key::Left => f.write_str("Left"),
+            Key::Right => f.write_str("Right"),
+            Key::Up => f.write_str("Up"),
+            Key::Down => f.write_str("Down"),
+            Key::Home => f.write_str("Home"),
+            Key::End => f.write_str("End"),
+            Key::PageUp => f.write_str("PageUp"),
+            Key::PageDown => f.write_str("PageDown"),
+            Key::Insert => f.write_str("Insert"),
+            Key::Escape => f.write_str("Escape"),
+            Key::F(n) => write!(f, "F{}", n),
+            Key::Keypad('\n') => f.write_str("KeypadEnter"),
+            Key::Keypad('+') => f.write_str("KeypadPlus"),
+            Key::Keypad(c) => write!(f, "Keypad{}", c),
+            Key::Media(key) => f.write_str(key.name()),
+            Key::Char(' ') => f.write_str("Space"),
+            Key::Char('\n') => f.write_str("Enter"),
+            Key::Char('\t') => f.write_str("Tab"),
+            Key::Char('\x7F') => f.write_str("Delete"),
+            Key::Char(c) => write!(f, "{}", uppercase.unwrap_or(c)),
+        }
+    }
+}
+
+/// Parses key presses in the format they are displayed in, like `Ctrl+x`, `Shift+Tab` or `A`.
+///
+/// Modifier and key names are case insensitive, except for single letters where an uppercase
+/// letter means shift is held.
+impl FromStr for KeyPress {
+    type Err = ParseKeyPressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseKeyPressError(s.to_owned());
+
+        // The `+` key itself is written like `Ctrl++`.
+        let (modifiers, key) = if s == "+" {
+            ("", s)
+        } else if let Some(modifiers) = s.strip_suffix("++") {
+            (modifiers, "+")
+        } else {
+            match s.rfind('+') {
+                Some(i) => (&s[..i], &s[i + 1..]),
+                None => ("", s),
+            }
+        };
+
+        let mut press = match key.to_ascii_lowercase().as_str() {
+            "backspace" => Self::from(Key::Backspace),
+            "left" => Self::from(Key::Left),
+            "right" => Self::from(Key::Right),
+            "up" => Self::from(Key::Up),
+            "down" => Self::from(Key::Down),
+            "home" => Self::from(Key::Home),
+            "end" => Self::from(Key::End),
+            "pageup" => Self::from(Key::PageUp),
+            "pagedown" => Self::from(Key::PageDown),
+            "insert" => Self::from(Key::Insert),
+            "escape" | "esc" => Self::from(Key::Escape),
+            "space" => Self::from(' '),
+            "enter" | "return" => Self::from('\n'),
+            "tab" => Self::from('\t'),
+            "delete" | "del" => Self::from('\x7F'),
+            name => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if !c.is_control() => Self::from(c),
+                    _ => Self::from(parse_key_name(name).ok_or_else(error)?),
+                }
+            }
+        };
+
+        for modifier in modifiers.split('+').filter(|_| !modifiers.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => press.modifiers.control = true,
+                "alt" => press.modifiers.alt = true,
+                "shift" => press.modifiers.shift = true,
+                _ => return Err(error()),
+            }
+        }
+
+        Ok(press)
+    }
+}
+
+/// Parse the lowercase name of a function, keypad or media key.
+fn parse_key_name(name: &str) -> Option<Key> {
+    if let Some(keypad) = name.strip_prefix("keypad") {
+        let mut chars = keypad.chars();
+        return Some(Key::Keypad(match (keypad, chars.next(), chars.next()) {
+            ("enter", _, _) => '\n',
+            ("plus", _, _) => '+',
+            (_, Some(c), None) if !c.is_control() => c,
+            _ => return None,
+        }));
+    }
+    if let Some(n) = name.strip_prefix('f') {
+        return n.parse().ok().map(Key::F);
+    }
+    MediaKey::ALL
+        .iter()
+        .find(|key| key.name().eq_ignore_ascii_case(name))
+        .copied()
+        .map(Key::Media)
+}
+
+impl From<Key> for KeyPress {
+    fn from(key: Key) -> Self {
+        Self {
+            key,
+            modifiers: Modifiers::default(),
+            kind: KeyKind::Press,
+        }
+    }
+}
+
+/// An error parsing a [`KeyPress`], containing the text that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyPressError(pub String);
+
+impl Display for ParseKeyPressError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key press {:?}", self.0)
+    }
+}
+impl StdError for ParseKeyPressError {}
+
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for KeyPress {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for KeyPress {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde_crate::de::{self, Visitor};
+
+        struct KeyPressVisitor;
+
+        impl<'de> Visitor<'de> for KeyPressVisitor {
+            type Value = KeyPress;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a key press like \"Ctrl+x\"")
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(KeyPressVisitor)
+    }
+}
+
 /// Any key.
 ///
 /// The enter/return, tab and delete keys are mapped to their respective ASCII characters.
@@ -144,13 +400,111 @@ pub enum Key {
     Insert,
     /// The escape key.
     Escape,
-    /// A function key (e.g. F(5) is F5).
+    /// A function key (e.g. F(5) is F5). Full-size keyboards have keys up to F24.
     F(u8),
+    /// A key on the numeric keypad, as the character it types, such as `'5'` or `'*'`. The
+    /// keypad's Enter key is `'\n'`.
+    ///
+    /// Only some terminals tell these apart from the keys on the main keyboard; others report the
+    /// main keyboard's key instead.
+    Keypad(char),
+    /// A media key. Only some terminals report these.
+    Media(MediaKey),
     /// A key which maps to a character. This character will never be uppercase.
     Char(char),
 }
 
-/// A mouse button was pressed, released or dragged, or the mouse wheel was scrolled.
+/// A media key, found on some keyboards.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MediaKey {
+    /// Start playing.
+    Play,
+    /// Pause playing.
+    Pause,
+    /// Toggle between playing and paused.
+    PlayPause,
+    /// Play in reverse.
+    Reverse,
+    /// Stop playing.
+    Stop,
+    /// Fast forward.
+    FastForward,
+    /// Rewind.
+    Rewind,
+    /// Skip to the next track.
+    NextTrack,
+    /// Skip to the previous track.
+    PreviousTrack,
+    /// Start recording.
+    Record,
+    /// Turn the volume down.
+    VolumeDown,
+    /// Turn the volume up.
+    VolumeUp,
+    /// Mute or unmute the volume.
+    Mute,
+}
+
+impl MediaKey {
+    pub(crate) const ALL: [Self; 13] = [
+        Self::Play,
+        Self::Pause,
+        Self::PlayPause,
+        Self::Reverse,
+        Self::Stop,
+        Self::FastForward,
+        Self::Rewind,
+        Self::NextTrack,
+        Self::PreviousTrack,
+        Self::Record,
+        Self::VolumeDown,
+        Self::VolumeUp,
+        Self::Mute,
+    ];
+
+    /// Get the name of the media key, as it is displayed in key presses.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Play => "Play",
+            Self::Pause => "Pause",
+            Self::PlayPause => "PlayPause",
+            Self::Reverse => "Reverse",
+            Self::Stop => "Stop",
+            Self::FastForward => "FastForward",
+            Self::Rewind => "Rewind",
+            Self::NextTrack => "NextTrack",
+            Self::PreviousTrack => "PreviousTrack",
+            Self::Record => "Record",
+            Self::VolumeDown => "VolumeDown",
+            Self::VolumeUp => "VolumeUp",
+            Self::Mute => "Mute",
+        }
+    }
+}
+
+/// A kind of key input.
+///
+/// Most terminals can only report presses, and report a key being held down as it being pressed
+/// repeatedly.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum KeyKind {
+    /// The key was pressed.
+    Press,
+    /// The key was held down and the press was repeated.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
+impl Default for KeyKind {
+    fn default() -> Self {
+        Self::Press
+    }
+}
+
+/// A mouse button was pressed, released or dragged, the mouse wheel was scrolled or the mouse
+/// moved onto or off an element.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Mouse {
     /// What kind of mouse input it is.
@@ -159,10 +513,50 @@ pub struct Mouse {
     pub at: Vec2<u16>,
     /// The size of the output that captured the mouse input.
     pub size: Vec2<u16>,
+    /// Where the input occurred on the terminal. Unlike `at`, this isn't made relative to each
+    /// element as the input is passed down.
+    pub terminal_at: Vec2<u16>,
     /// The modifiers active while the input occurred. Only some terminals report this.
     pub modifiers: Modifiers,
 }
 
+impl Mouse {
+    /// Get where the top left corner of the element receiving the input is on the terminal, like
+    /// [`Output::origin`](crate::Output::origin).
+    ///
+    /// This is useful for positioning popups next to the element. It can be outside the terminal
+    /// if the element is scrolled.
+    #[must_use]
+    pub fn origin(&self) -> Vec2<i32> {
+        self.terminal_at.into::<i32>() - self.at.into()
+    }
+
+    /// Whether the mouse entered the element receiving the input from outside it.
+    #[must_use]
+    pub fn entered(&self) -> bool {
+        match self.kind {
+            MouseKind::Enter(from) => from.map_or(true, |from| !self.contains(from)),
+            _ => false,
+        }
+    }
+
+    /// Whether the mouse left the element receiving the input.
+    #[must_use]
+    pub fn left(&self) -> bool {
+        match self.kind {
+            MouseKind::Leave(to) => !self.contains(to),
+            _ => false,
+        }
+    }
+
+    /// Whether a position relative to the mouse is inside the element receiving the input.
+    fn contains(&self, offset: Vec2<i32>) -> bool {
+        let pos = self.at.into::<i32>() + offset;
+        let size = self.size.into::<i32>();
+        pos.x >= 0 && pos.y >= 0 && pos.x < size.x && pos.y < size.y
+    }
+}
+
 /// A kind of mouse input.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum MouseKind {
@@ -178,6 +572,21 @@ pub enum MouseKind {
     ScrollDown,
     /// The scroll wheel was scrolled up.
     ScrollUp,
+    /// The scroll wheel was scrolled left, typically on a trackpad.
+    ScrollLeft,
+    /// The scroll wheel was scrolled right, typically on a trackpad.
+    ScrollRight,
+    /// The mouse moved to this position, from a position given relative to it. It is [`None`] if
+    /// the mouse wasn't previously known to be on the terminal.
+    ///
+    /// Elements receive this when the mouse moves onto them, but also when it moves within
+    /// elements that contain them; use [`Mouse::entered`] to tell the two apart.
+    Enter(Option<Vec2<i32>>),
+    /// The mouse moved away from this position, to a position given relative to it.
+    ///
+    /// Elements receive this when the mouse moves off them, but also when it moves within
+    /// elements that contain them; use [`Mouse::left`] to tell the two apart.
+    Leave(Vec2<i32>),
 }
 
 /// A mouse button.
@@ -191,6 +600,48 @@ pub enum MouseButton {
     Right,
 }
 
+/// How much mouse input the terminal captures.
+///
+/// While the mouse is captured most terminals only let the user select text while holding shift,
+/// and capturing motion with no buttons held down can produce a lot of inputs, so programs should
+/// capture as little as they need. Default is [`Drag`](Self::Drag).
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum MouseCapture {
+    /// The mouse is not captured.
+    None,
+    /// Presses, releases and scrolling are captured.
+    Click,
+    /// Presses, releases and scrolling are captured, as is moving the mouse while a button is held
+    /// down.
+    Drag,
+    /// All mouse input is captured, including moving the mouse with no buttons held down.
+    Motion,
+}
+
+impl Default for MouseCapture {
+    fn default() -> Self {
+        Self::Drag
+    }
+}
+
+/// A signal sent to the process, given to elements as an [`Input::Signal`].
+///
+/// Applications usually respond to all of these except [`Continue`](Self::Continue) by saving
+/// their state and exiting.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Signal {
+    /// The process was asked to stop, usually by a user (`SIGINT`). As the terminal is in raw mode
+    /// pressing Ctrl+C doesn't send this; it is reported as a key press instead.
+    Interrupt,
+    /// The process was asked to terminate, for example by the system shutting down (`SIGTERM`).
+    Terminate,
+    /// The terminal the process is running in was closed (`SIGHUP`).
+    Hangup,
+    /// The process was resumed after being suspended (`SIGCONT`). The terminal has already been set
+    /// up again and will be redrawn.
+    Continue,
+}
+
 /// Key modifiers.
 #[derive(Default, Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Modifiers {
@@ -227,6 +678,12 @@ impl Modifiers {
     pub const fn are_none(self) -> bool {
         !self.shift && !self.control && !self.alt
     }
+
+    /// Returns `true` if all the modifiers held down in `other` are also held down in `self`.
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        (self.shift || !other.shift) && (self.control || !other.control) && (self.alt || !other.alt)
+    }
 }
 
 impl BitOr for Modifiers {
@@ -247,51 +704,229 @@ impl BitOrAssign for Modifiers {
     }
 }
 
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Input {
+    fn arbitrary(g: &mut Gen) -> Self {
+        match u8::arbitrary(g) % 9 {
+            0 => Self::Paste(String::arbitrary(g)),
+            1 => Self::Compose(String::arbitrary(g)),
+            2 => Self::Action(String::arbitrary(g)),
+            3 => Self::Shortcut(KeyPress::arbitrary(g)),
+            4 => Self::Signal(Signal::arbitrary(g)),
+            5 | 6 => Self::Mouse(Mouse::arbitrary(g)),
+            _ => Self::Key(KeyPress::arbitrary(g)),
+        }
+    }
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        match self {
+            Self::Paste(text) => Box::new(text.shrink().map(Self::Paste)),
+            Self::Compose(text) => Box::new(text.shrink().map(Self::Compose)),
+            Self::Action(action) => Box::new(action.shrink().map(Self::Action)),
+            Self::Key(_) | Self::Mouse(_) | Self::Shortcut(_) | Self::Signal(_) => {
+                quickcheck_crate::empty_shrinker()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for KeyPress {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            key: Key::arbitrary(g),
+            modifiers: Modifiers::arbitrary(g),
+            kind: KeyKind::arbitrary(g),
+        }
+    }
+}
+
+/// Characters are never uppercase, and are only control characters if they are enter, tab or
+/// delete.
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Key {
+    fn arbitrary(g: &mut Gen) -> Self {
+        match u8::arbitrary(g) % 8 {
+            0 => *g
+                .choose(&[
+                    Self::Backspace,
+                    Self::Left,
+                    Self::Right,
+                    Self::Up,
+                    Self::Down,
+                    Self::Home,
+                    Self::End,
+                    Self::PageUp,
+                    Self::PageDown,
+                    Self::Insert,
+                    Self::Escape,
+                ])
+                .unwrap(),
+            1 => Self::F(u8::arbitrary(g) % 24 + 1),
+            2 => Self::Keypad(
+                *g.choose(&"0123456789.+-*/=\n".chars().collect::<Vec<_>>())
+                    .unwrap(),
+            ),
+            3 => Self::Media(*g.choose(&MediaKey::ALL).unwrap()),
+            _ => loop {
+                let c = char::arbitrary(g);
+                if !c.is_uppercase() && (!c.is_control() || matches!(c, '\n' | '\t' | '\x7F')) {
+                    break Self::Char(c);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for KeyKind {
+    fn arbitrary(g: &mut Gen) -> Self {
+        *g.choose(&[Self::Press, Self::Repeat, Self::Release])
+            .unwrap()
+    }
+}
+
+/// The mouse is always inside the element receiving it, so `at` is less than `size`.
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Mouse {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let size = Vec2::<u16>::arbitrary(g).map(|v| v.max(1));
+        Self {
+            kind: MouseKind::arbitrary(g),
+            at: Vec2::new(u16::arbitrary(g) % size.x, u16::arbitrary(g) % size.y),
+            size,
+            terminal_at: Vec2::arbitrary(g),
+            modifiers: Modifiers::arbitrary(g),
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for MouseKind {
+    fn arbitrary(g: &mut Gen) -> Self {
+        match u8::arbitrary(g) % 10 {
+            0 => Self::Press(MouseButton::arbitrary(g)),
+            1 => Self::Release(MouseButton::arbitrary(g)),
+            2 => Self::Drag(MouseButton::arbitrary(g)),
+            3 => Self::Move,
+            4 => Self::ScrollDown,
+            5 => Self::ScrollUp,
+            6 => Self::ScrollLeft,
+            7 => Self::ScrollRight,
+            8 => Self::Enter(Option::arbitrary(g)),
+            _ => Self::Leave(Vec2::arbitrary(g)),
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for MouseButton {
+    fn arbitrary(g: &mut Gen) -> Self {
+        *g.choose(&[Self::Left, Self::Middle, Self::Right]).unwrap()
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Signal {
+    fn arbitrary(g: &mut Gen) -> Self {
+        *g.choose(&[
+            Self::Interrupt,
+            Self::Terminate,
+            Self::Hangup,
+            Self::Continue,
+        ])
+        .unwrap()
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Modifiers {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            shift: bool::arbitrary(g),
+            control: bool::arbitrary(g),
+            alt: bool::arbitrary(g),
+        }
+    }
+}
+
 /// A pattern that matches inputs.
 ///
 /// This is implemented for:
 /// - Functions that take an input and return a boolean.
-/// - [`Input`], [`KeyPress`], [`Mouse`] and [`char`] which just perform an equality check.
-/// - [`Key`], which does not allow any modifiers to be held down.
+/// - [`Input`], [`KeyPress`] and [`Mouse`] which just perform an equality check.
+/// - [`char`], which matches the character being pressed or repeated but not released.
+/// - [`Key`], which does not allow any modifiers to be held down and matches the key being pressed
+/// or repeated but not released.
 /// - [`MouseKind`], which can occur at any position without modifiers.
+/// - [`Signal`], which matches the signal being sent.
 /// - Tuples, which detect any one of the inputs occurring.
 ///
 /// You can use the [`input`](crate::input!) macro to generate patterns concisely.
 pub trait Pattern {
     /// Whether the pattern matches this input.
     fn matches(&self, input: Input) -> bool;
+
+    /// Describe the pattern, for [`debug_tree`](crate::debug_tree).
+    ///
+    /// By default this returns the name of the pattern's type.
+    fn describe(&self) -> String {
+        crate::tree::type_name::<Self>()
+    }
 }
 
 impl<F: Fn(Input) -> bool> Pattern for F {
     fn matches(&self, input: Input) -> bool {
         (self)(input)
     }
+    fn describe(&self) -> String {
+        "closure".to_owned()
+    }
 }
 
 impl Pattern for Input {
     fn matches(&self, input: Input) -> bool {
         *self == input
     }
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
 }
 impl Pattern for KeyPress {
     fn matches(&self, input: Input) -> bool {
         *self == input
     }
+    fn describe(&self) -> String {
+        self.to_string()
+    }
 }
 impl Pattern for Mouse {
     fn matches(&self, input: Input) -> bool {
         *self == input
     }
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
 }
 impl Pattern for char {
     fn matches(&self, input: Input) -> bool {
-        *self == input
+        matches!(input, Input::Key(press) if press.kind != KeyKind::Release && KeyPress {
+            kind: press.kind,
+            ..KeyPress::from(*self)
+        } == press)
+    }
+    fn describe(&self) -> String {
+        format!("{:?}", self)
     }
 }
 
 impl Pattern for Key {
     fn matches(&self, input: Input) -> bool {
-        matches!(input, Input::Key(press) if press.key == *self && press.modifiers.are_none())
+        matches!(input, Input::Key(press) if press.key == *self
+            && press.modifiers.are_none()
+            && press.kind != KeyKind::Release)
+    }
+    fn describe(&self) -> String {
+        format!("{:?}", self)
     }
 }
 
@@ -299,6 +934,18 @@ impl Pattern for MouseKind {
     fn matches(&self, input: Input) -> bool {
         matches!(input, Input::Mouse(mouse) if mouse.kind == *self && mouse.modifiers.are_none())
     }
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl Pattern for Signal {
+    fn matches(&self, input: Input) -> bool {
+        input == Input::Signal(*self)
+    }
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
 }
 
 macro_rules! impl_input_pattern_for_tuples {
@@ -310,7 +957,17 @@ macro_rules! impl_input_pattern_for_tuples {
                     #[allow(non_snake_case)]
                     let ($($param,)*) = self;
                     false
-                    $(|| $param.matches(input))*
+                    $(|| $param.matches(input.clone()))*
+                }
+                fn describe(&self) -> String {
+                    #[allow(non_snake_case)]
+                    let ($($param,)*) = self;
+                    let alternatives: Vec<String> = vec![$($param.describe()),*];
+                    if alternatives.is_empty() {
+                        "()".to_owned()
+                    } else {
+                        alternatives.join(" | ")
+                    }
                 }
             }
         )*
@@ -332,6 +989,230 @@ impl_input_pattern_for_tuples! {
     (A, B, C, D, E, F, G, H, I, J, K, L),
 }
 
+/// A pattern that matches [`Input::Shortcut`]s whose key press matches an inner pattern, typically
+/// used through [`on_global`](crate::ElementExt::on_global).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shortcut<P>(pub P);
+
+impl<P: Pattern> Pattern for Shortcut<P> {
+    fn matches(&self, input: Input) -> bool {
+        match input {
+            Input::Shortcut(press) => self.0.matches(Input::Key(press)),
+            _ => false,
+        }
+    }
+    fn describe(&self) -> String {
+        format!("Shortcut({})", self.0.describe())
+    }
+}
+
+/// Create a pattern that matches a sequence of inputs, like `g g` in Vim or `Ctrl+X Ctrl+S` in
+/// Emacs.
+///
+/// The patterns of the sequence are given as a tuple or [`Vec`]. The returned pattern matches the
+/// last input of the sequence; the inputs before it aren't matched, so they are still passed on to
+/// other elements.
+///
+/// Elements are recreated every frame, so how far through the sequence the user is is stored in a
+/// [`SequenceState`] that lives in your application. Each sequence needs its own state.
+///
+/// # Examples
+///
+/// ```
+/// use toon::input::{self, SequenceState};
+/// use toon::{ElementExt, KeyPress, Modifiers};
+///
+/// # let element = toon::empty();
+/// # #[derive(Clone)]
+/// # enum Event { Top, Save }
+/// let top = SequenceState::new();
+/// let save = SequenceState::new();
+///
+/// let ctrl = |c| KeyPress { modifiers: Modifiers::CONTROL, ..KeyPress::from(c) };
+/// let element = element
+///     .on(input::sequence(&top, ('g', 'g')), |_| Event::Top)
+///     .on(input::sequence(&save, (ctrl('x'), ctrl('s'))), |_| Event::Save);
+/// ```
+#[must_use]
+pub fn sequence<P: SequencePatterns>(state: &SequenceState, patterns: P) -> Sequence<'_, P> {
+    Sequence { patterns, state }
+}
+
+/// A pattern that matches a sequence of inputs, created by the [`sequence`] function.
+#[derive(Debug, Clone, Copy)]
+pub struct Sequence<'a, P> {
+    /// The patterns that make up the sequence.
+    pub patterns: P,
+    state: &'a SequenceState,
+}
+
+impl<'a, P: SequencePatterns> Pattern for Sequence<'a, P> {
+    fn matches(&self, input: Input) -> bool {
+        // Releasing the keys of the sequence doesn't interrupt it.
+        if matches!(input, Input::Key(press) if press.kind == KeyKind::Release) {
+            return false;
+        }
+
+        let mut pending = self.state.pending.borrow_mut();
+        if self.state.timed_out() {
+            pending.clear();
+        }
+
+        let continues = |pending: &[Input]| {
+            self.patterns
+                .get(pending.len())
+                .map_or(false, |pattern| pattern.matches(input.clone()))
+        };
+        // An input that doesn't continue the sequence might start it again.
+        if !continues(&pending) {
+            pending.clear();
+            if !continues(&pending) {
+                return false;
+            }
+        }
+
+        pending.push(input);
+        if self.patterns.get(pending.len()).is_some() {
+            self.state.last.set(Some(self.state.clock.now()));
+            false
+        } else {
+            pending.clear();
+            self.state.last.set(None);
+            true
+        }
+    }
+    fn describe(&self) -> String {
+        let mut steps = Vec::new();
+        while let Some(pattern) = self.patterns.get(steps.len()) {
+            steps.push(pattern.describe());
+        }
+        format!("Sequence({})", steps.join(", "))
+    }
+}
+
+/// The patterns that make up a [`Sequence`].
+///
+/// This is implemented for tuples and [`Vec`]s of patterns.
+pub trait SequencePatterns {
+    /// Get the pattern at an index in the sequence, or [`None`] if it is past the end.
+    fn get(&self, index: usize) -> Option<&dyn Pattern>;
+}
+
+impl<P: Pattern> SequencePatterns for Vec<P> {
+    fn get(&self, index: usize) -> Option<&dyn Pattern> {
+        <[P]>::get(self, index).map(|pattern| pattern as &dyn Pattern)
+    }
+}
+
+macro_rules! impl_sequence_patterns_for_tuples {
+    ($(($($index:tt $param:ident),*),)*) => {
+        $(
+            impl<$($param: Pattern,)*> SequencePatterns for ($($param,)*) {
+                fn get(&self, index: usize) -> Option<&dyn Pattern> {
+                    match index {
+                        $($index => Some(&self.$index),)*
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    }
+}
+impl_sequence_patterns_for_tuples! {
+    (0 A),
+    (0 A, 1 B),
+    (0 A, 1 B, 2 C),
+    (0 A, 1 B, 2 C, 3 D),
+    (0 A, 1 B, 2 C, 3 D, 4 E),
+    (0 A, 1 B, 2 C, 3 D, 4 E, 5 F),
+    (0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G),
+    (0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H),
+}
+
+/// How far through a [`Sequence`] the user is.
+///
+/// If the user doesn't continue the sequence within the timeout, it is forgotten. Default is one
+/// second, measured with a [`SystemClock`].
+#[derive(Debug, Clone)]
+pub struct SequenceState {
+    pending: RefCell<Vec<Input>>,
+    last: Cell<Option<Instant>>,
+    timeout: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl SequenceState {
+    /// Create a new sequence state, with no inputs pending.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            pending: RefCell::new(Vec::new()),
+            last: Cell::new(None),
+            timeout: Duration::from_secs(1),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Set how long the user has to continue the sequence before it is forgotten.
+    #[must_use]
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Set the clock used to measure the timeout, such as the [terminal's
+    /// clock](crate::Terminal::clock).
+    #[must_use]
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Arc::new(clock),
+            ..self
+        }
+    }
+
+    /// Get the inputs of the sequence that have occurred so far, so that they can be displayed to
+    /// the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let state = toon::input::SequenceState::new();
+    ///
+    /// let pending: Vec<_> = state
+    ///     .pending()
+    ///     .into_iter()
+    ///     .filter_map(toon::Input::key)
+    ///     .map(|press| press.to_string())
+    ///     .collect();
+    /// let status = pending.join(" ");
+    /// ```
+    #[must_use]
+    pub fn pending(&self) -> Vec<Input> {
+        if self.timed_out() {
+            Vec::new()
+        } else {
+            self.pending.borrow().clone()
+        }
+    }
+
+    /// Forget the inputs of the sequence that have occurred so far.
+    pub fn clear(&self) {
+        self.pending.borrow_mut().clear();
+        self.last.set(None);
+    }
+
+    fn timed_out(&self) -> bool {
+        self.last
+            .get()
+            .map_or(false, |last| self.clock.elapsed(last) > self.timeout)
+    }
+}
+
+impl Default for SequenceState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// A macro that generates [input patterns](Pattern).
 ///
 /// # Examples
@@ -357,20 +1238,52 @@ impl_input_pattern_for_tuples! {
 /// # ;
 /// ```
 ///
+/// A pattern that matches any letter or digit, or the space bar:
+///
+/// ```
+/// toon::input!(Key('a'..='z') | Key(Digit) | Key(' '))
+/// # ;
+/// ```
+///
+/// A pattern that matches `s` with at least control and shift held down:
+///
+/// ```
+/// use toon::Modifiers;
+///
+/// toon::input!(Key(s) + Modifiers(Modifiers::CONTROL | Modifiers::SHIFT))
+/// # ;
+/// ```
+///
+/// A pattern that matches pasted text that is a single line:
+///
+/// ```
+/// toon::input!(Paste where (|text: &str| !text.contains('\n')))
+/// # ;
+/// ```
+///
 /// # Grammar
 ///
 /// ```text
-/// pattern = part [ '+' pattern ] | '!' pattern;
-/// part = '(' pattern ')' | 'Key' key-pattern | 'Mouse' mouse-pattern | modifier-pattern;
+/// pattern = alternative [ '|' pattern ];
+/// alternative = part [ '+' alternative ] | '!' alternative;
+/// part = '(' pattern ')'
+///     | 'Key' key-pattern
+///     | 'Mouse' mouse-pattern
+///     | 'Paste' [ 'where' '(' expression ')' ]
+///     | 'Compose' [ 'where' '(' expression ')' ]
+///     | 'Action' [ '(' expression ')' ]
+///     | modifier-pattern;
 ///
-/// key-pattern = [ '(' key ')' ] [ 'where' '(' expression ')' ];
+/// key-pattern = [ '(' [ key-kind ] [ key | key-range ] ')' ] [ 'where' '(' expression ')' ];
+/// key-kind = 'Press' | 'Repeat' | 'Release';
+/// key-range = char-literal '..=' char-literal | 'Digit';
 /// key = 'Backspace'
 ///     | 'Left' | 'Right' | 'Up' | 'Down'
 ///     | 'Home' | 'End'
 ///     | 'PageUp' | 'PageDown'
 ///     | 'Insert'
 ///     | 'Escape'
-///     | 'F1'-'F12'
+///     | 'F1'-'F24'
 ///     | 'F' expression
 ///     | 'Tab'
 ///     | 'Enter' | 'Return'
@@ -380,34 +1293,69 @@ impl_input_pattern_for_tuples! {
 ///     | 'a'-'z'
 ///     | '|' | ';' | ':' | '@' | '#' | '~' | '<' | '>' | ',' | '.' | '/' | '?'
 ///     | char-literal
-///     | 'Char' expression;
+///     | 'Char' expression
+///     | 'Keypad' ( 'Enter' | expression )
+///     | 'Media' media-key;
+/// media-key = 'Play' | 'Pause' | 'PlayPause' | 'Reverse' | 'Stop' | 'FastForward' | 'Rewind'
+///     | 'NextTrack' | 'PreviousTrack' | 'Record' | 'VolumeDown' | 'VolumeUp' | 'Mute';
 ///
 /// mouse-pattern = [ '(' mouse-kind ')' ] [ 'at' mouse-at ] [ 'where' '(' expression ')' ];
 /// mouse-kind = 'Press' [ mouse-button ]
 ///     | 'Release' [ mouse-button ]
 ///     | 'Drag' [ mouse-button ]
 ///     | 'Move'
-///     | 'ScrollDown' | 'ScrollUp';
+///     | 'ScrollDown' | 'ScrollUp' | 'ScrollLeft' | 'ScrollRight'
+///     | 'Enter' | 'Leave';
 /// mouse-button = 'Left' | 'Middle' | 'Right';
 /// mouse-at = '(' ( '_' | expression ) ',' ( '_' | expression ) [ ',' ] ')'
 ///
-/// modifier-pattern = 'Shift' | 'Control' | 'Alt' | 'None';
+/// modifier-pattern = 'Shift' | 'Control' | 'Alt' | 'None' | 'Modifiers' '(' expression ')';
 /// ```
 ///
+/// Key patterns without a `key-kind` match keys being pressed or repeated, but not released. The
+/// `Enter` and `Leave` mouse kinds only match when the mouse enters or leaves the element itself,
+/// as in [`Mouse::entered`] and [`Mouse::left`].
+/// Character ranges match the character of the key, which is never uppercase. Neither they nor
+/// character keys match keys on the keypad, which are matched with `Keypad`, like
+/// `Keypad '5'`.
+///
+/// Modifier patterns match when at least the given modifiers are held down, except for `None`,
+/// which matches when no modifiers are held down. The expression given to `Modifiers` is a
+/// [`Modifiers`].
+///
 /// The expression given in the `where` part of `key-pattern` and `mouse-pattern` is a function
-/// that takes a [`KeyPress`] or [`Mouse`] and returns a [`bool`].
+/// that takes a [`KeyPress`] or [`Mouse`] and returns a [`bool`]. For `Paste` and `Compose` it
+/// takes the pasted or composed `&str`.
 ///
 /// Note that the `!` operator might not work how you expect; `!Control + Key(f)` is equal to
-/// `!(Control + Key(f))` not `(!Control) + Key(f)`.
+/// `!(Control + Key(f))` not `(!Control) + Key(f)`. It doesn't extend past a `|` though, so
+/// `!Control | Key(f)` is equal to `(!Control) | Key(f)`.
 #[macro_export]
 macro_rules! input {
     ($($input:tt)*) => {
         move |input: $crate::Input| -> $crate::std::primitive::bool {
-            $crate::__internal_input!(input, $($input)*)
+            $crate::__internal_alternatives!(input [] $($input)*)
         }
     };
 }
 
+/// Splits a pattern into the alternatives separated by `|`, collecting the tokens of the current
+/// alternative in the square brackets.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __internal_alternatives {
+    ($input:ident [$($alternative:tt)*]) => {
+        $crate::__internal_input!($input, $($alternative)*)
+    };
+    ($input:ident [$($alternative:tt)*] | $($rest:tt)*) => {
+        $crate::__internal_input!($input, $($alternative)*)
+            || $crate::__internal_alternatives!($input [] $($rest)*)
+    };
+    ($input:ident [$($alternative:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__internal_alternatives!($input [$($alternative)* $next] $($rest)*)
+    };
+}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! __internal_input {
@@ -415,15 +1363,15 @@ macro_rules! __internal_input {
         !$crate::__internal_input!($input, $($rest)*)
     };
     ($input:ident, ($($inner:tt)*) $(+ $($rest:tt)*)?) => {
-        $crate::__internal_input!($input, $($inner)*) $(&& $crate::__internal_input!($input, $($rest)*))?
+        $crate::__internal_alternatives!($input [] $($inner)*)
+            $(&& $crate::__internal_input!($input, $($rest)*))?
     };
     // Key pattern
     ($input:ident, Key $(($($key:tt)*))? $(where ($f:expr))? $(+ $($rest:tt)*)?) => {{
         #[allow(unused_variables)]
         let b = $crate::std::matches!(
                 $input,
-                $crate::Input::Key(press) if true
-                    $(&& press.key == $crate::__internal_key!($($key)*))?
+                $crate::Input::Key(press) if $crate::__internal_key_pattern!(press, $($($key)*)?)
                     $(&& $f(press))?
             )
                 $(&& $crate::__internal_input!($input, $($rest)*))?;
@@ -448,13 +1396,90 @@ macro_rules! __internal_input {
             $(&& $crate::__internal_input!($input, $($rest)*))?;
         b
     }};
+    // Paste pattern
+    ($input:ident, Paste $(where ($f:expr))? $(+ $($rest:tt)*)?) => {{
+        #[allow(unused_variables, clippy::redundant_closure_call)]
+        let b = $crate::std::matches!(
+            &$input,
+            $crate::Input::Paste(text) if true $(&& ($f)(text.as_str()))?
+        )
+            $(&& $crate::__internal_input!($input, $($rest)*))?;
+        b
+    }};
+    // Compose pattern
+    ($input:ident, Compose $(where ($f:expr))? $(+ $($rest:tt)*)?) => {{
+        #[allow(unused_variables, clippy::redundant_closure_call)]
+        let b = $crate::std::matches!(
+            &$input,
+            $crate::Input::Compose(text) if true $(&& ($f)(text.as_str()))?
+        )
+            $(&& $crate::__internal_input!($input, $($rest)*))?;
+        b
+    }};
+    // Action pattern
+    ($input:ident, Action $(($action:expr))? $(+ $($rest:tt)*)?) => {{
+        #[allow(unused_variables)]
+        let b = $crate::std::matches!(
+            &$input,
+            $crate::Input::Action(action) if true $(&& action == $action)?
+        )
+            $(&& $crate::__internal_input!($input, $($rest)*))?;
+        b
+    }};
     // Modifier pattern
+    ($input:ident, Modifiers($modifiers:expr) $(+ $($rest:tt)*)?) => {
+        $input.modifiers().contains($modifiers)
+            $(&& $crate::__internal_input!($input, $($rest)*))?
+    };
     ($input:ident, $modifier:ident $(+ $($rest:tt)*)?) => {
         $crate::__internal_modifier_pattern!($input, $modifier)
             $(&& $crate::__internal_input!($input, $($rest)*))?
     };
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __internal_key_pattern {
+    ($press:ident, Press) => {
+        $press.kind == $crate::KeyKind::Press
+    };
+    ($press:ident, Press $($key:tt)+) => {
+        $press.kind == $crate::KeyKind::Press && $crate::__internal_key_matches!($press, $($key)+)
+    };
+    ($press:ident, Repeat) => {
+        $press.kind == $crate::KeyKind::Repeat
+    };
+    ($press:ident, Repeat $($key:tt)+) => {
+        $press.kind == $crate::KeyKind::Repeat && $crate::__internal_key_matches!($press, $($key)+)
+    };
+    ($press:ident, Release) => {
+        $press.kind == $crate::KeyKind::Release
+    };
+    ($press:ident, Release $($key:tt)+) => {
+        $press.kind == $crate::KeyKind::Release && $crate::__internal_key_matches!($press, $($key)+)
+    };
+    ($press:ident,) => {
+        $press.kind != $crate::KeyKind::Release
+    };
+    ($press:ident, $($key:tt)+) => {
+        $press.kind != $crate::KeyKind::Release && $crate::__internal_key_matches!($press, $($key)+)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __internal_key_matches {
+    ($press:ident, $start:literal ..= $end:literal) => {
+        $crate::std::matches!($press.key, $crate::Key::Char(c) if ($start..=$end).contains(&c))
+    };
+    ($press:ident, Digit) => {
+        $crate::std::matches!($press.key, $crate::Key::Char('0'..='9'))
+    };
+    ($press:ident, $($key:tt)+) => {
+        $press.key == $crate::__internal_key!($($key)+)
+    };
+}
+
 #[macro_export]
 #[doc(hidden)]
 #[rustfmt::skip]
@@ -482,6 +1507,18 @@ macro_rules! __internal_key {
     (F10) => ($crate::Key::F(10));
     (F11) => ($crate::Key::F(11));
     (F12) => ($crate::Key::F(12));
+    (F13) => ($crate::Key::F(13));
+    (F14) => ($crate::Key::F(14));
+    (F15) => ($crate::Key::F(15));
+    (F16) => ($crate::Key::F(16));
+    (F17) => ($crate::Key::F(17));
+    (F18) => ($crate::Key::F(18));
+    (F19) => ($crate::Key::F(19));
+    (F20) => ($crate::Key::F(20));
+    (F21) => ($crate::Key::F(21));
+    (F22) => ($crate::Key::F(22));
+    (F23) => ($crate::Key::F(23));
+    (F24) => ($crate::Key::F(24));
     (F $n:expr) => ($crate::Key::F($n));
     (Tab) => ($crate::Key::Char('\t'));
     (Enter) => ($crate::Key::Char('\n'));
@@ -547,6 +1584,9 @@ macro_rules! __internal_key {
     (?) => ($crate::Key::Char('?'));
     ($c:literal) => ($crate::Key::Char($c));
     (Char $c:expr) => ($crate::Key::Char($c));
+    (Keypad Enter) => ($crate::Key::Keypad('\n'));
+    (Keypad $c:expr) => ($crate::Key::Keypad($c));
+    (Media $key:ident) => ($crate::Key::Media($crate::input::MediaKey::$key));
 }
 
 #[macro_export]
@@ -570,6 +1610,12 @@ macro_rules! __internal_mouse_kind {
             $crate::MouseKind::Drag(button) $(if button == $crate::MouseButton::$button)?
         )
     };
+    ($input:ident, Enter) => {
+        $input.entered()
+    };
+    ($input:ident, Leave) => {
+        $input.left()
+    };
     ($input:ident, $other:ident $(at $($at:tt)*)?) => {
         $crate::std::matches!($input.kind, $crate::MouseKind::$other)
     }
@@ -612,6 +1658,7 @@ fn test_input_macro() {
         kind: MouseKind::Press(MouseButton::Middle),
         at: Vec2::new(5, 6),
         size: Vec2::new(7, 8),
+        terminal_at: Vec2::new(5, 6),
         modifiers: Modifiers::SHIFT,
     };
 
@@ -650,7 +1697,41 @@ fn test_input_macro() {
     assert!(input!(Control + Key(b)).matches(Input::Key(KeyPress {
         key: Key::Char('b'),
         modifiers: Modifiers::CONTROL,
+        kind: KeyKind::Press,
+    })));
+
+    let repeat = |c| {
+        Input::Key(KeyPress {
+            kind: KeyKind::Repeat,
+            ..KeyPress::from(c)
+        })
+    };
+    let release = |c| {
+        Input::Key(KeyPress {
+            kind: KeyKind::Release,
+            ..KeyPress::from(c)
+        })
+    };
+    assert!(input!(Key).matches(repeat('a')));
+    assert!(!input!(Key).matches(release('a')));
+    assert!(input!(Key(a)).matches(repeat('a')));
+    assert!(!input!(Key(a)).matches(release('a')));
+    assert!(input!(Key(Press a)).matches(Input::Key(KeyPress::from('a'))));
+    assert!(!input!(Key(Press a)).matches(repeat('a')));
+    assert!(input!(Key(Repeat Up)).matches(Input::Key(KeyPress {
+        key: Key::Up,
+        modifiers: Modifiers::default(),
+        kind: KeyKind::Repeat,
     })));
+    assert!(input!(Key(Release)).matches(release('b')));
+    assert!(input!(Key(Release b)).matches(release('b')));
+    assert!(!input!(Key(Release a)).matches(release('b')));
+    assert!(!input!(Key(Release a)).matches(Input::Key(KeyPress::from('a'))));
+
+    assert!('a'.matches(repeat('a')));
+    assert!(!'a'.matches(release('a')));
+    assert!(Key::Char('a').matches(repeat('a')));
+    assert!(!Key::Char('a').matches(release('a')));
 
     assert!(input!(Mouse(Press)).matches(Input::Mouse(mouse)));
     assert!(!input!(Mouse(Release)).matches(Input::Mouse(mouse)));
@@ -658,3 +1739,235 @@ fn test_input_macro() {
     assert!(input!(Mouse(Press Middle)).matches(Input::Mouse(mouse)));
     assert!(!input!(Mouse(Press Left)).matches(Input::Mouse(mouse)));
 }
+
+#[test]
+fn test_input_macro_alternatives() {
+    let key = |c| Input::Key(KeyPress::from(c));
+    let letter = input!(Key('a'..='f'));
+    assert!(letter.matches(key('a')));
+    assert!(letter.matches(key('F')));
+    assert!(!letter.matches(key('g')));
+    assert!(!letter.matches(Input::Key(KeyPress {
+        kind: KeyKind::Release,
+        ..KeyPress::from('a')
+    })));
+    assert!(input!(Key(Release 'a'..='f')).matches(Input::Key(KeyPress {
+        kind: KeyKind::Release,
+        ..KeyPress::from('b')
+    })));
+    assert!(input!(Key(Digit)).matches(key('7')));
+    assert!(!input!(Key(Digit)).matches(key('a')));
+
+    let alternatives = input!(Key(Digit) | Shift + Key(a) | Mouse(Press Middle));
+    assert!(alternatives.matches(key('0')));
+    assert!(alternatives.matches(key('A')));
+    assert!(!alternatives.matches(key('a')));
+    assert!(alternatives.matches(Input::Mouse(Mouse {
+        kind: MouseKind::Press(MouseButton::Middle),
+        at: Vec2::new(0, 0),
+        size: Vec2::new(1, 1),
+        terminal_at: Vec2::new(0, 0),
+        modifiers: Modifiers::default(),
+    })));
+    assert!(input!(!Shift | Key(a)).matches(key('A')));
+    assert!(!input!(!(Shift | Key(a))).matches(key('a')));
+    assert!(
+        input!(Control + (Key(a) | Key(b))).matches(Input::Key(KeyPress {
+            modifiers: Modifiers::CONTROL | Modifiers::ALT,
+            ..KeyPress::from('b')
+        }))
+    );
+    assert!(input!(Key(|)).matches(key('|')));
+
+    let modifiers = input!(Modifiers(Modifiers::CONTROL | Modifiers::SHIFT));
+    assert!(modifiers.matches(Input::Key(KeyPress {
+        modifiers: Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT,
+        ..KeyPress::from('b')
+    })));
+    assert!(!modifiers.matches(Input::Key(KeyPress {
+        modifiers: Modifiers::CONTROL,
+        ..KeyPress::from('b')
+    })));
+}
+
+#[test]
+fn test_input_macro_paste() {
+    let paste = |text: &str| Input::Paste(text.to_owned());
+    assert!(input!(Paste).matches(paste("a\nb")));
+    assert!(!input!(Paste).matches(Input::Key(KeyPress::from('a'))));
+    assert!(!input!(Key).matches(paste("a")));
+
+    let line = input!(Paste where (|text: &str| !text.contains('\n')) | Key(Enter));
+    assert!(line.matches(paste("a b")));
+    assert!(!line.matches(paste("a\nb")));
+    assert!(line.matches(Input::Key(KeyPress::from('\n'))));
+    assert!(!input!(Shift + Paste).matches(paste("a")));
+
+    let composing = input!(Compose where (|text: &str| !text.is_empty()));
+    assert!(composing.matches(Input::Compose("日".to_owned())));
+    assert!(!composing.matches(Input::Compose(String::new())));
+    assert!(!composing.matches(paste("日")));
+
+    let action = |name: &str| Input::Action(name.to_owned());
+    assert!(input!(Action).matches(action("quit")));
+    assert!(input!(Action("quit")).matches(action("quit")));
+    assert!(!input!(Action("quit")).matches(action("save")));
+    assert!(!input!(Action("q")).matches(Input::Key(KeyPress::from('q'))));
+}
+
+#[test]
+fn test_input_macro_extended_keys() {
+    let key = |key| Input::Key(KeyPress::from(key));
+
+    assert!(input!(Key(F13)).matches(key(Key::F(13))));
+    assert!(input!(Key(F24)).matches(key(Key::F(24))));
+    assert!(input!(Key(Keypad '5')).matches(key(Key::Keypad('5'))));
+    assert!(!input!(Key(Keypad '5')).matches(key(Key::Char('5'))));
+    assert!(!input!(Key(5)).matches(key(Key::Keypad('5'))));
+    assert!(!input!(Key(Digit)).matches(key(Key::Keypad('5'))));
+    assert!(input!(Key(Keypad Enter)).matches(key(Key::Keypad('\n'))));
+    assert!(!input!(Key(Enter)).matches(key(Key::Keypad('\n'))));
+    assert!(
+        input!(Key(Release Keypad '+')).matches(Input::Key(KeyPress {
+            kind: KeyKind::Release,
+            ..KeyPress::from(Key::Keypad('+'))
+        }))
+    );
+    assert!(input!(Key(Media PlayPause)).matches(key(Key::Media(MediaKey::PlayPause))));
+    assert!(!input!(Key(Media Play)).matches(key(Key::Media(MediaKey::PlayPause))));
+}
+
+#[test]
+fn test_key_press_display() {
+    assert_eq!(KeyPress::from('a').to_string(), "a");
+    assert_eq!(KeyPress::from('A').to_string(), "A");
+    assert_eq!(
+        KeyPress {
+            modifiers: Modifiers::CONTROL | Modifiers::SHIFT,
+            ..KeyPress::from('\t')
+        }
+        .to_string(),
+        "Ctrl+Shift+Tab"
+    );
+    assert_eq!(
+        KeyPress {
+            key: Key::F(5),
+            modifiers: Modifiers::ALT,
+            kind: KeyKind::Press,
+        }
+        .to_string(),
+        "Alt+F5"
+    );
+    assert_eq!(KeyPress::from(Key::F(24)).to_string(), "F24");
+    assert_eq!(KeyPress::from(Key::Keypad('5')).to_string(), "Keypad5");
+    assert_eq!(KeyPress::from(Key::Keypad('+')).to_string(), "KeypadPlus");
+    assert_eq!(
+        KeyPress::from(Key::Media(MediaKey::NextTrack)).to_string(),
+        "NextTrack"
+    );
+}
+
+#[test]
+fn test_key_press_from_str() {
+    let parse = |s: &str| s.parse::<KeyPress>();
+    let ctrl = |c| KeyPress {
+        modifiers: Modifiers::CONTROL,
+        ..KeyPress::from(c)
+    };
+
+    assert_eq!(parse("a"), Ok(KeyPress::from('a')));
+    assert_eq!(parse("A"), Ok(KeyPress::from('A')));
+    assert_eq!(parse("Ctrl+x"), Ok(ctrl('x')));
+    assert_eq!(
+        parse("control+X"),
+        Ok(KeyPress {
+            modifiers: Modifiers::CONTROL | Modifiers::SHIFT,
+            ..KeyPress::from('x')
+        })
+    );
+    assert_eq!(parse("ctrl++"), Ok(ctrl('+')));
+    assert_eq!(parse("+"), Ok(KeyPress::from('+')));
+    assert_eq!(parse("f12"), Ok(KeyPress::from(Key::F(12))));
+    assert_eq!(
+        parse("Alt+Shift+PageUp"),
+        Ok(KeyPress {
+            modifiers: Modifiers::ALT | Modifiers::SHIFT,
+            ..KeyPress::from(Key::PageUp)
+        })
+    );
+    assert_eq!(parse("Space"), Ok(KeyPress::from(' ')));
+    assert_eq!(
+        parse("Super+a"),
+        Err(ParseKeyPressError("Super+a".to_owned()))
+    );
+    assert!(parse("").is_err());
+    assert!(parse("foo").is_err());
+    assert_eq!(parse("keypad7"), Ok(KeyPress::from(Key::Keypad('7'))));
+    assert_eq!(
+        parse("Shift+playpause"),
+        Ok(KeyPress {
+            modifiers: Modifiers::SHIFT,
+            ..KeyPress::from(Key::Media(MediaKey::PlayPause))
+        })
+    );
+    assert!(parse("Keypad").is_err());
+
+    for press in &[
+        ctrl('x'),
+        ctrl('\t'),
+        KeyPress::from('A'),
+        KeyPress::from(Key::F(3)),
+        KeyPress::from(Key::Keypad('\n')),
+        KeyPress::from(Key::Keypad('*')),
+        KeyPress {
+            modifiers: Modifiers::CONTROL,
+            ..KeyPress::from(Key::Keypad('+'))
+        },
+        KeyPress::from(Key::Media(MediaKey::VolumeUp)),
+    ] {
+        assert_eq!(parse(&press.to_string()).as_ref(), Ok(press));
+    }
+}
+
+#[test]
+fn test_sequence() {
+    let key = |c| Input::Key(KeyPress::from(c));
+
+    let state = SequenceState::new();
+    let pattern = sequence(&state, ('g', 'g'));
+    assert!(!pattern.matches(key('g')));
+    assert_eq!(state.pending(), [key('g')]);
+    assert!(pattern.matches(key('g')));
+    assert!(state.pending().is_empty());
+
+    // Releases are ignored, and other inputs interrupt the sequence.
+    assert!(!pattern.matches(key('g')));
+    assert!(!pattern.matches(Input::Key(KeyPress {
+        kind: KeyKind::Release,
+        ..KeyPress::from('g')
+    })));
+    assert!(!pattern.matches(key('x')));
+    assert!(state.pending().is_empty());
+    assert!(!pattern.matches(key('g')));
+    assert!(pattern.matches(key('g')));
+
+    let state = SequenceState::new();
+    let pattern = sequence(&state, vec!['a', 'b', 'c']);
+    for &c in &['a', 'b', 'a', 'b'] {
+        assert!(!pattern.matches(key(c)));
+    }
+    assert_eq!(state.pending(), [key('a'), key('b')]);
+    assert!(pattern.matches(key('c')));
+
+    let clock = crate::clock::MockClock::new();
+    let state = SequenceState::new()
+        .with_timeout(Duration::from_millis(500))
+        .with_clock(clock.clone());
+    let pattern = sequence(&state, ('g', 'g'));
+    assert!(!pattern.matches(key('g')));
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(state.pending(), [key('g')]);
+    clock.advance(Duration::from_millis(1));
+    assert!(state.pending().is_empty());
+    assert!(!pattern.matches(key('g')));
+}
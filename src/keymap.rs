@@ -0,0 +1,275 @@
+//! Key bindings that can be loaded from configuration.
+
+use std::collections::BTreeMap;
+
+use crate::input::{Input, KeyKind, KeyPress, Pattern};
+
+/// A map from named actions to the keys that trigger them.
+///
+/// Applications can set up default bindings with [`bind`](Self::bind) and then override them with
+/// ones loaded from the user's configuration, so that users can rebind keys without recompiling.
+/// With the `serde` feature the keymap can be deserialized from a map of action names to a key
+/// press or list of key presses, written like [`KeyPress`]es are displayed.
+///
+/// A keymap is usually given to the [`Terminal`](crate::Terminal) with
+/// [`set_keymap`](crate::Terminal::set_keymap), which turns the keys bound in it into
+/// [`Input::Action`]s that elements listen to with [`on_action`](crate::ElementExt::on_action).
+/// Because all the bindings are in one place, help screens can be built from
+/// [`bindings`](Self::bindings).
+///
+/// # Examples
+///
+/// ```
+/// use toon::{ElementExt, KeyPress, Keymap};
+///
+/// # let element = toon::empty();
+/// # #[derive(Clone)]
+/// # enum Event { Quit }
+/// let mut keymap = Keymap::new()
+///     .bind("quit", 'q')
+///     .bind("quit", "Ctrl+c".parse::<KeyPress>()?);
+///
+/// // Loaded from the user's configuration.
+/// let user = Keymap::new().bind("quit", "Escape".parse::<KeyPress>()?);
+/// keymap.extend(user);
+///
+/// // The lines of a help screen.
+/// let help: Vec<String> = keymap
+///     .bindings()
+///     .map(|(action, keys)| {
+///         let keys: Vec<_> = keys.iter().map(ToString::to_string).collect();
+///         format!("{}: {}", keys.join(", "), action)
+///     })
+///     .collect();
+/// assert_eq!(help, ["Escape: quit"]);
+///
+/// let element = element.on_action("quit", |_| Event::Quit);
+/// # Ok::<_, toon::input::ParseKeyPressError>(())
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<String, Vec<KeyPress>>,
+}
+
+impl Keymap {
+    /// Create a new keymap with no bindings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a key that triggers an action.
+    #[must_use]
+    pub fn bind(mut self, action: impl Into<String>, key: impl Into<KeyPress>) -> Self {
+        self.bindings
+            .entry(action.into())
+            .or_default()
+            .push(key.into());
+        self
+    }
+
+    /// Get the keys that trigger an action.
+    #[must_use]
+    pub fn keys(&self, action: &str) -> &[KeyPress] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get a pattern that matches the keys that trigger an action.
+    ///
+    /// This is useful for keymaps that aren't given to the terminal.
+    #[must_use]
+    pub fn pattern<'a>(&'a self, action: &str) -> KeyPattern<'a> {
+        KeyPattern {
+            keys: self.keys(action),
+        }
+    }
+
+    /// Get the actions that an input triggers, in alphabetical order.
+    pub fn actions<'a>(&'a self, input: &'a Input) -> impl Iterator<Item = &'a str> + 'a {
+        self.bindings()
+            .filter(move |(_, keys)| KeyPattern { keys }.matches(input.clone()))
+            .map(|(action, _)| action)
+    }
+
+    /// Iterate over the actions in the keymap and the keys that trigger them, in alphabetical
+    /// order of action.
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &[KeyPress])> + '_ {
+        self.bindings
+            .iter()
+            .map(|(action, keys)| (action.as_str(), keys.as_slice()))
+    }
+
+    /// Replace the bindings of the actions in another keymap with its bindings.
+    ///
+    /// Actions that aren't in the other keymap keep their current bindings.
+    pub fn extend(&mut self, other: Self) {
+        self.bindings.extend(other.bindings);
+    }
+}
+
+/// A pattern that matches an [`Input::Action`], typically used through
+/// [`on_action`](crate::ElementExt::on_action).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Action<'a> {
+    /// The name of the action.
+    pub name: &'a str,
+}
+
+impl<'a> Pattern for Action<'a> {
+    fn matches(&self, input: Input) -> bool {
+        matches!(input, Input::Action(action) if action == self.name)
+    }
+    fn describe(&self) -> String {
+        format!("Action({:?})", self.name)
+    }
+}
+
+/// A pattern that matches the keys that trigger an action in a [`Keymap`], created by
+/// [`Keymap::pattern`].
+///
+/// Like [`char`] patterns, it matches keys being pressed or repeated but not released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPattern<'a> {
+    /// The keys that trigger the action.
+    pub keys: &'a [KeyPress],
+}
+
+impl<'a> Pattern for KeyPattern<'a> {
+    fn matches(&self, input: Input) -> bool {
+        match input {
+            Input::Key(press) if press.kind != KeyKind::Release => self.keys.iter().any(|key| {
+                KeyPress {
+                    kind: press.kind,
+                    ..*key
+                } == press
+            }),
+            _ => false,
+        }
+    }
+    fn describe(&self) -> String {
+        let keys: Vec<String> = self.keys.iter().map(ToString::to_string).collect();
+        format!("Keys({})", keys.join(" | "))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for Keymap {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.bindings.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Keymap {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bindings = BTreeMap::<String, Keys>::deserialize(deserializer)?;
+        Ok(Self {
+            bindings: bindings
+                .into_iter()
+                .map(|(action, keys)| (action, keys.0))
+                .collect(),
+        })
+    }
+}
+
+/// The keys bound to an action in a deserialized keymap, which can be either a single key or a
+/// list of keys.
+#[cfg(feature = "serde")]
+struct Keys(Vec<KeyPress>);
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Keys {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde_crate::de::{self, SeqAccess, Visitor};
+        use std::fmt::{self, Formatter};
+
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = Keys;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a key press or list of key presses")
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Keys(vec![v.parse().map_err(E::custom)?]))
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut keys = Vec::new();
+                while let Some(key) = seq.next_element()? {
+                    keys.push(key);
+                }
+                Ok(Keys(keys))
+            }
+        }
+
+        deserializer.deserialize_any(KeysVisitor)
+    }
+}
+
+#[test]
+fn test_keymap() {
+    let mut keymap = Keymap::new()
+        .bind("quit", 'q')
+        .bind("quit", 'x')
+        .bind("up", 'k');
+    keymap.extend(Keymap::new().bind("up", crate::Key::Up));
+
+    assert_eq!(
+        keymap.keys("quit"),
+        [KeyPress::from('q'), KeyPress::from('x')]
+    );
+    assert_eq!(keymap.keys("up"), [KeyPress::from(crate::Key::Up)]);
+    assert!(keymap.keys("down").is_empty());
+
+    assert_eq!(
+        keymap.bindings().collect::<Vec<_>>(),
+        [
+            ("quit", &[KeyPress::from('q'), KeyPress::from('x')][..]),
+            ("up", &[KeyPress::from(crate::Key::Up)][..]),
+        ]
+    );
+    let both = Keymap::new().bind("b", 'q').bind("a", 'q');
+    let input = Input::Key(KeyPress::from('q'));
+    assert_eq!(both.actions(&input).collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(keymap.actions(&input).collect::<Vec<_>>(), ["quit"]);
+
+    let quit = keymap.pattern("quit");
+    assert!(quit.matches(Input::Key(KeyPress::from('x'))));
+    assert!(quit.matches(Input::Key(KeyPress {
+        kind: KeyKind::Repeat,
+        ..KeyPress::from('q')
+    })));
+    assert!(!quit.matches(Input::Key(KeyPress {
+        kind: KeyKind::Release,
+        ..KeyPress::from('q')
+    })));
+    assert!(!quit.matches(Input::Key(KeyPress::from('Q'))));
+    assert!(!keymap
+        .pattern("down")
+        .matches(Input::Key(KeyPress::from('q'))));
+
+    let action = Action { name: "quit" };
+    assert!(action.matches(Input::Action("quit".to_owned())));
+    assert!(!action.matches(Input::Action("up".to_owned())));
+    assert!(!action.matches(Input::Key(KeyPress::from('q'))));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let keymap: Keymap =
+        serde_json::from_str(r#"{ "quit": ["q", "Ctrl+c"], "save": "Ctrl+s" }"#).unwrap();
+    assert_eq!(
+        keymap,
+        Keymap::new()
+            .bind("quit", 'q')
+            .bind("quit", "Ctrl+c".parse::<KeyPress>().unwrap())
+            .bind("save", "Ctrl+s".parse::<KeyPress>().unwrap()),
+    );
+
+    let json = serde_json::to_string(&keymap).unwrap();
+    assert_eq!(serde_json::from_str::<Keymap>(&json).unwrap(), keymap);
+
+    assert!(serde_json::from_str::<Keymap>(r#"{ "quit": "Hyper+q" }"#).is_err());
+}
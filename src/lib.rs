@@ -51,6 +51,12 @@
 //! # Features
 //!
 //! Toon offers the following features, none of which are enabled by default:
+//! - `async-io`: Allow reading captured standard output and standard error asynchronously without
+//! a thread on Unix-like platforms, through
+//! [`Captured::into_async`](https://docs.rs/toon/0.1/toon/struct.Captured.html#method.into_async)
+//! and
+//! [`CapturedStream::into_async`](https://docs.rs/toon/0.1/toon/struct.CapturedStream.html#method.into_async).
+//! This does nothing on Windows.
 //! - `crossterm`: Enable the
 //! [Crossterm](https://docs.rs/toon/0.1/toon/backend/struct.Crossterm.html) backend.
 //! - `dev`: Enable developer tools.
@@ -59,6 +65,33 @@
 //! [`Output`](https://docs.rs/toon/0.1/toon/output/trait.Output.html) and
 //! [`Collection`](https://docs.rs/toon/0.1/toon/elements/containers/trait.Collection.html) for
 //! `Either`.
+//! - `normalize`: Normalize text to [NFC](https://unicode.org/reports/tr15/) as it is written to
+//! cells, so that visually identical text written in composed and decomposed forms produces the
+//! same cells and isn't redrawn when it changes form.
+//! - `quickcheck`: Implement
+//! [`Arbitrary`](https://docs.rs/quickcheck/1/quickcheck/trait.Arbitrary.html) for inputs, styles
+//! and vectors, so that elements and filters can be property-tested with
+//! [`quickcheck`](https://crates.io/crates/quickcheck).
+//! - `ratatui`: Draw [Ratatui](https://crates.io/crates/ratatui) widgets as elements with
+//! [`ratatui_widget`](https://docs.rs/toon/0.1/toon/fn.ratatui_widget.html), so that existing
+//! widgets can be reused while migrating to Toon.
+//! - `serde`: Allow serializing and deserializing
+//! [`Keymap`](https://docs.rs/toon/0.1/toon/keymap/struct.Keymap.html)s, key presses and
+//! [`Style`](https://docs.rs/toon/0.1/toon/struct.Style.html)s with
+//! [`serde`](https://crates.io/crates/serde), so that keys and colors can be configured.
+//! - `stream`: Allow reading captured standard output and standard error asynchronously as a
+//! stream of lines, through
+//! [`Terminal::captured_stream`](https://docs.rs/toon/0.1/toon/struct.Terminal.html#method.captured_stream).
+//! - `termion`: Enable the
+//! [Termion](https://docs.rs/toon/0.1/toon/backend/struct.Termion.html) backend. This is only
+//! available on Unix-like platforms.
+//! - `tokio`: Integrate with [Tokio](https://crates.io/crates/tokio). This allows reading captured
+//! standard output and standard error with Tokio's `AsyncRead` through
+//! [`CapturedStream::into_tokio`](https://docs.rs/toon/0.1/toon/struct.CapturedStream.html#method.into_tokio)
+//! and drawing the value of a watch channel as a stream of events through
+//! [`Terminal::watch`](https://docs.rs/toon/0.1/toon/struct.Terminal.html#method.watch), and
+//! enables `stream`. See the `tokio_select` example for waiting on terminal events and
+//! other channels at the same time with `tokio::select!`.
 #![cfg_attr(feature = "doc_cfg", feature(doc_cfg))]
 #![warn(
     clippy::cargo,
@@ -106,21 +139,47 @@ pub use either_crate as either;
 #[cfg(feature = "either")]
 use either_crate::Either;
 
+#[cfg(feature = "ratatui")]
+pub use ratatui_crate as ratatui;
+
 pub mod backend;
 #[cfg(feature = "crossterm")]
 #[doc(no_inline)]
 pub use backend::Crossterm;
+#[cfg(all(unix, feature = "termion"))]
+#[doc(no_inline)]
+pub use backend::Termion;
+#[cfg(windows)]
+#[doc(no_inline)]
+pub use backend::Console;
 #[doc(no_inline)]
-pub use backend::{Backend, Dummy};
+pub use backend::{Ansi, Backend, Dummy, Generic};
 
 pub mod buffer;
 pub use buffer::*;
 
+pub mod clock;
+pub use clock::Clock;
+
+pub mod command;
+pub use command::{Command, Commands};
+
+pub mod component;
+pub use component::{Component, ComponentEvent, States};
+
 pub mod elements;
 pub use elements::*;
 
+pub mod export;
+
+pub mod keymap;
+pub use keymap::Keymap;
+
 pub mod input;
-pub use input::{Input, Key, KeyPress, Modifiers, Mouse, MouseButton, MouseKind};
+pub use input::{
+    Input, Key, KeyKind, KeyPress, MediaKey, Modifiers, Mouse, MouseButton, MouseCapture,
+    MouseKind, Signal,
+};
 
 pub mod output;
 pub use output::Output;
@@ -128,9 +187,34 @@ pub use output::Output;
 pub mod style;
 pub use style::*;
 
+pub mod test;
+#[doc(hidden)]
+pub use test::{__assert_grid_eq, __expected_grid};
+
+pub mod theme;
+pub use theme::Theme;
+
+pub mod tree;
+pub use tree::debug_tree;
+
+mod bitmap;
+pub use bitmap::Bitmap;
+
+mod blocks;
+pub use blocks::{left_block, lower_block, shade_block};
+
+mod box_drawing;
+pub use box_drawing::merge_box_drawing;
+
+mod braille;
+pub use braille::BrailleGrid;
+
 mod events;
 pub use events::Events;
 
+#[cfg(unix)]
+mod signals;
+
 mod terminal;
 pub use terminal::*;
 
@@ -190,6 +274,14 @@ pub trait Element {
     fn title(&self, _title: &mut dyn fmt::Write) -> fmt::Result {
         Ok(())
     }
+
+    /// Add a description of the element and the elements inside it to a tree, for
+    /// [`debug_tree`].
+    ///
+    /// By default this adds a node with the name of the element's type and no children.
+    fn describe(&self, tree: &mut tree::Tree) {
+        tree.leaf(tree::type_name::<Self>());
+    }
 }
 
 macro_rules! implement_element_forwarding {
@@ -216,6 +308,9 @@ macro_rules! implement_element_forwarding {
                 fn title(&self, title: &mut dyn fmt::Write) -> fmt::Result {
                     (**self).title(title)
                 }
+                fn describe(&self, tree: &mut tree::Tree) {
+                    (**self).describe(tree)
+                }
             }
         )*
     }
@@ -262,6 +357,12 @@ impl<L: Element, R: Element<Event = L::Event>> Element for Either<L, R> {
             Self::Right(r) => r.title(title),
         }
     }
+    fn describe(&self, tree: &mut tree::Tree) {
+        match self {
+            Self::Left(l) => l.describe(tree),
+            Self::Right(r) => r.describe(tree),
+        }
+    }
 }
 
 /// A terminal cursor.
@@ -271,6 +372,8 @@ pub struct Cursor {
     pub shape: CursorShape,
     /// Whether the cursor blinks.
     pub blinking: bool,
+    /// The color of the cursor. [`Color::Default`] uses the terminal's default cursor color.
+    pub color: Color,
     /// The zero-indexed position of the cursor.
     pub pos: Vec2<u16>,
 }
@@ -1,10 +1,14 @@
 //! Outputs which elements draw to.
 
+use std::cmp;
+use std::collections::HashMap;
 use std::fmt::{Display, Write};
 
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthChar;
 
-use crate::{Cursor, Style, Vec2};
+use crate::util::{grapheme_width, WriteGraphemesFn};
+use crate::{Attributes, Bitmap, Color, Cursor, Link, Style, Vec2};
 
 /// An output to which elements draw themselves.
 ///
@@ -23,10 +27,12 @@ pub trait Output {
     ///
     /// - Drawing a control character will fail.
     /// - Drawing a character out of bounds will fail.
-    /// - Drawing a double-width character to the last column of the screen will fail.
-    /// - Drawing a zero-width character on top of an existing character will add it to it, ignoring
-    /// the zero-width character's style.
-    /// - Drawing a zero-width character on the second column of a double-width character will fail.
+    /// - Drawing a double-width character so that only one of its columns is inside the output
+    /// will draw a space in that column instead, in the double-width character's style.
+    /// - Drawing a zero-width character, such as a combining accent, will add it to the character
+    /// covering the column before it, ignoring the zero-width character's style. This means it can
+    /// be drawn at the position after the character it combines with.
+    /// - Drawing a zero-width character to the first column will fail.
     /// - Drawing a single-width or double-width character on top of a single-width character will
     /// completely replace it.
     /// - Drawing a single-width or double-width character to either column of a double-width
@@ -35,10 +41,105 @@ pub trait Output {
     /// character.
     fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style);
 
+    /// Write a grapheme cluster, such as a letter followed by accents or an emoji made of several
+    /// characters, to a single cell of the output at a zero-indexed position.
+    ///
+    /// This works like [`write_char`](Self::write_char), treating the cluster as a single
+    /// character that is one or two columns wide. If the cluster starts with a zero-width
+    /// character it is added to the character before it.
+    ///
+    /// By default this writes the first character of the cluster followed by its zero-width
+    /// characters.
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        let mut chars = grapheme.chars();
+        let first = match chars.next() {
+            Some(first) => first,
+            None => return,
+        };
+        self.write_char(pos, first, style);
+
+        let after = Vec2::new(
+            pos.x.saturating_add(first.width().unwrap_or(0) as u16),
+            pos.y,
+        );
+        for c in chars.filter(|c| c.width() == Some(0)) {
+            self.write_char(after, c, style);
+        }
+    }
+
     /// Set the cursor of the output, if there is one.
     ///
     /// If this is called multiple times the last one will be used.
     fn set_cursor(&mut self, cursor: Option<Cursor>);
+
+    /// Draw an image covering an area of the output, with its top left corner at a zero-indexed
+    /// position.
+    ///
+    /// The image is scaled to cover the area. It is displayed on top of the characters in the
+    /// area, which will be shown instead on terminals that can't display images. Drawing an image
+    /// that doesn't fit completely inside the output will fail.
+    ///
+    /// By default this does nothing.
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        let _ = (pos, size, bitmap);
+    }
+
+    /// Register a named area of the output with its top left corner at a zero-indexed position,
+    /// so that elements can later find out what was drawn at a position with
+    /// [`Terminal::regions_at`](crate::Terminal::regions_at).
+    ///
+    /// Regions are cut off at the edges of the output, and multiple regions can share an id.
+    ///
+    /// By default this does nothing.
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        let _ = (id, pos, size);
+    }
+
+    /// Attach a value, such as the id of a row in a table, to the cell at a zero-indexed
+    /// position, so that elements can later find out what was drawn at a position with
+    /// [`Terminal::cell_data_at`](crate::Terminal::cell_data_at) without recomputing their
+    /// layout.
+    ///
+    /// Setting data for a cell replaces any data previously set for it. The data isn't removed when
+    /// the cell is drawn over, so elements drawn on top of others should set their own data.
+    /// Setting data out of bounds will fail.
+    ///
+    /// By default this does nothing.
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        let _ = (pos, data);
+    }
+
+    /// Make the cell at a zero-indexed position a hyperlink, in terminals that support it.
+    ///
+    /// The link is removed when the cell is drawn over, so it should be set after the cell's
+    /// character is written. Setting a link out of bounds will fail.
+    ///
+    /// By default this does nothing.
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        let _ = (pos, link);
+    }
+
+    /// Get where the top left corner of the output is on the terminal.
+    ///
+    /// This is useful for positioning popups next to an element. It can be outside the terminal
+    /// if the output is scrolled.
+    ///
+    /// By default this is zero, which is correct for outputs that don't draw to an area of
+    /// another output.
+    fn origin(&self) -> Vec2<i32> {
+        Vec2::new(0, 0)
+    }
+
+    /// Get the style that characters drawn to the output inherit their colors from, set by an
+    /// enclosing element with [`with_base_style`](Ext::with_base_style).
+    ///
+    /// Characters drawn with [`Color::Default`](crate::Color::Default) colors take the colors of
+    /// the base style instead. See [`Style::inherit`].
+    ///
+    /// By default this is the default style.
+    fn base_style(&self) -> Style {
+        Style::default()
+    }
 }
 
 impl<'a, O: Output + ?Sized> Output for &'a mut O {
@@ -48,9 +149,30 @@ impl<'a, O: Output + ?Sized> Output for &'a mut O {
     fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
         (**self).write_char(pos, c, style)
     }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        (**self).write_grapheme(pos, grapheme, style)
+    }
     fn set_cursor(&mut self, cursor: Option<Cursor>) {
         (**self).set_cursor(cursor)
     }
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        (**self).draw_image(pos, size, bitmap)
+    }
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        (**self).register_region(id, pos, size)
+    }
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        (**self).set_cell_data(pos, data)
+    }
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        (**self).set_link(pos, link)
+    }
+    fn origin(&self) -> Vec2<i32> {
+        (**self).origin()
+    }
+    fn base_style(&self) -> Style {
+        (**self).base_style()
+    }
 }
 
 #[cfg(feature = "either")]
@@ -67,12 +189,54 @@ impl<L: Output, R: Output> Output for either_crate::Either<L, R> {
             Self::Right(r) => r.write_char(pos, c, style),
         }
     }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        match self {
+            Self::Left(l) => l.write_grapheme(pos, grapheme, style),
+            Self::Right(r) => r.write_grapheme(pos, grapheme, style),
+        }
+    }
     fn set_cursor(&mut self, cursor: Option<Cursor>) {
         match self {
             Self::Left(l) => l.set_cursor(cursor),
             Self::Right(r) => r.set_cursor(cursor),
         }
     }
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        match self {
+            Self::Left(l) => l.draw_image(pos, size, bitmap),
+            Self::Right(r) => r.draw_image(pos, size, bitmap),
+        }
+    }
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        match self {
+            Self::Left(l) => l.register_region(id, pos, size),
+            Self::Right(r) => r.register_region(id, pos, size),
+        }
+    }
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        match self {
+            Self::Left(l) => l.set_cell_data(pos, data),
+            Self::Right(r) => r.set_cell_data(pos, data),
+        }
+    }
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        match self {
+            Self::Left(l) => l.set_link(pos, link),
+            Self::Right(r) => r.set_link(pos, link),
+        }
+    }
+    fn origin(&self) -> Vec2<i32> {
+        match self {
+            Self::Left(l) => l.origin(),
+            Self::Right(r) => r.origin(),
+        }
+    }
+    fn base_style(&self) -> Style {
+        match self {
+            Self::Left(l) => l.base_style(),
+            Self::Right(r) => r.base_style(),
+        }
+    }
 }
 
 /// Extension methods for outputs.
@@ -89,31 +253,181 @@ impl<L: Output, R: Output> Output for either_crate::Either<L, R> {
 pub trait Ext: Output {
     /// Write a type implementing [`Display`] to the specified position in the output.
     ///
-    /// If it overflows the width of the terminal it will be cut off. Control characters will be
-    /// ignored.
+    /// Each grapheme cluster is written to its own cell. If it overflows the width of the terminal
+    /// it will be cut off. Control characters will be ignored.
     fn write(&mut self, pos: impl Into<Vec2<u16>>, value: impl Display, style: Style) {
         let total_width = self.size().x;
         let mut pos = pos.into();
-        let _ = write!(
-            crate::util::WriteCharsFn(|c| {
-                let width = match c.width() {
-                    Some(width) => width,
-                    None => return Ok(()),
-                } as u16;
+        let mut writer = WriteGraphemesFn::new(|grapheme| {
+            let width = match grapheme_width(grapheme) {
+                Some(width) => width,
+                None => return Ok(()),
+            } as u16;
 
-                self.write_char(pos, c, style);
+            self.write_grapheme(pos, grapheme, style);
 
-                pos.x += width;
+            pos.x += width;
 
-                if pos.x >= total_width {
-                    Err(std::fmt::Error)
-                } else {
-                    Ok(())
+            if pos.x >= total_width {
+                Err(std::fmt::Error)
+            } else {
+                Ok(())
+            }
+        });
+        let _ = write!(writer, "{}", value).and_then(|()| writer.finish());
+    }
+
+    /// Fill a rectangle of the output with a character, with its top left corner at a zero-indexed
+    /// position.
+    ///
+    /// Each row of the rectangle is drawn with [`hline`](Self::hline), and the parts of the
+    /// rectangle outside the output are ignored.
+    fn fill_rect(
+        &mut self,
+        pos: impl Into<Vec2<u16>>,
+        size: impl Into<Vec2<u16>>,
+        c: char,
+        style: Style,
+    ) {
+        let pos = pos.into();
+        let size = size.into();
+        let end = cmp::min(pos.y.saturating_add(size.y), self.size().y);
+        for y in pos.y..end {
+            self.hline((pos.x, y), size.x, c, style);
+        }
+    }
+
+    /// Draw a horizontal line of a character that is `len` columns long, starting at a
+    /// zero-indexed position and going right.
+    ///
+    /// A double-width character is drawn in every other column, so if the length is odd the last
+    /// one is cut off.
+    fn hline(&mut self, pos: impl Into<Vec2<u16>>, len: u16, c: char, style: Style) {
+        let pos = pos.into();
+        let end = cmp::min(pos.x.saturating_add(len), self.size().x);
+        for x in (pos.x..end).step_by(c.width().unwrap_or(1).max(1)) {
+            self.write_char(Vec2::new(x, pos.y), c, style);
+        }
+    }
+
+    /// Draw a vertical line of a character that is `len` rows long, starting at a zero-indexed
+    /// position and going down.
+    fn vline(&mut self, pos: impl Into<Vec2<u16>>, len: u16, c: char, style: Style) {
+        let pos = pos.into();
+        let end = cmp::min(pos.y.saturating_add(len), self.size().y);
+        for y in pos.y..end {
+            self.write_char(Vec2::new(pos.x, y), c, style);
+        }
+    }
+
+    /// Draw a horizontal bar that is a fractional number of columns long, starting at a
+    /// zero-indexed position and going right.
+    ///
+    /// The length is rounded to the nearest eighth of a column, and the end of the bar is drawn
+    /// with a [`left_block`](crate::left_block). The bar is drawn in the foreground color of the
+    /// style.
+    fn hbar(&mut self, pos: impl Into<Vec2<u16>>, len: f64, style: Style) {
+        let pos = pos.into();
+        let max = f64::from(self.size().x.saturating_sub(pos.x)) * 8.0;
+        let eighths = (len * 8.0).round().max(0.0).min(max) as u32;
+
+        let full = (eighths / 8) as u16;
+        self.hline(pos, full, '█', style);
+        if eighths % 8 != 0 {
+            let c = crate::left_block(f64::from(eighths % 8) / 8.0);
+            self.write_char(Vec2::new(pos.x + full, pos.y), c, style);
+        }
+    }
+
+    /// Draw a vertical bar that is a fractional number of rows high, starting at a zero-indexed
+    /// position and going up.
+    ///
+    /// The height is rounded to the nearest eighth of a row, and the top of the bar is drawn with
+    /// a [`lower_block`](crate::lower_block). The bar is drawn in the foreground color of the
+    /// style.
+    fn vbar(&mut self, pos: impl Into<Vec2<u16>>, height: f64, style: Style) {
+        let pos = pos.into();
+        let max = (f64::from(pos.y) + 1.0) * 8.0;
+        let eighths = (height * 8.0).round().max(0.0).min(max) as u32;
+
+        let full = (eighths / 8) as u16;
+        for y in 0..full {
+            self.write_char(Vec2::new(pos.x, pos.y - y), '█', style);
+        }
+        if eighths % 8 != 0 {
+            let c = crate::lower_block(f64::from(eighths % 8) / 8.0);
+            self.write_char(Vec2::new(pos.x, pos.y - full), c, style);
+        }
+    }
+
+    /// Draw a bitmap using upper half block characters, with its top left corner at a zero-indexed
+    /// position.
+    ///
+    /// Each cell shows two pixels stacked on top of each other, using the foreground color for the
+    /// top pixel and the background color for the bottom one. The bitmap is scaled to be `size.x`
+    /// pixels wide and `size.y * 2` pixels high, so small pixel art with an even height can be
+    /// drawn unscaled by passing its width and half its height.
+    ///
+    /// This works on every terminal that supports true color, so it is used as the fallback for
+    /// [`draw_image`](Output::draw_image).
+    fn draw_half_blocks(
+        &mut self,
+        pos: impl Into<Vec2<u16>>,
+        size: impl Into<Vec2<u16>>,
+        bitmap: &Bitmap,
+    ) {
+        let pos = pos.into();
+        let size = size.into();
+        if size.x == 0 || size.y == 0 || bitmap.size().x == 0 || bitmap.size().y == 0 {
+            return;
+        }
+
+        let pixels = Vec2::new(size.x, size.y.saturating_mul(2));
+        for y in 0..pixels.y / 2 {
+            for x in 0..size.x {
+                let top = bitmap.sample(Vec2::new(x, y * 2), pixels);
+                let bottom = bitmap.sample(Vec2::new(x, y * 2 + 1), pixels);
+                let style = Style::new(Color::Rgb(top), Color::Rgb(bottom), Attributes::default());
+                if let Some(pos) = pos.checked_add(Vec2::new(x, y)) {
+                    self.write_char(pos, '▀', style);
                 }
-            }),
-            "{}",
-            value,
-        );
+            }
+        }
+    }
+
+    /// Show the text being composed by an input method at a cursor, and set the cursor of the
+    /// output to the end of it.
+    ///
+    /// Elements that accept text should call this instead of [`set_cursor`](Output::set_cursor)
+    /// while there is an [`Input::Compose`](crate::Input::Compose) in progress. The composition is
+    /// drawn underlined on top of the output, and cut off if it overflows the width of the output.
+    fn write_composition(&mut self, cursor: Cursor, composition: &str, style: Style) {
+        let style = Style {
+            attributes: Attributes {
+                underlined: true,
+                ..style.attributes
+            },
+            ..style
+        };
+        let total_width = self.size().x;
+        let mut pos = cursor.pos;
+
+        for grapheme in composition.graphemes(true) {
+            let width = match grapheme_width(grapheme) {
+                Some(width) => width as u16,
+                None => continue,
+            };
+            if pos.x.saturating_add(width) > total_width {
+                break;
+            }
+            self.write_grapheme(pos, grapheme, style);
+            pos.x += width;
+        }
+
+        self.set_cursor(Some(Cursor {
+            pos: Vec2::new(cmp::min(pos.x, total_width.saturating_sub(1)), pos.y),
+            ..cursor
+        }));
     }
 
     /// Create an output that draws to the specified area of this output.
@@ -134,6 +448,37 @@ pub trait Ext: Output {
         }
     }
 
+    /// Create an output whose characters inherit their colors from a base style, where they are
+    /// [`Color::Default`](crate::Color::Default).
+    ///
+    /// The base style itself inherits from the [`base_style`](Output::base_style) of this output,
+    /// so base styles can be nested.
+    #[must_use]
+    fn with_base_style(self, style: Style) -> WithBaseStyle<Self>
+    where
+        Self: Sized,
+    {
+        let style = style.inherit(self.base_style());
+        WithBaseStyle { inner: self, style }
+    }
+
+    /// Create an output that joins box-drawing characters drawn on top of each other into
+    /// junctions, so that for example `─` drawn over `│` becomes `┼`.
+    ///
+    /// Only characters drawn through the returned output are joined, since the contents of this
+    /// output can't be read. See [`merge_box_drawing`](crate::merge_box_drawing) for how the
+    /// characters are merged.
+    #[must_use]
+    fn join_box_drawing(self) -> JoinBoxDrawing<Self>
+    where
+        Self: Sized,
+    {
+        JoinBoxDrawing {
+            inner: self,
+            drawn: HashMap::new(),
+        }
+    }
+
     /// Call the callback when the cursor is set on the output.
     #[must_use]
     fn on_set_cursor<F: FnMut(&mut Self, Option<Cursor>)>(self, f: F) -> OnSetCursor<Self, F>
@@ -156,26 +501,55 @@ pub struct Area<O> {
     size: Vec2<u16>,
 }
 
-impl<O: Output> Output for Area<O> {
-    fn size(&self) -> Vec2<u16> {
-        self.size
-    }
-    fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
+impl<O> Area<O> {
+    /// Get the position in the inner output of a character of the given width written to the
+    /// area, if it fits inside the area.
+    ///
+    /// Zero-width characters combine with the character before them, so they don't fit in the
+    /// first column.
+    fn inner_pos(&self, pos: Vec2<u16>, width: Option<usize>) -> Option<Vec2<u16>> {
         if pos.x >= self.size.x
             || pos.y >= self.size.y
-            || (pos.x == self.size.x - 1 && c.width() == Some(2))
+            || (pos.x == self.size.x - 1 && width == Some(2))
+            || (pos.x == 0 && width == Some(0))
         {
-            return;
+            return None;
         }
-        let pos = match pos
-            .map(i32::from)
+        pos.map(i32::from)
             .checked_add(self.top_left)
             .and_then(|v| v.try_into::<u16>().ok())
-        {
-            Some(pos) => pos,
-            None => return,
-        };
-        self.inner.write_char(pos, c, style);
+    }
+}
+
+impl<O: Output> Area<O> {
+    /// Draw the part of a double-width character that doesn't fit inside the area as spaces.
+    fn write_clipped_double(&mut self, pos: Vec2<u16>, style: Style) {
+        for &x in &[pos.x, pos.x.saturating_add(1)] {
+            if let Some(pos) = self.inner_pos(Vec2::new(x, pos.y), Some(1)) {
+                self.inner.write_char(pos, ' ', style);
+            }
+        }
+    }
+}
+
+impl<O: Output> Output for Area<O> {
+    fn size(&self) -> Vec2<u16> {
+        self.size
+    }
+    fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
+        match self.inner_pos(pos, c.width()) {
+            Some(pos) => self.inner.write_char(pos, c, style),
+            None if c.width() == Some(2) => self.write_clipped_double(pos, style),
+            None => {}
+        }
+    }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        let width = grapheme_width(grapheme);
+        match self.inner_pos(pos, width) {
+            Some(pos) => self.inner.write_grapheme(pos, grapheme, style),
+            None if width == Some(2) => self.write_clipped_double(pos, style),
+            None => {}
+        }
     }
     fn set_cursor(&mut self, cursor: Option<Cursor>) {
         self.inner.set_cursor(
@@ -194,6 +568,50 @@ impl<O: Output> Output for Area<O> {
                 }),
         );
     }
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        let end = match pos.checked_add(size) {
+            Some(end) => end,
+            None => return,
+        };
+        if end.x > self.size.x || end.y > self.size.y {
+            return;
+        }
+        let pos = match pos
+            .map(i32::from)
+            .checked_add(self.top_left)
+            .and_then(|v| v.try_into::<u16>().ok())
+        {
+            Some(pos) => pos,
+            None => return,
+        };
+        self.inner.draw_image(pos, size, bitmap);
+    }
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        let end = (pos.into::<i32>() + size.into()).min(self.size.into()) + self.top_left;
+        let start = (pos.into::<i32>() + self.top_left).max(Vec2::new(0, 0));
+        if end.x <= start.x || end.y <= start.y {
+            return;
+        }
+        if let (Ok(pos), Ok(size)) = (start.try_into::<u16>(), (end - start).try_into::<u16>()) {
+            self.inner.register_region(id, pos, size);
+        }
+    }
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        if let Some(pos) = self.inner_pos(pos, Some(1)) {
+            self.inner.set_cell_data(pos, data);
+        }
+    }
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        if let Some(pos) = self.inner_pos(pos, Some(1)) {
+            self.inner.set_link(pos, link);
+        }
+    }
+    fn origin(&self) -> Vec2<i32> {
+        self.inner.origin() + self.top_left
+    }
+    fn base_style(&self) -> Style {
+        self.inner.base_style()
+    }
 }
 
 /// An [`Output`] that calls a callback when its cursor is set, created by the
@@ -212,7 +630,403 @@ impl<O: Output, F: FnMut(&mut O, Option<Cursor>)> Output for OnSetCursor<O, F> {
     fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
         self.inner.write_char(pos, c, style);
     }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        self.inner.write_grapheme(pos, grapheme, style);
+    }
     fn set_cursor(&mut self, cursor: Option<Cursor>) {
         (self.f)(&mut self.inner, cursor);
     }
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        self.inner.draw_image(pos, size, bitmap);
+    }
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        self.inner.register_region(id, pos, size);
+    }
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        self.inner.set_cell_data(pos, data);
+    }
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        self.inner.set_link(pos, link);
+    }
+    fn origin(&self) -> Vec2<i32> {
+        self.inner.origin()
+    }
+    fn base_style(&self) -> Style {
+        self.inner.base_style()
+    }
+}
+
+/// An [`Output`] whose characters inherit their colors from a base style, created by the
+/// [`with_base_style`](Ext::with_base_style) method.
+#[derive(Debug)]
+pub struct WithBaseStyle<O> {
+    /// The inner output.
+    pub inner: O,
+    style: Style,
+}
+
+impl<O: Output> Output for WithBaseStyle<O> {
+    fn size(&self) -> Vec2<u16> {
+        self.inner.size()
+    }
+    fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
+        self.inner.write_char(pos, c, style.inherit(self.style));
+    }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        self.inner
+            .write_grapheme(pos, grapheme, style.inherit(self.style));
+    }
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.inner.set_cursor(cursor);
+    }
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        self.inner.draw_image(pos, size, bitmap);
+    }
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        self.inner.register_region(id, pos, size);
+    }
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        self.inner.set_cell_data(pos, data);
+    }
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        self.inner.set_link(pos, link);
+    }
+    fn origin(&self) -> Vec2<i32> {
+        self.inner.origin()
+    }
+    fn base_style(&self) -> Style {
+        self.style
+    }
+}
+
+/// An [`Output`] that joins box-drawing characters drawn on top of each other, created by the
+/// [`join_box_drawing`](Ext::join_box_drawing) method.
+#[derive(Debug)]
+pub struct JoinBoxDrawing<O> {
+    /// The inner output.
+    pub inner: O,
+    /// The box-drawing characters that have been drawn to the output.
+    drawn: HashMap<Vec2<u16>, char>,
+}
+
+impl<O> JoinBoxDrawing<O> {
+    /// Forget the box-drawing characters covered by a character of the given width, returning the
+    /// one at its position.
+    fn cover(&mut self, pos: Vec2<u16>, width: Option<usize>) -> Option<char> {
+        match width {
+            Some(0) | None => None,
+            Some(width) => {
+                if width == 2 {
+                    self.drawn
+                        .remove(&Vec2::new(pos.x.saturating_add(1), pos.y));
+                }
+                self.drawn.remove(&pos)
+            }
+        }
+    }
+}
+
+impl<O: Output> Output for JoinBoxDrawing<O> {
+    fn size(&self) -> Vec2<u16> {
+        self.inner.size()
+    }
+    fn write_char(&mut self, pos: Vec2<u16>, c: char, style: Style) {
+        let below = self.cover(pos, c.width());
+        let c = if crate::box_drawing::is_box_drawing(c) {
+            let c = below
+                .and_then(|below| crate::merge_box_drawing(below, c))
+                .unwrap_or(c);
+            self.drawn.insert(pos, c);
+            c
+        } else {
+            c
+        };
+        self.inner.write_char(pos, c, style);
+    }
+    fn write_grapheme(&mut self, pos: Vec2<u16>, grapheme: &str, style: Style) {
+        let mut chars = grapheme.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return self.write_char(pos, c, style);
+        }
+        self.cover(pos, grapheme_width(grapheme));
+        self.inner.write_grapheme(pos, grapheme, style);
+    }
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.inner.set_cursor(cursor);
+    }
+    fn draw_image(&mut self, pos: Vec2<u16>, size: Vec2<u16>, bitmap: &Bitmap) {
+        self.inner.draw_image(pos, size, bitmap);
+    }
+    fn register_region(&mut self, id: &str, pos: Vec2<u16>, size: Vec2<u16>) {
+        self.inner.register_region(id, pos, size);
+    }
+    fn set_cell_data(&mut self, pos: Vec2<u16>, data: u64) {
+        self.inner.set_cell_data(pos, data);
+    }
+    fn set_link(&mut self, pos: Vec2<u16>, link: &Link) {
+        self.inner.set_link(pos, link);
+    }
+    fn origin(&self) -> Vec2<i32> {
+        self.inner.origin()
+    }
+    fn base_style(&self) -> Style {
+        self.inner.base_style()
+    }
+}
+
+#[test]
+fn test_write_graphemes() {
+    use crate::Grid;
+
+    let mut grid = Grid::new((7, 1));
+    (&mut grid).area((1, 0), (6, 1)).write(
+        (0, 0),
+        "x\u{301}👨\u{200D}👩\u{200D}👧x🇬🇧",
+        Style::default(),
+    );
+
+    let cells: Vec<_> = grid.lines()[0]
+        .cells()
+        .iter()
+        .map(|cell| cell.contents())
+        .collect();
+    assert_eq!(
+        cells,
+        [
+            Some(" "),
+            Some("x\u{301}"),
+            Some("👨\u{200D}👩\u{200D}👧"),
+            None,
+            Some("x"),
+            Some("🇬🇧"),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn test_clip_double_width() {
+    use crate::{Grid, Styled as _};
+
+    let mut grid = Grid::new((6, 2));
+    grid.write((0, 0), "abcdef", Style::default());
+
+    (&mut grid)
+        .area((1, 0), (3, 1))
+        .write((0, 0), "日本", Style::default().bold());
+    crate::assert_grid_eq!(grid, ["a日 ef", "      "]);
+    assert_eq!(
+        grid.lines()[0].cells()[3].style(),
+        Some(Style::default().bold())
+    );
+
+    (&mut grid)
+        .area((-1, 1), (5, 1))
+        .write((0, 0), "日本ab", Style::default());
+    crate::assert_grid_eq!(grid, ["a日 ef", " 本a  "]);
+}
+
+#[test]
+fn test_lines() {
+    use crate::{Grid, Styled as _};
+
+    let mut grid = Grid::new((5, 4));
+    grid.fill_rect((1, 1), (10, 2), '.', Style::default().bold());
+    grid.hline((0, 0), 4, '-', Style::default());
+    grid.vline((0, 1), 10, '|', Style::default());
+    grid.hline((0, 3), 5, '日', Style::default());
+    crate::assert_grid_eq!(
+        grid,
+        ["---- ", "|....", "|....", "日日 "],
+        ["     ", " bbbb", " bbbb"],
+        { 'b' => Style::default().bold() },
+    );
+}
+
+#[test]
+fn test_bars() {
+    use crate::Grid;
+
+    let mut grid = Grid::new((4, 3));
+    grid.hbar((0, 0), 2.3, Style::default());
+    grid.hbar((1, 1), 100.0, Style::default());
+    grid.hbar((0, 2), f64::NAN, Style::default());
+    crate::assert_grid_eq!(grid, ["██▎ ", " ███", "    "]);
+
+    let mut grid = Grid::new((4, 3));
+    grid.vbar((0, 2), 1.5, Style::default());
+    grid.vbar((1, 2), 0.01, Style::default());
+    grid.vbar((2, 1), 10.0, Style::default());
+    grid.vbar((3, 2), -1.0, Style::default());
+    crate::assert_grid_eq!(grid, ["  █ ", "▄ █ ", "█   "]);
+}
+
+#[test]
+fn test_draw_half_blocks() {
+    use crate::{Grid, Rgb};
+
+    let red = Rgb::new(255, 0, 0);
+    let blue = Rgb::new(0, 0, 255);
+    let bitmap = Bitmap::new((2, 4), vec![red, blue, blue, blue, red, red, blue, red]);
+
+    let mut grid = Grid::new((4, 3));
+    grid.draw_half_blocks((1, 1), (2, 2), &bitmap);
+    crate::assert_grid_eq!(grid, ["    ", " ▀▀ ", " ▀▀ "]);
+
+    let colors: Vec<_> = grid.lines()[1..]
+        .iter()
+        .flat_map(|line| &line.cells()[1..3])
+        .map(|cell| {
+            let style = cell.style().unwrap();
+            (style.foreground, style.background)
+        })
+        .collect();
+    assert_eq!(
+        colors,
+        [
+            (Color::Rgb(red), Color::Rgb(blue)),
+            (Color::Rgb(blue), Color::Rgb(blue)),
+            (Color::Rgb(red), Color::Rgb(blue)),
+            (Color::Rgb(red), Color::Rgb(red)),
+        ]
+    );
+}
+
+#[test]
+fn test_join_box_drawing() {
+    use crate::Grid;
+
+    let mut grid = Grid::new((5, 3));
+    grid.write((0, 1), "│", Style::default());
+
+    let mut output = (&mut grid).join_box_drawing();
+    output.hline((0, 1), 5, '─', Style::default());
+    output.vline((2, 0), 3, '┃', Style::default());
+    output.write((3, 0), "┐", Style::default());
+    output.write((3, 0), "x", Style::default());
+    output.write((3, 0), "┘", Style::default());
+    output.write((4, 1), "═", Style::default());
+    output.write((4, 2), "│", Style::default());
+    output.write((4, 2), "日", Style::default());
+    output.write((4, 2), "─", Style::default());
+    output.write((0, 2), "┌", Style::default());
+    output.write((0, 2), "╯", Style::default());
+
+    crate::assert_grid_eq!(grid, ["  ┃┘ ", "──╂─═", "┼ ┃ ─"]);
+}
+
+#[test]
+fn test_write_composition() {
+    use crate::{Buffer, CursorShape, Grid};
+
+    let cursor = Cursor {
+        shape: CursorShape::Bar,
+        blinking: true,
+        color: Color::Default,
+        pos: Vec2::new(1, 0),
+    };
+
+    let mut buffer = Buffer::from(Grid::new((6, 1)));
+    buffer.write_composition(cursor, "日本", Style::default());
+    crate::assert_grid_eq!(buffer.grid, [" 日本 "]);
+    assert!(
+        buffer.grid.lines()[0].cells()[1]
+            .style()
+            .unwrap()
+            .attributes
+            .underlined
+    );
+    assert_eq!(buffer.cursor.unwrap().pos, Vec2::new(5, 0));
+
+    let mut buffer = Buffer::from(Grid::new((4, 1)));
+    buffer.write_composition(cursor, "日本", Style::default());
+    crate::assert_grid_eq!(buffer.grid, [" 日 "]);
+    assert_eq!(buffer.cursor.unwrap().pos, Vec2::new(3, 0));
+}
+
+#[test]
+fn test_origin() {
+    use crate::{Buffer, Grid};
+
+    let mut buffer = Buffer::from(Grid::new((6, 4)));
+    assert_eq!(buffer.origin(), Vec2::new(0, 0));
+    let mut area = (&mut buffer).area((2, 1), (4, 3));
+    assert_eq!(area.origin(), Vec2::new(2, 1));
+    let inner = (&mut area).area((-3, 1), (2, 2));
+    assert_eq!(inner.origin(), Vec2::new(-1, 2));
+}
+
+#[test]
+fn test_register_region() {
+    use crate::{Buffer, Grid};
+
+    let mut buffer = Buffer::from(Grid::new((6, 4)));
+    buffer.register_region("all", Vec2::new(0, 0), Vec2::new(10, 10));
+    let mut area = (&mut buffer).area((-1, 1), (4, 2));
+    area.register_region("button", Vec2::new(0, 0), Vec2::new(3, 5));
+    area.register_region("hidden", Vec2::new(4, 0), Vec2::new(1, 1));
+
+    assert_eq!(buffer.regions.len(), 2);
+    assert_eq!(buffer.regions[0].size, Vec2::new(6, 4));
+    assert_eq!(buffer.regions[1].pos, Vec2::new(0, 1));
+    assert_eq!(buffer.regions[1].size, Vec2::new(2, 2));
+
+    let at = |x, y| buffer.regions_at((x, y)).collect::<Vec<_>>();
+    assert_eq!(at(1, 2), ["button", "all"]);
+    assert_eq!(at(2, 2), ["all"]);
+    assert_eq!(at(1, 3), ["all"]);
+    assert_eq!(at(1, 0), ["all"]);
+}
+
+#[test]
+fn test_set_cell_data() {
+    use crate::{Buffer, Grid};
+
+    let mut buffer = Buffer::from(Grid::new((6, 4)));
+    buffer.set_cell_data(Vec2::new(0, 0), 1);
+    buffer.set_cell_data(Vec2::new(6, 0), 2);
+    let mut area = (&mut buffer).area((-1, 1), (4, 2));
+    area.set_cell_data(Vec2::new(0, 0), 3);
+    area.set_cell_data(Vec2::new(2, 1), 4);
+    area.set_cell_data(Vec2::new(4, 1), 5);
+    area.set_cell_data(Vec2::new(1, 1), 6);
+    area.set_cell_data(Vec2::new(1, 1), 7);
+
+    assert_eq!(buffer.cell_data.len(), 3);
+    assert_eq!(buffer.cell_data_at((0, 0)), Some(1));
+    assert_eq!(buffer.cell_data_at((1, 2)), Some(4));
+    assert_eq!(buffer.cell_data_at((0, 2)), Some(7));
+    assert_eq!(buffer.cell_data_at((1, 1)), None);
+
+    buffer.reset();
+    assert_eq!(buffer.cell_data_at((0, 0)), None);
+}
+
+#[test]
+fn test_base_style() {
+    use crate::{Grid, Styled};
+
+    let mut grid = Grid::new((3, 1));
+    let mut output = (&mut grid).with_base_style(Style::default().red().on_black());
+    assert_eq!(output.base_style(), Style::default().red().on_black());
+
+    let mut inner = (&mut output).with_base_style(Style::default().blue());
+    assert_eq!(inner.base_style(), Style::default().blue().on_black());
+    inner.write_char(Vec2::new(0, 0), 'a', Style::default());
+    inner.write_char(Vec2::new(1, 0), 'b', Style::default().on_white().bold());
+    output.write_char(Vec2::new(2, 0), 'c', Style::default().green());
+
+    let styles: Vec<_> = grid.lines()[0]
+        .cells()
+        .iter()
+        .map(|cell| cell.style().unwrap())
+        .collect();
+    assert_eq!(
+        styles,
+        [
+            Style::default().blue().on_black(),
+            Style::default().blue().on_white().bold(),
+            Style::default().green().on_black(),
+        ]
+    );
 }
@@ -0,0 +1,204 @@
+//! Catching signals sent to the process, used by
+//! [`Terminal::catch_signals`](crate::Terminal::catch_signals).
+
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Read};
+use std::mem::{self, MaybeUninit};
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use std::thread;
+
+use os_pipe::PipeWriter;
+
+use crate::Signal;
+
+/// The signals that are caught, and what they are reported as.
+const SIGNALS: [(libc::c_int, Signal); 4] = [
+    (libc::SIGINT, Signal::Interrupt),
+    (libc::SIGTERM, Signal::Terminate),
+    (libc::SIGHUP, Signal::Hangup),
+    (libc::SIGCONT, Signal::Continue),
+];
+
+/// The file descriptor that the signal handler writes caught signals to, or -1 if signals aren't
+/// being caught.
+///
+/// The handler can't do much more than this safely, so the signals are read from the other end of
+/// the pipe by a thread.
+static PIPE: AtomicI32 = AtomicI32::new(-1);
+
+/// The number of signal handlers that are currently running, so that the pipe isn't closed while
+/// one of them might still write to it.
+static HANDLING: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn handler(signal: libc::c_int) {
+    // The handler can interrupt code that is about to read `errno`, so it must not change it.
+    let errno = errno_location().map(|location| (location, unsafe { *location }));
+
+    HANDLING.fetch_add(1, Ordering::SeqCst);
+    let fd = PIPE.load(Ordering::SeqCst);
+    if fd != -1 {
+        // All the caught signals' numbers fit in a byte.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let byte = signal as u8;
+        unsafe { libc::write(fd, (&byte as *const u8).cast(), 1) };
+    }
+    HANDLING.fetch_sub(1, Ordering::SeqCst);
+
+    if let Some((location, errno)) = errno {
+        unsafe { *location = errno };
+    }
+}
+
+/// Get a pointer to the calling thread's `errno`.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "emscripten",
+    target_os = "fuchsia",
+    target_os = "redox"
+))]
+fn errno_location() -> Option<*mut libc::c_int> {
+    Some(unsafe { libc::__errno_location() })
+}
+
+/// Get a pointer to the calling thread's `errno`.
+#[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd"))]
+fn errno_location() -> Option<*mut libc::c_int> {
+    Some(unsafe { libc::__errno() })
+}
+
+/// Get a pointer to the calling thread's `errno`.
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+fn errno_location() -> Option<*mut libc::c_int> {
+    Some(unsafe { libc::__error() })
+}
+
+/// Get a pointer to the calling thread's `errno`.
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+fn errno_location() -> Option<*mut libc::c_int> {
+    Some(unsafe { libc::___errno() })
+}
+
+/// Get a pointer to the calling thread's `errno`.
+#[cfg(target_os = "haiku")]
+fn errno_location() -> Option<*mut libc::c_int> {
+    Some(unsafe { libc::_errnop() })
+}
+
+/// On other platforms `errno` isn't preserved, since there is no portable way to get at it.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "emscripten",
+    target_os = "fuchsia",
+    target_os = "redox",
+    target_os = "android",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "solaris",
+    target_os = "illumos",
+    target_os = "haiku"
+)))]
+fn errno_location() -> Option<*mut libc::c_int> {
+    None
+}
+
+/// Catches signals while it is alive, calling a function with each one on a separate thread.
+///
+/// Only one of these can exist at once; creating another one while it is alive fails.
+pub(crate) struct Catcher {
+    /// The write end of the pipe; the thread stops when it is closed.
+    writer: PipeWriter,
+    /// The actions of the signals from before they were caught, restored when this is dropped.
+    previous: Vec<(libc::c_int, libc::sigaction)>,
+}
+
+impl Catcher {
+    pub(crate) fn new(on_signal: impl Fn(Signal) + Send + 'static) -> io::Result<Self> {
+        let (mut reader, writer) = os_pipe::pipe()?;
+
+        if PIPE
+            .compare_exchange(-1, writer.as_raw_fd(), Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "signals are already being caught",
+            ));
+        }
+
+        // If installing any of the handlers fails, dropping this restores the ones that were
+        // already installed and allows signals to be caught again.
+        let mut catcher = Self {
+            writer,
+            previous: Vec::with_capacity(SIGNALS.len()),
+        };
+
+        thread::spawn(move || {
+            let mut byte = [0];
+            loop {
+                match reader.read(&mut byte) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        let number = libc::c_int::from(byte[0]);
+                        if let Some(&(_, signal)) = SIGNALS.iter().find(|&&(n, _)| n == number) {
+                            on_signal(signal);
+                        }
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(_) => break,
+                }
+            }
+        });
+
+        for &(signal, _) in &SIGNALS {
+            let mut action: libc::sigaction = unsafe { mem::zeroed() };
+            action.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            unsafe { libc::sigemptyset(&mut action.sa_mask) };
+
+            let mut previous = MaybeUninit::uninit();
+            if unsafe { libc::sigaction(signal, &action, previous.as_mut_ptr()) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            catcher
+                .previous
+                .push((signal, unsafe { previous.assume_init() }));
+        }
+
+        Ok(catcher)
+    }
+}
+
+impl Drop for Catcher {
+    fn drop(&mut self) {
+        // Stop the handlers from writing to the pipe before restoring the previous actions, and
+        // wait for any that are still running to finish before the pipe is closed.
+        PIPE.store(-1, Ordering::SeqCst);
+        for (signal, previous) in &self.previous {
+            unsafe { libc::sigaction(*signal, previous, ptr::null_mut()) };
+        }
+        while HANDLING.load(Ordering::SeqCst) != 0 {
+            thread::yield_now();
+        }
+    }
+}
+
+impl Debug for Catcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Catcher")
+            .field("writer", &self.writer)
+            .field(
+                "signals",
+                &self
+                    .previous
+                    .iter()
+                    .map(|&(signal, _)| signal)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
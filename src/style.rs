@@ -1,7 +1,23 @@
 //! Text styling.
 
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[cfg(feature = "quickcheck")]
+use quickcheck_crate::{Arbitrary, Gen};
+
 /// How text is written.
+///
+/// With the `serde` feature styles can be loaded from configuration. Any field can be left out, in
+/// which case it takes its default value.
 #[derive(Default, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", default)
+)]
 pub struct Style {
     /// The foreground color of the text.
     pub foreground: Color,
@@ -9,6 +25,9 @@ pub struct Style {
     pub background: Color,
     /// The attributes of the text.
     pub attributes: Attributes,
+    /// The color of the text's underline. If this is [`Color::Default`] the underline is the same
+    /// color as the text. Not widely supported.
+    pub underline_color: Color,
 }
 
 impl Style {
@@ -28,6 +47,269 @@ impl Style {
             foreground,
             background,
             attributes,
+            underline_color: Color::Default,
+        }
+    }
+}
+
+/// Parses styles written as a list of words, like `bold red on black`.
+///
+/// Each word is one of:
+/// - An attribute: `bold` (or `b`), `dim`, `italic` (or `i`), `underlined` (or `u`), `blinking`
+/// `crossed-out` (or `s`), `reversed`, `hidden` or `overlined`.
+/// - `underline=` followed by an [`UnderlineKind`], like `underline=curly`.
+/// - A [`Color`], which sets the foreground color. It can also be written like `fg=red`.
+/// - `on` followed by a color, which sets the background color. It can also be written like
+/// `bg=black`.
+///
+/// Words are case insensitive and later words override earlier ones.
+impl FromStr for Style {
+    type Err = ParseStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Self::default();
+        style.apply_words(s)?;
+        Ok(style)
+    }
+}
+
+impl Style {
+    /// Interpolate between two styles for animations, where a `t` of 0 gives `a` and 1 gives `b`.
+    ///
+    /// The colors are mixed with [`Color::mix`], while attributes can't be partially applied so
+    /// they switch from `a` to `b` halfway through. Colors that are the same in both styles are
+    /// left as they are.
+    #[must_use]
+    pub fn lerp(a: Self, b: Self, t: f64) -> Self {
+        let color = |a: Color, b: Color| {
+            if a == b || t <= 0.0 {
+                a
+            } else if t >= 1.0 {
+                b
+            } else {
+                a.mix(b, t)
+            }
+        };
+        Self {
+            foreground: color(a.foreground, b.foreground),
+            background: color(a.background, b.background),
+            underline_color: color(a.underline_color, b.underline_color),
+            ..if t < 0.5 { a } else { b }
+        }
+    }
+
+    /// Take the colors of this style that are [`Color::Default`] from a base style, like the
+    /// [`base_style`](crate::Output::base_style) of an output.
+    #[must_use]
+    pub fn inherit(mut self, base: Self) -> Self {
+        for (color, base) in [
+            (&mut self.foreground, base.foreground),
+            (&mut self.background, base.background),
+            (&mut self.underline_color, base.underline_color),
+        ] {
+            if *color == Color::Default {
+                *color = base;
+            }
+        }
+        self
+    }
+
+    /// Blend the translucent colors of this style over the style of the cell below it.
+    ///
+    /// The background is blended over the old background, and the foreground and underline
+    /// colors are blended over the new background.
+    pub(crate) fn blend_over(mut self, below: Self) -> Self {
+        self.background = self.background.blend_over(below.background);
+        self.foreground = self.foreground.blend_over(self.background);
+        self.underline_color = self.underline_color.blend_over(self.background);
+        self
+    }
+
+    /// Resolve the translucent colors of this style to opaque ones, given the terminal's
+    /// background color if it is known.
+    pub(crate) fn to_opaque(mut self, background: Option<Rgb>) -> Self {
+        self.background = self.background.to_opaque(background);
+        let below = self.background.to_rgb().or(background);
+        self.foreground = self.foreground.to_opaque(below);
+        self.underline_color = self.underline_color.to_opaque(below);
+        self
+    }
+
+    /// Apply the words of a style written like `bold red on black` to this style.
+    pub(crate) fn apply_words(&mut self, s: &str) -> Result<(), ParseStyleError> {
+        let mut words = s.split_whitespace();
+
+        while let Some(word) = words.next() {
+            let error = || ParseStyleError(word.to_owned());
+            let color = |s: &str| s.parse::<Color>().map_err(|_| error());
+
+            let attributes = &mut self.attributes;
+            match word.to_ascii_lowercase().as_str() {
+                "bold" | "b" => attributes.intensity = Intensity::Bold,
+                "dim" => attributes.intensity = Intensity::Dim,
+                "italic" | "i" => attributes.italic = true,
+                "underlined" | "u" => attributes.underlined = true,
+                "blinking" => attributes.blinking = true,
+                "crossed-out" | "s" => attributes.crossed_out = true,
+                "reversed" => attributes.reversed = true,
+                "hidden" => attributes.hidden = true,
+                "overlined" => attributes.overlined = true,
+                "on" => {
+                    let word = words.next().ok_or_else(error)?;
+                    self.background = word.parse().map_err(|_| ParseStyleError(word.to_owned()))?;
+                }
+                lower => {
+                    if let Some(kind) = lower.strip_prefix("underline=") {
+                        attributes.underlined = true;
+                        attributes.underline_kind = match kind {
+                            "single" => UnderlineKind::Single,
+                            "double" => UnderlineKind::Double,
+                            "curly" => UnderlineKind::Curly,
+                            "dotted" => UnderlineKind::Dotted,
+                            "dashed" => UnderlineKind::Dashed,
+                            _ => return Err(error()),
+                        };
+                    } else if let Some(background) = lower.strip_prefix("bg=") {
+                        self.background = color(background)?;
+                    } else {
+                        self.foreground = color(lower.strip_prefix("fg=").unwrap_or(lower))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An error parsing a [`Style`], containing the word that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStyleError(pub String);
+
+impl Display for ParseStyleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid style {:?}", self.0)
+    }
+}
+impl StdError for ParseStyleError {}
+
+/// Changes to make to a [`Style`], where fields that are [`None`] are left as they are.
+///
+/// This is useful for roles in a theme that only set some parts of the style, such as a highlight
+/// that makes text bold without changing its color. Apply it with [`Styled::patch`]. Like
+/// [`Style`], with the `serde` feature patches can be loaded from configuration.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Color, Intensity, Style, StylePatch, Styled};
+///
+/// let highlight = StylePatch {
+///     background: Some(Color::Yellow),
+///     intensity: Some(Intensity::Bold),
+///     ..StylePatch::default()
+/// };
+/// assert_eq!(
+///     Style::default().red().patch(highlight),
+///     Style::default().red().on_yellow().bold(),
+/// );
+/// ```
+#[derive(Default, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", default)
+)]
+pub struct StylePatch {
+    /// The foreground color of the text.
+    pub foreground: Option<Color>,
+    /// The background color of the text.
+    pub background: Option<Color>,
+    /// The color of the text's underline.
+    pub underline_color: Option<Color>,
+    /// The text intensity.
+    pub intensity: Option<Intensity>,
+    /// Whether the text is emphasized.
+    pub italic: Option<bool>,
+    /// Whether the text is underlined.
+    pub underlined: Option<bool>,
+    /// The kind of underline used when the text is underlined.
+    pub underline_kind: Option<UnderlineKind>,
+    /// Whether the text blinks.
+    pub blinking: Option<bool>,
+    /// Whether the text is crossed out.
+    pub crossed_out: Option<bool>,
+    /// Whether the foreground and background colors of the text are swapped.
+    pub reversed: Option<bool>,
+    /// Whether the text is hidden.
+    pub hidden: Option<bool>,
+    /// Whether the text has a line over it.
+    pub overlined: Option<bool>,
+}
+
+impl StylePatch {
+    /// Create a patch that changes nothing.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            foreground: None,
+            background: None,
+            underline_color: None,
+            intensity: None,
+            italic: None,
+            underlined: None,
+            underline_kind: None,
+            blinking: None,
+            crossed_out: None,
+            reversed: None,
+            hidden: None,
+            overlined: None,
+        }
+    }
+
+    /// Apply the patch to a style.
+    #[must_use]
+    pub fn apply(self, mut style: Style) -> Style {
+        fn set<T>(field: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+
+        set(&mut style.foreground, self.foreground);
+        set(&mut style.background, self.background);
+        set(&mut style.underline_color, self.underline_color);
+        let attributes = &mut style.attributes;
+        set(&mut attributes.intensity, self.intensity);
+        set(&mut attributes.italic, self.italic);
+        set(&mut attributes.underlined, self.underlined);
+        set(&mut attributes.underline_kind, self.underline_kind);
+        set(&mut attributes.blinking, self.blinking);
+        set(&mut attributes.crossed_out, self.crossed_out);
+        set(&mut attributes.reversed, self.reversed);
+        set(&mut attributes.hidden, self.hidden);
+        set(&mut attributes.overlined, self.overlined);
+        style
+    }
+}
+
+/// Creates a patch that replaces every part of a style.
+impl From<Style> for StylePatch {
+    fn from(style: Style) -> Self {
+        let attributes = style.attributes;
+        Self {
+            foreground: Some(style.foreground),
+            background: Some(style.background),
+            underline_color: Some(style.underline_color),
+            intensity: Some(attributes.intensity),
+            italic: Some(attributes.italic),
+            underlined: Some(attributes.underlined),
+            underline_kind: Some(attributes.underline_kind),
+            blinking: Some(attributes.blinking),
+            crossed_out: Some(attributes.crossed_out),
+            reversed: Some(attributes.reversed),
+            hidden: Some(attributes.hidden),
+            overlined: Some(attributes.overlined),
         }
     }
 }
@@ -63,9 +345,9 @@ macro_rules! color_setters {
 }
 
 macro_rules! attribute_setters {
-    ($(#[doc = $doc:literal] $name:ident($property:ident = $value:expr),)*) => {
+    ($($(#[doc = $doc:literal])* $name:ident($property:ident = $value:expr),)*) => {
         $(
-            #[doc = $doc]
+            $(#[doc = $doc])*
             #[must_use]
             fn $name(mut self) -> Self {
                 self.as_mut().attributes.$property = $value;
@@ -96,6 +378,43 @@ pub trait Styled: AsRef<Style> + AsMut<Style> + Sized {
         self.as_mut().attributes = attributes;
         self
     }
+    /// Replace the whole style.
+    #[must_use]
+    fn style(mut self, style: Style) -> Self {
+        *self.as_mut() = style;
+        self
+    }
+    /// Set the foreground color to black or white, whichever is more readable on the current
+    /// background color.
+    ///
+    /// See [`Color::contrasting_text`].
+    #[must_use]
+    fn contrasting_foreground(mut self) -> Self {
+        let style = self.as_mut();
+        style.foreground = Color::contrasting_text(style.background);
+        self
+    }
+    /// Change the parts of the style that are set in the patch, leaving the others as they are.
+    #[must_use]
+    fn patch(mut self, patch: impl Into<StylePatch>) -> Self {
+        let style = self.as_mut();
+        *style = patch.into().apply(*style);
+        self
+    }
+    /// Underline the text with the given kind of underline.
+    #[must_use]
+    fn underline_kind(mut self, underline_kind: UnderlineKind) -> Self {
+        let attributes = &mut self.as_mut().attributes;
+        attributes.underlined = true;
+        attributes.underline_kind = underline_kind;
+        self
+    }
+    /// Set the color of the underline.
+    #[must_use]
+    fn underline_color(mut self, underline_color: impl Into<Color>) -> Self {
+        self.as_mut().underline_color = underline_color.into();
+        self
+    }
 
     attribute_setters! {
         /// Make the intensity bold.
@@ -110,6 +429,13 @@ pub trait Styled: AsRef<Style> + AsMut<Style> + Sized {
         blinking(blinking = true),
         /// Cross out the text.
         crossed_out(crossed_out = true),
+        /// Swap the foreground and background colors of the text. This is often the most portable
+        /// way to highlight a selection.
+        reversed(reversed = true),
+        /// Hide the text, while still taking up space. This is useful for password fields.
+        hidden(hidden = true),
+        /// Draw a line over the text. Not widely supported.
+        overlined(overlined = true),
     }
 
     color_setters! {
@@ -134,6 +460,10 @@ pub trait Styled: AsRef<Style> + AsMut<Style> + Sized {
 impl<T: AsRef<Style> + AsMut<Style>> Styled for T {}
 
 /// A color.
+///
+/// Colors can be displayed and parsed like `dark-red`, `123` for ANSI colors, `#ff8000` for RGB
+/// colors or `#00000080` for translucent colors, which is also how they are written in
+/// configuration with the `serde` feature.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Color {
     /// The terminal's default color.
@@ -174,6 +504,11 @@ pub enum Color {
     AnsiValue(AnsiColor),
     /// A full 24-bit RGB color.
     Rgb(Rgb),
+    /// An RGB color with an alpha channel, which is blended with the color of the cell below it
+    /// when it is drawn. This is useful for translucent overlays and shadows.
+    ///
+    /// See [`blend_over`](Self::blend_over) for how colors are blended.
+    Rgba(Rgba),
 }
 
 impl Color {
@@ -204,9 +539,199 @@ impl Color {
         }
     }
 
-    /// Darken a color if it is a named color variant.
+    /// Get the ANSI value of the color, the inverse of [`new_ansi`](Self::new_ansi).
+    ///
+    /// Returns [`None`] for [`Color::Default`] and [`Color::Rgb`].
+    pub(crate) fn ansi_value(self) -> Option<u8> {
+        Some(match self {
+            Self::Default | Self::Rgb(_) | Self::Rgba(_) => return None,
+            Self::Black => 0,
+            Self::DarkRed => 1,
+            Self::DarkGreen => 2,
+            Self::DarkYellow => 3,
+            Self::DarkBlue => 4,
+            Self::DarkMagenta => 5,
+            Self::DarkCyan => 6,
+            Self::LightGray => 7,
+            Self::DarkGray => 8,
+            Self::Red => 9,
+            Self::Green => 10,
+            Self::Yellow => 11,
+            Self::Blue => 12,
+            Self::Magenta => 13,
+            Self::Cyan => 14,
+            Self::White => 15,
+            Self::AnsiValue(v) => v.get(),
+        })
+    }
+
+    /// Get the RGB value that this color is usually displayed as, using xterm's default palette
+    /// for the named colors.
+    ///
+    /// Returns [`None`] for [`Color::Default`], since it depends on the terminal. The alpha channel
+    /// of [`Color::Rgba`] is ignored.
+    #[must_use]
+    pub fn to_rgb(self) -> Option<Rgb> {
+        Some(match self {
+            Self::Default => return None,
+            Self::Black => Rgb::new(0x00, 0x00, 0x00),
+            Self::DarkRed => Rgb::new(0xCD, 0x00, 0x00),
+            Self::DarkGreen => Rgb::new(0x00, 0xCD, 0x00),
+            Self::DarkYellow => Rgb::new(0xCD, 0xCD, 0x00),
+            Self::DarkBlue => Rgb::new(0x00, 0x00, 0xEE),
+            Self::DarkMagenta => Rgb::new(0xCD, 0x00, 0xCD),
+            Self::DarkCyan => Rgb::new(0x00, 0xCD, 0xCD),
+            Self::LightGray => Rgb::new(0xE5, 0xE5, 0xE5),
+            Self::DarkGray => Rgb::new(0x7F, 0x7F, 0x7F),
+            Self::Red => Rgb::new(0xFF, 0x00, 0x00),
+            Self::Green => Rgb::new(0x00, 0xFF, 0x00),
+            Self::Yellow => Rgb::new(0xFF, 0xFF, 0x00),
+            Self::Blue => Rgb::new(0x5C, 0x5C, 0xFF),
+            Self::Magenta => Rgb::new(0xFF, 0x00, 0xFF),
+            Self::Cyan => Rgb::new(0x00, 0xFF, 0xFF),
+            Self::White => Rgb::new(0xFF, 0xFF, 0xFF),
+            Self::AnsiValue(color) => color.to_rgb(),
+            Self::Rgb(rgb) => rgb,
+            Self::Rgba(rgba) => rgba.rgb(),
+        })
+    }
+
+    /// Blend this color over the color below it, if this color is translucent.
+    ///
+    /// Opaque colors are returned unchanged. When the color below is known the colors are
+    /// composited, giving an opaque [`Color::Rgb`] if the color below is opaque. When the color
+    /// below is [`Color::Default`] its RGB value isn't known, so this color is returned unchanged
+    /// and is blended with the terminal's background color when it is drawn.
+    #[must_use]
+    pub fn blend_over(self, below: Self) -> Self {
+        let rgba = match self {
+            Self::Rgba(rgba) => rgba,
+            _ => return self,
+        };
+        let below = match below {
+            Self::Default => return self,
+            Self::Rgba(below) => below,
+            _ => Rgba::from(below.to_rgb().unwrap()),
+        };
+        let blended = rgba.over(below);
+        if blended.a == u8::MAX {
+            Self::Rgb(blended.rgb())
+        } else {
+            Self::Rgba(blended)
+        }
+    }
+
+    /// Resolve a translucent color to an opaque one by blending it over the terminal's background
+    /// color.
+    ///
+    /// If the background color isn't known, mostly opaque colors are drawn opaque and mostly
+    /// transparent colors aren't drawn at all.
+    pub(crate) fn to_opaque(self, background: Option<Rgb>) -> Self {
+        match self.blend_over(background.map_or(Self::Default, Self::Rgb)) {
+            Self::Rgba(rgba) if rgba.a >= 0x80 => Self::Rgb(rgba.rgb()),
+            Self::Rgba(_) => Self::Default,
+            color => color,
+        }
+    }
+
+    /// Create an RGB color from a hue in degrees and a saturation and lightness between 0 and 1.
+    ///
+    /// See [`Rgb::from_hsl`].
+    #[must_use]
+    pub fn hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        Self::Rgb(Rgb::from_hsl(hue, saturation, lightness))
+    }
+
+    /// Create an RGB color from a hue in degrees and a saturation and value between 0 and 1.
+    ///
+    /// See [`Rgb::from_hsv`].
+    #[must_use]
+    pub fn hsv(hue: f64, saturation: f64, value: f64) -> Self {
+        Self::Rgb(Rgb::from_hsv(hue, saturation, value))
+    }
+
+    /// Increase the lightness of the color by an amount between 0 and 1, converting it to RGB.
+    ///
+    /// [`Color::Default`] is returned unchanged, since its RGB value isn't known, and
+    /// [`Color::Rgba`] keeps its alpha channel.
+    #[must_use]
+    pub fn lighten(self, amount: f64) -> Self {
+        self.map_rgb(|rgb| rgb.lighten(amount))
+    }
+
+    /// Decrease the lightness of the color by an amount between 0 and 1, converting it to RGB.
+    ///
+    /// [`Color::Default`] is returned unchanged, since its RGB value isn't known, and
+    /// [`Color::Rgba`] keeps its alpha channel.
+    #[must_use]
+    pub fn darken(self, amount: f64) -> Self {
+        self.map_rgb(|rgb| rgb.darken(amount))
+    }
+
+    /// Increase the saturation of the color by an amount between -1 and 1, converting it to RGB.
+    /// Negative amounts desaturate the color.
+    ///
+    /// [`Color::Default`] is returned unchanged, since its RGB value isn't known, and
+    /// [`Color::Rgba`] keeps its alpha channel.
+    #[must_use]
+    pub fn saturate(self, amount: f64) -> Self {
+        self.map_rgb(|rgb| rgb.saturate(amount))
+    }
+
+    /// Mix this color with another, where a `ratio` of 0 gives this color and 1 gives the other.
+    ///
+    /// If either color is [`Color::Default`] the colors can't be mixed, so whichever color is
+    /// closer to the ratio is returned.
+    #[must_use]
+    pub fn mix(self, other: Self, ratio: f64) -> Self {
+        match (self.to_rgb(), other.to_rgb()) {
+            (Some(a), Some(b)) => Self::Rgb(a.mix(b, ratio)),
+            _ if ratio < 0.5 => self,
+            _ => other,
+        }
+    }
+
+    /// Get black or white, whichever is more readable on top of the background color.
+    ///
+    /// [`Color::Default`] is returned for a [`Color::Default`] background, since the terminal's
+    /// default foreground color is chosen to be readable on its default background.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toon::{Color, Rgb};
+    ///
+    /// assert_eq!(Color::contrasting_text(Color::DarkBlue), Color::White);
+    /// assert_eq!(Color::contrasting_text(Rgb::new(255, 200, 0)), Color::Black);
+    /// ```
+    #[must_use]
+    pub fn contrasting_text(background: impl Into<Self>) -> Self {
+        Self::contrasting_text_from(background, Self::Black, Self::White)
+    }
+
+    /// Get whichever of a dark and a light color is more readable on top of the background color.
+    ///
+    /// This is like [`contrasting_text`](Self::contrasting_text), but lets a theme provide its own
+    /// colors instead of black and white.
+    #[must_use]
+    pub fn contrasting_text_from(background: impl Into<Self>, dark: Self, light: Self) -> Self {
+        match background.into().to_rgb().map(BackgroundKind::of) {
+            Some(BackgroundKind::Light) => dark,
+            Some(_) => light,
+            None => Self::Default,
+        }
+    }
+
+    fn map_rgb(self, f: impl FnOnce(Rgb) -> Rgb) -> Self {
+        match self {
+            Self::Rgba(rgba) => Self::Rgba(Rgba::from_rgb(f(rgba.rgb()), rgba.a)),
+            _ => self.to_rgb().map_or(self, |rgb| Self::Rgb(f(rgb))),
+        }
+    }
+
+    /// Get the dark variant of a color if it is a named color variant.
     #[must_use]
-    pub fn darken(self) -> Self {
+    pub fn to_dark(self) -> Self {
         match self {
             Self::Black | Self::DarkGray => Self::Black,
             Self::LightGray => Self::DarkGray,
@@ -233,12 +758,142 @@ impl From<AnsiColor> for Color {
         Self::AnsiValue(color)
     }
 }
+
+/// The names of the named colors, as they are displayed.
+const COLOR_NAMES: [(&str, Color); 17] = [
+    ("default", Color::Default),
+    ("black", Color::Black),
+    ("dark-gray", Color::DarkGray),
+    ("light-gray", Color::LightGray),
+    ("white", Color::White),
+    ("red", Color::Red),
+    ("dark-red", Color::DarkRed),
+    ("green", Color::Green),
+    ("dark-green", Color::DarkGreen),
+    ("yellow", Color::Yellow),
+    ("dark-yellow", Color::DarkYellow),
+    ("blue", Color::Blue),
+    ("dark-blue", Color::DarkBlue),
+    ("magenta", Color::Magenta),
+    ("dark-magenta", Color::DarkMagenta),
+    ("cyan", Color::Cyan),
+    ("dark-cyan", Color::DarkCyan),
+];
+
+/// Displays the color like `dark-red`, `123` for ANSI colors, `#ff8000` for RGB colors or
+/// `#00000080` for translucent colors.
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AnsiValue(color) => write!(f, "{}", color.get()),
+            Self::Rgb(rgb) => write!(f, "{}", rgb),
+            Self::Rgba(rgba) => write!(f, "{}", rgba),
+            _ => f.write_str(COLOR_NAMES.iter().find(|(_, c)| c == self).unwrap().0),
+        }
+    }
+}
+
+/// Parses colors in the format they are displayed in, like `dark-red`, `123`, `#ff8000` or
+/// `#00000080`. RGB colors can also be written like `rgb(255, 128, 0)`.
+///
+/// Color names are case insensitive, and ANSI values below 16 are parsed as the named colors.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Color, Rgb};
+///
+/// assert_eq!("dark-red".parse(), Ok(Color::DarkRed));
+/// assert_eq!("208".parse(), Ok(Color::new_ansi(208)));
+/// assert_eq!("rgb(255, 128, 0)".parse(), Ok(Color::Rgb(Rgb::new(255, 128, 0))));
+/// ```
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("rgb(") {
+            return s.parse().map(Self::Rgb);
+        }
+        if s.starts_with('#') {
+            return match s.len() {
+                9 => s.parse().map(Self::Rgba),
+                _ => s.parse().map(Self::Rgb),
+            };
+        }
+        if let Ok(value) = s.parse() {
+            return Ok(Self::new_ansi(value));
+        }
+        COLOR_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|&(_, color)| color)
+            .ok_or_else(|| ParseColorError(s.to_owned()))
+    }
+}
+
+/// An error parsing a [`Color`], [`Rgb`] or [`Rgba`], containing the text that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(pub String);
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid color {:?}", self.0)
+    }
+}
+impl StdError for ParseColorError {}
+
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for Color {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::AnsiValue(color) => serializer.serialize_u8(color.get()),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+/// Colors can be deserialized from strings in the format they are displayed in or from ANSI
+/// values.
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Color {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde_crate::de::{self, Unexpected, Visitor};
+        use std::convert::TryFrom;
+
+        struct ColorVisitor;
+
+        impl<'de> Visitor<'de> for ColorVisitor {
+            type Value = Color;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a color name, ANSI value or RGB color like \"#ff8000\"")
+            }
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                match u8::try_from(v) {
+                    Ok(value) => Ok(Color::new_ansi(value)),
+                    Err(_) => Err(E::invalid_value(Unexpected::Unsigned(v), &self)),
+                }
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
 impl From<Rgb> for Color {
     fn from(rgb: Rgb) -> Self {
         Self::Rgb(rgb)
     }
 }
 
+impl From<Rgba> for Color {
+    fn from(rgba: Rgba) -> Self {
+        Self::Rgba(rgba)
+    }
+}
+
 /// An ANSI value color.
 ///
 /// This can either be an RGB color with each part being 6 values, or it can be a grayscale color
@@ -320,6 +975,30 @@ impl AnsiColor {
         Some((value / 36, value % 36 / 6, value % 6))
     }
 
+    /// Get the RGB value that this color is displayed as in xterm's 256-color palette.
+    #[must_use]
+    pub fn to_rgb(self) -> Rgb {
+        if let Some((r, g, b)) = self.rgb() {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            Rgb::new(level(r), level(g), level(b))
+        } else {
+            let shade = 8 + (self.0 - 0xE8) * 10;
+            Rgb::new(shade, shade, shade)
+        }
+    }
+
+    /// Get the closest of the 16 named colors to this color, as measured by [`color_distance`].
+    #[must_use]
+    pub fn to_ansi16(self) -> Color {
+        self.to_ansi16_by(color_distance)
+    }
+
+    /// Get the closest of the 16 named colors to this color, as measured by the distance function.
+    #[must_use]
+    pub fn to_ansi16_by(self, distance: fn(Rgb, Rgb) -> u32) -> Color {
+        self.to_rgb().to_ansi16_by(distance)
+    }
+
     /// Get the grayscale shade of the color, if the color is grayscale.
     ///
     /// The returned value is guaranteed to be < 26.
@@ -358,6 +1037,10 @@ fn test_ansi() {
     assert_eq!(color.get(), 231);
     assert_eq!(color.rgb(), Some((5, 5, 5)));
     assert_eq!(color.grayscale(), Some(25));
+    assert_eq!(color.to_rgb(), Rgb::new(255, 255, 255));
+
+    assert_eq!(AnsiColor::new_rgb(1, 0, 5).to_rgb(), Rgb::new(95, 0, 255));
+    assert_eq!(AnsiColor::new(0xE8).to_rgb(), Rgb::new(8, 8, 8));
 }
 
 impl From<AnsiColor> for u8 {
@@ -366,6 +1049,302 @@ impl From<AnsiColor> for u8 {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for AnsiColor {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for AnsiColor {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde_crate::de::{Error, Unexpected};
+
+        let value = u8::deserialize(deserializer)?;
+        if value < 16 {
+            return Err(D::Error::invalid_value(
+                Unexpected::Unsigned(value.into()),
+                &"an ANSI value of at least 16",
+            ));
+        }
+        Ok(Self(value))
+    }
+}
+
+/// A hyperlink, which can be attached to the text drawn by an element with
+/// [`ElementExt::link`](crate::ElementExt::link).
+///
+/// Links aren't part of [`Style`] so that styles can stay [`Copy`]. Instead each cell of a
+/// [`Grid`](crate::Grid) stores the link it points to, if any. The URL is reference counted, so
+/// links are cheap to clone.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Link(
+    /// This is a thin pointer to keep the cells of grids small.
+    Arc<String>,
+);
+
+impl Link {
+    /// Create a link to a URL.
+    #[must_use]
+    pub fn new(url: &str) -> Self {
+        Self(Arc::new(url.to_owned()))
+    }
+
+    /// Get the URL the link points to.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for Link {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Link {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::new(&String::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_link() {
+    let link = Link::new("https://example.com");
+    assert_eq!(link.url(), "https://example.com");
+    assert!(std::ptr::eq(link.url(), link.clone().url()));
+    assert_eq!(link, Link::new(&"https://example.com".to_owned()));
+    assert_ne!(link, Link::new("https://example.org"));
+}
+
+/// The colors that a terminal is able to display.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// Only the 16 named colors are supported.
+    Ansi16,
+    /// The 16 named colors and the 240 [`AnsiColor`]s are supported.
+    Ansi256,
+    /// All colors, including full 24-bit RGB colors, are supported.
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Guess the color support of the current terminal from the `COLORTERM` and `TERM`
+    /// environment variables.
+    #[must_use]
+    pub fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+
+        let colorterm = var("COLORTERM");
+        let term = var("TERM");
+
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else if cfg!(windows) && term.is_empty() {
+            // The Windows console supports true color since Windows 10.
+            Self::TrueColor
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Convert a color to the closest color that is supported, as measured by the distance
+    /// function (such as [`color_distance`]).
+    ///
+    /// Named colors and [`Color::Default`] are always supported.
+    #[must_use]
+    pub fn downgrade(self, color: Color, distance: fn(Rgb, Rgb) -> u32) -> Color {
+        match (self, color) {
+            (Self::Ansi16, Color::AnsiValue(ansi)) => ansi.to_ansi16_by(distance),
+            (Self::Ansi16, Color::Rgb(rgb)) => rgb.to_ansi16_by(distance),
+            (Self::Ansi16, Color::Rgba(rgba)) => rgba.rgb().to_ansi16_by(distance),
+            (Self::Ansi256, Color::Rgb(rgb)) => Color::AnsiValue(rgb.to_ansi256_by(distance)),
+            (Self::Ansi256, Color::Rgba(rgba)) => {
+                Color::AnsiValue(rgba.rgb().to_ansi256_by(distance))
+            }
+            _ => color,
+        }
+    }
+}
+
+/// How bold text in one of the eight dark named colors is displayed, set with
+/// [`Terminal::set_bold_colors`](crate::Terminal::set_bold_colors).
+///
+/// Many terminals historically showed bold text in the bright version of its color, and some
+/// still do while others only make it bold. This lets applications choose what they want instead
+/// of depending on the terminal.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum BoldColors {
+    /// Bold only makes text bold. The colors are sent to the terminal unchanged, which may still
+    /// brighten them itself.
+    Unchanged,
+    /// Bold also selects the bright version of dark named colors, so that bold
+    /// [`DarkRed`](Color::DarkRed) text is shown as bold [`Red`](Color::Red) text.
+    Bright,
+}
+
+impl BoldColors {
+    /// Apply the policy to the foreground color of a style.
+    #[must_use]
+    pub fn apply(self, mut style: Style) -> Style {
+        if self == Self::Bright && style.attributes.intensity == Intensity::Bold {
+            if let Some(value @ 0..=7) = style.foreground.ansi_value() {
+                style.foreground = Color::new_ansi(value + 8);
+            }
+        }
+        style
+    }
+}
+
+impl Default for BoldColors {
+    fn default() -> Self {
+        Self::Unchanged
+    }
+}
+
+/// Whether a terminal has a dark or light background, from
+/// [`Terminal::background_kind`](crate::Terminal::background_kind).
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum BackgroundKind {
+    /// The background is dark, so text should be light.
+    Dark,
+    /// The background is light, so text should be dark.
+    Light,
+    /// The background color is not known.
+    Unknown,
+}
+
+impl BackgroundKind {
+    /// Classify a background color by its luminance.
+    #[must_use]
+    pub fn of(background: Rgb) -> Self {
+        let luminance = 2126 * u32::from(background.r)
+            + 7152 * u32::from(background.g)
+            + 722 * u32::from(background.b);
+        if luminance < 10000 * 128 {
+            Self::Dark
+        } else {
+            Self::Light
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_background_kind() {
+    assert_eq!(BackgroundKind::of(Rgb::new(0, 0, 0)), BackgroundKind::Dark);
+    assert_eq!(
+        BackgroundKind::of(Rgb::new(0x28, 0x2C, 0x34)),
+        BackgroundKind::Dark
+    );
+    assert_eq!(
+        BackgroundKind::of(Rgb::new(0xFD, 0xF6, 0xE3)),
+        BackgroundKind::Light
+    );
+    // Pure blue is dark despite being fully saturated.
+    assert_eq!(
+        BackgroundKind::of(Rgb::new(0, 0, 255)),
+        BackgroundKind::Dark
+    );
+}
+
+/// The default distance function used for downgrading colors and converting between palettes.
+///
+/// This is an approximation of how different two colors look to humans, weighting the red, green
+/// and blue components by how sensitive eyes are to them. It is much more accurate than the plain
+/// Euclidean distance, especially for mapping saturated colors to the 16 named colors.
+#[must_use]
+pub fn color_distance(a: Rgb, b: Rgb) -> u32 {
+    let red_mean = (u32::from(a.r) + u32::from(b.r)) / 2;
+    let dr = u32::from(a.r.max(b.r) - a.r.min(b.r));
+    let dg = u32::from(a.g.max(b.g) - a.g.min(b.g));
+    let db = u32::from(a.b.max(b.b) - a.b.min(b.b));
+
+    (((512 + red_mean) * dr * dr) >> 8) + 4 * dg * dg + (((767 - red_mean) * db * db) >> 8)
+}
+
+#[cfg(test)]
+#[test]
+fn test_contrasting_text() {
+    assert_eq!(Color::contrasting_text(Color::Black), Color::White);
+    assert_eq!(Color::contrasting_text(Color::LightGray), Color::Black);
+    assert_eq!(Color::contrasting_text(Color::DarkRed), Color::White);
+    assert_eq!(Color::contrasting_text(Color::Yellow), Color::Black);
+    assert_eq!(Color::contrasting_text(AnsiColor::new(232)), Color::White);
+    assert_eq!(Color::contrasting_text(Color::Default), Color::Default);
+
+    let (dark, light) = (Color::DarkBlue, Color::Cyan);
+    assert_eq!(
+        Color::contrasting_text_from(Rgb::new(250, 250, 250), dark, light),
+        dark
+    );
+    assert_eq!(
+        Color::contrasting_text_from(Rgb::new(30, 30, 30), dark, light),
+        light
+    );
+
+    assert_eq!(
+        Style::default().on_dark_blue().contrasting_foreground(),
+        Style::default().white().on_dark_blue()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_downgrade() {
+    let orange = Color::Rgb(Rgb::new(0xFF, 0x87, 0x00));
+
+    assert_eq!(
+        ColorSupport::TrueColor.downgrade(orange, color_distance),
+        orange
+    );
+    assert_eq!(
+        ColorSupport::Ansi256.downgrade(orange, color_distance),
+        Color::AnsiValue(AnsiColor::new(208)),
+    );
+    assert_eq!(
+        ColorSupport::Ansi16.downgrade(Color::Rgb(Rgb::new(0xF0, 0x10, 0x08)), color_distance),
+        Color::Red
+    );
+    assert_eq!(
+        ColorSupport::Ansi16.downgrade(Color::AnsiValue(AnsiColor::new(21)), color_distance),
+        Color::DarkBlue
+    );
+    assert_eq!(
+        ColorSupport::Ansi16.downgrade(Color::Default, color_distance),
+        Color::Default
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_palette() {
+    // Exact palette colors map to themselves.
+    for value in 16..=255 {
+        let ansi = AnsiColor::new(value);
+        assert_eq!(ansi.to_rgb().to_ansi256(), ansi);
+    }
+    for value in 0..=15 {
+        let color = Color::new_ansi(value);
+        assert_eq!(color.to_rgb().unwrap().to_ansi16(), color);
+    }
+
+    assert_eq!(Rgb::new(0x80, 0x80, 0x80).to_ansi256(), AnsiColor::new(244));
+    assert_eq!(Rgb::new(0x00, 0x00, 0x00).to_ansi256(), AnsiColor::new(16));
+    assert_eq!(Rgb::new(0xFF, 0x80, 0xC0).to_ansi256(), AnsiColor::new(211));
+    assert_eq!(Rgb::new(0x10, 0x20, 0xA0).to_ansi16(), Color::DarkBlue);
+    assert_eq!(Rgb::new(0x20, 0xE0, 0x30).to_ansi16(), Color::DarkGreen);
+    assert_eq!(AnsiColor::new(196).to_ansi16(), Color::Red);
+    assert_eq!(AnsiColor::new(236).to_ansi16(), Color::Black);
+}
+
 /// A full 24-bit RGB color.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Rgb {
@@ -392,10 +1371,311 @@ impl Rgb {
             b: u8::MAX - self.b,
         }
     }
+
+    /// Creates a color from a hue in degrees and a saturation and lightness between 0 and 1.
+    ///
+    /// Hues outside of 0 to 360 wrap around, and saturations and lightnesses outside of 0 to 1 are
+    /// clamped.
+    #[must_use]
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Self::from_hue_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+    /// Gets the hue in degrees, saturation and lightness of the color.
+    ///
+    /// Grays have a hue of 0.
+    #[must_use]
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max - min == 0.0 {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+    /// Creates a color from a hue in degrees and a saturation and value between 0 and 1.
+    ///
+    /// Hues outside of 0 to 360 wrap around, and saturations and values outside of 0 to 1 are
+    /// clamped.
+    #[must_use]
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+        let chroma = value * saturation;
+        Self::from_hue_chroma(hue, chroma, value - chroma)
+    }
+    /// Gets the hue in degrees, saturation and value of the color.
+    ///
+    /// Grays have a hue of 0.
+    #[must_use]
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (hue, saturation, max)
+    }
+
+    /// Increases the lightness of the color by an amount between 0 and 1.
+    #[must_use]
+    pub fn lighten(self, amount: f64) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Self::from_hsl(hue, saturation, lightness + amount)
+    }
+    /// Decreases the lightness of the color by an amount between 0 and 1.
+    #[must_use]
+    pub fn darken(self, amount: f64) -> Self {
+        self.lighten(-amount)
+    }
+    /// Increases the saturation of the color by an amount between -1 and 1. Negative amounts
+    /// desaturate the color.
+    #[must_use]
+    pub fn saturate(self, amount: f64) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Self::from_hsl(hue, saturation + amount, lightness)
+    }
+    /// Mixes this color with another, where a `ratio` of 0 gives this color and 1 gives the
+    /// other.
+    #[must_use]
+    pub fn mix(self, other: Self, ratio: f64) -> Self {
+        let ratio = ratio.clamp(0.0, 1.0);
+        let mix =
+            |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * ratio).round() as u8;
+        Self {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+        }
+    }
+
+    /// Gets the closest of the 16 named colors to this color, as measured by [`color_distance`].
+    #[must_use]
+    pub fn to_ansi16(self) -> Color {
+        self.to_ansi16_by(color_distance)
+    }
+    /// Gets the closest of the 16 named colors to this color, as measured by the distance
+    /// function.
+    #[must_use]
+    pub fn to_ansi16_by(self, distance: fn(Self, Self) -> u32) -> Color {
+        (0..=15)
+            .map(Color::new_ansi)
+            .min_by_key(|candidate| distance(self, candidate.to_rgb().unwrap()))
+            .unwrap()
+    }
+    /// Gets the closest color in xterm's 256-color palette to this color, excluding the 16 named
+    /// colors whose values differ between terminals, as measured by [`color_distance`].
+    #[must_use]
+    pub fn to_ansi256(self) -> AnsiColor {
+        self.to_ansi256_by(color_distance)
+    }
+    /// Gets the closest color in xterm's 256-color palette to this color, excluding the 16 named
+    /// colors whose values differ between terminals, as measured by the distance function.
+    #[must_use]
+    pub fn to_ansi256_by(self, distance: fn(Self, Self) -> u32) -> AnsiColor {
+        (16..=255)
+            .map(AnsiColor::new)
+            .min_by_key(|candidate| distance(self, candidate.to_rgb()))
+            .unwrap()
+    }
+
+    /// Creates a color from its hue, chroma and the amount to add to each component, all but the
+    /// hue being between 0 and 1.
+    fn from_hue_chroma(hue: f64, chroma: f64, add: f64) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let component = |c: f64| ((c + add) * 255.0).round() as u8;
+        Self::new(component(r), component(g), component(b))
+    }
+    /// Gets the hue of the color in degrees and its largest and smallest components between 0
+    /// and 1.
+    fn hue_max_min(self) -> (f64, f64, f64) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let [r, g, b, max_f, min_f] =
+            [self.r, self.g, self.b, max, min].map(|c| f64::from(c) / 255.0);
+        let chroma = max_f - min_f;
+        let hue = if max == min {
+            0.0
+        } else if max == self.r {
+            ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == self.g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        (hue * 60.0, max_f, min_f)
+    }
+}
+
+/// Displays the color in hexadecimal, like `#ff8000`.
+impl Display for Rgb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Parses colors in hexadecimal, like `#ff8000`, or in CSS functional notation, like
+/// `rgb(255, 128, 0)`.
+impl FromStr for Rgb {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(components) = s.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+            let mut components = components.split(',').map(|c| c.trim().parse::<u8>());
+            return match (
+                components.next(),
+                components.next(),
+                components.next(),
+                components.next(),
+            ) {
+                (Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) => Ok(Self { r, g, b }),
+                _ => Err(ParseColorError(s.to_owned())),
+            };
+        }
+
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .ok_or_else(|| ParseColorError(s.to_owned()))?;
+        let component = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| ParseColorError(s.to_owned()))
+        };
+        Ok(Self {
+            r: component(0)?,
+            g: component(2)?,
+            b: component(4)?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for Rgb {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Rgb {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde_crate::de::Error::custom)
+    }
+}
+
+/// A 24-bit RGB color with an alpha channel, where an alpha of 0 is fully transparent and 255 is
+/// fully opaque.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Rgba {
+    /// The red component.
+    pub r: u8,
+    /// The green component.
+    pub g: u8,
+    /// The blue component.
+    pub b: u8,
+    /// The alpha component.
+    pub a: u8,
+}
+
+impl Rgba {
+    /// Creates a new RGBA color.
+    #[must_use]
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+    /// Creates an RGBA color from an RGB color and an alpha.
+    #[must_use]
+    pub const fn from_rgb(rgb: Rgb, a: u8) -> Self {
+        Self::new(rgb.r, rgb.g, rgb.b, a)
+    }
+    /// Gets the RGB components of the color, ignoring its alpha.
+    #[must_use]
+    pub const fn rgb(self) -> Rgb {
+        Rgb::new(self.r, self.g, self.b)
+    }
+    /// Composites this color over another, using the "over" operator.
+    #[must_use]
+    pub fn over(self, below: Self) -> Self {
+        if self.a == 0 && below.a == 0 {
+            return Self::new(0, 0, 0, 0);
+        }
+        let alpha = f64::from(self.a) / 255.0;
+        let below_alpha = f64::from(below.a) / 255.0 * (1.0 - alpha);
+        let out_alpha = alpha + below_alpha;
+        let component = |above: u8, below: u8| {
+            ((f64::from(above) * alpha + f64::from(below) * below_alpha) / out_alpha).round() as u8
+        };
+        Self {
+            r: component(self.r, below.r),
+            g: component(self.g, below.g),
+            b: component(self.b, below.b),
+            a: (out_alpha * 255.0).round() as u8,
+        }
+    }
+}
+
+impl From<Rgb> for Rgba {
+    fn from(rgb: Rgb) -> Self {
+        Self::from_rgb(rgb, u8::MAX)
+    }
+}
+
+/// Displays the color in hexadecimal, like `#ff800080`.
+impl Display for Rgba {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{:02x}", self.rgb(), self.a)
+    }
+}
+
+/// Parses colors in hexadecimal, like `#ff800080`.
+impl FromStr for Rgba {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseColorError(s.to_owned());
+        if s.len() != 9 || !s.is_ascii() {
+            return Err(err());
+        }
+        let rgb: Rgb = s[..7].parse().map_err(|_| err())?;
+        let a = u8::from_str_radix(&s[7..], 16).map_err(|_| err())?;
+        Ok(Self::from_rgb(rgb, a))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for Rgba {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for Rgba {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde_crate::de::Error::custom)
+    }
 }
 
 /// Attributes of text. Not all of these attributes are supported by all terminals.
 #[derive(Default, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", default)
+)]
 #[non_exhaustive]
 pub struct Attributes {
     /// The text intensity.
@@ -404,10 +1684,19 @@ pub struct Attributes {
     pub italic: bool,
     /// Whether the text is underlined.
     pub underlined: bool,
+    /// The kind of underline used when the text is underlined. Not widely supported.
+    pub underline_kind: UnderlineKind,
     /// Whether the text blinks. Not widely supported.
     pub blinking: bool,
     /// Whether the text is crossed out. Not widely supported.
     pub crossed_out: bool,
+    /// Whether the foreground and background colors of the text are swapped.
+    pub reversed: bool,
+    /// Whether the text is hidden. It still takes up space and can be selected and copied in some
+    /// terminals, so it shouldn't be relied on to keep secrets.
+    pub hidden: bool,
+    /// Whether the text has a line over it. Not widely supported.
+    pub overlined: bool,
 }
 
 impl Attributes {
@@ -418,14 +1707,23 @@ impl Attributes {
             intensity: Intensity::Normal,
             italic: false,
             underlined: false,
+            underline_kind: UnderlineKind::Single,
             blinking: false,
             crossed_out: false,
+            reversed: false,
+            hidden: false,
+            overlined: false,
         }
     }
 }
 
 /// The intensity of text.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", rename_all = "kebab-case")
+)]
 pub enum Intensity {
     /// Less intense text. Not widely supported.
     Dim,
@@ -440,3 +1738,352 @@ impl Default for Intensity {
         Self::Normal
     }
 }
+
+/// A kind of underline.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", rename_all = "kebab-case")
+)]
+pub enum UnderlineKind {
+    /// A single straight line.
+    Single,
+    /// Two straight lines.
+    Double,
+    /// A wavy line, often used to mark spelling mistakes.
+    Curly,
+    /// A dotted line.
+    Dotted,
+    /// A dashed line.
+    Dashed,
+}
+
+impl Default for UnderlineKind {
+    fn default() -> Self {
+        Self::Single
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Style {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            foreground: Color::arbitrary(g),
+            background: Color::arbitrary(g),
+            attributes: Attributes::arbitrary(g),
+            underline_color: Color::arbitrary(g),
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Color {
+    fn arbitrary(g: &mut Gen) -> Self {
+        match u8::arbitrary(g) % 4 {
+            0 => Self::Default,
+            1 => Self::new_ansi(u8::arbitrary(g)),
+            2 => Self::Rgb(Rgb::arbitrary(g)),
+            _ => Self::Rgba(Rgba::arbitrary(g)),
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Rgb {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self::new(u8::arbitrary(g), u8::arbitrary(g), u8::arbitrary(g))
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Rgba {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self::new(
+            u8::arbitrary(g),
+            u8::arbitrary(g),
+            u8::arbitrary(g),
+            u8::arbitrary(g),
+        )
+    }
+}
+
+/// Links are chosen from a few example URLs.
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Link {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self::new(
+            g.choose(&[
+                "https://example.com",
+                "https://example.com/a",
+                "https://example.org",
+            ])
+            .unwrap(),
+        )
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Attributes {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            intensity: Intensity::arbitrary(g),
+            italic: bool::arbitrary(g),
+            underlined: bool::arbitrary(g),
+            underline_kind: UnderlineKind::arbitrary(g),
+            blinking: bool::arbitrary(g),
+            crossed_out: bool::arbitrary(g),
+            reversed: bool::arbitrary(g),
+            hidden: bool::arbitrary(g),
+            overlined: bool::arbitrary(g),
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for Intensity {
+    fn arbitrary(g: &mut Gen) -> Self {
+        *g.choose(&[Self::Dim, Self::Normal, Self::Bold]).unwrap()
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl Arbitrary for UnderlineKind {
+    fn arbitrary(g: &mut Gen) -> Self {
+        *g.choose(&[
+            Self::Single,
+            Self::Double,
+            Self::Curly,
+            Self::Dotted,
+            Self::Dashed,
+        ])
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_hsl() {
+    let orange = Rgb::new(255, 128, 0);
+    let (hue, saturation, lightness) = orange.to_hsl();
+    assert!((hue - 30.1).abs() < 0.1);
+    assert!((saturation - 1.0).abs() < f64::EPSILON);
+    assert!((lightness - 0.5).abs() < 0.01);
+    assert_eq!(Rgb::from_hsl(hue, saturation, lightness), orange);
+    let (hue, saturation, value) = orange.to_hsv();
+    assert_eq!(Rgb::from_hsv(hue, saturation, value), orange);
+
+    assert_eq!(Color::hsl(0.0, 1.0, 0.5), Color::Rgb(Rgb::new(255, 0, 0)));
+    assert_eq!(
+        Color::hsl(480.0, 1.0, 0.25),
+        Color::Rgb(Rgb::new(0, 128, 0))
+    );
+    assert_eq!(Color::hsv(240.0, 1.0, 1.0), Color::Rgb(Rgb::new(0, 0, 255)));
+    assert!(Rgb::new(50, 50, 50).to_hsl().0.abs() < f64::EPSILON);
+
+    assert_eq!(Color::Red.darken(0.25), Color::Rgb(Rgb::new(128, 0, 0)));
+    assert_eq!(
+        Color::Black.lighten(2.0),
+        Color::Rgb(Rgb::new(255, 255, 255))
+    );
+    assert_eq!(
+        Color::Red.saturate(-1.0),
+        Color::Rgb(Rgb::new(128, 128, 128))
+    );
+    assert_eq!(Color::Default.lighten(0.5), Color::Default);
+
+    assert_eq!(
+        Color::Black.mix(Color::White, 0.5),
+        Color::Rgb(Rgb::new(128, 128, 128))
+    );
+    assert_eq!(Color::Red.mix(Color::Default, 0.25), Color::Red);
+    assert_eq!(Color::Red.mix(Color::Default, 0.75), Color::Default);
+}
+
+#[cfg(test)]
+#[test]
+fn test_blend() {
+    let shadow = Color::Rgba(Rgba::new(0, 0, 0, 0x80));
+    assert_eq!(
+        shadow.blend_over(Color::White),
+        Color::Rgb(Rgb::new(127, 127, 127))
+    );
+    assert_eq!(shadow.blend_over(Color::Default), shadow);
+    assert_eq!(
+        shadow.blend_over(shadow),
+        Color::Rgba(Rgba::new(0, 0, 0, 0xC0))
+    );
+    assert_eq!(Color::Red.blend_over(Color::White), Color::Red);
+
+    assert_eq!(
+        shadow.to_opaque(Some(Rgb::new(255, 255, 255))),
+        Color::Rgb(Rgb::new(127, 127, 127))
+    );
+    assert_eq!(shadow.to_opaque(None), Color::Rgb(Rgb::new(0, 0, 0)));
+    assert_eq!(
+        Color::Rgba(Rgba::new(0, 0, 0, 0x7F)).to_opaque(None),
+        Color::Default
+    );
+
+    let style = Style::default()
+        .foreground(Rgba::new(255, 255, 255, 0x80))
+        .background(shadow)
+        .blend_over(Style::default().on_white());
+    assert_eq!(style.background, Color::Rgb(Rgb::new(127, 127, 127)));
+    assert_eq!(style.foreground, Color::Rgb(Rgb::new(191, 191, 191)));
+}
+
+#[cfg(test)]
+#[test]
+fn test_color_from_str() {
+    for &color in &[
+        Color::Default,
+        Color::DarkRed,
+        Color::LightGray,
+        Color::AnsiValue(AnsiColor::new(123)),
+        Color::Rgb(Rgb::new(255, 128, 0)),
+        Color::Rgba(Rgba::new(255, 128, 0, 64)),
+    ] {
+        assert_eq!(color.to_string().parse(), Ok(color));
+    }
+    assert_eq!(Color::DarkRed.to_string(), "dark-red");
+    assert_eq!(Rgb::new(255, 128, 0).to_string(), "#ff8000");
+
+    assert_eq!("Dark-Cyan".parse(), Ok(Color::DarkCyan));
+    assert_eq!("9".parse(), Ok(Color::Red));
+    assert_eq!("#FF8000".parse(), Ok(Color::Rgb(Rgb::new(255, 128, 0))));
+    assert_eq!(
+        "orange".parse::<Color>(),
+        Err(ParseColorError("orange".to_owned()))
+    );
+    assert!("#ff80".parse::<Color>().is_err());
+    assert!("#ff80é".parse::<Color>().is_err());
+    assert!("256".parse::<Color>().is_err());
+
+    assert_eq!("rgb(255,128,0)".parse(), Ok(Rgb::new(255, 128, 0)));
+    assert_eq!("rgb( 1, 2 ,3 )".parse(), Ok(Color::Rgb(Rgb::new(1, 2, 3))));
+    assert!("rgb(1, 2)".parse::<Color>().is_err());
+    assert!("rgb(1, 2, 3, 4)".parse::<Color>().is_err());
+    assert!("rgb(1, 2, 256)".parse::<Color>().is_err());
+    assert!("rgb(1, 2, 3".parse::<Color>().is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let style: Style = serde_json::from_str(
+        r##"{
+            "foreground": "dark-red",
+            "background": 123,
+            "attributes": { "intensity": "bold", "underline_kind": "curly" },
+            "underline_color": "#ff8000"
+        }"##,
+    )
+    .unwrap();
+    assert_eq!(
+        style,
+        Style {
+            foreground: Color::DarkRed,
+            background: Color::AnsiValue(AnsiColor::new(123)),
+            attributes: Attributes {
+                intensity: Intensity::Bold,
+                underline_kind: UnderlineKind::Curly,
+                ..Attributes::new()
+            },
+            underline_color: Color::Rgb(Rgb::new(255, 128, 0)),
+        }
+    );
+
+    let json = serde_json::to_string(&style).unwrap();
+    assert_eq!(serde_json::from_str::<Style>(&json).unwrap(), style);
+
+    assert_eq!(
+        serde_json::from_str::<Style>(r#"{ "foreground": 1 }"#).unwrap(),
+        Style::default().dark_red()
+    );
+    assert!(serde_json::from_str::<Color>(r#""orange""#).is_err());
+    assert!(serde_json::from_str::<Color>("256").is_err());
+    assert!(serde_json::from_str::<AnsiColor>("15").is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn test_style_lerp() {
+    let a = Style::default()
+        .foreground(Rgb::new(0, 0, 0))
+        .on_blue()
+        .italic();
+    let b = Style::default()
+        .foreground(Rgb::new(200, 100, 50))
+        .on_blue()
+        .bold();
+
+    assert_eq!(Style::lerp(a, b, 0.0), a);
+    assert_eq!(Style::lerp(a, b, 1.0), b);
+    assert_eq!(Style::lerp(a, b, 2.0), b);
+    assert_eq!(Style::lerp(a, b, 0.25), a.foreground(Rgb::new(50, 25, 13)));
+    assert_eq!(Style::lerp(a, b, 0.5), b.foreground(Rgb::new(100, 50, 25)));
+
+    // Default colors can't be mixed.
+    let c = Style::default().red();
+    assert_eq!(Style::lerp(Style::default(), c, 0.4), Style::default());
+    assert_eq!(Style::lerp(Style::default(), c, 0.6), c);
+}
+
+#[cfg(test)]
+#[test]
+fn test_style_patch() {
+    let style = Style::default().red().on_black().italic();
+
+    assert_eq!(style.patch(StylePatch::new()), style);
+    assert_eq!(
+        style.patch(StylePatch {
+            foreground: Some(Color::Default),
+            intensity: Some(Intensity::Bold),
+            italic: Some(false),
+            ..StylePatch::new()
+        }),
+        Style::default().on_black().bold()
+    );
+    assert_eq!(
+        style.patch(Style::default().blue()),
+        Style::default().blue()
+    );
+    assert_eq!(style.style(Style::default()), Style::default());
+}
+
+#[cfg(test)]
+#[test]
+fn test_style_from_str() {
+    assert_eq!("".parse(), Ok(Style::default()));
+    assert_eq!(
+        "bold red on black".parse(),
+        Ok(Style::default().bold().red().on_black())
+    );
+    assert_eq!(
+        "I u DIM fg=#ff8000 bg=123 underline=curly s blinking reversed hidden overlined".parse(),
+        Ok(Style::default()
+            .italic()
+            .dim()
+            .foreground(Rgb::new(255, 128, 0))
+            .background(AnsiColor::new(123))
+            .underline_kind(UnderlineKind::Curly)
+            .crossed_out()
+            .blinking()
+            .reversed()
+            .hidden()
+            .overlined())
+    );
+    assert_eq!(
+        "bold orange".parse::<Style>(),
+        Err(ParseStyleError("orange".to_owned()))
+    );
+    assert_eq!(
+        "red on".parse::<Style>(),
+        Err(ParseStyleError("on".to_owned()))
+    );
+    assert_eq!(
+        "underline=wavy".parse::<Style>(),
+        Err(ParseStyleError("underline=wavy".to_owned()))
+    );
+}
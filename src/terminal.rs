@@ -1,18 +1,42 @@
+#[cfg(feature = "tokio")]
+use std::cell::RefCell;
 use std::cmp::min;
+use std::collections::VecDeque;
 use std::error::Error as StdError;
 use std::fmt::{self, Display, Formatter};
-use std::io::{self, IoSliceMut, Read};
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{self, BufRead, IoSliceMut, Read, Write};
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 #[cfg(windows)]
 use std::os::windows::io::{AsRawHandle, FromRawHandle, IntoRawHandle, RawHandle};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+#[cfg(feature = "tokio")]
+use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
 
 use os_pipe::PipeReader;
 
-use crate::backend::{Backend, Bound, ReadEvents, TerminalEvent, TerminalMouseKind, Tty};
-use crate::buffer::{Buffer, CellKind, Grid};
-use crate::{Color, Element, Input, Intensity, Mouse, MouseButton, MouseKind, Output, Style, Vec2};
+use crate::backend::{
+    Backend, Bound, ReadEvents, TerminalEvent, TerminalMouse, TerminalMouseKind, Tty,
+};
+use crate::buffer::{self, Buffer, Grid, ImageArea};
+use crate::clock::Clock;
+use crate::command::Commands;
+use crate::component::{Component, States};
+use crate::events::Vector;
+use crate::output::Ext as _;
+use crate::style::{color_distance, BackgroundKind, BoldColors, ColorSupport, Rgb, UnderlineKind};
+use crate::{
+    Color, Element, Events, Input, Intensity, Keymap, Link, Modifiers, Mouse, MouseButton,
+    MouseCapture, MouseKind, Output, Signal, Style, Vec2,
+};
 
 static TERMINAL_EXISTS: AtomicBool = AtomicBool::new(false);
 
@@ -22,14 +46,20 @@ static TERMINAL_EXISTS: AtomicBool = AtomicBool::new(false);
 /// create more than one at once will panic.
 ///
 /// Terminals automatically capture all standard output and standard error while they are alive,
-/// and print it when they are dropped. However, you can also take ownership of it via the
-/// [`take_captured`](Self::take_captured) method.
+/// and print it when they are dropped. Since the two streams are captured separately, all of the
+/// standard output is printed before all of the standard error, so the order in which they were
+/// written is lost. However, you can also take ownership of it via the
+/// [`take_captured`](Self::take_captured) method, write it to a file as it arrives with
+/// [`log_captured_to_file`](Self::log_captured_to_file), or not capture it at all by creating the
+/// terminal with [`new_without_capture`](Self::new_without_capture).
 #[derive(Debug)]
 pub struct Terminal<B: Backend> {
     /// Only [`None`] during destruction of the type.
     backend: Option<B::Bound>,
     /// The previous title of the terminal.
     title: String,
+    /// The title of the terminal from before it was created, if it could be read.
+    original_title: Option<String>,
     /// Holds the previous frame to diff against.
     old_buffer: Buffer,
     /// Is always a clear buffer, kept around to avoid cloning the buffer each draw.
@@ -41,15 +71,57 @@ pub struct Terminal<B: Backend> {
     cursor_pos: Vec2<u16>,
     /// The current style being written with.
     style: Style,
+    /// The current hyperlink being written with.
+    link: Option<Link>,
+    /// The colors the backend is able to display.
+    color_support: ColorSupport,
+    /// The function used to find the closest supported color.
+    color_distance: fn(Rgb, Rgb) -> u32,
+    /// How bold text in dark named colors is displayed.
+    bold_colors: BoldColors,
+    /// Whether the backend is able to display images.
+    supports_graphics: bool,
+    /// Whether the terminal has a dark or light background, if it has been queried.
+    background_kind: Option<BackgroundKind>,
+    /// The background color of the terminal, if it has been queried and is known. Translucent
+    /// colors are blended with it.
+    background_color: Option<Rgb>,
+    /// The images currently displayed on the terminal.
+    drawn_images: Vec<ImageArea>,
     /// The captured stdout and stderr.
-    captured: Option<PipeReader>,
+    captured: Option<Captured>,
+    /// How much mouse input is captured.
+    mouse_capture: MouseCapture,
     /// The held down mouse button.
     mouse: Option<MouseButton>,
+    /// The last position of the mouse, if it is known and inside the drawn area.
+    hover: Option<Vec2<u16>>,
+    /// The modifiers held during the last key or mouse input.
+    modifiers: Modifiers,
+    /// Statistics about the last drawn frame.
+    stats: FrameStats,
+    /// The clock given by the backend.
+    clock: Arc<dyn Clock>,
+    /// When the last frame started being drawn.
+    last_frame: Option<Instant>,
+    /// When the input that caused the events last returned from drawing was received.
+    input_received: Option<Instant>,
+    /// The states of components drawn with `draw_component`.
+    states: States,
+    /// The keymap that turns keys into actions.
+    keymap: Keymap,
+    /// Inputs injected by the application.
+    injector: InputInjector,
+    /// Catches signals sent to the process, if the application asked to.
+    #[cfg(unix)]
+    signals: Option<crate::signals::Catcher>,
 }
 
 impl<B: Backend> Terminal<B> {
     /// Create a new terminal with the given backend.
     ///
+    /// The mouse is captured with the default [`MouseCapture`].
+    ///
     /// # Panics
     ///
     /// Panics if the backend is not a dummy and a terminal already exists.
@@ -58,6 +130,51 @@ impl<B: Backend> Terminal<B> {
     ///
     /// Fails if setting up the terminal fails.
     pub fn new(backend: B) -> Result<Self, Error<B::Error>> {
+        Self::with_mouse_capture(backend, MouseCapture::default())
+    }
+
+    /// Create a new terminal with the given backend, capturing the given amount of mouse input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the backend is not a dummy and a terminal already exists.
+    ///
+    /// # Errors
+    ///
+    /// Fails if setting up the terminal fails.
+    pub fn with_mouse_capture(
+        backend: B,
+        mouse_capture: MouseCapture,
+    ) -> Result<Self, Error<B::Error>> {
+        Self::create(backend, mouse_capture, true)
+    }
+
+    /// Create a new terminal with the given backend that doesn't capture standard output and
+    /// standard error.
+    ///
+    /// This is useful for applications that deliberately write to the standard error, for example
+    /// when it is redirected to a log file. Anything written to a stream that goes to the terminal
+    /// will mess up its display, and there is nothing to [take](Self::take_captured) or print when
+    /// the terminal is dropped.
+    ///
+    /// The mouse is captured with the default [`MouseCapture`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the backend is not a dummy and a terminal already exists.
+    ///
+    /// # Errors
+    ///
+    /// Fails if setting up the terminal fails.
+    pub fn new_without_capture(backend: B) -> Result<Self, Error<B::Error>> {
+        Self::create(backend, MouseCapture::default(), false)
+    }
+
+    fn create(
+        backend: B,
+        mouse_capture: MouseCapture,
+        capture_stdio: bool,
+    ) -> Result<Self, Error<B::Error>> {
         if !B::is_dummy() && TERMINAL_EXISTS.swap(true, Ordering::Acquire) {
             panic!("Terminal already exists!");
         }
@@ -65,33 +182,55 @@ impl<B: Backend> Terminal<B> {
         let (tty, captured) = if B::is_dummy() {
             (Tty::dummy(), None)
         } else {
-            let (tty, captured) = Tty::new().map_err(Error::Stdio)?;
-            (tty, Some(captured))
+            let (tty, captured) = Tty::new(capture_stdio).map_err(Error::Stdio)?;
+            let captured = captured.map(|(stdout, stderr)| Captured {
+                stdout: CapturedStream(stdout),
+                stderr: CapturedStream(stderr),
+            });
+            (tty, captured)
         };
 
         let mut backend = backend.bind(tty)?;
 
-        backend.hide_cursor()?;
-        backend.set_cursor_pos(Vec2::default())?;
-        backend.set_foreground(Color::Default)?;
-        backend.set_background(Color::Default)?;
-        backend.set_intensity(Intensity::Normal)?;
-        backend.set_italic(false)?;
-        backend.set_underlined(false)?;
-        backend.set_blinking(false)?;
-        backend.set_crossed_out(false)?;
+        let original_title = backend.title()?;
+        backend.push_title()?;
+        Self::reset_state(&mut backend, mouse_capture)?;
 
         let buffer = Buffer::from(Grid::new(backend.size()?));
+        let color_support = backend.color_support();
+        let supports_graphics = backend.supports_graphics();
+        let clock = backend.clock();
 
         Ok(Self {
             backend: Some(backend),
             title: String::new(),
+            original_title,
             old_buffer: buffer.clone(),
             buffer,
             cursor_pos: Vec2::default(),
             style: Style::default(),
+            link: None,
+            color_support,
+            color_distance,
+            bold_colors: BoldColors::default(),
+            supports_graphics,
+            background_kind: None,
+            background_color: None,
+            drawn_images: Vec::new(),
             captured,
+            mouse_capture,
             mouse: None,
+            hover: None,
+            modifiers: Modifiers::default(),
+            stats: FrameStats::default(),
+            clock,
+            last_frame: None,
+            input_received: None,
+            states: States::new(),
+            keymap: Keymap::new(),
+            injector: InputInjector::default(),
+            #[cfg(unix)]
+            signals: None,
         })
     }
 
@@ -105,6 +244,191 @@ impl<B: Backend> Terminal<B> {
     ///
     /// Fails when drawing to the backend fails.
     pub async fn draw<E: Element>(&mut self, element: E) -> Result<Vec<E::Event>, Error<B::Error>> {
+        self.draw_sized(element, None).await
+    }
+
+    /// Draw an element to the terminal at a specific size and wait for an event, like
+    /// [`draw`](Self::draw).
+    ///
+    /// The element is drawn to the top left of the terminal, and the given size is capped to the
+    /// size of the terminal. Mouse inputs outside the element's area are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Fails when drawing to the backend fails.
+    pub async fn draw_with_size<E: Element>(
+        &mut self,
+        element: E,
+        size: impl Into<Vec2<u16>>,
+    ) -> Result<Vec<E::Event>, Error<B::Error>> {
+        self.draw_sized(element, Some(size.into())).await
+    }
+
+    /// Draw an element to the terminal and wait for an event, like [`draw`](Self::draw), while
+    /// running the commands in the queue.
+    ///
+    /// If any commands complete before the user causes an event, the events of all the completed
+    /// commands are returned instead.
+    ///
+    /// # Errors
+    ///
+    /// Fails when drawing to the backend fails.
+    pub async fn draw_with_commands<E: Element>(
+        &mut self,
+        element: E,
+        commands: &mut Commands<'_, E::Event>,
+    ) -> Result<Vec<E::Event>, Error<B::Error>> {
+        let mut draw = Box::pin(self.draw(element));
+
+        crate::util::PollFn(|cx: &mut Context<'_>| {
+            if let Poll::Ready(events) = draw.as_mut().poll(cx) {
+                return Poll::Ready(events);
+            }
+            commands.poll_events(cx).map(Ok)
+        })
+        .await
+    }
+
+    /// Draw a [`Component`] to the terminal and wait for an event.
+    ///
+    /// The component's state is kept by the terminal, and is updated by any messages the component
+    /// produces. If no events are produced by the updates the component is redrawn, so like
+    /// [`draw`](Self::draw) this function will never return an empty vector.
+    ///
+    /// Each frame the component is drawn from a clone of its state, so dropping the future
+    /// produced by this function never loses any updates.
+    ///
+    /// # Errors
+    ///
+    /// Fails when drawing to the backend fails.
+    pub async fn draw_component<C: Component + 'static>(
+        &mut self,
+        component: &C,
+    ) -> Result<Vec<C::Event>, Error<B::Error>> {
+        loop {
+            let state = self.states.get(component).clone();
+            let component_events = self.draw(component.view(&state)).await?;
+
+            let events: Vec<_> = component_events
+                .into_iter()
+                .filter_map(|event| self.states.apply(component, event))
+                .collect();
+
+            if !events.is_empty() {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// Get the store of the states of components drawn with
+    /// [`draw_component`](Self::draw_component).
+    #[must_use]
+    pub fn states(&mut self) -> &mut States {
+        &mut self.states
+    }
+
+    /// Draw the value of a Tokio [watch channel](tokio_crate::sync::watch), producing a stream of
+    /// the events of the element.
+    ///
+    /// Each frame the current value is cloned and drawn with `view`. Whenever the value changes
+    /// the frame is redrawn, so other tasks can update what is shown by sending to the channel.
+    /// Like [`draw`](Self::draw), the terminal only reads input while the stream is being polled.
+    ///
+    /// If drawing fails the stream produces the error and ends.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "crossterm")]
+    /// # async {
+    /// use futures_lite::StreamExt;
+    /// use toon::{Crossterm, ElementExt, Terminal};
+    /// # use tokio_crate as tokio;
+    ///
+    /// let mut terminal = Terminal::new(Crossterm::default())?;
+    /// let (sender, receiver) = tokio::sync::watch::channel(0);
+    ///
+    /// let mut events = terminal.watch(receiver, |count| {
+    ///     toon::span(format!("Count: {}", count)).on(' ', |_| ()).boxed()
+    /// });
+    /// while let Some(()) = events.next().await.transpose()? {
+    ///     sender.send_modify(|count| *count += 1);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # };
+    /// ```
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub fn watch<'a, T, Event, F>(
+        &'a mut self,
+        mut state: tokio_crate::sync::watch::Receiver<T>,
+        mut view: F,
+    ) -> WatchEvents<'a, B, Event>
+    where
+        T: Clone + 'a,
+        Event: 'a,
+        F: for<'s> FnMut(&'s T) -> Box<dyn Element<Event = Event> + 's> + 'a,
+    {
+        let events = Rc::new(RefCell::new(VecDeque::new()));
+        let queue = Rc::clone(&events);
+
+        let frames = async move {
+            // Whether the sender still exists, so that the value can still change.
+            let mut open = true;
+            loop {
+                let value = state.borrow_and_update().clone();
+                let drawn = {
+                    let mut draw = Box::pin(self.draw(view(&value)));
+                    let mut changed = Box::pin(state.changed());
+                    crate::util::PollFn(|cx: &mut Context<'_>| {
+                        if let Poll::Ready(events) = draw.as_mut().poll(cx) {
+                            return Poll::Ready(Some(events));
+                        }
+                        if open {
+                            match changed.as_mut().poll(cx) {
+                                Poll::Ready(Ok(())) => return Poll::Ready(None),
+                                Poll::Ready(Err(_)) => open = false,
+                                Poll::Pending => {}
+                            }
+                        }
+                        Poll::Pending
+                    })
+                    .await
+                };
+
+                match drawn {
+                    Some(Ok(events)) => {
+                        queue.borrow_mut().extend(events);
+                        // Wait for the stream to take the events before drawing again. This
+                        // doesn't need to wake the task, as the stream polls this again as soon as
+                        // the queue is empty.
+                        crate::util::PollFn(|_: &mut Context<'_>| {
+                            if queue.borrow().is_empty() {
+                                Poll::Ready(())
+                            } else {
+                                Poll::Pending
+                            }
+                        })
+                        .await;
+                    }
+                    Some(Err(e)) => return e,
+                    // The value changed, so redraw it.
+                    None => {}
+                }
+            }
+        };
+
+        WatchEvents {
+            frames: Some(Box::pin(frames)),
+            events,
+        }
+    }
+
+    async fn draw_sized<E: Element>(
+        &mut self,
+        element: E,
+        size_override: Option<Vec2<u16>>,
+    ) -> Result<Vec<E::Event>, Error<B::Error>> {
         // Update title
         let mut old_title_bytes = self.title.bytes();
         let title_is_same = !self.title.is_empty()
@@ -129,38 +453,79 @@ impl<B: Backend> Terminal<B> {
         }
 
         loop {
-            element.draw(&mut self.buffer);
+            let draw_size =
+                size_override.map_or_else(|| self.size(), |size| size.min(self.size()));
+
+            let frame_start = self.clock.now();
+            self.stats.frame_interval = self
+                .last_frame
+                .map_or_else(Duration::default, |last| self.clock.elapsed(last));
+            self.last_frame = Some(frame_start);
 
+            let start = Instant::now();
+            element.draw(&mut (&mut self.buffer).area(Vec2::default(), draw_size));
+            self.stats.draw_time = start.elapsed();
+
+            let start = Instant::now();
             self.diff()?;
+            self.stats.diff_time = start.elapsed();
+
+            let start = Instant::now();
             self.backend_mut().flush()?;
+            self.stats.flush_time = start.elapsed();
+            self.stats.operations += 1;
+            self.stats.frames += 1;
+            self.stats.input_latency = self
+                .input_received
+                .take()
+                .map(|received| self.clock.elapsed(received));
 
             self.old_buffer.reset();
             std::mem::swap(&mut self.old_buffer, &mut self.buffer);
 
             loop {
-                let input = match self.backend_mut().read_event().await? {
-                    TerminalEvent::Key(key) => Input::Key(key),
-                    TerminalEvent::Mouse(mouse) => Input::Mouse(Mouse {
-                        kind: match mouse.kind {
-                            TerminalMouseKind::Press(button) => {
-                                self.mouse = Some(button);
-                                MouseKind::Press(button)
-                            }
-                            TerminalMouseKind::Release => match self.mouse.take() {
-                                Some(button) => MouseKind::Release(button),
-                                None => continue,
-                            },
-                            TerminalMouseKind::Move => match self.mouse {
-                                Some(button) => MouseKind::Drag(button),
-                                None => MouseKind::Move,
-                            },
-                            TerminalMouseKind::ScrollUp => MouseKind::ScrollUp,
-                            TerminalMouseKind::ScrollDown => MouseKind::ScrollDown,
-                        },
-                        at: mouse.at,
-                        size: self.buffer.size(),
-                        modifiers: mouse.modifiers,
-                    }),
+                let incoming = self.read_incoming().await?;
+                let received = self.clock.now();
+                let event = match incoming {
+                    Incoming::Terminal(event) => event,
+                    Incoming::Injected(input) => {
+                        match &input {
+                            Input::Key(press) => self.modifiers = press.modifiers,
+                            Input::Mouse(mouse) => self.modifiers = mouse.modifiers,
+                            _ => {}
+                        }
+                        let mut events = Vector::new();
+                        self.handle(&element, input, &mut events);
+                        if !events.0.is_empty() {
+                            self.input_received = Some(received);
+                            return Ok(events.0);
+                        }
+                        continue;
+                    }
+                };
+                let inputs = match event {
+                    TerminalEvent::Key(key) => {
+                        self.modifiers = key.modifiers;
+                        vec![Input::Key(key)]
+                    }
+                    TerminalEvent::Paste(text) => vec![Input::Paste(text)],
+                    TerminalEvent::Compose(text) => vec![Input::Compose(text)],
+                    TerminalEvent::Signal(Signal::Continue) => {
+                        self.resume()?;
+                        let mut events = Vector::new();
+                        self.handle(&element, Input::Signal(Signal::Continue), &mut events);
+                        if !events.0.is_empty() {
+                            self.input_received = Some(received);
+                            return Ok(events.0);
+                        }
+                        // Redraw the screen that was cleared.
+                        break;
+                    }
+                    TerminalEvent::Signal(signal) => vec![Input::Signal(signal)],
+                    TerminalEvent::Mouse(mouse) => match self.mouse_inputs(mouse, draw_size) {
+                        Some(inputs) => inputs,
+                        None => continue,
+                    },
                     TerminalEvent::Resize(size) if size == self.buffer.grid.size() => continue,
                     TerminalEvent::Resize(size) => {
                         self.buffer.grid.resize_width(size.x);
@@ -178,95 +543,262 @@ impl<B: Backend> Terminal<B> {
 
                         break;
                     }
+                    TerminalEvent::BackgroundColor(color) => {
+                        self.background_color = Some(color);
+                        self.background_kind = Some(BackgroundKind::of(color));
+                        // Translucent colors need to be blended with the new background.
+                        break;
+                    }
                 };
 
-                let mut events = crate::events::Vector(Vec::new());
-                element.handle(input, &mut events);
+                let mut events = Vector::new();
+                for input in inputs {
+                    self.handle(&element, input, &mut events);
+                }
                 if !events.0.is_empty() {
+                    self.input_received = Some(received);
                     return Ok(events.0);
                 }
             }
         }
     }
 
+    /// Wait for the next event from the backend, caught signal or input from the injector.
+    async fn read_incoming(&mut self) -> Result<Incoming, Error<B::Error>> {
+        let injected = &self.injector.0;
+        let mut read = Box::pin(self.backend.as_mut().unwrap().read_event());
+
+        let incoming = crate::util::PollFn(|cx: &mut Context<'_>| {
+            let mut injected = injected.lock().unwrap();
+            if let Some(signal) = injected.signals.pop_front() {
+                return Poll::Ready(Ok(Incoming::Terminal(TerminalEvent::Signal(signal))));
+            }
+            if let Some(input) = injected.inputs.pop_front() {
+                return Poll::Ready(Ok(Incoming::Injected(input)));
+            }
+            injected.waker = Some(cx.waker().clone());
+            drop(injected);
+
+            read.as_mut()
+                .poll(cx)
+                .map(|event| event.map(Incoming::Terminal))
+        })
+        .await?;
+        Ok(incoming)
+    }
+
+    /// Get the inputs caused by a mouse event, or [`None`] if it should be ignored.
+    fn mouse_inputs(&mut self, mouse: TerminalMouse, draw_size: Vec2<u16>) -> Option<Vec<Input>> {
+        self.modifiers = mouse.modifiers;
+        let kind = match mouse.kind {
+            TerminalMouseKind::Press(button) => {
+                self.mouse = Some(button);
+                MouseKind::Press(button)
+            }
+            TerminalMouseKind::Release => MouseKind::Release(self.mouse.take()?),
+            TerminalMouseKind::Move => match self.mouse {
+                Some(button) => MouseKind::Drag(button),
+                None => MouseKind::Move,
+            },
+            TerminalMouseKind::ScrollUp => MouseKind::ScrollUp,
+            TerminalMouseKind::ScrollDown => MouseKind::ScrollDown,
+            TerminalMouseKind::ScrollLeft => MouseKind::ScrollLeft,
+            TerminalMouseKind::ScrollRight => MouseKind::ScrollRight,
+        };
+        let mut inputs = self.hover(mouse.at, draw_size, mouse.modifiers);
+        if mouse.at.x < draw_size.x && mouse.at.y < draw_size.y {
+            inputs.push(Input::Mouse(Mouse {
+                kind,
+                at: mouse.at,
+                size: draw_size,
+                terminal_at: mouse.at,
+                modifiers: mouse.modifiers,
+            }));
+        }
+        Some(inputs)
+    }
+
+    /// Get the inputs for the mouse leaving and entering elements as it moves to a position.
+    fn hover(&mut self, at: Vec2<u16>, size: Vec2<u16>, modifiers: Modifiers) -> Vec<Input> {
+        let inside = at.x < size.x && at.y < size.y;
+        let previous = std::mem::replace(&mut self.hover, Some(at).filter(|_| inside));
+        if previous == Some(at) {
+            return Vec::new();
+        }
+
+        let offset = |from: Vec2<u16>, to: Vec2<u16>| to.into::<i32>() - from.into::<i32>();
+        let mouse = |kind, at| {
+            Input::Mouse(Mouse {
+                kind,
+                at,
+                size,
+                terminal_at: at,
+                modifiers,
+            })
+        };
+
+        let mut inputs = Vec::new();
+        if let Some(previous) = previous {
+            inputs.push(mouse(MouseKind::Leave(offset(previous, at)), previous));
+        }
+        if inside {
+            let from = previous.map(|previous| offset(at, previous));
+            inputs.push(mouse(MouseKind::Enter(from), at));
+        }
+        inputs
+    }
+
+    /// Give an input to the element, preceded by the shortcut and actions it triggers.
+    fn handle<E: Element>(&self, element: &E, input: Input, events: &mut Vector<E::Event>) {
+        if let Input::Key(press) = input {
+            events.set_propagation_stopped(false);
+            element.handle(Input::Shortcut(press), events);
+            if events.propagation_stopped() {
+                return;
+            }
+        }
+        for action in self.keymap.actions(&input) {
+            events.set_propagation_stopped(false);
+            element.handle(Input::Action(action.to_owned()), events);
+            if events.propagation_stopped() {
+                return;
+            }
+        }
+        events.set_propagation_stopped(false);
+        element.handle(input, events);
+    }
+
     /// Diffs `old_buffer` and `new_buffer` and draws them to the backend.
+    #[allow(clippy::too_many_lines)]
     fn diff(&mut self) -> Result<(), Error<B::Error>> {
         let backend = self.backend.as_mut().unwrap();
 
-        for (y, (old_line, new_line)) in self
-            .old_buffer
-            .grid
-            .lines()
-            .iter()
-            .zip(self.buffer.grid.lines())
-            .enumerate()
-        {
-            for (x, (old_cell, new_cell)) in
-                old_line.cells().iter().zip(new_line.cells()).enumerate()
-            {
-                if new_cell == old_cell {
-                    continue;
-                }
+        let images: &[ImageArea] = if self.supports_graphics {
+            &self.buffer.images
+        } else {
+            &[]
+        };
 
-                let pos = Vec2::new(x as u16, y as u16);
+        let mut cells_changed = 0;
+        let mut operations = 0;
 
-                let (new_contents, new_contents_double, new_style) = match new_cell.kind() {
-                    CellKind::Char {
-                        contents,
-                        double,
-                        style,
-                    } => (contents, double, style),
-                    CellKind::Continuation => continue,
-                };
+        macro_rules! op {
+            ($method:ident($($arg:expr),*)) => {{
+                operations += 1;
+                backend.$method($($arg),*)?;
+            }};
+        }
 
-                macro_rules! diff_styles {
-                    ($($(.$path:ident)+ => $set_style:ident,)*) => {
-                        $(
-                            if self.style$(.$path)+ != new_style$(.$path)+ {
-                                backend.$set_style(new_style$(.$path)+)?;
-                            }
-                        )*
-                    }
-                }
-                diff_styles! {
-                    .foreground => set_foreground,
-                    .background => set_background,
-                    .attributes.intensity => set_intensity,
-                    .attributes.italic => set_italic,
-                    .attributes.underlined => set_underlined,
-                    .attributes.blinking => set_blinking,
-                    .attributes.crossed_out => set_crossed_out,
+        // Don't draw over images, but do draw over images that have been removed even if the cells
+        // underneath haven't changed.
+        let drawn_images = &*self.drawn_images;
+        let changes = buffer::diff_redrawing(&self.old_buffer.grid, &self.buffer.grid, |pos| {
+            covered(drawn_images, pos)
+        })
+        .filter(|change| !covered(images, change.pos));
+
+        for change in changes {
+            let pos = change.pos;
+            let mut new_style = self
+                .bold_colors
+                .apply(change.style.to_opaque(self.background_color));
+            new_style.foreground = self
+                .color_support
+                .downgrade(new_style.foreground, self.color_distance);
+            new_style.background = self
+                .color_support
+                .downgrade(new_style.background, self.color_distance);
+            new_style.underline_color = self
+                .color_support
+                .downgrade(new_style.underline_color, self.color_distance);
+
+            macro_rules! diff_styles {
+                ($($(.$path:ident)+ => $set_style:ident,)*) => {
+                    $(
+                        if self.style$(.$path)+ != new_style$(.$path)+ {
+                            op!($set_style(new_style$(.$path)+));
+                        }
+                    )*
                 }
+            }
+            diff_styles! {
+                .foreground => set_foreground,
+                .background => set_background,
+                .attributes.intensity => set_intensity,
+                .attributes.italic => set_italic,
+                .attributes.blinking => set_blinking,
+                .attributes.crossed_out => set_crossed_out,
+                .attributes.reversed => set_reversed,
+                .attributes.hidden => set_hidden,
+                .attributes.overlined => set_overlined,
+                .underline_color => set_underline_color,
+            }
+            if self.link.as_ref() != change.link {
+                op!(set_link(change.link));
+                self.link = change.link.cloned();
+            }
 
-                if self.cursor_pos != pos {
-                    backend.set_cursor_pos(pos)?;
+            // The kind of underline only matters when the text is underlined, and setting it
+            // also starts underlining text.
+            let underline = |style: Style| {
+                Some(style.attributes.underline_kind).filter(|_| style.attributes.underlined)
+            };
+            if underline(self.style) != underline(new_style) {
+                match underline(new_style) {
+                    None => op!(set_underlined(false)),
+                    Some(UnderlineKind::Single) => op!(set_underlined(true)),
+                    Some(kind) => op!(set_underline_kind(kind)),
                 }
+            }
+
+            if self.cursor_pos != pos {
+                op!(set_cursor_pos(pos));
+            }
 
-                backend.write(&new_contents)?;
+            op!(write(change.contents));
+            cells_changed += 1;
 
-                self.style = new_style;
+            self.style = new_style;
 
-                self.cursor_pos = Vec2::new(
-                    min(
-                        // TODO: The terminal's idea of how wide a character is doesn't always line
-                        // up with unicode-width. For example in iTerm2 the family emoji, which is
-                        // 8 wide in Unicode displays as 2 wide.
-                        pos.x + if new_contents_double { 2 } else { 1 },
-                        self.buffer.grid.width() - 1,
-                    ),
-                    pos.y,
-                );
+            self.cursor_pos = Vec2::new(
+                min(
+                    // TODO: The terminal's idea of how wide a character is doesn't always line
+                    // up with unicode-width. For example some terminals display flags as two
+                    // separate letters, which are 1 wide each.
+                    pos.x + if change.double { 2 } else { 1 },
+                    self.buffer.grid.width() - 1,
+                ),
+                pos.y,
+            );
+        }
+
+        // Images can overlap each other, so if any have changed redraw all of them.
+        if images != &*self.drawn_images {
+            for image in images {
+                if self.cursor_pos != image.pos {
+                    op!(set_cursor_pos(image.pos));
+                    self.cursor_pos = image.pos;
+                }
+                op!(draw_image(image.size, &image.bitmap));
             }
+            self.drawn_images = images.to_vec();
         }
 
         // Some terminals use the background color of the cursor to fill in space created by a
         // resize, so reset it.
-        backend.set_background(Color::Default)?;
+        op!(set_background(Color::Default));
         self.style.background = Color::Default;
 
+        // Don't leave a hyperlink open, or it will apply to any text written after the terminal is
+        // cleaned up.
+        if self.link.is_some() {
+            op!(set_link(None));
+            self.link = None;
+        }
+
         if let Some(new_cursor) = self.buffer.cursor {
             if self.old_buffer.cursor.is_none() {
-                backend.show_cursor()?;
+                op!(show_cursor());
             }
 
             if self
@@ -274,109 +806,612 @@ impl<B: Backend> Terminal<B> {
                 .cursor
                 .map_or(true, |c| c.shape != new_cursor.shape)
             {
-                backend.set_cursor_shape(new_cursor.shape)?;
+                op!(set_cursor_shape(new_cursor.shape));
             }
             if self
                 .old_buffer
                 .cursor
                 .map_or(true, |c| c.blinking != new_cursor.blinking)
             {
-                backend.set_cursor_blinking(new_cursor.blinking)?;
+                op!(set_cursor_blinking(new_cursor.blinking));
+            }
+            if self
+                .old_buffer
+                .cursor
+                .map_or(true, |c| c.color != new_cursor.color)
+            {
+                op!(set_cursor_color(new_cursor.color));
             }
             if self.cursor_pos != new_cursor.pos {
-                backend.set_cursor_pos(new_cursor.pos)?;
+                op!(set_cursor_pos(new_cursor.pos));
             }
         } else if self.old_buffer.cursor.is_some() {
-            backend.hide_cursor()?;
+            op!(hide_cursor());
         }
 
+        self.stats.cells_changed = cells_changed;
+        self.stats.operations = operations;
+
         Ok(())
     }
 
-    /// Get a reference to the terminal's backend.
+    /// Get the size of the terminal.
     #[must_use]
-    pub fn backend(&self) -> &B::Bound {
-        self.backend.as_ref().unwrap()
+    pub fn size(&self) -> Vec2<u16> {
+        self.buffer.grid.size()
     }
 
-    /// Get a mutable reference to the terminal's backend.
+    /// Get the size of a single cell of the terminal in pixels, or [`None`] if the backend doesn't
+    /// know it.
+    ///
+    /// This can be used to find the aspect ratio of cells when drawing images and charts.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the backend fails to get the cell size.
+    pub fn cell_size(&mut self) -> Result<Option<Vec2<u16>>, Error<B::Error>> {
+        Ok(self.backend_mut().cell_size()?)
+    }
+
+    /// Get the frame that was last drawn to the terminal.
+    ///
+    /// This is what the terminal is currently displaying, as far as Toon knows. Before the first
+    /// draw it is empty.
     #[must_use]
-    pub fn backend_mut(&mut self) -> &mut B::Bound {
-        self.backend.as_mut().unwrap()
+    pub fn current_frame(&self) -> &Grid {
+        &self.old_buffer.grid
     }
 
-    /// Take the captured standard output and standard error from the terminal.
+    /// Free the memory the terminal has kept to reuse when drawing later frames.
     ///
-    /// The terminal will no longer print all captured data to the standard output when the program
-    /// terminates.
+    /// The frame buffers keep the rows removed when the terminal shrinks, so that they don't have
+    /// to be allocated again when it grows back, for example when a maximized window is resized.
+    /// This can be called after the terminal has been made much smaller to release that memory.
+    pub fn shrink_to_fit(&mut self) {
+        self.buffer.shrink_to_fit();
+        self.old_buffer.shrink_to_fit();
+        self.drawn_images.shrink_to_fit();
+    }
+
+    /// Get the ids of the regions registered by elements in the last frame drawn to the terminal
+    /// that cover a position, from the last registered to the first.
     ///
-    /// This will return [`None`] if the backend is a dummy backend or the captured stdio has already
-    /// been taken.
-    pub fn take_captured(&mut self) -> Option<Captured> {
-        self.captured.take().map(Captured)
+    /// Regions are registered with [`Output::register_region`].
+    pub fn regions_at(&self, pos: impl Into<Vec2<u16>>) -> impl Iterator<Item = &str> + '_ {
+        self.old_buffer.regions_at(pos)
     }
 
-    /// Clean up the terminal.
+    /// Get the data attached to the cell at a position by elements in the last frame drawn to the
+    /// terminal, if there is any.
     ///
-    /// This will be called in the destructor too, but use this if you want to handle errors
-    /// instead of ignoring them.
+    /// Data is attached to cells with [`Output::set_cell_data`].
+    #[must_use]
+    pub fn cell_data_at(&self, pos: impl Into<Vec2<u16>>) -> Option<u64> {
+        self.old_buffer.cell_data_at(pos)
+    }
+
+    /// Get statistics about the last frame drawn to the terminal.
+    #[must_use]
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    /// Get the clock the terminal reads the time from, which is given by its backend.
     ///
-    /// # Errors
+    /// Elements that animate or otherwise depend on time should read it from this clock, so that
+    /// they can be tested deterministically with a [`Dummy`](crate::Dummy) backend.
+    #[must_use]
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Get the colors that the terminal is able to display.
     ///
-    /// Fails if cleaning up the backend fails.
-    pub fn cleanup(mut self) -> Result<(), Error<B::Error>> {
-        self.cleanup_inner()?;
-        Ok(())
+    /// This is initially the color support reported by the backend.
+    #[must_use]
+    pub fn color_support(&self) -> ColorSupport {
+        self.color_support
     }
 
-    fn cleanup_inner(&mut self) -> Result<(), Error<B::Error>> {
-        if let Some(backend) = self.backend.take() {
-            backend.reset()?.cleanup().map_err(Error::Stdio)?;
-        }
+    /// Set the colors that the terminal is able to display, overriding the color support reported
+    /// by the backend.
+    ///
+    /// Colors that aren't supported are converted to the closest supported color when they are
+    /// drawn. This only affects cells that change after it is called.
+    pub fn set_color_support(&mut self, color_support: ColorSupport) {
+        self.color_support = color_support;
+    }
 
-        if let Some(mut captured) = self.captured.take() {
-            io::copy(&mut captured, &mut io::stdout()).map_err(Error::Stdio)?;
-        }
+    /// Set the function used to find the closest supported color when converting unsupported
+    /// colors.
+    ///
+    /// Default is [`color_distance`].
+    pub fn set_color_distance(&mut self, distance: fn(Rgb, Rgb) -> u32) {
+        self.color_distance = distance;
+    }
 
-        Ok(())
+    /// Get how bold text in dark named colors is displayed.
+    ///
+    /// Default is [`BoldColors::Unchanged`].
+    #[must_use]
+    pub fn bold_colors(&self) -> BoldColors {
+        self.bold_colors
     }
-}
 
-impl<B: Backend> Drop for Terminal<B> {
-    fn drop(&mut self) {
-        let _ = self.cleanup_inner();
+    /// Set how bold text in dark named colors is displayed. This only affects cells that change
+    /// after it is called.
+    pub fn set_bold_colors(&mut self, bold_colors: BoldColors) {
+        self.bold_colors = bold_colors;
+    }
 
-        if !B::is_dummy() {
-            TERMINAL_EXISTS.store(false, Ordering::Release);
+    /// Get whether the terminal has a dark or light background, so that colors can be chosen to
+    /// be readable on it.
+    ///
+    /// The first call asks the terminal for its background color. Backends that have to wait for
+    /// the terminal to reply give a guess based on the environment at first, and the result is
+    /// updated when the reply is read while drawing. The result is remembered for later calls, and
+    /// is also used to blend [translucent colors](crate::Color::Rgba) drawn over the default
+    /// background.
+    ///
+    /// # Errors
+    ///
+    /// Fails if querying the backend fails.
+    pub fn background_kind(&mut self) -> Result<BackgroundKind, Error<B::Error>> {
+        if let Some(kind) = self.background_kind {
+            return Ok(kind);
         }
+        self.background_color = self.backend_mut().background_color()?;
+        let kind = self
+            .background_color
+            .map_or(BackgroundKind::Unknown, BackgroundKind::of);
+        self.background_kind = Some(kind);
+        Ok(kind)
     }
-}
 
-/// An error in Toon.
-#[derive(Debug)]
-#[non_exhaustive]
-pub enum Error<B> {
-    /// An error in the backend.
-    Backend(B),
-    /// An error overriding the standard I/O streams.
-    Stdio(io::Error),
-}
+    /// Copy text to the user's clipboard.
+    ///
+    /// This is done with an escape sequence, so it works even when the program is running over
+    /// SSH. Terminals that don't support it will ignore it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to the backend fails.
+    pub fn copy_to_clipboard(&mut self, text: &str) -> Result<(), Error<B::Error>> {
+        let backend = self.backend_mut();
+        backend.set_clipboard(text)?;
+        backend.flush()?;
+        Ok(())
+    }
 
-impl<B> From<B> for Error<B> {
-    fn from(e: B) -> Self {
-        Self::Backend(e)
+    /// Get how much mouse input the terminal captures.
+    #[must_use]
+    pub fn mouse_capture(&self) -> MouseCapture {
+        self.mouse_capture
     }
-}
-impl<B: Display> Display for Error<B> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Backend(e) => e.fmt(f),
-            Self::Stdio(e) => e.fmt(f),
+
+    /// Set how much mouse input the terminal captures.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to the backend fails.
+    pub fn set_mouse_capture(
+        &mut self,
+        mouse_capture: MouseCapture,
+    ) -> Result<(), Error<B::Error>> {
+        let backend = self.backend_mut();
+        backend.set_mouse_capture(mouse_capture)?;
+        backend.flush()?;
+        self.mouse_capture = mouse_capture;
+        // Releases and movement won't be reported if the mouse stops being captured.
+        if mouse_capture == MouseCapture::None {
+            self.mouse = None;
+            self.hover = None;
         }
+        Ok(())
     }
-}
-impl<B: StdError + 'static> StdError for Error<B> {
-    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+
+    /// Get the modifiers that are currently held down, as of the last key or mouse input.
+    ///
+    /// This is useful for showing hints that depend on the modifiers, like "hold Shift to
+    /// select". Terminals don't report modifier keys being pressed or released on their own, so
+    /// this only changes when another key is pressed, repeated or released (releases are only
+    /// reported by terminals supporting the kitty keyboard protocol) or when the mouse is used.
+    #[must_use]
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Give a synthetic input to the element being drawn, as if it came from the terminal.
+    ///
+    /// If no element is being drawn, the input is given to the next element that is. Key inputs
+    /// are turned into actions by the [keymap](Self::set_keymap) like real keys are. To inject
+    /// inputs from other tasks or threads, use an [`InputInjector`].
+    pub fn inject_input(&self, input: Input) {
+        self.injector.inject(input);
+    }
+
+    /// Get a handle that injects inputs into the terminal, which can be cloned and sent to other
+    /// tasks or threads.
+    #[must_use]
+    pub fn input_injector(&self) -> InputInjector {
+        self.injector.clone()
+    }
+
+    /// Catch signals sent to the process, giving them to the element being drawn as
+    /// [`Input::Signal`]s.
+    ///
+    /// This lets applications save their state and exit cleanly when they are interrupted,
+    /// terminated or their terminal is closed, in the same place they handle other inputs. Once
+    /// this is called `SIGINT`, `SIGTERM` and `SIGHUP` no longer stop the process, so the
+    /// application must exit by itself when it receives them. `SIGCONT` is also reported, so that
+    /// applications can tell when they have been resumed after being suspended. When it is caught
+    /// the terminal is set up again and redrawn, since the shell may have taken it out of raw mode
+    /// and the alternate screen while the process was suspended.
+    ///
+    /// Signals that are caught while no element is being drawn are given to the next element that
+    /// is. The signals stop being caught and their previous handlers are restored when the
+    /// terminal is dropped.
+    ///
+    /// This does nothing if the backend is a dummy or signals are already being caught.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the signal handlers could not be installed, or if another terminal is already
+    /// catching signals.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "crossterm")]
+    /// # {
+    /// use toon::{ElementExt, Signal, Terminal};
+    ///
+    /// let mut terminal = Terminal::new(toon::Crossterm::default())?;
+    /// terminal.catch_signals()?;
+    ///
+    /// let element = toon::span("Press q to quit")
+    ///     .on(('q', Signal::Terminate, Signal::Hangup), |_| ());
+    /// futures_lite::future::block_on(terminal.draw(element))?;
+    /// terminal.cleanup()?;
+    /// # }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn catch_signals(&mut self) -> io::Result<()> {
+        if B::is_dummy() || self.signals.is_some() {
+            return Ok(());
+        }
+
+        let injected = Arc::clone(&self.injector.0);
+        self.signals = Some(crate::signals::Catcher::new(move |signal| {
+            let mut injected = injected.lock().unwrap();
+            injected.signals.push_back(signal);
+            if let Some(waker) = injected.waker.take() {
+                waker.wake();
+            }
+        })?);
+        Ok(())
+    }
+
+    /// Get the keymap that turns keys into actions.
+    #[must_use]
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// Set the keymap that turns keys into actions.
+    ///
+    /// When a key bound to actions is pressed, each action is given to the element as an
+    /// [`Input::Action`] until one stops propagation, and only if none of them do is the key
+    /// itself given to the element. By default the keymap is empty.
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    /// Ring the terminal's bell.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to the backend fails.
+    pub fn bell(&mut self) -> Result<(), Error<B::Error>> {
+        let backend = self.backend_mut();
+        backend.bell()?;
+        backend.flush()?;
+        Ok(())
+    }
+
+    /// Show a desktop notification, for example to tell the user that a long-running task has
+    /// completed while the terminal is in the background.
+    ///
+    /// This is done with an escape sequence, so it works even when the program is running over
+    /// SSH. Terminals that don't support it will ignore it, so you may want to
+    /// [ring the bell](Self::bell) as well.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to the backend fails.
+    pub fn notify(&mut self, title: &str, body: &str) -> Result<(), Error<B::Error>> {
+        let backend = self.backend_mut();
+        backend.notify(title, body)?;
+        backend.flush()?;
+        Ok(())
+    }
+
+    /// Get whether the terminal is able to display images.
+    ///
+    /// This is initially whether the backend reports that it supports graphics. If it doesn't,
+    /// images are not drawn and the characters underneath them are displayed instead.
+    #[must_use]
+    pub fn supports_graphics(&self) -> bool {
+        self.supports_graphics
+    }
+
+    /// Set whether the terminal is able to display images, overriding the support reported by the
+    /// backend.
+    ///
+    /// This only affects frames drawn after it is called.
+    pub fn set_supports_graphics(&mut self, supports_graphics: bool) {
+        self.supports_graphics = supports_graphics;
+    }
+
+    /// Get a reference to the terminal's backend.
+    #[must_use]
+    pub fn backend(&self) -> &B::Bound {
+        self.backend.as_ref().unwrap()
+    }
+
+    /// Get a mutable reference to the terminal's backend.
+    #[must_use]
+    pub fn backend_mut(&mut self) -> &mut B::Bound {
+        self.backend.as_mut().unwrap()
+    }
+
+    /// Take the captured standard output and standard error from the terminal.
+    ///
+    /// The terminal will no longer print the captured data to the standard output and standard
+    /// error when the program terminates.
+    ///
+    /// This will return [`None`] if the backend is a dummy backend, the terminal was created with
+    /// [`new_without_capture`](Self::new_without_capture) or the captured stdio has already been
+    /// taken.
+    pub fn take_captured(&mut self) -> Option<Captured> {
+        self.captured.take()
+    }
+
+    /// Write the captured standard output and standard error to a file as it arrives, instead of
+    /// keeping it to print when the terminal is dropped.
+    ///
+    /// This is a shortcut for [`take_captured`](Self::take_captured) followed by
+    /// [`Captured::log_to_file`]; see that for details. If the terminal has no captured stdio, this
+    /// does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file could not be opened.
+    pub fn log_captured_to_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        max_size: Option<u64>,
+    ) -> io::Result<()> {
+        match self.take_captured() {
+            Some(captured) => captured.log_to_file(path, max_size),
+            None => Ok(()),
+        }
+    }
+
+    /// Take the captured standard output and standard error from the terminal as an asynchronous
+    /// stream of lines.
+    ///
+    /// This is a shortcut for [`take_captured`](Self::take_captured) followed by
+    /// [`Captured::into_lines`].
+    #[cfg(feature = "stream")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stream")))]
+    pub fn captured_stream(&mut self) -> Option<CapturedLines> {
+        self.take_captured().map(Captured::into_lines)
+    }
+
+    /// Clean up the terminal.
+    ///
+    /// This will be called in the destructor too, but use this if you want to handle errors
+    /// instead of ignoring them.
+    ///
+    /// # Errors
+    ///
+    /// Fails if cleaning up the backend fails.
+    pub fn cleanup(mut self) -> Result<(), Error<B::Error>> {
+        self.cleanup_inner()?;
+        Ok(())
+    }
+
+    fn cleanup_inner(&mut self) -> Result<(), Error<B::Error>> {
+        if let Some(mut backend) = self.backend.take() {
+            // Reset the backend even if restoring the title fails.
+            let restored = Self::restore_title(&mut backend, self.original_title.as_deref());
+            backend.reset()?.cleanup().map_err(Error::Stdio)?;
+            restored?;
+        }
+
+        // The two streams are in separate pipes, so their relative order can't be recovered.
+        if let Some(mut captured) = self.captured.take() {
+            io::copy(&mut captured.stdout, &mut io::stdout()).map_err(Error::Stdio)?;
+            io::copy(&mut captured.stderr, &mut io::stderr()).map_err(Error::Stdio)?;
+        }
+
+        Ok(())
+    }
+
+    /// Capture the mouse, hide the cursor at the top left and reset the style, bringing the
+    /// backend to the state the terminal expects it to start in.
+    fn reset_state(backend: &mut B::Bound, mouse_capture: MouseCapture) -> Result<(), B::Error> {
+        backend.set_mouse_capture(mouse_capture)?;
+        backend.hide_cursor()?;
+        backend.set_cursor_pos(Vec2::default())?;
+        backend.set_foreground(Color::Default)?;
+        backend.set_background(Color::Default)?;
+        backend.set_intensity(Intensity::Normal)?;
+        backend.set_italic(false)?;
+        backend.set_underlined(false)?;
+        backend.set_underline_color(Color::Default)?;
+        backend.set_blinking(false)?;
+        backend.set_crossed_out(false)?;
+        backend.set_reversed(false)?;
+        backend.set_hidden(false)?;
+        backend.set_overlined(false)
+    }
+
+    /// Set the terminal up again after the process was resumed from being suspended, since the
+    /// shell may have reset it in the meantime. The next frame is drawn from scratch.
+    fn resume(&mut self) -> Result<(), Error<B::Error>> {
+        let backend = self.backend.as_mut().unwrap();
+        backend.resume()?;
+        Self::reset_state(backend, self.mouse_capture)?;
+        backend.set_title(&self.title)?;
+
+        // The screen has been cleared.
+        self.old_buffer.reset();
+        self.drawn_images.clear();
+        self.cursor_pos = Vec2::default();
+        self.style = Style::default();
+        Ok(())
+    }
+
+    /// Restore the title the terminal had before it was created from the title stack, falling
+    /// back to setting it directly for terminals without a title stack.
+    fn restore_title(backend: &mut B::Bound, original: Option<&str>) -> Result<(), B::Error> {
+        backend.pop_title()?;
+        if let Some(original) = original {
+            backend.set_title(original)?;
+        }
+        Ok(())
+    }
+}
+
+impl<B: Backend> Drop for Terminal<B> {
+    fn drop(&mut self) {
+        let _ = self.cleanup_inner();
+
+        if !B::is_dummy() {
+            TERMINAL_EXISTS.store(false, Ordering::Release);
+        }
+    }
+}
+
+fn covered(images: &[ImageArea], pos: Vec2<u16>) -> bool {
+    images.iter().any(|image| image.contains(pos))
+}
+
+/// Something that happened while the terminal was waiting for an event.
+enum Incoming {
+    Terminal(TerminalEvent),
+    Injected(Input),
+}
+
+/// A handle that gives synthetic inputs to the elements drawn by a [`Terminal`], created by
+/// [`Terminal::input_injector`].
+///
+/// Injected inputs are handled just like inputs from the terminal, so this is useful for scripted
+/// tutorials, automated testing and bridging other sources of input like remote control into the
+/// app.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "crossterm")]
+/// # {
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// use toon::{ElementExt, Input, KeyPress, Terminal};
+///
+/// let mut terminal = Terminal::new(toon::Crossterm::default())?;
+///
+/// let injector = terminal.input_injector();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_secs(5));
+///     injector.inject(Input::Key(KeyPress::from('q')));
+/// });
+///
+/// // Quits after five seconds if the user doesn't press `q` first.
+/// let element = toon::span("Press q to quit").on('q', |_| ());
+/// futures_lite::future::block_on(terminal.draw(element))?;
+/// terminal.cleanup()?;
+/// # }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct InputInjector(Arc<Mutex<Injected>>);
+
+impl InputInjector {
+    /// Give a synthetic input to the element being drawn by the terminal, like
+    /// [`Terminal::inject_input`].
+    pub fn inject(&self, input: Input) {
+        let mut injected = self.0.lock().unwrap();
+        injected.inputs.push_back(input);
+        if let Some(waker) = injected.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Inputs that have been injected and signals that have been caught but not yet handled.
+#[derive(Debug, Default)]
+struct Injected {
+    inputs: VecDeque<Input>,
+    signals: VecDeque<Signal>,
+    waker: Option<Waker>,
+}
+
+/// Statistics about a frame drawn by a [`Terminal`], returned by [`Terminal::stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct FrameStats {
+    /// The total number of frames drawn by the terminal, including this one.
+    pub frames: u64,
+    /// The number of cells that were changed since the previous frame.
+    pub cells_changed: usize,
+    /// The number of operations issued to the backend, including the final flush.
+    pub operations: usize,
+    /// How long the element took to draw itself.
+    pub draw_time: Duration,
+    /// How long it took to diff the frame with the previous one and send the changes to the
+    /// backend.
+    pub diff_time: Duration,
+    /// How long it took to flush the backend.
+    pub flush_time: Duration,
+    /// How long it has been since the previous frame started being drawn, or zero for the first
+    /// frame.
+    pub frame_interval: Duration,
+    /// How long it took from receiving the input that caused this frame to finishing drawing it,
+    /// if the frame was drawn in response to events returned from the previous one.
+    pub input_latency: Option<Duration>,
+}
+
+/// An error in Toon.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error<B> {
+    /// An error in the backend.
+    Backend(B),
+    /// An error overriding the standard I/O streams.
+    Stdio(io::Error),
+}
+
+impl<B> From<B> for Error<B> {
+    fn from(e: B) -> Self {
+        Self::Backend(e)
+    }
+}
+impl<B: Display> Display for Error<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Backend(e) => e.fmt(f),
+            Self::Stdio(e) => e.fmt(f),
+        }
+    }
+}
+impl<B: StdError + 'static> StdError for Error<B> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             Self::Backend(e) => Some(e),
             Self::Stdio(e) => Some(e),
@@ -384,54 +1419,454 @@ impl<B: StdError + 'static> StdError for Error<B> {
     }
 }
 
-/// Standard output and standard error that has been captured by Toon.
+/// Standard output and standard error that have been captured by Toon.
+///
+/// The two streams are captured separately, so that they can be displayed differently or only
+/// one of them can be shown. As a consequence the order in which data was written to the two
+/// streams isn't preserved, only the order within each stream.
+///
+/// Note that if one of the streams is dropped, writing to it will fail (making [`println!`] or
+/// [`eprintln!`] panic), so to discard a stream keep reading from it and throw the data away.
+#[derive(Debug)]
+pub struct Captured {
+    /// The captured standard output.
+    pub stdout: CapturedStream,
+    /// The captured standard error.
+    pub stderr: CapturedStream,
+}
+
+/// The number of lines that a [`CapturedLines`] buffers before the threads reading the captured
+/// data wait for them to be read.
+#[cfg(feature = "stream")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stream")))]
+pub const CAPTURED_LINES_CAPACITY: usize = 1024;
+
+impl Captured {
+    /// Convert this into an asynchronous stream of the lines written to the standard output and
+    /// standard error, tagged with the stream they were written to.
+    ///
+    /// This spawns two threads which read the captured data, so it works with any async runtime.
+    /// Lines are decoded lossily as UTF-8 and have their line terminators removed. The stream will
+    /// terminate only when the terminal where the [`Captured`] came from is destroyed.
+    ///
+    /// At most [`CAPTURED_LINES_CAPACITY`] lines are buffered. If the stream isn't read quickly
+    /// enough the threads stop reading, so writing to the standard output and standard error blocks
+    /// once the pipes fill up instead of using more and more memory.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stream")))]
+    #[must_use]
+    pub fn into_lines(self) -> CapturedLines {
+        let (sender, receiver) = async_channel::bounded(CAPTURED_LINES_CAPACITY);
+
+        self.stdout.send_lines(StdStream::Stdout, sender.clone());
+        self.stderr.send_lines(StdStream::Stderr, sender);
+
+        CapturedLines(receiver)
+    }
+
+    /// Convert both streams into non-blocking readers that can be read asynchronously with
+    /// [`async-io`](https://crates.io/crates/async-io), returning the standard output and standard
+    /// error in that order.
+    ///
+    /// This is a shortcut for calling [`CapturedStream::into_async`] on both streams; see that for
+    /// details.
+    ///
+    /// # Errors
+    ///
+    /// Fails if either pipe could not be made non-blocking or registered with the reactor.
+    #[cfg(all(unix, feature = "async-io"))]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(all(unix, feature = "async-io"))))]
+    #[allow(clippy::type_complexity)]
+    pub fn into_async(
+        self,
+    ) -> io::Result<(
+        async_io::Async<CapturedStream>,
+        async_io::Async<CapturedStream>,
+    )> {
+        Ok((self.stdout.into_async()?, self.stderr.into_async()?))
+    }
+
+    /// Write the standard output and standard error to a file as they are captured.
+    ///
+    /// This spawns two threads which read the captured data, so that noisy programs aren't blocked
+    /// by the pipes filling up. Data is written a line at a time, so lines written to the two
+    /// streams aren't mixed together. The file is appended to if it already exists.
+    ///
+    /// If a maximum size in bytes is given, once writing a line would make the file larger than it
+    /// the file is rotated: it is renamed by adding `.1` to the end of its name, replacing any file
+    /// that was already there, and a new file is started. If writing to the file fails the data is
+    /// discarded.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file could not be opened.
+    pub fn log_to_file(self, path: impl AsRef<Path>, max_size: Option<u64>) -> io::Result<()> {
+        let file = Arc::new(Mutex::new(LogFile::open(
+            path.as_ref().to_owned(),
+            max_size,
+        )?));
+
+        for stream in vec![self.stdout, self.stderr] {
+            let file = Arc::clone(&file);
+            std::thread::spawn(move || {
+                let mut reader = io::BufReader::new(stream);
+                let mut line = Vec::new();
+                // Keep reading even if writing fails so that writes to stdio don't block.
+                while matches!(reader.read_until(b'\n', &mut line), Ok(len) if len > 0) {
+                    let _ = file.lock().unwrap().write_line(&line);
+                    line.clear();
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// A file that captured stdio is logged to, used by [`Captured::log_to_file`].
+#[derive(Debug)]
+struct LogFile {
+    path: PathBuf,
+    /// Only [`None`] while the file is being rotated.
+    file: Option<File>,
+    /// The length of the file in bytes.
+    len: u64,
+    /// The length at which the file is rotated.
+    max_size: Option<u64>,
+}
+
+impl LogFile {
+    fn open(path: PathBuf, max_size: Option<u64>) -> io::Result<Self> {
+        let mut this = Self {
+            path,
+            file: None,
+            len: 0,
+            max_size,
+        };
+        this.reopen()?;
+        Ok(this)
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.len = file.metadata()?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let len = line.len() as u64;
+        if self
+            .max_size
+            .map_or(false, |max_size| self.len > 0 && self.len + len > max_size)
+        {
+            let mut rotated = self.path.clone().into_os_string();
+            rotated.push(".1");
+
+            // Windows doesn't allow renaming open files.
+            self.file = None;
+            let renamed = fs::rename(&self.path, rotated);
+            self.reopen()?;
+            renamed?;
+        }
+
+        if self.file.is_none() {
+            self.reopen()?;
+        }
+        self.file.as_mut().unwrap().write_all(line)?;
+        self.len += len;
+        Ok(())
+    }
+}
+
+/// Which standard stream captured data was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StdStream {
+    /// The standard output.
+    Stdout,
+    /// The standard error.
+    Stderr,
+}
+
+/// One of the standard streams that has been captured by Toon, available in [`Captured`].
+///
+/// Note that this is a synchronous reader. On Unix-like platforms it can be made asynchronous
+/// without spawning a thread by enabling the `async-io` feature and using
+/// [`into_async`](Self::into_async), but that is not possible on Windows (as wepoll does not
+/// support pipes). So if you want to use it asynchronously and cross-platform you'll have to wrap
+/// it in an [`Unblock`](https://docs.rs/blocking/1/blocking/struct.Unblock.html) or similar type,
+/// enable the `stream` feature and use [`Captured::into_lines`], or enable the `tokio` feature and
+/// use [`into_tokio`](Self::into_tokio).
+#[derive(Debug)]
+pub struct CapturedStream(PipeReader);
+
+impl CapturedStream {
+    /// Convert this into a non-blocking reader that can be read asynchronously with
+    /// [`async-io`](https://crates.io/crates/async-io), which works with any async runtime.
+    ///
+    /// Unlike [`Captured::into_lines`] this doesn't spawn a thread, so it is the cheapest way to
+    /// show captured output in the user interface. It is only available on Unix-like platforms, as
+    /// pipes can't be polled on Windows.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pipe could not be made non-blocking or registered with the reactor.
+    #[cfg(all(unix, feature = "async-io"))]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(all(unix, feature = "async-io"))))]
+    pub fn into_async(self) -> io::Result<async_io::Async<Self>> {
+        async_io::Async::new(self)
+    }
+
+    /// Convert this into a reader that can be read asynchronously with
+    /// [Tokio](https://crates.io/crates/tokio).
+    ///
+    /// On Unix-like platforms the pipe is registered with Tokio's reactor, so this must be called
+    /// from within a Tokio runtime. Pipes can't be polled on Windows, so there this spawns a thread
+    /// which reads the captured data instead. The reader will reach its end only when the terminal
+    /// where the [`Captured`] came from is destroyed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pipe could not be made non-blocking or registered with the reactor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime on a Unix-like platform.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub fn into_tokio(self) -> io::Result<TokioCapturedStream> {
+        #[cfg(unix)]
+        {
+            let file = unsafe { File::from_raw_fd(self.into_raw_fd()) };
+            Ok(TokioCapturedStream {
+                pipe: tokio_crate::net::unix::pipe::Receiver::from_file(file)?,
+            })
+        }
+
+        #[cfg(windows)]
+        {
+            let mut this = self;
+            let (sender, receiver) = tokio_crate::sync::mpsc::channel(4);
+
+            std::thread::spawn(move || {
+                let mut buf = [0; 1024];
+                loop {
+                    let data = match this.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(len) => Ok(buf[..len].to_vec()),
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => Err(e),
+                    };
+                    let failed = data.is_err();
+                    if sender.blocking_send(data).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+
+            Ok(TokioCapturedStream {
+                receiver,
+                buffer: Vec::new(),
+                pos: 0,
+            })
+        }
+    }
+
+    /// Spawn a thread which sends the lines of this stream to a channel.
+    #[cfg(feature = "stream")]
+    fn send_lines(self, stream: StdStream, sender: async_channel::Sender<(StdStream, String)>) {
+        std::thread::spawn(move || {
+            for line in io::BufReader::new(self).split(b'\n') {
+                let mut line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                if sender
+                    .send_blocking((stream, String::from_utf8_lossy(&line).into_owned()))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// A captured standard stream that implements Tokio's
+/// [`AsyncRead`](tokio_crate::io::AsyncRead), created by [`CapturedStream::into_tokio`].
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct TokioCapturedStream {
+    /// The pipe, registered with Tokio's reactor.
+    #[cfg(unix)]
+    pipe: tokio_crate::net::unix::pipe::Receiver,
+    /// Chunks of data read by the thread reading the pipe.
+    #[cfg(windows)]
+    receiver: tokio_crate::sync::mpsc::Receiver<io::Result<Vec<u8>>>,
+    /// The last chunk of data received.
+    #[cfg(windows)]
+    buffer: Vec<u8>,
+    /// How much of the buffer has been read.
+    #[cfg(windows)]
+    pos: usize,
+}
+
+#[cfg(feature = "tokio")]
+impl tokio_crate::io::AsyncRead for TokioCapturedStream {
+    #[cfg(unix)]
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio_crate::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.pipe).poll_read(cx, buf)
+    }
+
+    #[cfg(windows)]
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio_crate::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+
+        if this.pos == this.buffer.len() {
+            match this.receiver.poll_recv(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+                Poll::Ready(Some(Ok(data))) => {
+                    this.buffer = data;
+                    this.pos = 0;
+                }
+            }
+        }
+
+        let len = min(buf.remaining(), this.buffer.len() - this.pos);
+        buf.put_slice(&this.buffer[this.pos..this.pos + len]);
+        this.pos += len;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A stream of the events produced by drawing the value of a watch channel, created by
+/// [`Terminal::watch`].
+///
+/// Dropping the stream stops the terminal reading input, after which the terminal can be used
+/// again.
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+pub struct WatchEvents<'a, B: Backend, Event> {
+    /// Draws frames and queues their events until drawing fails, or [`None`] once it has.
+    frames: Option<Pin<Box<dyn Future<Output = Error<B::Error>> + 'a>>>,
+    /// Events that have been produced but not yet taken from the stream.
+    events: Rc<RefCell<VecDeque<Event>>>,
+}
+
+#[cfg(feature = "tokio")]
+impl<B: Backend, Event> fmt::Debug for WatchEvents<'_, B, Event> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchEvents")
+            .field("ended", &self.frames.is_none())
+            .field("queued", &self.events.borrow().len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<B: Backend, Event> futures_core::Stream for WatchEvents<'_, B, Event> {
+    type Item = Result<Event, Error<B::Error>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(event) = self.events.borrow_mut().pop_front() {
+            return Poll::Ready(Some(Ok(event)));
+        }
+
+        let frames = match &mut self.frames {
+            Some(frames) => frames,
+            None => return Poll::Ready(None),
+        };
+        if let Poll::Ready(e) = frames.as_mut().poll(cx) {
+            self.frames = None;
+            return Poll::Ready(Some(Err(e)));
+        }
+
+        match self.events.borrow_mut().pop_front() {
+            Some(event) => Poll::Ready(Some(Ok(event))),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// An asynchronous stream of lines written to the standard output and standard error, created by
+/// [`Captured::into_lines`] and [`Terminal::captured_stream`].
 ///
-/// Note that this is a synchronous reader. It is also not able to be made asynchronous by
-/// [`async-io`](https://crates.io/crates/async-io) on Windows (as wepoll does not support pipes).
-/// So if you want to use it asynchronously and cross-platform you'll have to wrap it in an
-/// [`Unblock`](https://docs.rs/blocking/1/blocking/struct.Unblock.html) or similar type.
+/// Each line is yielded with the stream it was written to. Lines are in the order they were
+/// written within each stream, but lines written to different streams at around the same time
+/// may be reordered.
+#[cfg(feature = "stream")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stream")))]
 #[derive(Debug)]
-pub struct Captured(PipeReader);
+pub struct CapturedLines(async_channel::Receiver<(StdStream, String)>);
+
+#[cfg(feature = "stream")]
+impl futures_core::Stream for CapturedLines {
+    type Item = (StdStream, String);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        futures_core::Stream::poll_next(Pin::new(&mut self.0), cx)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        futures_core::Stream::size_hint(&self.0)
+    }
+}
 
 #[cfg(unix)]
-impl AsRawFd for Captured {
+impl AsRawFd for CapturedStream {
     fn as_raw_fd(&self) -> RawFd {
         self.0.as_raw_fd()
     }
 }
 #[cfg(unix)]
-impl FromRawFd for Captured {
+impl FromRawFd for CapturedStream {
     unsafe fn from_raw_fd(fd: RawFd) -> Self {
         Self(PipeReader::from_raw_fd(fd))
     }
 }
 #[cfg(unix)]
-impl IntoRawFd for Captured {
+impl IntoRawFd for CapturedStream {
     fn into_raw_fd(self) -> RawFd {
         self.0.into_raw_fd()
     }
 }
 
 #[cfg(windows)]
-impl AsRawHandle for Captured {
+impl AsRawHandle for CapturedStream {
     fn as_raw_handle(&self) -> RawHandle {
         self.0.as_raw_handle()
     }
 }
 #[cfg(windows)]
-impl FromRawHandle for Captured {
+impl FromRawHandle for CapturedStream {
     unsafe fn from_raw_handle(handle: RawHandle) -> Self {
         Self(PipeReader::from_raw_handle(handle))
     }
 }
 #[cfg(windows)]
-impl IntoRawHandle for Captured {
+impl IntoRawHandle for CapturedStream {
     fn into_raw_handle(self) -> RawHandle {
         self.0.into_raw_handle()
     }
 }
 
-impl Read for Captured {
+impl Read for CapturedStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.0.read(buf)
     }
@@ -439,7 +1874,7 @@ impl Read for Captured {
         self.0.read_vectored(bufs)
     }
 }
-impl<'a> Read for &'a Captured {
+impl<'a> Read for &'a CapturedStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         (&self.0).read(buf)
     }
@@ -448,6 +1883,467 @@ impl<'a> Read for &'a Captured {
     }
 }
 
+#[cfg(test)]
+#[test]
+fn test_log_file() {
+    let path = std::env::temp_dir().join(format!("toon-test-log-{}", std::process::id()));
+    let mut rotated = path.clone().into_os_string();
+    rotated.push(".1");
+    let _ = fs::remove_file(&path);
+
+    let mut file = LogFile::open(path.clone(), Some(10)).unwrap();
+    file.write_line(b"hello\n").unwrap();
+    file.write_line(b"abc\n").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"hello\nabc\n");
+
+    file.write_line(b"world\n").unwrap();
+    // Lines longer than the maximum size are still written.
+    file.write_line(b"a long line\n").unwrap();
+    assert_eq!(fs::read(&rotated).unwrap(), b"world\n");
+    assert_eq!(fs::read(&path).unwrap(), b"a long line\n");
+    drop(file);
+
+    let mut file = LogFile::open(path.clone(), None).unwrap();
+    file.write_line(b"more\n").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"a long line\nmore\n");
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&rotated).unwrap();
+}
+
+#[cfg(all(test, unix, feature = "async-io"))]
+#[test]
+fn test_async_captured_stream() {
+    use futures_lite::AsyncReadExt;
+
+    let (stdout, mut stdout_writer) = os_pipe::pipe().unwrap();
+    let (stderr, mut stderr_writer) = os_pipe::pipe().unwrap();
+    let captured = Captured {
+        stdout: CapturedStream(stdout),
+        stderr: CapturedStream(stderr),
+    };
+    let (mut stdout, mut stderr) = captured.into_async().unwrap();
+    stdout_writer.write_all(b"hello").unwrap();
+    stderr_writer.write_all(b"world").unwrap();
+    drop((stdout_writer, stderr_writer));
+
+    let mut data = Vec::new();
+    async_io::block_on(stdout.read_to_end(&mut data)).unwrap();
+    assert_eq!(data, b"hello");
+    data.clear();
+    async_io::block_on(stderr.read_to_end(&mut data)).unwrap();
+    assert_eq!(data, b"world");
+}
+
+#[cfg(all(test, feature = "tokio"))]
+#[test]
+fn test_tokio_captured_stream() {
+    use tokio_crate::io::AsyncReadExt;
+
+    let runtime = tokio_crate::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+
+    let (reader, mut writer) = os_pipe::pipe().unwrap();
+    writer.write_all(b"hello").unwrap();
+    drop(writer);
+
+    let data = runtime.block_on(async {
+        let mut stream = CapturedStream(reader).into_tokio().unwrap();
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).await.unwrap();
+        data
+    });
+    assert_eq!(data, b"hello");
+}
+
+#[cfg(all(test, feature = "tokio"))]
+#[test]
+fn test_watch() {
+    use crate::backend::TerminalEvent;
+    use crate::{ElementExt, KeyPress};
+    use futures_lite::StreamExt;
+
+    let mut terminal = Terminal::new(crate::backend::Dummy::new(Vec2::new(3, 1))).unwrap();
+    for _ in 0..2 {
+        terminal
+            .backend_mut()
+            .events
+            .push_back(TerminalEvent::Key(KeyPress::from('a')));
+    }
+
+    let (sender, receiver) = tokio_crate::sync::watch::channel(1);
+    let mut events = terminal.watch(receiver, |&n| crate::span(n).on('a', move |_| n).boxed());
+    futures_lite::future::block_on(async {
+        assert_eq!(events.next().await.unwrap().unwrap(), 1);
+        sender.send(2).unwrap();
+        assert_eq!(events.next().await.unwrap().unwrap(), 2);
+    });
+    drop(events);
+
+    crate::assert_grid_eq!(terminal.current_frame(), ["2  "]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_title_stack() {
+    use crate::backend::{Operation, TerminalEvent};
+    use crate::{ElementExt, KeyPress};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(4, 4));
+    backend.title = "Shell".to_owned();
+
+    let mut terminal = Terminal::new(backend).unwrap();
+    assert_eq!(terminal.backend().operations[0], Operation::PushTitle);
+    assert_eq!(terminal.backend().title_stack, ["Shell"]);
+
+    terminal
+        .backend_mut()
+        .events
+        .push_back(TerminalEvent::Key(KeyPress::from('q')));
+    futures_lite::future::block_on(terminal.draw(crate::empty().on('q', |_| ()))).unwrap();
+    assert_eq!(terminal.backend().title, "Toon App");
+
+    // The title is popped from the stack when the terminal is cleaned up.
+    let original = terminal.original_title.clone();
+    let backend = terminal.backend_mut();
+    Terminal::<crate::backend::Dummy>::restore_title(backend, original.as_deref()).unwrap();
+    assert!(backend.operations.contains(&Operation::PopTitle));
+    assert_eq!(backend.title, "Shell");
+
+    // Terminals that ignore the title stack have their title set back directly.
+    backend.set_title("Toon App").unwrap();
+    backend.title_stack.clear();
+    Terminal::<crate::backend::Dummy>::restore_title(backend, original.as_deref()).unwrap();
+    assert_eq!(backend.title, "Shell");
+}
+
+#[cfg(test)]
+#[test]
+fn test_mouse_capture() {
+    use crate::backend::Dummy;
+
+    let terminal = Terminal::new(Dummy::new(Vec2::new(4, 4))).unwrap();
+    assert_eq!(terminal.mouse_capture(), MouseCapture::Drag);
+    assert_eq!(terminal.backend().mouse_capture, MouseCapture::Drag);
+
+    let backend = Dummy::new(Vec2::new(4, 4));
+    let mut terminal = Terminal::with_mouse_capture(backend, MouseCapture::Click).unwrap();
+    assert_eq!(terminal.backend().mouse_capture, MouseCapture::Click);
+
+    terminal.set_mouse_capture(MouseCapture::Motion).unwrap();
+    assert_eq!(terminal.mouse_capture(), MouseCapture::Motion);
+    assert_eq!(terminal.backend().mouse_capture, MouseCapture::Motion);
+}
+
+#[cfg(test)]
+#[test]
+fn test_background_kind() {
+    use crate::backend::{Dummy, TerminalEvent};
+    use crate::{ElementExt, KeyPress};
+
+    let mut terminal = Terminal::new(Dummy::new(Vec2::new(4, 4))).unwrap();
+    assert_eq!(terminal.background_kind().unwrap(), BackgroundKind::Unknown);
+
+    let mut backend = Dummy::new(Vec2::new(4, 4));
+    backend.background_color = Some(Rgb::new(0xFF, 0xFF, 0xFF));
+    let mut terminal = Terminal::new(backend).unwrap();
+    assert_eq!(terminal.background_kind().unwrap(), BackgroundKind::Light);
+
+    // The result is remembered.
+    terminal.backend_mut().background_color = None;
+    assert_eq!(terminal.background_kind().unwrap(), BackgroundKind::Light);
+
+    // It is updated when the terminal replies to a query later.
+    let backend = terminal.backend_mut();
+    backend
+        .events
+        .push_back(TerminalEvent::BackgroundColor(Rgb::new(0, 0, 0)));
+    backend
+        .events
+        .push_back(TerminalEvent::Key(KeyPress::from('q')));
+    futures_lite::future::block_on(terminal.draw(crate::empty().on('q', |_| ()))).unwrap();
+    assert_eq!(terminal.background_kind().unwrap(), BackgroundKind::Dark);
+}
+
+#[cfg(test)]
+#[test]
+fn test_draw_with_size() {
+    use crate::backend::TerminalEvent;
+    use crate::{ElementExt, KeyPress};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(5, 2));
+    backend.events.push_back(TerminalEvent::Key(KeyPress::from('q')));
+
+    let mut terminal = Terminal::new(backend).unwrap();
+    assert_eq!(terminal.size(), Vec2::new(5, 2));
+
+    let events = futures_lite::future::block_on(terminal.draw_with_size(
+        crate::span("Hello").tile((0, 0)).on('q', |_| ()),
+        (3, 1),
+    ))
+    .unwrap();
+    assert_eq!(events, [()]);
+    crate::assert_grid_eq!(terminal.backend().buffer.grid, ["Hel  ", "     "]);
+    assert_eq!(terminal.current_frame(), &terminal.backend().buffer.grid);
+}
+
+#[test]
+fn test_stats() {
+    use crate::backend::TerminalEvent;
+    use crate::{ElementExt, KeyPress};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(5, 2));
+    backend.events.push_back(TerminalEvent::Key(KeyPress::from('q')));
+
+    let mut terminal = Terminal::new(backend).unwrap();
+    futures_lite::future::block_on(terminal.draw(crate::span("Hel").on('q', |_| ()))).unwrap();
+
+    let stats = terminal.stats();
+    assert_eq!(stats.frames, 1);
+    assert_eq!(stats.cells_changed, 3);
+    assert_eq!(stats.frame_interval, Duration::default());
+    assert_eq!(stats.input_latency, None);
+
+    terminal.backend().clock.advance(Duration::from_millis(16));
+    terminal
+        .backend_mut()
+        .events
+        .push_back(TerminalEvent::Key(KeyPress::from('q')));
+    futures_lite::future::block_on(terminal.draw(crate::span("Hi").on('q', |_| ()))).unwrap();
+
+    let stats = terminal.stats();
+    assert_eq!(stats.frames, 2);
+    assert_eq!(stats.frame_interval, Duration::from_millis(16));
+    assert_eq!(stats.input_latency, Some(Duration::from_millis(16)));
+}
+
+#[test]
+fn test_keymap() {
+    use crate::backend::TerminalEvent;
+    use crate::{ElementExt, KeyPress};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(5, 2));
+    backend.events.push_back(TerminalEvent::Key(KeyPress::from('q')));
+    backend.events.push_back(TerminalEvent::Key(KeyPress::from('x')));
+
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal.set_keymap(Keymap::new().bind("quit", 'q').bind("quit", 'x'));
+
+    let element = crate::empty()
+        .on('q', |_| "key")
+        .on_action("quit", |_| "quit")
+        .on_passive('q', |_| "passive key");
+    let events = futures_lite::future::block_on(terminal.draw(element)).unwrap();
+    assert_eq!(events, ["quit"]);
+
+    // Keys whose actions aren't handled are given to the element.
+    let element = crate::empty().on('x', |_| "key");
+    let events = futures_lite::future::block_on(terminal.draw(element)).unwrap();
+    assert_eq!(events, ["key"]);
+}
+
+#[test]
+fn test_on_global() {
+    use crate::backend::TerminalEvent;
+    use crate::{ElementExt, KeyPress};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(5, 2));
+    backend.events.push_back(TerminalEvent::Key(KeyPress::from('q')));
+    backend.events.push_back(TerminalEvent::Key(KeyPress::from('x')));
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    // The main element is behind a modal and has its inputs masked.
+    let element = || {
+        let main = crate::empty()
+            .on_global('q', |_| "quit")
+            .on(('q', 'x'), |_| "main")
+            .mask_inputs(());
+        let modal = crate::empty().on(('q', 'x'), |_| "modal");
+        crate::stack((main, modal))
+    };
+    let events = futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(events, ["quit"]);
+    let events = futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(events, ["modal"]);
+}
+
+#[test]
+fn test_inject_input() {
+    use crate::{ElementExt, KeyPress};
+
+    let mut terminal = Terminal::new(crate::backend::Dummy::new(Vec2::new(5, 2))).unwrap();
+    terminal.set_keymap(Keymap::new().bind("quit", 'q'));
+
+    terminal.inject_input(Input::Key(KeyPress::from('q')));
+    let element = || {
+        crate::empty()
+            .on_action("quit", |_| "quit")
+            .on('x', |_| "x")
+    };
+    let events = futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(events, ["quit"]);
+
+    // Inputs can be injected while the terminal is waiting for events.
+    let injector = terminal.input_injector();
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(10));
+        injector.inject(Input::Key(KeyPress::from('x')));
+    });
+    let events = futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(events, ["x"]);
+    thread.join().unwrap();
+}
+
+#[test]
+fn test_signals() {
+    use crate::{ElementExt, KeyPress, Signal};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(5, 2));
+    backend
+        .events
+        .push_back(TerminalEvent::Signal(Signal::Hangup));
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    // Signals are given to every element, regardless of focus.
+    let element = || {
+        crate::column(
+            crate::Static,
+            (
+                crate::empty(),
+                crate::empty().on((Signal::Terminate, Signal::Hangup), |input| input.signal()),
+            ),
+        )
+        .focus(0)
+    };
+    let events = futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(events, [Some(Signal::Hangup)]);
+
+    // Caught signals are handled before injected inputs.
+    terminal.inject_input(Input::Key(KeyPress::from('x')));
+    terminal
+        .injector
+        .0
+        .lock()
+        .unwrap()
+        .signals
+        .push_back(Signal::Terminate);
+    let events = futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(events, [Some(Signal::Terminate)]);
+}
+
+#[test]
+fn test_resume() {
+    use crate::backend::Operation;
+    use crate::{ElementExt, KeyPress, Signal};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(3, 1));
+    backend
+        .events
+        .push_back(TerminalEvent::Signal(Signal::Continue));
+    backend
+        .events
+        .push_back(TerminalEvent::Key(KeyPress::from('q')));
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    let element = crate::span("Hi").on('q', |_| ());
+    futures_lite::future::block_on(terminal.draw(element)).unwrap();
+
+    // The terminal is set up again and the frame is redrawn from scratch.
+    let operations = &terminal.backend().operations;
+    let resume = operations
+        .iter()
+        .position(|operation| *operation == Operation::Resume)
+        .unwrap();
+    assert!(operations[resume..].contains(&Operation::HideCursor));
+    assert!(operations[resume..].contains(&Operation::SetTitle("Toon App".to_owned())));
+    assert!(operations[resume..].ends_with(&[
+        Operation::Write("H".to_owned()),
+        Operation::Write("i".to_owned()),
+        Operation::SetBackground(Color::Default),
+        Operation::Flush,
+    ]));
+}
+
+#[test]
+fn test_modifiers() {
+    use crate::backend::{TerminalEvent, TerminalMouse};
+    use crate::{input, ElementExt, KeyPress};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(5, 2));
+    backend.events.push_back(TerminalEvent::Key(KeyPress {
+        modifiers: Modifiers::CONTROL,
+        ..KeyPress::from('a')
+    }));
+    backend.events.push_back(TerminalEvent::Paste("text".to_owned()));
+    backend.events.push_back(TerminalEvent::Mouse(TerminalMouse {
+        kind: TerminalMouseKind::Press(MouseButton::Left),
+        at: Vec2::new(0, 0),
+        modifiers: Modifiers::SHIFT,
+    }));
+    let mut terminal = Terminal::new(backend).unwrap();
+    assert_eq!(terminal.modifiers(), Modifiers::default());
+
+    let element = || crate::empty().on(input!(Key | Paste | Mouse), |_| ());
+    futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(terminal.modifiers(), Modifiers::CONTROL);
+    // Other inputs don't change the modifiers.
+    futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(terminal.modifiers(), Modifiers::CONTROL);
+    futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(terminal.modifiers(), Modifiers::SHIFT);
+}
+
+#[test]
+fn test_hover() {
+    use crate::backend::{TerminalEvent, TerminalMouse};
+    use crate::{input, Alignment, ElementExt};
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(5, 2));
+    for &(x, y) in &[(0, 0), (1, 0), (3, 1)] {
+        backend.events.push_back(TerminalEvent::Mouse(TerminalMouse {
+            kind: TerminalMouseKind::Move,
+            at: Vec2::new(x, y),
+            modifiers: Modifiers::default(),
+        }));
+    }
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    let element = || {
+        crate::empty()
+            .on(input!(Mouse(Enter)), |_| "enter")
+            .on(input!(Mouse(Leave)), |_| "leave")
+            .size((2, 1))
+            .float((Alignment::Start, Alignment::Start))
+    };
+    let events = futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(events, ["enter"]);
+    // Moving within the element doesn't enter or leave it.
+    let events = futures_lite::future::block_on(terminal.draw(element())).unwrap();
+    assert_eq!(events, ["leave"]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_draw_with_commands() {
+    use crate::Command;
+
+    let mut terminal = Terminal::new(crate::backend::Dummy::new(Vec2::new(5, 2))).unwrap();
+
+    let mut commands = Commands::new();
+    commands.push(Command::future(futures_lite::future::pending()));
+    commands.push(Command::event(1).map(|x| x + 1));
+    commands.spawn(async { 3 });
+
+    let events = futures_lite::future::block_on(
+        terminal.draw_with_commands(crate::empty(), &mut commands),
+    )
+    .unwrap();
+    assert_eq!(events, [2, 3]);
+    assert_eq!(commands.len(), 1);
+}
+
 #[cfg(test)]
 #[test]
 fn test_diff_grid() {
@@ -543,3 +2439,228 @@ fn test_diff_grid() {
         ],
     );
 }
+
+#[cfg(test)]
+#[test]
+fn test_color_downgrading() {
+    use crate::backend::Operation;
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(2, 1));
+    backend.color_support = ColorSupport::Ansi16;
+
+    let mut terminal = Terminal::new(backend).unwrap();
+    assert_eq!(terminal.color_support(), ColorSupport::Ansi16);
+    terminal.backend_mut().operations.clear();
+
+    let style = Style::new(
+        Color::Rgb(Rgb::new(0xF0, 0x10, 0x08)),
+        Color::new_ansi(21),
+        crate::Attributes::default(),
+    );
+    terminal.buffer.grid.write((0, 0), "a", style);
+    terminal.diff().unwrap();
+
+    assert_eq!(
+        terminal.backend().operations[..2],
+        [
+            Operation::SetForeground(Color::Red),
+            Operation::SetBackground(Color::DarkBlue),
+        ],
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_bold_colors() {
+    use crate::backend::Operation;
+    use crate::Styled;
+
+    let backend = crate::backend::Dummy::new(Vec2::new(3, 1));
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal.set_bold_colors(BoldColors::Bright);
+    terminal.backend_mut().operations.clear();
+
+    let dark_red = Style::default().dark_red();
+    let grid = &mut terminal.buffer.grid;
+    grid.write((0, 0), "a", dark_red.bold());
+    grid.write((1, 0), "b", dark_red.blue().bold());
+    grid.write((2, 0), "c", dark_red);
+    terminal.diff().unwrap();
+
+    assert_eq!(
+        terminal.backend().operations,
+        [
+            Operation::SetForeground(Color::Red),
+            Operation::SetIntensity(Intensity::Bold),
+            Operation::Write("a".to_owned()),
+            Operation::SetForeground(Color::Blue),
+            Operation::Write("b".to_owned()),
+            Operation::SetForeground(Color::DarkRed),
+            Operation::SetIntensity(Intensity::Normal),
+            Operation::Write("c".to_owned()),
+            Operation::SetBackground(Color::Default),
+        ],
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_images() {
+    use crate::backend::Operation;
+    use crate::Bitmap;
+
+    let mut backend = crate::backend::Dummy::new(Vec2::new(3, 1));
+    backend.supports_graphics = true;
+
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal.backend_mut().operations.clear();
+
+    let bitmap = Bitmap::new((1, 1), vec![Rgb::new(1, 2, 3)]);
+    terminal.buffer.grid.write((0, 0), "abc", Style::default());
+    terminal
+        .buffer
+        .draw_image(Vec2::new(0, 0), Vec2::new(2, 1), &bitmap);
+    terminal.diff().unwrap();
+
+    assert_eq!(
+        terminal.backend().operations,
+        [
+            Operation::SetCursorPos(Vec2::new(2, 0)),
+            Operation::Write("c".to_owned()),
+            Operation::SetCursorPos(Vec2::new(0, 0)),
+            Operation::DrawImage(Vec2::new(2, 1), bitmap),
+            Operation::SetBackground(Color::Default),
+        ],
+    );
+    terminal.backend_mut().operations.clear();
+
+    // Removing the image redraws the cells underneath it.
+    terminal.old_buffer.reset();
+    std::mem::swap(&mut terminal.old_buffer, &mut terminal.buffer);
+    terminal.buffer.grid.write((0, 0), "abc", Style::default());
+    terminal.diff().unwrap();
+
+    assert_eq!(
+        terminal.backend().operations,
+        [
+            Operation::Write("a".to_owned()),
+            Operation::Write("b".to_owned()),
+            Operation::SetBackground(Color::Default),
+        ],
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_combining_characters() {
+    use crate::backend::Operation;
+
+    let backend = crate::backend::Dummy::new(Vec2::new(2, 1));
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal.buffer.grid.write((0, 0), "xb", Style::default());
+    terminal.diff().unwrap();
+    terminal.backend_mut().operations.clear();
+
+    // Adding a combining character to a cell redraws the whole cell. A character without a
+    // precomposed form is used so that this works with the `normalize` feature.
+    terminal.old_buffer.reset();
+    std::mem::swap(&mut terminal.old_buffer, &mut terminal.buffer);
+    terminal.buffer.grid.write((0, 0), "x", Style::default());
+    terminal
+        .buffer
+        .write_char(Vec2::new(1, 0), '\u{301}', Style::default());
+    terminal.diff().unwrap();
+
+    assert_eq!(
+        terminal.backend().operations,
+        [
+            Operation::SetCursorPos(Vec2::new(0, 0)),
+            Operation::Write("x\u{301}".to_owned()),
+            Operation::Write(" ".to_owned()),
+            Operation::SetBackground(Color::Default),
+        ],
+    );
+    assert_eq!(terminal.backend().buffer.grid, terminal.buffer.grid);
+}
+
+#[cfg(test)]
+#[test]
+fn test_underlines() {
+    use crate::backend::Operation;
+    use crate::Styled;
+
+    let backend = crate::backend::Dummy::new(Vec2::new(4, 1));
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal.backend_mut().operations.clear();
+
+    let style = Style::default();
+    terminal.buffer.grid.write((0, 0), "a", style.underlined());
+    terminal.buffer.grid.write(
+        (1, 0),
+        "b",
+        style
+            .underline_kind(UnderlineKind::Curly)
+            .underline_color(Color::Red),
+    );
+    terminal.buffer.grid.write((2, 0), "c", style.underline_color(Color::Red));
+    terminal.buffer.grid.write(
+        (3, 0),
+        "d",
+        Style {
+            attributes: crate::Attributes {
+                underline_kind: UnderlineKind::Dotted,
+                ..crate::Attributes::default()
+            },
+            ..style
+        },
+    );
+    terminal.diff().unwrap();
+
+    assert_eq!(
+        terminal.backend().operations,
+        [
+            Operation::SetUnderlined(true),
+            Operation::Write("a".to_owned()),
+            Operation::SetUnderlineColor(Color::Red),
+            Operation::SetUnderlineKind(UnderlineKind::Curly),
+            Operation::Write("b".to_owned()),
+            Operation::SetUnderlined(false),
+            Operation::Write("c".to_owned()),
+            Operation::SetUnderlineColor(Color::Default),
+            Operation::Write("d".to_owned()),
+            Operation::SetBackground(Color::Default),
+        ],
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_links() {
+    use crate::backend::Operation;
+
+    let backend = crate::backend::Dummy::new(Vec2::new(4, 1));
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal.backend_mut().operations.clear();
+
+    let link = Link::new("https://example.com");
+    terminal.buffer.grid.write((0, 0), "abc", Style::default());
+    terminal.buffer.grid.set_link(Vec2::new(0, 0), &link);
+    terminal.buffer.grid.set_link(Vec2::new(1, 0), &link);
+    terminal.diff().unwrap();
+
+    assert_eq!(
+        terminal.backend().operations,
+        [
+            Operation::SetLink(Some(link.clone())),
+            Operation::Write("a".to_owned()),
+            Operation::Write("b".to_owned()),
+            Operation::SetLink(None),
+            Operation::Write("c".to_owned()),
+            Operation::SetBackground(Color::Default),
+        ],
+    );
+    assert_eq!(
+        terminal.backend().buffer.grid.lines()[0].cells()[1].link(),
+        Some(&link)
+    );
+}
@@ -0,0 +1,780 @@
+//! Utilities for testing applications built with Toon.
+//!
+//! [`Harness`] runs a whole draw and input loop without a real terminal, and the
+//! [`assert_grid_eq!`](crate::assert_grid_eq) macro compares the frames it draws. Frames can also
+//! be compared to snapshots stored in files with [`assert_snapshot!`](crate::assert_snapshot), so
+//! that changes to how elements are drawn are reviewed as diffs.
+
+use std::convert::TryFrom;
+use std::fmt::Write as _;
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::time::{Duration, Instant};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::backend::{Dummy, TerminalEvent, TerminalMouse, TerminalMouseKind};
+use crate::clock::{Clock, MockClock};
+use crate::util::{grapheme_width, str_width};
+use crate::{
+    Cell, Element, Grid, Intensity, KeyPress, Line, Link, Modifiers, MouseButton, Output, Style,
+    Terminal, UnderlineKind, Vec2,
+};
+
+/// A headless terminal for end-to-end tests of a draw loop.
+///
+/// The harness draws to a [`Dummy`] backend. Inputs queued with methods like [`key`](Self::key),
+/// [`click`](Self::click) and [`resize`](Self::resize) are given to the element passed to the next
+/// call to [`draw`](Self::draw), which draws a frame and handles the queued inputs until one of
+/// them causes events. Unlike [`Terminal::draw`] it never waits for input, so no async runtime is
+/// needed.
+///
+/// The terminal's [clock](Self::clock) is a [`MockClock`] that only moves when it is advanced with
+/// [`advance`](Self::advance). Elements that animate can be built from the time of the clock, or
+/// from [`elapsed`](Self::elapsed), instead of the real time so that each frame of the animation
+/// can be tested deterministically.
+///
+/// # Examples
+///
+/// ```
+/// use toon::test::Harness;
+/// use toon::ElementExt;
+///
+/// let mut harness = Harness::new((5, 1));
+/// harness.keys("++");
+///
+/// let mut count = 0;
+/// while harness.has_pending_inputs() {
+///     count += harness.draw(toon::span(count.to_string()).on('+', |_| ())).len();
+/// }
+/// harness.draw(toon::span::<_, ()>(count.to_string()));
+///
+/// toon::assert_grid_eq!(harness.frame(), ["2    "]);
+/// assert_eq!(harness.frames().len(), 3);
+/// ```
+#[derive(Debug)]
+pub struct Harness {
+    terminal: Terminal<Dummy>,
+    start: Instant,
+}
+
+impl Harness {
+    /// Create a new harness with a terminal of the given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if creating the terminal fails, which it shouldn't.
+    #[must_use]
+    pub fn new(size: impl Into<Vec2<u16>>) -> Self {
+        let backend = Dummy::new(size.into());
+        Self {
+            start: backend.clock.now(),
+            terminal: Terminal::new(backend).expect("creating a dummy terminal failed"),
+        }
+    }
+
+    /// Get the terminal being drawn to.
+    #[must_use]
+    pub fn terminal(&self) -> &Terminal<Dummy> {
+        &self.terminal
+    }
+
+    /// Get the terminal being drawn to mutably, for example to set its keymap.
+    pub fn terminal_mut(&mut self) -> &mut Terminal<Dummy> {
+        &mut self.terminal
+    }
+
+    /// Get the dummy backend, which records the operations it has received.
+    #[must_use]
+    pub fn backend(&self) -> &Dummy {
+        self.terminal.backend()
+    }
+
+    /// Queue an event for the terminal to receive.
+    pub fn event(&mut self, event: impl Into<TerminalEvent>) -> &mut Self {
+        self.terminal.backend_mut().events.push_back(event.into());
+        self
+    }
+
+    /// Queue a key press.
+    pub fn key(&mut self, key: impl Into<KeyPress>) -> &mut Self {
+        self.event(key.into())
+    }
+
+    /// Queue a key press for each character of some text.
+    pub fn keys(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            self.key(c);
+        }
+        self
+    }
+
+    /// Queue a mouse event at a position on the terminal.
+    pub fn mouse(&mut self, kind: TerminalMouseKind, at: impl Into<Vec2<u16>>) -> &mut Self {
+        self.event(TerminalMouse {
+            kind,
+            at: at.into(),
+            modifiers: Modifiers::default(),
+        })
+    }
+
+    /// Queue a press and release of the left mouse button at a position on the terminal.
+    pub fn click(&mut self, at: impl Into<Vec2<u16>>) -> &mut Self {
+        let at = at.into();
+        self.mouse(TerminalMouseKind::Press(MouseButton::Left), at)
+            .mouse(TerminalMouseKind::Release, at)
+    }
+
+    /// Queue text being pasted.
+    pub fn paste(&mut self, text: impl Into<String>) -> &mut Self {
+        self.event(TerminalEvent::Paste(text.into()))
+    }
+
+    /// Queue the terminal being resized.
+    pub fn resize(&mut self, size: impl Into<Vec2<u16>>) -> &mut Self {
+        self.event(TerminalEvent::Resize(size.into()))
+    }
+
+    /// Get whether there are queued inputs that haven't been given to an element yet.
+    #[must_use]
+    pub fn has_pending_inputs(&self) -> bool {
+        !self.backend().events.is_empty()
+    }
+
+    /// Get the mock clock of the terminal.
+    #[must_use]
+    pub fn clock(&self) -> &MockClock {
+        &self.backend().clock
+    }
+
+    /// Advance the terminal's clock.
+    pub fn advance(&mut self, duration: Duration) -> &mut Self {
+        self.clock().advance(duration);
+        self
+    }
+
+    /// Get how far the terminal's clock has been advanced since the harness was created.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.clock().elapsed(self.start)
+    }
+
+    /// Draw a frame of an element, and then give it queued inputs until one of them causes
+    /// events, returning those events.
+    ///
+    /// The terminal redraws the element after it is resized, so a single call can draw several
+    /// frames. If the queued inputs run out without causing any events this returns no events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if drawing fails, which it shouldn't.
+    pub fn draw<E: Element>(&mut self, element: E) -> Vec<E::Event> {
+        let mut future = Box::pin(self.terminal.draw(element));
+        let waker = noop_waker();
+        match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(events) => events.expect("drawing to a dummy terminal failed"),
+            // The dummy backend only returns a pending future once its events have run out.
+            Poll::Pending => Vec::new(),
+        }
+    }
+
+    /// Get the last frame drawn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no frames have been drawn.
+    #[must_use]
+    pub fn frame(&self) -> &Grid {
+        self.frames().last().expect("no frames have been drawn")
+    }
+
+    /// Get all the frames drawn, from the first to the last.
+    #[must_use]
+    pub fn frames(&self) -> &[Grid] {
+        &self.backend().frames
+    }
+
+    /// Get a [`snapshot`] of the last frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no frames have been drawn.
+    #[must_use]
+    pub fn snapshot(&self) -> String {
+        snapshot(self.frame())
+    }
+}
+
+/// Create a waker that does nothing when woken.
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // SAFETY: The waker's functions don't use its data, so they are trivially thread-safe.
+    unsafe { Waker::from_raw(clone(std::ptr::null())) }
+}
+
+/// Assert that a [`Grid`] is equal to another grid or to the lines of text it is expected to
+/// contain.
+///
+/// On failure the two grids are printed side by side with the differing cells marked underneath,
+/// followed by the contents and styles of each differing cell.
+///
+/// The macro has three forms:
+/// - `assert_grid_eq!(grid, other)` compares the contents and styles of two grids.
+/// - `assert_grid_eq!(grid, ["line", ...])` only compares the contents of the grid to the lines,
+/// which must all be the same width.
+/// - `assert_grid_eq!(grid, ["line", ...], ["mask", ...], { 'c' => style, ... })` also compares
+/// the styles of the cells. Each character of the mask gives the style of the cell in that
+/// column, looked up in the map. Spaces and columns past the end of a mask line have the default
+/// style unless `' '` is in the map.
+///
+/// # Panics
+///
+/// Panics if the grids aren't equal, if the expected lines aren't all the same width or if the
+/// mask contains a character that isn't in the map.
+///
+/// # Examples
+///
+/// ```
+/// use toon::output::Ext as _;
+/// use toon::{Style, Styled as _};
+///
+/// let mut grid = toon::Grid::new((5, 2));
+/// grid.write((0, 0), "Hi", Style::default().bold());
+/// grid.write((0, 1), "there", Style::default());
+///
+/// toon::assert_grid_eq!(grid, ["Hi   ", "there"]);
+/// toon::assert_grid_eq!(
+///     grid,
+///     ["Hi   ", "there"],
+///     ["bb"],
+///     { 'b' => Style::default().bold() },
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_grid_eq {
+    ($grid:expr, [$($line:expr),* $(,)?] $(,)?) => {
+        $crate::__assert_grid_eq(
+            &$grid,
+            &$crate::__expected_grid(&[$($line),*], &[], &[]),
+            false,
+            $crate::std::stringify!($grid),
+            $crate::std::stringify!([$($line),*]),
+        )
+    };
+    (
+        $grid:expr,
+        [$($line:expr),* $(,)?],
+        [$($mask:expr),* $(,)?],
+        { $($key:literal => $style:expr),* $(,)? } $(,)?
+    ) => {
+        $crate::__assert_grid_eq(
+            &$grid,
+            &$crate::__expected_grid(&[$($line),*], &[$($mask),*], &[$(($key, $style)),*]),
+            true,
+            $crate::std::stringify!($grid),
+            $crate::std::stringify!([$($line),*]),
+        )
+    };
+    ($grid:expr, $expected:expr $(,)?) => {
+        $crate::__assert_grid_eq(
+            &$grid,
+            &$expected,
+            true,
+            $crate::std::stringify!($grid),
+            $crate::std::stringify!($expected),
+        )
+    };
+}
+
+/// Create the grid expected by `assert_grid_eq!` from its lines and style mask.
+#[doc(hidden)]
+#[must_use]
+pub fn __expected_grid(lines: &[&str], mask: &[&str], styles: &[(char, Style)]) -> Grid {
+    let width = lines.iter().map(|line| str_width(line)).max().unwrap_or(0);
+    if let Some(line) = lines.iter().find(|line| str_width(line) != width) {
+        panic!("expected line {:?} is not {} columns wide", line, width);
+    }
+    let mut grid = Grid::new((
+        u16::try_from(width).expect("expected lines are too wide"),
+        u16::try_from(lines.len()).expect("too many expected lines"),
+    ));
+
+    for (y, line) in lines.iter().enumerate() {
+        let mut x = 0;
+        for grapheme in line.graphemes(true) {
+            let key = mask
+                .get(y)
+                .and_then(|mask| mask.chars().nth(x))
+                .unwrap_or(' ');
+            let style = match styles.iter().find(|&&(k, _)| k == key) {
+                Some(&(_, style)) => style,
+                None if key == ' ' => Style::default(),
+                None => panic!("style mask character {:?} is not in the map", key),
+            };
+            grid.write_grapheme(Vec2::new(x as u16, y as u16), grapheme, style);
+            x += grapheme_width(grapheme).unwrap_or(0);
+        }
+    }
+
+    grid
+}
+
+/// Compare two grids for `assert_grid_eq!`, panicking with a visual diff if they differ.
+#[doc(hidden)]
+#[track_caller]
+pub fn __assert_grid_eq(
+    left: &Grid,
+    right: &Grid,
+    compare_styles: bool,
+    left_expr: &str,
+    right_expr: &str,
+) {
+    let cell = |grid: &Grid, x: u16, y: u16| {
+        grid.lines()
+            .get(usize::from(y))?
+            .cells()
+            .get(usize::from(x))
+            .cloned()
+    };
+    let differs = |x, y| match (cell(left, x, y), cell(right, x, y)) {
+        (Some(l), Some(r)) => {
+            l.contents() != r.contents() || (compare_styles && l.style() != r.style())
+        }
+        _ => true,
+    };
+
+    let size = left.size().max(right.size());
+    let differences: Vec<Vec2<u16>> = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| Vec2::new(x, y)))
+        .filter(|pos| differs(pos.x, pos.y))
+        .collect();
+    if differences.is_empty() {
+        return;
+    }
+
+    let row = |grid: &Grid, y: u16| {
+        let mut row = grid
+            .lines()
+            .get(usize::from(y))
+            .map_or_else(String::new, Line::contents);
+        let padding = usize::from(size.x).saturating_sub(str_width(&row));
+        row.extend(std::iter::repeat(' ').take(padding));
+        row
+    };
+    let describe = |cell: Option<Cell>| match cell {
+        None => "nothing".to_owned(),
+        Some(cell) => match (cell.contents(), cell.style()) {
+            (Some(contents), Some(style)) if compare_styles => {
+                format!("{:?} {:?}", contents, style)
+            }
+            (Some(contents), _) => format!("{:?}", contents),
+            _ => "continuation".to_owned(),
+        },
+    };
+
+    let mut message = format!(
+        "assertion failed: grids are not equal\n  left: `{}` ({}x{})\n right: `{}` ({}x{})\n\n",
+        left_expr,
+        left.width(),
+        left.height(),
+        right_expr,
+        right.width(),
+        right.height(),
+    );
+    for y in 0..size.y {
+        let _ = writeln!(message, "{:>3} │{}│ │{}│", y, row(left, y), row(right, y));
+        if differences.iter().any(|pos| pos.y == y) {
+            let markers: String = (0..size.x)
+                .map(|x| if differs(x, y) { '^' } else { ' ' })
+                .collect();
+            let _ = writeln!(message, "     {}   {}", markers, markers);
+        }
+    }
+    message.push_str("\ndiffering cells:\n");
+    for pos in differences {
+        let _ = writeln!(
+            message,
+            "  ({}, {}): {} != {}",
+            pos.x,
+            pos.y,
+            describe(cell(left, pos.x, pos.y)),
+            describe(cell(right, pos.x, pos.y)),
+        );
+    }
+
+    panic!("{}", message);
+}
+
+/// The characters used for the styles of cells in snapshots.
+const SNAPSHOT_KEYS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Get a textual snapshot of a grid, containing its contents and the styles and links of its
+/// cells.
+///
+/// The contents are drawn inside a box so that trailing spaces are visible. If any cells aren't
+/// in the default style or are links a mask is drawn next to them, where each cell is replaced by
+/// a character standing for its style and link, followed by a list of the styles written like a
+/// [`Style`] parsed from a string. Colors of underlines and links, which can't be parsed, are
+/// written like `underline-color=red` and `link=https://example.com`.
+///
+/// Snapshots can be compared to ones stored in files with
+/// [`assert_snapshot!`](crate::assert_snapshot), or with snapshot testing libraries like
+/// [`insta`](https://crates.io/crates/insta).
+///
+/// # Examples
+///
+/// ```
+/// use toon::output::Ext as _;
+/// use toon::{Style, Styled as _};
+///
+/// let mut grid = toon::Grid::new((5, 2));
+/// grid.write((0, 0), "Hi", Style::default().bold().red());
+/// grid.write((0, 1), "there", Style::default());
+///
+/// assert_eq!(
+///     toon::test::snapshot(&grid),
+///     "\
+/// ┌─────┐ ┌─────┐
+/// │Hi   │ │aa   │
+/// │there│ │     │
+/// └─────┘ └─────┘
+/// a: bold red
+/// ",
+/// );
+/// ```
+#[must_use]
+pub fn snapshot(grid: &Grid) -> String {
+    let mut keys = SNAPSHOT_KEYS
+        .chars()
+        .chain((0x100..).filter_map(std::char::from_u32));
+    let mut styles: Vec<(char, Style, Option<&Link>)> = Vec::new();
+
+    let masks: Vec<String> = grid
+        .lines()
+        .iter()
+        .map(|line| {
+            let mut key = ' ';
+            line.cells()
+                .iter()
+                .map(|cell| {
+                    // Continuation cells have the key of the cell before them.
+                    if let Some(style) = cell.style() {
+                        let link = cell.link();
+                        key = match styles.iter().find(|&&(_, s, l)| s == style && l == link) {
+                            _ if style == Style::default() && link.is_none() => ' ',
+                            Some(&(key, _, _)) => key,
+                            None => {
+                                let key = keys.next().unwrap();
+                                styles.push((key, style, link));
+                                key
+                            }
+                        };
+                    }
+                    key
+                })
+                .collect()
+        })
+        .collect();
+
+    let border = "─".repeat(usize::from(grid.width()));
+    let edge = |left, right| {
+        let edge = format!("{}{}{}", left, border, right);
+        if styles.is_empty() {
+            edge
+        } else {
+            format!("{} {}", edge, edge)
+        }
+    };
+
+    let mut snapshot = String::new();
+    let _ = writeln!(snapshot, "{}", edge('┌', '┐'));
+    for (line, mask) in grid.lines().iter().zip(&masks) {
+        let _ = write!(snapshot, "│{}│", line.contents());
+        if !styles.is_empty() {
+            let _ = write!(snapshot, " │{}│", mask);
+        }
+        snapshot.push('\n');
+    }
+    let _ = writeln!(snapshot, "{}", edge('└', '┘'));
+    for (key, style, link) in styles {
+        let _ = writeln!(snapshot, "{}: {}", key, describe_style(style, link));
+    }
+
+    snapshot
+}
+
+/// Describe a style and link in words, like `bold red on black`.
+fn describe_style(style: Style, link: Option<&Link>) -> String {
+    let attributes = style.attributes;
+    let mut words = Vec::new();
+
+    match attributes.intensity {
+        Intensity::Bold => words.push("bold".to_owned()),
+        Intensity::Dim => words.push("dim".to_owned()),
+        Intensity::Normal => {}
+    }
+    if attributes.italic {
+        words.push("italic".to_owned());
+    }
+    if attributes.underlined {
+        words.push(
+            match attributes.underline_kind {
+                UnderlineKind::Single => "underlined",
+                UnderlineKind::Double => "underline=double",
+                UnderlineKind::Curly => "underline=curly",
+                UnderlineKind::Dotted => "underline=dotted",
+                UnderlineKind::Dashed => "underline=dashed",
+            }
+            .to_owned(),
+        );
+    }
+    for &(set, word) in &[
+        (attributes.blinking, "blinking"),
+        (attributes.crossed_out, "crossed-out"),
+        (attributes.reversed, "reversed"),
+        (attributes.hidden, "hidden"),
+        (attributes.overlined, "overlined"),
+    ] {
+        if set {
+            words.push(word.to_owned());
+        }
+    }
+    if style.foreground != crate::Color::Default {
+        words.push(style.foreground.to_string());
+    }
+    if style.background != crate::Color::Default {
+        words.push(format!("on {}", style.background));
+    }
+    if style.underline_color != crate::Color::Default {
+        words.push(format!("underline-color={}", style.underline_color));
+    }
+    if let Some(link) = link {
+        words.push(format!("link={}", link.url()));
+    }
+
+    words.join(" ")
+}
+
+/// Assert that a [`Grid`] matches a snapshot stored in a file.
+///
+/// The [`snapshot`] of the grid is compared to the one stored in `snapshots/<name>.snap` in the
+/// directory of the crate being tested.
+///
+/// If the snapshot doesn't exist or is different, the new snapshot is written to
+/// `snapshots/<name>.snap.new` and the assertion fails, showing the differing lines. After
+/// reviewing the new snapshot, rename it to replace the old one. Alternatively, run the tests with
+/// the `TOON_UPDATE_SNAPSHOTS` environment variable set to overwrite the old snapshots directly.
+///
+/// # Panics
+///
+/// Panics if the snapshot doesn't match, or if reading or writing the snapshot files fails.
+///
+/// # Examples
+///
+/// ```no_run
+/// use toon::Element;
+///
+/// let mut grid = toon::Grid::new((13, 1));
+/// toon::span::<_, ()>("Hello, world!").draw(&mut grid);
+///
+/// toon::assert_snapshot!(grid, "hello_world");
+/// ```
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($grid:expr, $name:expr $(,)?) => {
+        $crate::test::__assert_snapshot(
+            &$grid,
+            $crate::std::path::Path::new($crate::std::env!("CARGO_MANIFEST_DIR")),
+            $name,
+        )
+    };
+}
+
+/// Compare a grid to a snapshot stored in `snapshots/<name>.snap` in a directory for
+/// `assert_snapshot!`.
+#[doc(hidden)]
+#[track_caller]
+pub fn __assert_snapshot(grid: &Grid, dir: &Path, name: &str) {
+    let dir = dir.join("snapshots");
+    let path = dir.join(format!("{}.snap", name));
+    let new_path = dir.join(format!("{}.snap.new", name));
+
+    let new = snapshot(grid);
+    let old = fs::read_to_string(&path).ok();
+    if old.as_deref() == Some(&*new) {
+        let _ = fs::remove_file(&new_path);
+        return;
+    }
+
+    fs::create_dir_all(&dir).expect("failed to create the snapshots directory");
+    if std::env::var_os("TOON_UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &new).expect("failed to write the snapshot");
+        let _ = fs::remove_file(&new_path);
+        return;
+    }
+    fs::write(&new_path, &new).expect("failed to write the new snapshot");
+
+    let mut message = match &old {
+        Some(_) => format!("snapshot {:?} does not match:\n\n", name),
+        None => format!("snapshot {:?} does not exist:\n\n", name),
+    };
+    let old_lines: Vec<&str> = old.as_deref().unwrap_or("").lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    for i in 0..old_lines.len().max(new_lines.len()) {
+        match (old_lines.get(i), new_lines.get(i)) {
+            (Some(old), Some(new)) if old == new => {
+                let _ = writeln!(message, "  {}", old);
+            }
+            (old, new) => {
+                if let Some(old) = old {
+                    let _ = writeln!(message, "- {}", old);
+                }
+                if let Some(new) = new {
+                    let _ = writeln!(message, "+ {}", new);
+                }
+            }
+        }
+    }
+    let _ = write!(
+        message,
+        "\nthe new snapshot was written to {}; review it and rename it to {} to accept it, or \
+         rerun with TOON_UPDATE_SNAPSHOTS=1",
+        new_path.display(),
+        path.display(),
+    );
+
+    panic!("{}", message);
+}
+
+#[test]
+fn test_assert_grid_eq() {
+    use crate::output::Ext as _;
+    use crate::Styled as _;
+
+    let mut grid = Grid::new((4, 2));
+    grid.write((0, 0), "a日", Style::default().bold());
+
+    crate::assert_grid_eq!(grid, ["a日 ", "    "]);
+    crate::assert_grid_eq!(grid, ["a日 ", "    "], ["bb"], { 'b' => Style::default().bold() });
+    crate::assert_grid_eq!(grid, grid.clone());
+
+    let message = std::panic::catch_unwind(|| {
+        crate::assert_grid_eq!(grid, ["ab  ", "    "], ["b"], { 'b' => Style::default().bold() });
+    })
+    .unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains("  0 │a日 │ │ab  │\n      ^^     ^^ \n"));
+    assert!(message.contains("  1 │    │ │    │\n\n"));
+    assert!(message.contains("  (1, 0): \"日\" Style {"));
+    assert!(message.contains("  (2, 0): continuation != \" \" Style {"));
+    assert!(!message.contains("(3, 0)"));
+
+    let message = std::panic::catch_unwind(|| crate::assert_grid_eq!(grid, ["a日"])).unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains(" right: `[\"a日\"]` (3x1)\n"));
+    assert!(message.contains("  (3, 0): \" \" != nothing\n"));
+    assert!(message.contains("  (0, 1): \" \" != nothing\n"));
+}
+
+#[test]
+fn test_harness() {
+    use crate::ElementExt as _;
+
+    let mut harness = Harness::new((4, 1));
+    assert!(!harness.has_pending_inputs());
+    harness.keys("ab").click((1, 0)).resize((3, 2));
+    assert!(harness.has_pending_inputs());
+
+    let draw = |harness: &mut Harness| {
+        let text = harness.elapsed().as_millis().to_string();
+        harness.draw(
+            crate::span(text)
+                .on('a', |_| "a")
+                .on(crate::input!(Mouse(Press Left)), |_| "click"),
+        )
+    };
+
+    assert_eq!(draw(&mut harness), ["a"]);
+    crate::assert_grid_eq!(harness.frame(), ["0   "]);
+
+    harness.advance(Duration::from_millis(16));
+    assert_eq!(draw(&mut harness), ["click"]);
+    assert_eq!(harness.snapshot(), "┌────┐\n│16  │\n└────┘\n");
+
+    harness.advance(Duration::from_millis(234));
+    assert_eq!(harness.elapsed(), Duration::from_millis(250));
+    assert!(draw(&mut harness).is_empty());
+    assert!(!harness.has_pending_inputs());
+    crate::assert_grid_eq!(harness.frame(), ["250", "   "]);
+    assert_eq!(harness.frames().len(), 4);
+
+    assert!(draw(&mut harness).is_empty());
+    assert_eq!(harness.frames().len(), 5);
+}
+
+#[test]
+fn test_snapshot() {
+    use crate::output::Ext as _;
+    use crate::Styled as _;
+
+    let mut grid = Grid::new((4, 2));
+    assert_eq!(snapshot(&grid), "┌────┐\n│    │\n│    │\n└────┘\n");
+
+    grid.write((0, 0), "a日", Style::default().italic().on_blue());
+    grid.write(
+        (0, 1),
+        "b",
+        Style::default().underline_color(crate::Color::Red),
+    );
+    grid.write((3, 1), "c", Style::default().italic().on_blue());
+    assert_eq!(
+        snapshot(&grid),
+        "\
+┌────┐ ┌────┐
+│a日 │ │aaa │
+│b  c│ │b  a│
+└────┘ └────┘
+a: italic on blue
+b: underline-color=red
+",
+    );
+}
+
+#[test]
+fn test_assert_snapshot() {
+    use crate::output::Ext as _;
+
+    let dir = std::env::temp_dir().join(format!("toon-snapshots-{}", std::process::id()));
+    let path = dir.join("snapshots/grid.snap");
+    let new_path = dir.join("snapshots/grid.snap.new");
+    let check = |grid: &Grid| {
+        std::panic::catch_unwind(|| __assert_snapshot(grid, &dir, "grid"))
+            .map_err(|message| message.downcast::<String>().unwrap())
+    };
+
+    let mut grid = Grid::new((3, 1));
+    grid.write((0, 0), "abc", Style::default());
+    assert!(check(&grid).unwrap_err().contains("+ │abc│\n"));
+    assert_eq!(fs::read_to_string(&new_path).unwrap(), snapshot(&grid));
+
+    fs::rename(&new_path, &path).unwrap();
+    check(&grid).unwrap();
+
+    grid.write((1, 0), "x", Style::default());
+    let message = check(&grid).unwrap_err();
+    assert!(message.contains("  ┌───┐\n- │abc│\n+ │axc│\n  └───┘\n"));
+    assert!(new_path.exists());
+
+    grid.write((1, 0), "b", Style::default());
+    check(&grid).unwrap();
+    assert!(!new_path.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,174 @@
+//! Styles for named roles that can be loaded from configuration.
+
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::Style;
+
+/// A map from named roles, like `title` or `selection`, to the styles they are drawn in.
+///
+/// Applications can set up default styles with [`set`](Self::set) and then override them with
+/// ones loaded from the user's configuration, so that users can change colors without
+/// recompiling. Themes can be parsed from lines like `title = bold yellow`, where the style is
+/// written like a [`Style`] parsed from a string. Blank lines and lines starting with `#` are
+/// ignored. With the `serde` feature the theme can also be deserialized from a map of role names
+/// to styles.
+///
+/// With the `dev` feature a theme file can be reloaded whenever it changes with
+/// [`dev::watch_theme`](crate::dev::watch_theme).
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Style, Styled as _, Theme};
+///
+/// let mut theme = Theme::new()
+///     .set("title", Style::default().bold())
+///     .set("selection", Style::default().reversed());
+///
+/// // Loaded from the user's configuration.
+/// let user: Theme = "
+///     ## Make titles stand out more
+///     title = bold yellow on blue
+/// ".parse()?;
+/// theme.extend(user);
+///
+/// assert_eq!(theme.style("title"), Style::default().bold().yellow().on_blue());
+/// assert_eq!(theme.style("selection"), Style::default().reversed());
+/// assert_eq!(theme.style("unknown"), Style::default());
+/// # Ok::<_, toon::theme::ParseThemeError>(())
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", transparent)
+)]
+pub struct Theme {
+    styles: BTreeMap<String, Style>,
+}
+
+impl Theme {
+    /// Create a new theme with no roles.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the style of a role.
+    #[must_use]
+    pub fn set(mut self, role: impl Into<String>, style: Style) -> Self {
+        self.styles.insert(role.into(), style);
+        self
+    }
+
+    /// Get the style of a role, if the theme has it.
+    #[must_use]
+    pub fn get(&self, role: &str) -> Option<Style> {
+        self.styles.get(role).copied()
+    }
+
+    /// Get the style of a role, or the default style if the theme doesn't have it.
+    #[must_use]
+    pub fn style(&self, role: &str) -> Style {
+        self.get(role).unwrap_or_default()
+    }
+
+    /// Iterate over the roles in the theme and their styles, in alphabetical order of role.
+    pub fn styles(&self) -> impl Iterator<Item = (&str, Style)> + '_ {
+        self.styles
+            .iter()
+            .map(|(role, &style)| (role.as_str(), style))
+    }
+
+    /// Replace the styles of the roles in another theme with its styles.
+    ///
+    /// Roles that aren't in the other theme keep their current styles.
+    pub fn extend(&mut self, other: Self) {
+        self.styles.extend(other.styles);
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ParseThemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut theme = Self::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = || ParseThemeError {
+                line: i + 1,
+                text: line.to_owned(),
+            };
+            let equals = line.find('=').ok_or_else(error)?;
+            let role = line[..equals].trim();
+            if role.is_empty() {
+                return Err(error());
+            }
+            let style = line[equals + 1..].parse().map_err(|_| error())?;
+            theme.styles.insert(role.to_owned(), style);
+        }
+
+        Ok(theme)
+    }
+}
+
+/// An error parsing a [`Theme`], containing the line that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseThemeError {
+    /// The one-indexed number of the line.
+    pub line: usize,
+    /// The text of the line.
+    pub text: String,
+}
+
+impl Display for ParseThemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid theme on line {}: {:?}", self.line, self.text)
+    }
+}
+impl StdError for ParseThemeError {}
+
+#[test]
+fn test_parse() {
+    use crate::Styled as _;
+
+    let theme: Theme = "
+        # A comment
+        title = bold red
+
+        selection=reversed
+        plain =
+        title = italic
+    "
+    .parse()
+    .unwrap();
+    assert_eq!(
+        theme.styles().collect::<Vec<_>>(),
+        [
+            ("plain", Style::default()),
+            ("selection", Style::default().reversed()),
+            ("title", Style::default().italic()),
+        ]
+    );
+
+    let error = |line: usize, text: &str| {
+        Err::<Theme, _>(ParseThemeError {
+            line,
+            text: text.to_owned(),
+        })
+    };
+    assert_eq!("a = b\n\ntitle".parse(), error(3, "title"));
+    assert_eq!("= bold".parse(), error(1, "= bold"));
+    assert_eq!(
+        "title = bold orange".parse(),
+        error(1, "title = bold orange")
+    );
+}
@@ -0,0 +1,187 @@
+//! Printing the structure of elements for debugging.
+
+use std::fmt::{self, Debug, Display, Formatter};
+
+use crate::{Element, Vec2};
+
+/// Get the tree of filters, containers and elements that make up an element, to help understand
+/// long chains of [`ElementExt`](crate::ElementExt) methods.
+///
+/// Each element and filter is shown with its parameters, like sizes, alignments and input patterns,
+/// as given by [`Element::describe`]. Filters are shown above the elements they are applied to, so
+/// the outermost filter is at the top. Elements and patterns that don't describe themselves are
+/// shown by their type name, so closures are shown as `closure`.
+///
+/// # Examples
+///
+/// ```
+/// use toon::{Alignment, ElementExt};
+///
+/// let element = toon::column(
+///     toon::Static,
+///     (
+///         toon::span::<_, ()>("Title").float_x(Alignment::Middle),
+///         toon::span("Press q to quit").on('q', |_| ()),
+///     ),
+/// )
+/// .size((20, 2));
+///
+/// assert_eq!(
+///     toon::debug_tree(&element).to_string(),
+///     "\
+/// Size(x: 20, y: 2)
+/// └─ Column(Static)
+///    ├─ Float(x: Middle)
+///    │  └─ Span(\"Title\")
+///    └─ On('q')
+///       └─ Span(\"Press q to quit\")
+/// ",
+/// );
+/// ```
+#[must_use]
+pub fn debug_tree<E: Element + ?Sized>(element: &E) -> Tree {
+    let mut tree = Tree::default();
+    element.describe(&mut tree);
+    tree
+}
+
+/// A tree of descriptions of elements, created by [`debug_tree`] and built up by
+/// [`Element::describe`].
+///
+/// Its [`Display`] implementation draws the tree with one node on each line.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Tree {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    label: String,
+    children: Tree,
+}
+
+impl Tree {
+    /// Add a node with no children to the tree.
+    pub fn leaf(&mut self, label: impl Display) {
+        self.node(label, |_| {});
+    }
+
+    /// Add a node to the tree, whose children are added by a function.
+    pub fn node(&mut self, label: impl Display, children: impl FnOnce(&mut Self)) {
+        let mut tree = Self::default();
+        children(&mut tree);
+        self.nodes.push(Node {
+            label: label.to_string(),
+            children: tree,
+        });
+    }
+
+    fn fmt_nodes(&self, f: &mut Formatter<'_>, prefix: &mut String) -> fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            let last = i + 1 == self.nodes.len();
+            writeln!(
+                f,
+                "{}{}{}",
+                prefix,
+                if last { "└─ " } else { "├─ " },
+                node.label
+            )?;
+
+            let len = prefix.len();
+            prefix.push_str(if last { "   " } else { "│  " });
+            node.children.fmt_nodes(f, prefix)?;
+            prefix.truncate(len);
+        }
+        Ok(())
+    }
+}
+
+impl Display for Tree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for node in &self.nodes {
+            writeln!(f, "{}", node.label)?;
+            node.children.fmt_nodes(f, &mut String::new())?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the shortened name of a type, which is the default description of elements, filters and
+/// patterns.
+pub(crate) fn type_name<T: ?Sized>() -> String {
+    crate::util::short_type_name(std::any::type_name::<T>())
+}
+
+/// Describe a value that may be set in each axis, like `Float(x: Start, y: End)`. Axes that aren't
+/// set are left out.
+pub(crate) fn describe_axes<T: Debug>(name: &str, axes: Vec2<Option<T>>) -> String {
+    match axes {
+        Vec2 {
+            x: Some(x),
+            y: Some(y),
+        } => format!("{}(x: {:?}, y: {:?})", name, x, y),
+        Vec2 { x: Some(x), .. } => format!("{}(x: {:?})", name, x),
+        Vec2 { y: Some(y), .. } => format!("{}(y: {:?})", name, y),
+        Vec2 { .. } => name.to_owned(),
+    }
+}
+
+#[test]
+fn test_tree() {
+    let mut tree = Tree::default();
+    tree.node("a", |tree| {
+        tree.node("b", |tree| {
+            tree.leaf("c");
+            tree.leaf("d");
+        });
+        tree.leaf("e");
+    });
+    assert_eq!(tree.to_string(), "a\n├─ b\n│  ├─ c\n│  └─ d\n└─ e\n");
+
+    assert_eq!(
+        describe_axes("Size", Vec2::new(None, Some(1))),
+        "Size(y: 1)"
+    );
+    assert_eq!(
+        describe_axes("Size", Vec2::<Option<u8>>::new(None, None)),
+        "Size"
+    );
+}
+
+#[test]
+fn test_debug_tree() {
+    use crate::{input, Border, ElementExt, MouseButton, MouseKind};
+
+    let element = crate::stack((
+        crate::empty::<u8>(),
+        crate::row(
+            crate::stretch(1),
+            (
+                crate::span("a").wrapped().title("A"),
+                crate::span("b")
+                    .filter(Border::THIN)
+                    .on_passive(('b', MouseKind::Press(MouseButton::Left)), |_| 1),
+            ),
+        )
+        .focus(1)
+        .on_bubble(input!(Key(Escape)), |_| 2),
+    ))
+    .broadcast_inputs()
+    .map_event(u32::from);
+
+    assert_eq!(
+        debug_tree(&element).to_string(),
+        "\
+MapEvent
+└─ Stack(broadcast_inputs)
+   ├─ Block<u8>
+   └─ On(closure, bubble)
+      └─ Row(Stretch(1), focused: 1)
+         ├─ Title(\"A\")
+         │  └─ Span(\"a\", wrapped)
+         └─ On('b' | Press(Left), passive)
+            └─ Border
+               └─ Span(\"b\")
+",
+    );
+}
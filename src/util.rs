@@ -1,4 +1,11 @@
 use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use smartstring::{LazyCompact, SmartString};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub(crate) struct WriteFn<F: FnMut(&str) -> fmt::Result>(pub(crate) F);
 
@@ -22,6 +29,84 @@ impl<F: FnMut(char) -> fmt::Result> fmt::Write for WriteCharsFn<F> {
     }
 }
 
+/// Splits the written text into extended grapheme clusters and calls the function with each one.
+///
+/// A cluster can be split across several writes, so the last cluster is only given to the
+/// function when [`finish`](Self::finish) is called.
+pub(crate) struct WriteGraphemesFn<F: FnMut(&str) -> fmt::Result> {
+    f: F,
+    /// The characters of the cluster being written so far.
+    pending: SmartString<LazyCompact>,
+}
+
+impl<F: FnMut(&str) -> fmt::Result> WriteGraphemesFn<F> {
+    pub(crate) fn new(f: F) -> Self {
+        Self {
+            f,
+            pending: SmartString::new(),
+        }
+    }
+
+    /// Give the last cluster to the function.
+    pub(crate) fn finish(mut self) -> fmt::Result {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            (self.f)(&self.pending)
+        }
+    }
+}
+
+impl<F: FnMut(&str) -> fmt::Result> fmt::Write for WriteGraphemesFn<F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.pending.push(c);
+            // Whether a character continues a cluster only depends on the cluster before it.
+            if self.pending.graphemes(true).nth(1).is_some() {
+                let end = self.pending.len() - c.len_utf8();
+                (self.f)(&self.pending[..end])?;
+                self.pending.clear();
+                self.pending.push(c);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Get the number of columns a grapheme cluster takes up, or [`None`] if it is a control character.
+///
+/// Clusters that start with a zero-width character are added to the cell before them, so they are
+/// zero columns wide. Other clusters take up a single cell, so they are one or two columns wide
+/// even if the widths of their characters add up to more.
+pub(crate) fn grapheme_width(grapheme: &str) -> Option<usize> {
+    Some(match grapheme.chars().next()?.width()? {
+        0 => 0,
+        _ => grapheme.width().min(2),
+    })
+}
+
+/// Get the number of columns text takes up when it is drawn.
+pub(crate) fn str_width(s: &str) -> usize {
+    s.graphemes(true).filter_map(grapheme_width).sum()
+}
+
+/// Shorten a type name by removing the paths of the types in it.
+pub(crate) fn short_type_name(name: &str) -> String {
+    fn last_segment(path: &str) -> &str {
+        path.rfind("::").map_or(path, |i| &path[i + 2..])
+    }
+
+    let mut short = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(end) = rest.find(|c: char| "<>,()[]&*; ".contains(c)) {
+        short.push_str(last_segment(&rest[..end]));
+        short.push_str(&rest[end..=end]);
+        rest = &rest[end + 1..];
+    }
+    short.push_str(last_segment(rest));
+    short
+}
+
 // pub(crate) struct DisplayFn<F: Fn(&mut Formatter<'_>) -> fmt::Result>(pub(crate) F);
 //
 // impl<F: Fn(&mut Formatter<'_>) -> fmt::Result> Display for DisplayFn<F> {
@@ -30,6 +115,33 @@ impl<F: FnMut(char) -> fmt::Result> fmt::Write for WriteCharsFn<F> {
 //     }
 // }
 
+/// Displays bytes as standard padded base64.
+pub(crate) struct Base64<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Display for Base64<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        for chunk in self.0.chunks(3) {
+            let bits = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |bits, (i, &byte)| bits | u32::from(byte) << (16 - i * 8));
+
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let index = (bits >> (18 - i * 6)) & 0b11_1111;
+                    fmt::Write::write_char(f, char::from(ALPHABET[index as usize]))?;
+                } else {
+                    f.write_str("=")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 pub(crate) enum Lazy<T, F> {
     Computed(T),
     Uncomputed(F),
@@ -57,3 +169,41 @@ impl<T: Default, F: FnOnce() -> T> Lazy<T, F> {
         }
     }
 }
+
+pub(crate) struct PollFn<F>(pub(crate) F);
+
+impl<T, F: FnMut(&mut Context<'_>) -> Poll<T> + Unpin> Future for PollFn<F> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        (self.0)(cx)
+    }
+}
+
+#[test]
+fn test_base64() {
+    assert_eq!(Base64(b"").to_string(), "");
+    assert_eq!(Base64(b"f").to_string(), "Zg==");
+    assert_eq!(Base64(b"fo").to_string(), "Zm8=");
+    assert_eq!(Base64(b"foo").to_string(), "Zm9v");
+    assert_eq!(Base64(b"foobar").to_string(), "Zm9vYmFy");
+    assert_eq!(Base64(&[0xFF, 0xFE]).to_string(), "//4=");
+}
+
+#[test]
+fn test_write_graphemes() {
+    let mut graphemes = Vec::new();
+    let mut writer = WriteGraphemesFn::new(|grapheme| {
+        graphemes.push(grapheme.to_owned());
+        Ok(())
+    });
+    fmt::Write::write_str(&mut writer, "ae").unwrap();
+    fmt::Write::write_str(&mut writer, "\u{301}🇬").unwrap();
+    fmt::Write::write_str(&mut writer, "🇧").unwrap();
+    writer.finish().unwrap();
+    assert_eq!(graphemes, ["a", "e\u{301}", "🇬🇧"]);
+
+    assert_eq!(grapheme_width("\u{301}"), Some(0));
+    assert_eq!(grapheme_width("\t"), None);
+    assert_eq!(str_width("e\u{301}👨\u{200D}👩\u{200D}👧\t"), 3);
+}
@@ -2,6 +2,9 @@ use std::convert::TryInto;
 use std::mem;
 use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 
+#[cfg(feature = "quickcheck")]
+use quickcheck_crate::{Arbitrary, Gen};
+
 /// A 2-dimensional vector.
 #[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Vec2<T> {
@@ -303,6 +306,20 @@ impl<T> From<Vec2<T>> for [T; 2] {
     }
 }
 
+#[cfg(feature = "quickcheck")]
+impl<T: Arbitrary> Arbitrary for Vec2<T> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self::new(T::arbitrary(g), T::arbitrary(g))
+    }
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(
+            (self.x.clone(), self.y.clone())
+                .shrink()
+                .map(|(x, y)| Self::new(x, y)),
+        )
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn vec_test() {